@@ -69,6 +69,37 @@ fn bench_decode_u128(c: &mut Criterion) {
 	c.bench_function("decode_u128", |b| b.iter(|| decode_u128(&buf)));
 }
 
+fn bench_decode_u64_mixed(c: &mut Criterion) {
+	// Lengths vary pseudo-randomly, so the prefix class is unpredictable.
+	let mut state = 0x2545_F491_4F6C_DD1Du64;
+	let mut buf = Vec::new();
+	for _ in 0..1024 {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		let value = state >> (state % 64);
+		let mut value_buf = [0u8; 9];
+		let len = encode_u64(&mut value_buf, value);
+		buf.extend_from_slice(&value_buf[..len]);
+	}
+	let encoded_len = buf.len();
+	buf.resize(encoded_len + 8, 0);
+
+	c.bench_function("decode_u64_mixed", |b| {
+		b.iter(|| {
+			let mut offset = 0;
+			let mut sum = 0u64;
+			while offset < encoded_len {
+				let chunk = buf[offset..].first_chunk::<9>().unwrap();
+				let (value, len) = decode_u64(chunk);
+				sum = sum.wrapping_add(value);
+				offset += len;
+			}
+			sum
+		})
+	});
+}
+
 #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 fn bench_bulk_encode_u32(c: &mut Criterion) {
 	let mut buf = [0u8; 5 * 1024];
@@ -122,6 +153,7 @@ criterion_group!(
 	bench_decode_u32,
 	bench_decode_u64,
 	bench_decode_u128,
+	bench_decode_u64_mixed,
 	bench_bulk_encode_u32,
 	bench_bulk_decode_u32
 );
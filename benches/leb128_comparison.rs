@@ -102,6 +102,7 @@ fn bench_leb128_bulk_encode(c: &mut Criterion) {
 	});
 }
 
+#[allow(clippy::needless_range_loop)]
 fn bench_leb128_bulk_decode(c: &mut Criterion) {
 	let mut buf = [0u8; 5 * 1024];
 	let values: Vec<u32> = (0..1024)
@@ -158,6 +158,60 @@ fn bench_bulk_decode_u32_large_values(c: &mut Criterion) {
 	});
 }
 
+/// Compares the AVX-512 kernels with the SSE2 ones on values below `2^28`,
+/// which the AVX-512 backend handles sixteen at a time.
+#[cfg(all(feature = "simd", not(feature = "safe"), target_arch = "x86_64"))]
+fn bench_avx512_bulk(c: &mut Criterion) {
+	use vlen::simd::{avx512_available, Avx512Simd, CurrentSimd, SimdImpl};
+
+	if !avx512_available() {
+		return;
+	}
+
+	let values: Vec<u32> =
+		(0..1024u32).map(|i| i * 262_139 % (1 << 28)).collect();
+	let wide: Vec<u64> = values.iter().map(|&v| u64::from(v)).collect();
+	let mut buf = [0u8; 5 * 1024];
+	let encoded_len = bulk_encode(&mut buf, &values).unwrap();
+	let mut decoded = [0u32; 1024];
+	let mut decoded_wide = [0u64; 1024];
+
+	let mut group = c.benchmark_group("avx512_bulk");
+	// SAFETY: AVX-512 support was checked above; SSE2 is always present on
+	// x86_64, and `buf` holds the worst-case encoding of `values`.
+	group.bench_function("encode_u32_avx512", |b| {
+		b.iter(|| unsafe { Avx512Simd::bulk_encode_u32(&mut buf, &values) })
+	});
+	group.bench_function("encode_u32_sse2", |b| {
+		b.iter(|| unsafe { CurrentSimd::bulk_encode_u32(&mut buf, &values) })
+	});
+	group.bench_function("encode_u64_avx512", |b| {
+		b.iter(|| unsafe { Avx512Simd::bulk_encode_u64(&mut buf, &wide) })
+	});
+	let encoded = &buf[..encoded_len];
+	group.bench_function("decode_u32_avx512", |b| {
+		b.iter(|| unsafe { Avx512Simd::bulk_decode_u32(encoded, &mut decoded) })
+	});
+	group.bench_function("decode_u32_sse2", |b| {
+		b.iter(|| unsafe {
+			CurrentSimd::bulk_decode_u32(encoded, &mut decoded)
+		})
+	});
+	group.bench_function("decode_u64_avx512", |b| {
+		b.iter(|| unsafe {
+			Avx512Simd::bulk_decode_u64(encoded, &mut decoded_wide)
+		})
+	});
+	group.finish();
+}
+
+#[cfg(not(all(
+	feature = "simd",
+	not(feature = "safe"),
+	target_arch = "x86_64"
+)))]
+fn bench_avx512_bulk(_c: &mut Criterion) {}
+
 criterion_group!(
 	benches,
 	bench_single_encode_u32,
@@ -173,6 +227,7 @@ criterion_group!(
 	bench_bulk_encode_u32_small_values,
 	bench_bulk_decode_u32_small_values,
 	bench_bulk_encode_u32_large_values,
-	bench_bulk_decode_u32_large_values
+	bench_bulk_decode_u32_large_values,
+	bench_avx512_bulk
 );
 criterion_main!(benches);
@@ -0,0 +1,234 @@
+//! Command-line tool for encoding, decoding and inspecting vlen data
+//!
+//! Reads from stdin and writes to stdout:
+//!
+//! - `vlen encode` reads whitespace-separated numbers and prints the hex
+//!   encoding of each one on its own line, or of all of them as a single
+//!   stream with `--bulk`.
+//! - `vlen decode` reads hex, one value per line, and prints each decoded
+//!   value. With `--bulk` the whole input is decoded as a single stream.
+//! - `vlen explain` reads hex and prints the offset, prefix, length and
+//!   value of every encoded value in it.
+//!
+//! The value type defaults to `u64` and can be changed with `--type`. To
+//! decode data of unknown type, `--type any` reads every value at full width.
+
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use vlen::{Decode, Encode};
+
+const USAGE: &str = "\
+usage: vlen <encode|decode|explain> [--type TYPE] [--bulk]
+
+Reads numbers (encode) or hex (decode, explain) from stdin.
+
+options:
+  -t, --type TYPE  u16, u32, u64, u128, i16, i32, i64, i128, f32 or f64
+                   (default: u64), or any to decode at full width
+  -b, --bulk       treat the input as a single stream of values
+  -h, --help       print this help";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+	Encode,
+	Decode,
+	Explain,
+}
+
+struct Options {
+	command: Command,
+	ty: String,
+	bulk: bool,
+}
+
+fn parse_args(
+	mut args: impl Iterator<Item = String>,
+) -> Result<Options, String> {
+	let command = match args.next().as_deref() {
+		Some("encode") => Command::Encode,
+		Some("decode") => Command::Decode,
+		Some("explain") => Command::Explain,
+		Some(other) => return Err(format!("unknown command `{other}`")),
+		None => return Err("missing command".into()),
+	};
+	let mut options = Options {
+		command,
+		ty: "u64".into(),
+		bulk: false,
+	};
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-t" | "--type" => {
+				options.ty = args.next().ok_or("missing value for --type")?;
+			},
+			"-b" | "--bulk" => options.bulk = true,
+			_ => return Err(format!("unknown option `{arg}`")),
+		}
+	}
+	Ok(options)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Parses hex digits, ignoring whitespace, `0x` prefixes and separators.
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+	let digits: Vec<u8> = text
+		.split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+		.map(|word| word.trim_start_matches("0x").trim_start_matches("0X"))
+		.flat_map(str::bytes)
+		.collect();
+	if !digits.len().is_multiple_of(2) {
+		return Err("odd number of hex digits".into());
+	}
+	digits
+		.chunks(2)
+		.map(|pair| {
+			let pair = std::str::from_utf8(pair).map_err(|e| e.to_string())?;
+			u8::from_str_radix(pair, 16)
+				.map_err(|_| format!("invalid hex byte `{pair}`"))
+		})
+		.collect()
+}
+
+fn encode<T>(
+	input: &str,
+	bulk: bool,
+	out: &mut impl Write,
+) -> Result<(), String>
+where
+	T: Encode + Copy + FromStr,
+	T::Err: Display,
+{
+	let mut stream = Vec::new();
+	for word in input.split_whitespace() {
+		let value: T = word
+			.parse()
+			.map_err(|e| format!("invalid number `{word}`: {e}"))?;
+		let mut buf = vec![0u8; T::MAX_ENCODED_SIZE];
+		let len = T::encode(&mut buf, value)?;
+		if bulk {
+			stream.extend_from_slice(&buf[..len]);
+		} else {
+			writeln!(out, "{}", to_hex(&buf[..len]))
+				.map_err(|e| e.to_string())?;
+		}
+	}
+	if bulk {
+		writeln!(out, "{}", to_hex(&stream)).map_err(|e| e.to_string())?;
+	}
+	Ok(())
+}
+
+/// A value of unknown type, decoded at full width with `vlen::decode_any`.
+struct Any(u128);
+
+impl Decode for Any {
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = vlen::decode_any(buf)?;
+		Ok((Any(value), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = 17;
+}
+
+impl Display for Any {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+fn decode<T>(
+	input: &str,
+	bulk: bool,
+	out: &mut impl Write,
+) -> Result<(), String>
+where
+	T: Decode + Display,
+{
+	if bulk {
+		let bytes = parse_hex(input)?;
+		for value in vlen::decode_iter::<T>(&bytes) {
+			writeln!(out, "{}", value?).map_err(|e| e.to_string())?;
+		}
+		return Ok(());
+	}
+	for line in input.lines().filter(|line| !line.trim().is_empty()) {
+		let bytes = parse_hex(line)?;
+		let (value, len) = T::decode(&bytes)?;
+		if len != bytes.len() {
+			return Err(format!(
+				"trailing bytes after value in `{}`",
+				line.trim()
+			));
+		}
+		writeln!(out, "{value}").map_err(|e| e.to_string())?;
+	}
+	Ok(())
+}
+
+fn explain(input: &str, out: &mut impl Write) -> Result<(), String> {
+	let bytes = parse_hex(input)?;
+	for token in vlen::explain(&bytes) {
+		writeln!(out, "{token}").map_err(|e| e.to_string())?;
+	}
+	Ok(())
+}
+
+/// Runs `$run::<T>` with `T` named by the `--type` option.
+macro_rules! dispatch_type {
+	($ty:expr, $run:ident($($arg:expr),*)) => {
+		match $ty {
+			"u16" => $run::<u16>($($arg),*),
+			"u32" => $run::<u32>($($arg),*),
+			"u64" => $run::<u64>($($arg),*),
+			"u128" => $run::<u128>($($arg),*),
+			"i16" => $run::<i16>($($arg),*),
+			"i32" => $run::<i32>($($arg),*),
+			"i64" => $run::<i64>($($arg),*),
+			"i128" => $run::<i128>($($arg),*),
+			"f32" => $run::<f32>($($arg),*),
+			"f64" => $run::<f64>($($arg),*),
+			other => Err(format!("unsupported type `{other}`")),
+		}
+	};
+}
+
+fn run(options: &Options) -> Result<(), String> {
+	let mut input = String::new();
+	io::stdin()
+		.read_to_string(&mut input)
+		.map_err(|e| e.to_string())?;
+	let mut out = io::stdout().lock();
+	let ty = options.ty.as_str();
+	let bulk = options.bulk;
+	match options.command {
+		Command::Encode => dispatch_type!(ty, encode(&input, bulk, &mut out)),
+		Command::Decode if ty == "any" => decode::<Any>(&input, bulk, &mut out),
+		Command::Decode => dispatch_type!(ty, decode(&input, bulk, &mut out)),
+		Command::Explain => explain(&input, &mut out),
+	}
+}
+
+fn main() -> ExitCode {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+		println!("{USAGE}");
+		return ExitCode::SUCCESS;
+	}
+	let result = parse_args(args.into_iter()).and_then(|options| run(&options));
+	match result {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("vlen: {err}");
+			if err.starts_with("unknown") || err.starts_with("missing") {
+				eprintln!("{USAGE}");
+			}
+			ExitCode::FAILURE
+		},
+	}
+}
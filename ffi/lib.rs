@@ -0,0 +1,9 @@
+//! Static and dynamic C libraries of the vlen FFI surface
+//!
+//! `vlen` itself is `no_std` by default and cannot be built as a
+//! `staticlib` or `cdylib`, so this package links it with the `ffi` feature
+//! and re-exports [`vlen::ffi`]. `cargo build --release -p vlen-ffi` writes
+//! `libvlen_ffi.a` and `libvlen_ffi.so` (or the platform's equivalents),
+//! which export the functions declared in `include/vlen.h`.
+
+pub use vlen::ffi::*;
@@ -0,0 +1,46 @@
+/*
+ * C declarations for the vlen FFI surface (`ffi` feature), implemented by
+ * the libvlen_ffi static and dynamic libraries of the `vlen-ffi` package.
+ *
+ * Every function returns a non-negative byte count on success, or one of
+ * the negative VLEN_ERR_* codes on failure.
+ */
+
+#ifndef VLEN_H
+#define VLEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define VLEN_ERR_NULL_POINTER (-1)
+#define VLEN_ERR_BUFFER_TOO_SMALL (-2)
+#define VLEN_ERR_TRUNCATED (-3)
+#define VLEN_ERR_INVALID (-4)
+
+ptrdiff_t vlen_encode_u32(uint8_t *buf, size_t buf_len, uint32_t value);
+ptrdiff_t vlen_decode_u32(const uint8_t *buf, size_t buf_len, uint32_t *value);
+ptrdiff_t vlen_encoded_size_u32(uint32_t value);
+ptrdiff_t vlen_bulk_encode_u32(uint8_t *buf, size_t buf_len,
+                               const uint32_t *values, size_t count);
+ptrdiff_t vlen_bulk_decode_u32(const uint8_t *buf, size_t buf_len,
+                               uint32_t *values, size_t capacity,
+                               size_t *decoded);
+
+ptrdiff_t vlen_encode_u64(uint8_t *buf, size_t buf_len, uint64_t value);
+ptrdiff_t vlen_decode_u64(const uint8_t *buf, size_t buf_len, uint64_t *value);
+ptrdiff_t vlen_encoded_size_u64(uint64_t value);
+ptrdiff_t vlen_bulk_encode_u64(uint8_t *buf, size_t buf_len,
+                               const uint64_t *values, size_t count);
+ptrdiff_t vlen_bulk_decode_u64(const uint8_t *buf, size_t buf_len,
+                               uint64_t *values, size_t capacity,
+                               size_t *decoded);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VLEN_H */
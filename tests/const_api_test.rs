@@ -1,6 +1,9 @@
 
-use vlen::const_encode::{encode_u32, encode_i64};
-use vlen::const_decode::{decode_u32, decode_i64};
+use vlen::const_encode::{
+    encode_f32, encode_f64, encode_i128_array, encode_i64, encode_u32,
+    encode_u32_array, encoded_size_f64, encoded_size_i32, encoded_size_u64,
+};
+use vlen::const_decode::{decode_f32, decode_f64, decode_i64, decode_u32};
 
 const fn test_const_encode_decode() -> bool {
     // Test u32
@@ -24,7 +27,82 @@ const fn test_const_encode_decode() -> bool {
 
 const TEST_RESULT: bool = test_const_encode_decode();
 
+const fn test_const_floats() -> bool {
+    let mut buf_f32 = [0u8; 5];
+    let len_f32 = encode_f32(&mut buf_f32, -1.5);
+    let (val_f32, len_decoded_f32) = decode_f32(&buf_f32);
+
+    if len_f32 != len_decoded_f32 { return false; }
+    if val_f32.to_bits() != (-1.5f32).to_bits() { return false; }
+
+    let mut buf_f64 = [0u8; 9];
+    let len_f64 = encode_f64(&mut buf_f64, 1.0e300);
+    let (val_f64, len_decoded_f64) = decode_f64(&buf_f64);
+
+    if len_f64 != len_decoded_f64 { return false; }
+    if val_f64.to_bits() != (1.0e300f64).to_bits() { return false; }
+    if encoded_size_f64(1.0e300) != len_f64 { return false; }
+
+    true
+}
+
+const FLOAT_RESULT: bool = test_const_floats();
+
+// Compile-time sized tables are the main use for the size calculators.
+const SIZE_TABLE: [usize; 3] = [
+    encoded_size_u64(u64::MAX),
+    encoded_size_i32(-1),
+    encoded_size_f64(0.0),
+];
+
+// By-value encoding needs no caller-declared scratch buffer.
+const U32_ARRAY: ([u8; 5], usize) = encode_u32_array(70000);
+
+// Tables baked in at compile time.
+const U32_TABLE: [u8; 6] = vlen::vlen_const!(u32: 1, 300, 70000);
+const F64_TABLE: &[u8] = &vlen::vlen_const!(f64: 0.0, 1.5, -2.0,);
+const EMPTY_TABLE: [u8; 0] = vlen::vlen_const!(i16:);
+
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test_const_works() {
     assert!(TEST_RESULT);
 }
+
+#[test]
+fn test_const_floats_and_sizes() {
+    const { assert!(FLOAT_RESULT) };
+    assert_eq!(SIZE_TABLE, [9, 1, 1]);
+}
+
+#[test]
+fn test_vlen_const_tables() {
+    let mut expected = Vec::new();
+    for value in [1u32, 300, 70000] {
+        let mut buf = [0u8; 17];
+        let len = vlen::encode(&mut buf, value).unwrap();
+        expected.extend_from_slice(&buf[..len]);
+    }
+    assert_eq!(U32_TABLE.as_slice(), expected.as_slice());
+
+    let mut offset = 0;
+    for value in [0.0f64, 1.5, -2.0] {
+        let mut buf = [0u8; 17];
+        buf[..F64_TABLE.len() - offset].copy_from_slice(&F64_TABLE[offset..]);
+        let (decoded, len) = vlen::decode::<f64>(&buf).unwrap();
+        assert_eq!(decoded, value);
+        offset += len;
+    }
+    assert_eq!(offset, F64_TABLE.len());
+    assert!(EMPTY_TABLE.is_empty());
+}
+
+#[test]
+fn test_encode_array_by_value() {
+    let (buf, len) = U32_ARRAY;
+    assert_eq!(decode_u32(&buf), (70000, len));
+
+    let (buf, len) = encode_i128_array(i128::MIN);
+    assert_eq!(len, 17);
+    assert_eq!(vlen::decode::<i128>(&buf).unwrap(), (i128::MIN, len));
+}
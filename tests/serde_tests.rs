@@ -1,9 +1,10 @@
 #[cfg(feature = "serde")]
+#[allow(clippy::approx_constant)]
 mod serde_tests {
 	use serde::{Deserialize, Serialize};
 	use vlen::serde::*;
 
-	#[derive(Debug, Serialize, Deserialize, PartialEq)]
+	#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
 	struct TestStruct {
 		u16_val: VlenU16,
 		u32_val: VlenU32,
@@ -196,6 +197,54 @@ mod serde_tests {
 		assert_eq!(*f64_val, 3.14159);
 	}
 
+	#[test]
+	fn test_binary_format_uses_bytes() {
+		let value = VlenU64(300);
+		let mut cbor = Vec::new();
+		ciborium::into_writer(&value, &mut cbor).unwrap();
+		// A two-byte CBOR byte string holding the two-byte encoding.
+		assert_eq!(cbor.len(), 3);
+		assert_eq!(cbor[0], 0x42);
+		let decoded: VlenU64 = ciborium::from_reader(&cbor[..]).unwrap();
+		assert_eq!(decoded, value);
+
+		let mut cbor = Vec::new();
+		ciborium::into_writer(&TestStruct::default(), &mut cbor).unwrap();
+		let decoded: TestStruct = ciborium::from_reader(&cbor[..]).unwrap();
+		assert_eq!(decoded, TestStruct::default());
+	}
+
+	#[test]
+	fn test_human_readable_uses_base64() {
+		assert_eq!(serde_json::to_string(&VlenU64(300)).unwrap(), "\"rAQ=\"");
+		let value: VlenI32 = serde_json::from_str("\"Aw==\"").unwrap();
+		assert_eq!(value, VlenI32(-2));
+
+		let too_long = format!("\"{}\"", "A".repeat(32));
+		assert!(serde_json::from_str::<VlenU32>(&too_long).is_err());
+		assert!(serde_json::from_str::<VlenU32>("\"not base64\"").is_err());
+	}
+
+	#[test]
+	fn test_deserialize_rejects_malformed_encodings() {
+		// A full-length u128 encoding is longer than any u16 encoding.
+		let json = serde_json::to_string(&VlenU128(u128::MAX)).unwrap();
+		assert!(serde_json::from_str::<VlenU16>(&json).is_err());
+
+		// A u64 value that does not fit in a u32.
+		let json = serde_json::to_string(&VlenU64(1 << 40)).unwrap();
+		assert!(serde_json::from_str::<VlenU32>(&json).is_err());
+
+		// Empty, truncated and padded encodings.
+		assert!(serde_json::from_str::<VlenU32>("\"\"").is_err());
+		let truncated = ciborium::Value::Bytes(vec![0xAC]);
+		assert!(truncated.deserialized::<VlenU32>().is_err());
+		let padded = ciborium::Value::Bytes(vec![0xAC, 0x04, 0x00]);
+		assert!(padded.deserialized::<VlenU32>().is_err());
+		let exact = ciborium::Value::Bytes(vec![0xAC, 0x04]);
+		assert_eq!(exact.deserialized::<VlenU32>().unwrap(), VlenU32(300));
+	}
+
 	#[test]
 	fn test_serde_with_vectors() {
 		#[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -215,6 +264,48 @@ mod serde_tests {
 		let deserialized: VectorTest = serde_json::from_str(&json).unwrap();
 		assert_eq!(data, deserialized);
 	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_vlen_seq_single_blob() {
+		#[derive(Debug, PartialEq, Serialize, Deserialize)]
+		struct Column {
+			#[serde(with = "vlen::serde::seq")]
+			values: Vec<i64>,
+			wrapped: VlenSeq<u32>,
+		}
+
+		let values: Vec<u32> = (0..100).map(|i| i * 37).collect();
+		let column = Column {
+			values: vec![-1, 0, 1 << 40],
+			wrapped: VlenSeq(values.clone()),
+		};
+
+		let json = serde_json::to_string(&column).unwrap();
+		let decoded: Column = serde_json::from_str(&json).unwrap();
+		assert_eq!(decoded, column);
+
+		let mut cbor = Vec::new();
+		ciborium::into_writer(&column, &mut cbor).unwrap();
+		let decoded: Column = ciborium::from_reader(&cbor[..]).unwrap();
+		assert_eq!(decoded, column);
+
+		// One blob is far smaller than one string per element.
+		let per_element: Vec<VlenU32> =
+			values.iter().map(|&v| VlenU32(v)).collect();
+		let seq_json = serde_json::to_string(&VlenSeq(values)).unwrap();
+		let element_json = serde_json::to_string(&per_element).unwrap();
+		assert!(seq_json.len() * 2 < element_json.len());
+
+		// The count must match the blob.
+		let blob = vlen::bulk_encode_to_vec(&[1u32, 2]).unwrap();
+		let short = ciborium::Value::Bytes([&[3u8][..], &blob].concat());
+		let result: Result<VlenSeq<u32>, _> = short.deserialized();
+		assert!(result.is_err());
+		let long = ciborium::Value::Bytes([&[1u8][..], &blob].concat());
+		let result: Result<VlenSeq<u32>, _> = long.deserialized();
+		assert!(result.is_err());
+	}
 }
 
 #[test]
@@ -532,7 +532,7 @@ fn test_overlong_encodings() {
 }
 
 #[test]
-#[cfg(feature = "simd")]
+#[cfg(all(feature = "simd", not(feature = "safe")))]
 fn test_bulk_decode_u32() {
 	let mut buf = [0u8; 20];
 	let values = [1u32, 1000, 1000000, 1000000000];
@@ -546,7 +546,7 @@ fn test_bulk_decode_u32() {
 }
 
 #[test]
-#[cfg(feature = "simd")]
+#[cfg(all(feature = "simd", not(feature = "safe")))]
 fn test_bulk_decode_u32_mixed() {
 	let mut buf = [0u8; 20];
 	let values = [1u32, 1000, 1000000, 1000000000];
@@ -612,6 +612,3547 @@ fn test_generic_bulk_operations() {
 	assert_eq!(values, decoded_values);
 }
 
+#[test]
+fn test_encode_seq_count_prefix() {
+	let values = [1u32, 1000, 1000000, 1000000000];
+	let mut buf = [0u8; 32];
+	let encoded_len = vlen::encode_seq(&mut buf, &values).unwrap();
+	assert_eq!(buf[0], 4);
+
+	let bulk_len = vlen::bulk_encode(&mut [0u8; 20], &values).unwrap();
+	assert_eq!(encoded_len, bulk_len + 1);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decode_seq_to_vec() {
+	let values = [1u64, 300, 70000, u64::MAX];
+	let mut buf = [0u8; 64];
+	let encoded_len = vlen::encode_seq(&mut buf, &values).unwrap();
+	let (decoded, decoded_len) =
+		vlen::decode_seq_to_vec::<u64>(&buf[..encoded_len]).unwrap();
+	assert_eq!(decoded, values);
+	assert_eq!(decoded_len, encoded_len);
+
+	let mut buf = [0u8; 9];
+	let encoded_len = vlen::encode_seq::<u32>(&mut buf, &[]).unwrap();
+	let (decoded, _) =
+		vlen::decode_seq_to_vec::<u32>(&buf[..encoded_len]).unwrap();
+	assert!(decoded.is_empty());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decode_seq_rejects_bad_count() {
+	// Claims 100 elements but only holds two.
+	assert!(vlen::decode_seq_to_vec::<u32>(&[100, 1, 2]).is_err());
+	// Claims two elements but the second is truncated.
+	assert!(vlen::decode_seq_to_vec::<u32>(&[2, 1, 0xF3, 0x00]).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_vlen_value_round_trip() {
+	use vlen::value::VlenValue;
+
+	let value = VlenValue::Seq(vec![
+		VlenValue::U64(u64::MAX),
+		VlenValue::I64(-1234567890),
+		VlenValue::F64(-0.5),
+		VlenValue::Bytes(vec![0xDE, 0xAD, 0xBE, 0xEF]),
+		VlenValue::Seq(vec![]),
+	]);
+	let buf = value.encode_to_vec();
+	assert_eq!(buf.len(), value.encoded_size());
+	assert_eq!(buf[0], vlen::value::TAG_SEQ);
+
+	let (decoded, len) = VlenValue::decode(&buf).unwrap();
+	assert_eq!(decoded, value);
+	assert_eq!(len, buf.len());
+
+	let mut exact = vec![0u8; buf.len()];
+	assert_eq!(value.encode(&mut exact).unwrap(), buf.len());
+	assert_eq!(exact, buf);
+	assert!(value.encode(&mut exact[..buf.len() - 1]).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_vlen_value_rejects_malformed() {
+	use vlen::value::VlenValue;
+
+	assert!(VlenValue::decode(&[]).is_err());
+	assert!(VlenValue::decode(&[0x7F]).is_err());
+	// Byte string claiming more bytes than are present.
+	assert!(VlenValue::decode(&[vlen::value::TAG_BYTES, 5, 1, 2]).is_err());
+	// Deeply nested single-element sequences.
+	let nested = [vlen::value::TAG_SEQ, 1].repeat(vlen::value::MAX_DEPTH + 1);
+	assert!(VlenValue::decode(&nested).is_err());
+}
+
+#[test]
+fn test_duration_round_trip() {
+	use core::time::Duration;
+
+	for value in [
+		Duration::ZERO,
+		Duration::from_millis(1500),
+		Duration::new(u64::MAX, 999_999_999),
+	] {
+		let mut buf = [0u8; 14];
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(len, vlen::encoded_size(value).unwrap());
+		let (decoded, decoded_len) = vlen::decode::<Duration>(&buf).unwrap();
+		assert_eq!(decoded, value);
+		assert_eq!(decoded_len, len);
+	}
+
+	// Nanoseconds must be below one second.
+	let mut buf = [0u8; 14];
+	vlen::encode(&mut buf[1..], 1_000_000_000u32).unwrap();
+	assert!(vlen::decode::<Duration>(&buf).is_err());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_system_time_round_trip() {
+	use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+	for value in [
+		UNIX_EPOCH,
+		UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+		UNIX_EPOCH - Duration::new(86_400, 1),
+		SystemTime::now(),
+	] {
+		let mut buf = [0u8; 14];
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(len, vlen::encoded_size(value).unwrap());
+		let (decoded, decoded_len) = vlen::decode::<SystemTime>(&buf).unwrap();
+		assert_eq!(decoded, value);
+		assert_eq!(decoded_len, len);
+	}
+}
+
+#[test]
+fn test_uuid_round_trip() {
+	let mut sequential = [0u8; 16];
+	sequential[15] = 42;
+	for uuid in [[0u8; 16], sequential, [0xA5; 16]] {
+		let mut buf = [0u8; 17];
+		let len = vlen::encode_uuid(&mut buf, uuid);
+		assert_eq!(vlen::decode_uuid(&buf), (uuid, len));
+	}
+
+	let mut buf = [0u8; 17];
+	assert_eq!(vlen::encode_uuid(&mut buf, sequential), 1);
+	assert_eq!(buf[0], 42);
+}
+
+#[test]
+#[cfg(feature = "uuid")]
+fn test_uuid_crate_round_trip() {
+	let uuid = uuid::Uuid::from_u128(0x0123_4567_89AB_CDEF);
+	let mut buf = [0u8; 17];
+	let len = vlen::encode(&mut buf, uuid).unwrap();
+	assert_eq!(len, vlen::encoded_size(uuid).unwrap());
+	assert_eq!(vlen::decode::<uuid::Uuid>(&buf).unwrap(), (uuid, len));
+}
+
+#[test]
+#[cfg(feature = "num-traits")]
+fn test_prim_int_dispatch() {
+	use vlen::num::{decode_prim_int, encode_prim_int, Prim};
+
+	let mut buf = [0u8; 17];
+	let len = encode_prim_int(&mut buf, 255u8).unwrap();
+	assert_eq!(&buf[..len], &[0b10111111, 0x03]);
+	assert_eq!(decode_prim_int::<u8>(&buf).unwrap(), (255, len));
+
+	let len = encode_prim_int(&mut buf, isize::MIN).unwrap();
+	assert_eq!(decode_prim_int::<isize>(&buf).unwrap(), (isize::MIN, len));
+
+	// Matches the dedicated encoder for the same width.
+	let mut expected = [0u8; 17];
+	let expected_len = vlen::encode(&mut expected, -300i32).unwrap();
+	let len = vlen::encode(&mut buf, Prim(-300i32)).unwrap();
+	assert_eq!(&buf[..len], &expected[..expected_len]);
+
+	// Values that do not fit the requested type are rejected.
+	vlen::encode(&mut buf, 300u16).unwrap();
+	assert!(decode_prim_int::<u8>(&buf).is_err());
+	assert_eq!(<Prim<u8> as vlen::Encode>::MAX_ENCODED_SIZE, 3);
+}
+
+fn check_size_bounds<T>(small: T, large: T)
+where
+	T: vlen::Encode + vlen::Decode + Copy + PartialEq + core::fmt::Debug,
+{
+	let mut buf = [0u8; 32];
+	let min = <T as vlen::Encode>::MIN_ENCODED_SIZE;
+	let max = <T as vlen::Encode>::MAX_ENCODED_SIZE;
+	assert_eq!(min, <T as vlen::Decode>::MIN_ENCODED_SIZE);
+	assert_eq!(max, <T as vlen::Decode>::MAX_ENCODED_SIZE);
+	assert_eq!(vlen::encode(&mut buf[..max], small).unwrap(), min);
+	assert_eq!(vlen::encode(&mut buf[..max], large).unwrap(), max);
+	assert_eq!(vlen::decode::<T>(&buf[..max]).unwrap(), (large, max));
+	assert!(vlen::encode(&mut buf[..max - 1], small).is_err());
+}
+
+#[test]
+fn test_encoded_size_bounds() {
+	check_size_bounds(0u16, u16::MAX);
+	check_size_bounds(0u32, u32::MAX);
+	check_size_bounds(0u64, u64::MAX);
+	check_size_bounds(0u128, u128::MAX);
+	check_size_bounds(0i32, i32::MIN);
+	check_size_bounds(0i128, i128::MIN);
+	check_size_bounds(0.0f64, f64::from_bits(0x00FF_FFFF_FFFF_FFFF));
+	check_size_bounds(
+		core::time::Duration::ZERO,
+		core::time::Duration::new(u64::MAX, 999_999_999),
+	);
+}
+
+#[test]
+fn test_decode_short_buffers() {
+	// A complete encoding decodes without trailing padding.
+	assert_eq!(vlen::decode::<u32>(&[0x05]).unwrap(), (5, 1));
+	assert_eq!(vlen::decode::<u16>(&[0xDE, 0x34, 0x12]).unwrap(), (0x1234, 3));
+	assert_eq!(vlen::decode::<i64>(&[0x03]).unwrap(), (-2, 1));
+	assert_eq!(vlen::decode::<u128>(&[0xF0, 0xFF]).unwrap(), (0xFF, 2));
+
+	for value in [0u64, 127, 128, 0x3FFF, 0x1F_FFFF, 0xFFF_FFFF, u64::MAX] {
+		let mut buf = [0u8; 9];
+		let len = vlen::encode(&mut buf, value).unwrap();
+		let encoded = &buf[..len];
+		assert_eq!(vlen::decode::<u64>(encoded).unwrap(), (value, len));
+		// Dropping the last byte leaves an incomplete value.
+		let truncated = &encoded[..encoded.len() - 1];
+		assert!(vlen::decode::<u64>(truncated).is_err());
+	}
+
+	assert!(vlen::decode::<u32>(&[]).is_err());
+	assert_eq!(
+		vlen::decode::<u32>(&[0xF3, 0x01]),
+		Err("truncated vlen value")
+	);
+}
+
+#[test]
+fn test_bulk_decode_counted() {
+	let values = [1u32, 300, 70000];
+	let mut buf = [0u8; 32];
+	let encoded_len = vlen::bulk_encode(&mut buf, &values).unwrap();
+
+	// The buffer runs out before `decoded` is full.
+	let mut decoded = [0u32; 5];
+	let result = vlen::bulk_decode_counted(&buf[..encoded_len], &mut decoded);
+	assert_eq!(result, Ok((3, encoded_len)));
+	assert_eq!(&decoded[..3], &values);
+
+	// `decoded` fills up before the buffer runs out.
+	let mut decoded = [0u32; 2];
+	let result = vlen::bulk_decode_counted(&buf[..encoded_len], &mut decoded);
+	assert_eq!(result, Ok((2, 3)));
+
+	// A partial value at the end of the buffer is an error.
+	let mut decoded = [0u32; 5];
+	let result =
+		vlen::bulk_decode_counted(&buf[..encoded_len - 1], &mut decoded);
+	assert!(result.is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_bulk_decode_values_limits() {
+	let values = [1u32, 300, 70000];
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+
+	let decoded: Vec<u32> =
+		vlen::bulk_decode_values_bounded(&buf, 3, buf.len()).unwrap();
+	assert_eq!(decoded, values);
+	assert!(vlen::bulk_decode_values_bounded::<u32>(&buf, 2, 64).is_err());
+	assert!(
+		vlen::bulk_decode_values_bounded::<u32>(&buf, 8, buf.len() - 1)
+			.is_err()
+	);
+
+	let decoded: Vec<u32> = vlen::bulk_decode_values_exact(&buf, 3).unwrap();
+	assert_eq!(decoded, values);
+	assert!(vlen::bulk_decode_values_exact::<u32>(&buf, 2).is_err());
+	assert!(vlen::bulk_decode_values_exact::<u32>(&buf, 4).is_err());
+	assert!(vlen::bulk_decode_values_exact::<u32>(&buf, usize::MAX).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_encode_append() {
+	let mut buf = vec![0xAA];
+	assert_eq!(vlen::encode_append(&mut buf, 300u32).unwrap(), 2);
+	assert_eq!(vlen::encode_append(&mut buf, -1i64).unwrap(), 1);
+	assert_eq!(buf, [0xAA, 0xAC, 0x04, 0x01]);
+
+	let values = [1u64, 1 << 20, u64::MAX];
+	let mut buf = vlen::encode_to_vec(7u16).unwrap();
+	let appended = vlen::bulk_encode_append(&mut buf, &values).unwrap();
+	assert_eq!(appended, buf.len() - 1);
+	assert_eq!(&buf[1..], vlen::bulk_encode_to_vec(&values).unwrap());
+}
+
+#[test]
+fn test_encode_uninit() {
+	use core::mem::MaybeUninit;
+
+	let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+	let len = vlen::encode_uninit(&mut buf, 300u64).unwrap();
+	assert_eq!(len, 2);
+	let encoded: Vec<u8> =
+		buf[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+	assert_eq!(encoded, [0xAC, 0x04]);
+
+	// Only the actual encoding needs to fit.
+	let mut buf = [MaybeUninit::<u8>::uninit(); 1];
+	assert!(vlen::encode_uninit(&mut buf, 127u128).is_ok());
+	assert!(vlen::encode_uninit(&mut buf, 128u128).is_err());
+
+	let values = [1u32, 300, 70000];
+	let mut buf = [MaybeUninit::<u8>::uninit(); 16];
+	let len = vlen::bulk_encode_uninit(&mut buf, &values).unwrap();
+	let encoded: Vec<u8> =
+		buf[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+	let mut decoded = [0u32; 3];
+	vlen::bulk_decode(&encoded, &mut decoded).unwrap();
+	assert_eq!(decoded, values);
+}
+
+#[test]
+fn test_encode_uninit_rejects_overlong_length() {
+	use core::mem::MaybeUninit;
+
+	// Reports more bytes than it could have written.
+	#[derive(Clone)]
+	struct Overlong;
+
+	impl vlen::Encode for Overlong {
+		fn encode(_buf: &mut [u8], _value: Self) -> Result<usize, &'static str> {
+			Ok(64)
+		}
+
+		fn encoded_size(_value: Self) -> Result<usize, &'static str> {
+			Ok(64)
+		}
+
+		const MAX_ENCODED_SIZE: usize = 40;
+	}
+
+	let mut buf = [MaybeUninit::<u8>::uninit(); 48];
+	assert!(vlen::encode_uninit(&mut buf, Overlong).is_err());
+	assert!(vlen::bulk_encode_uninit(&mut buf, &[Overlong]).is_err());
+}
+
+#[test]
+fn test_bulk_decode_uninit() {
+	use core::mem::MaybeUninit;
+
+	let values = [1i64, -300, 70000, i64::MIN];
+	let mut buf = [0u8; 32];
+	let len = vlen::bulk_encode(&mut buf, &values).unwrap();
+
+	let mut out = [MaybeUninit::<i64>::uninit(); 8];
+	assert_eq!(vlen::bulk_decode_uninit(&buf[..len], &mut out), Ok((4, len)));
+	let decoded: Vec<i64> =
+		out[..4].iter().map(|v| unsafe { v.assume_init() }).collect();
+	assert_eq!(decoded, values);
+
+	// Stops when the output is full.
+	let mut out = [MaybeUninit::<i64>::uninit(); 2];
+	let (count, consumed) = vlen::bulk_decode_uninit(&buf, &mut out).unwrap();
+	assert_eq!((count, consumed), (2, 3));
+
+	let mut out = [MaybeUninit::<i64>::uninit(); 8];
+	assert!(vlen::bulk_decode_uninit(&buf[..len - 1], &mut out).is_err());
+}
+
+#[test]
+#[cfg(feature = "heapless")]
+fn test_heapless_round_trip() {
+	use vlen::heapless::{
+		bulk_decode_values_heapless,
+		bulk_encode_to_heapless,
+		encode_append_heapless,
+		encode_to_heapless,
+	};
+
+	let buf = encode_to_heapless::<u64, 9>(u64::MAX).unwrap();
+	assert_eq!(buf.len(), 9);
+	assert!(encode_to_heapless::<u64, 8>(u64::MAX).is_err());
+
+	let values = [1u32, 300, 70000];
+	let buf = bulk_encode_to_heapless::<u32, 8>(&values).unwrap();
+	let decoded = bulk_decode_values_heapless::<u32, 3>(&buf).unwrap();
+	assert_eq!(decoded, values);
+	assert!(bulk_decode_values_heapless::<u32, 2>(&buf).is_err());
+
+	// A failed append leaves the vector untouched.
+	let mut buf = heapless::Vec::<u8, 2>::new();
+	encode_append_heapless(&mut buf, 1u32).unwrap();
+	assert!(encode_append_heapless(&mut buf, 300u32).is_err());
+	assert_eq!(buf, [1]);
+}
+
+#[test]
+#[cfg(feature = "embedded-io")]
+fn test_embedded_io_round_trip() {
+	use vlen::embedded_io::{VlenIoError, VlenRead, VlenWrite};
+
+	let mut buf = [0u8; 64];
+	let mut writer = &mut buf[..];
+	assert_eq!(writer.write_vlen_u32(300).unwrap(), 2);
+	writer.write_vlen_u128(u128::MAX).unwrap();
+	writer.write_vlen_f64(-0.5).unwrap();
+	writer.write_vlen(i16::MIN).unwrap();
+	assert_eq!(writer.write_vlen('😀').unwrap(), 3);
+	let written = 64 - writer.len();
+
+	let mut reader = &buf[..written];
+	assert_eq!(reader.read_vlen_u32().unwrap(), 300);
+	assert_eq!(reader.read_vlen_u128().unwrap(), u128::MAX);
+	assert_eq!(reader.read_vlen_f64().unwrap(), -0.5);
+	assert_eq!(reader.read_vlen_i16().unwrap(), i16::MIN);
+	assert_eq!(reader.read_vlen::<char>().unwrap(), '😀');
+	assert!(reader.is_empty());
+
+	// A truncated stream reports end of input.
+	let mut reader = &[0xF3u8, 0x01][..];
+	assert_eq!(reader.read_vlen_u32(), Err(VlenIoError::UnexpectedEof));
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn test_async_io_round_trip() {
+	use std::pin::Pin;
+	use std::task::{Context, Poll};
+
+	use futures_util::io::{AsyncRead, Cursor};
+	use futures_util::FutureExt;
+	use vlen::async_io::{AsyncVlenReadExt, AsyncVlenWriteExt};
+
+	/// Yields at most one byte per read to exercise partial reads.
+	struct Trickle<'a>(&'a [u8]);
+
+	impl AsyncRead for Trickle<'_> {
+		fn poll_read(
+			mut self: Pin<&mut Self>,
+			_cx: &mut Context<'_>,
+			buf: &mut [u8],
+		) -> Poll<std::io::Result<usize>> {
+			let len = buf.len().min(self.0.len()).min(1);
+			buf[..len].copy_from_slice(&self.0[..len]);
+			self.0 = &self.0[len..];
+			Poll::Ready(Ok(len))
+		}
+	}
+
+	let mut cursor = Cursor::new(Vec::new());
+	async {
+		cursor.write_vlen_u32(300).await.unwrap();
+		cursor.write_vlen_i128(i128::MIN).await.unwrap();
+		cursor.write_vlen(2.5f32).await.unwrap();
+	}
+	.now_or_never()
+	.unwrap();
+	let buf = cursor.into_inner();
+
+	let mut reader = Trickle(&buf);
+	async {
+		assert_eq!(reader.read_vlen_u32().await.unwrap(), 300);
+		assert_eq!(reader.read_vlen_i128().await.unwrap(), i128::MIN);
+		assert_eq!(reader.read_vlen_f32().await.unwrap(), 2.5);
+		let err = reader.read_vlen_u64().await.unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+	}
+	.now_or_never()
+	.unwrap();
+}
+
+#[test]
+#[cfg(feature = "simd")]
+fn test_safe_bulk_u64_round_trip() {
+	let values: Vec<u64> = (0..40).map(|i| (1u64 << (i % 64)) + i).collect();
+	let mut buf = vec![0u8; values.len() * 9];
+	let encoded_len = vlen::bulk_encode_u64_safe(&mut buf, &values).unwrap();
+	let mut decoded_values = vec![0u64; values.len()];
+	let decoded_len =
+		vlen::bulk_decode_u64_safe(&buf[..encoded_len], &mut decoded_values)
+			.unwrap();
+	assert_eq!(decoded_len, encoded_len);
+	assert_eq!(values, decoded_values);
+}
+
+#[test]
+fn test_decode_iter() {
+	let values = [1u32, 300, 70000, 0x12345678];
+	let mut buf = [0u8; 20];
+	let len = vlen::bulk_encode(&mut buf, &values).unwrap();
+	let buf = &buf[..len];
+
+	let decoded: Result<Vec<u32>, _> = vlen::decode_iter::<u32>(buf).collect();
+	assert_eq!(decoded.unwrap(), values);
+	assert_eq!(vlen::count_values::<u32>(buf), Ok(4));
+
+	let skipped = vlen::skip_values::<u32>(buf, 2).unwrap();
+	assert_eq!(skipped, 3);
+	let mut iter = vlen::decode_iter::<u32>(&buf[skipped..]);
+	assert_eq!(iter.next(), Some(Ok(70000)));
+	assert_eq!(iter.offset(), 3);
+	assert_eq!(iter.remaining(), &buf[6..]);
+	assert!(vlen::skip_values::<u32>(buf, 5).is_err());
+
+	// A truncated value is reported once, then the iterator ends.
+	let mut iter = vlen::decode_iter::<u32>(&buf[..len - 1]);
+	assert_eq!(iter.by_ref().filter(Result::is_err).count(), 1);
+	assert_eq!(iter.next(), None);
+	assert!(vlen::count_values::<u32>(&buf[..len - 1]).is_err());
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn test_vlen_file() {
+	let values: Vec<u64> = (0..1000).map(|i| i * i * 1000).collect();
+	let path = std::env::temp_dir()
+		.join(format!("vlen-test-{}.vlen", std::process::id()));
+	std::fs::write(&path, vlen::bulk_encode_to_vec(&values).unwrap()).unwrap();
+
+	let file = vlen::mmap::VlenFile::open(&path).unwrap();
+	assert_eq!(file.count_values::<u64>(), Ok(1000));
+	let decoded: Vec<u64> = file.iter::<u64>().map(Result::unwrap).collect();
+	assert_eq!(decoded, values);
+	let tail: Vec<u64> =
+		file.skip::<u64>(990).unwrap().map(Result::unwrap).collect();
+	assert_eq!(tail, values[990..]);
+	assert!(file.skip::<u64>(1001).is_err());
+
+	drop(file);
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_explain() {
+	let values = [5u64, 300, 70000, 0x12345678, u64::MAX];
+	let mut buf = [0u8; 40];
+	let len = vlen::bulk_encode(&mut buf, &values).unwrap();
+
+	let tokens: Vec<vlen::Token> = vlen::explain(&buf[..len]).collect();
+	assert_eq!(tokens.len(), values.len());
+	let mut offset = 0;
+	for (token, &value) in tokens.iter().zip(&values) {
+		assert_eq!(token.offset, offset);
+		assert_eq!(token.prefix, buf[offset]);
+		assert_eq!(token.len, vlen::encoded_size(value).unwrap());
+		assert_eq!(token.value, u128::from(value));
+		assert!(!token.truncated);
+		offset += token.len;
+	}
+	assert_eq!(
+		tokens[1].to_string(),
+		"offset 1: prefix 0xac, length 2, value 300"
+	);
+
+	// The last value is cut short, and iteration stops after it.
+	let tokens: Vec<vlen::Token> = vlen::explain(&buf[..len - 2]).collect();
+	assert_eq!(tokens.len(), values.len());
+	assert!(tokens[4].truncated);
+	assert_eq!(tokens[4].len, 9);
+	assert!(tokens[4].to_string().ends_with("(truncated)"));
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn test_cli() {
+	use std::io::Write;
+	use std::process::{Command, Stdio};
+
+	fn run(args: &[&str], input: &str) -> String {
+		let mut child = Command::new(env!("CARGO_BIN_EXE_vlen"))
+			.args(args)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.spawn()
+			.unwrap();
+		child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+		let output = child.wait_with_output().unwrap();
+		assert!(output.status.success());
+		String::from_utf8(output.stdout).unwrap()
+	}
+
+	assert_eq!(run(&["encode", "-t", "u32"], "1 300"), "01\nac04\n");
+	assert_eq!(run(&["encode", "--bulk", "-t", "i64"], "-5 5"), "090a\n");
+	assert_eq!(run(&["decode", "-t", "u32"], "ac04\n0x01\n"), "300\n1\n");
+	assert_eq!(
+		run(&["decode", "-b", "-t", "f64"], "dfc107 8003"),
+		"1.5\n-2\n"
+	);
+	assert_eq!(
+		run(&["explain"], "01 ac04"),
+		"offset 0: prefix 0x01, length 1, value 1\n\
+		 offset 1: prefix 0xac, length 2, value 300\n"
+	);
+}
+
+#[test]
+#[cfg(feature = "ffi")]
+fn test_ffi() {
+	use vlen::ffi::*;
+
+	let mut buf = [0u8; 32];
+	let values = [1u64, 300, 1 << 40];
+	let len = unsafe {
+		vlen_bulk_encode_u64(buf.as_mut_ptr(), buf.len(), values.as_ptr(), 3)
+	};
+	assert_eq!(len, 10);
+	assert_eq!(vlen_encoded_size_u64(300), 2);
+
+	let mut decoded = [0u64; 4];
+	let mut count = 0;
+	let used = unsafe {
+		vlen_bulk_decode_u64(
+			buf.as_ptr(),
+			10,
+			decoded.as_mut_ptr(),
+			4,
+			&mut count,
+		)
+	};
+	assert_eq!((used, count), (10, 3));
+	assert_eq!(decoded[..3], values);
+
+	let mut value = 0u32;
+	assert_eq!(
+		unsafe { vlen_decode_u32(buf.as_ptr().add(1), 2, &mut value) },
+		2
+	);
+	assert_eq!(value, 300);
+	assert_eq!(
+		unsafe { vlen_decode_u32(buf.as_ptr().add(1), 1, &mut value) },
+		VLEN_ERR_TRUNCATED
+	);
+	assert_eq!(
+		unsafe { vlen_encode_u64(buf.as_mut_ptr(), 2, 5) },
+		VLEN_ERR_BUFFER_TOO_SMALL
+	);
+	assert_eq!(
+		unsafe { vlen_encode_u64(core::ptr::null_mut(), 9, 5) },
+		VLEN_ERR_NULL_POINTER
+	);
+}
+
+#[test]
+#[cfg(feature = "wasm")]
+fn test_wasm_bindings() {
+	use vlen::wasm::*;
+
+	assert_eq!(encode_u32(300), [0xAC, 0x04]);
+	assert_eq!(decode_u32(&[0xAC, 0x04]).unwrap(), 300);
+	assert_eq!(decode_u64(&encode_u64(u64::MAX)).unwrap(), u64::MAX);
+	assert_eq!(decode_f64(&encode_f64(-1.5)).unwrap(), -1.5);
+
+	let values = [1u32, 300, 70000, u32::MAX];
+	let encoded = bulk_encode_u32(&values).unwrap();
+	assert_eq!(bulk_decode_u32(&encoded).unwrap(), values);
+}
+
+#[test]
+#[cfg(feature = "python")]
+fn test_python_module() {
+	use pyo3::prelude::*;
+	use pyo3::types::PyDict;
+
+	Python::initialize();
+	Python::attach(|py| {
+		let module = PyModule::new(py, "vlen").unwrap();
+		vlen::python::python_module(&module).unwrap();
+		let locals = PyDict::new(py);
+		locals.set_item("vlen", module).unwrap();
+		py.run(
+			c"
+import array
+assert vlen.encode_u64(300) == b'\\xac\\x04'
+assert vlen.decode_u64(b'\\xac\\x04\\xff') == (300, 2)
+data = vlen.bulk_encode_u32(array.array('I', [1, 300, 70000]))
+assert data == b'\\x01\\xac\\x04\\xd0\\x8b\\x08'
+assert list(array.array('I', vlen.bulk_decode_u32(data))) == [1, 300, 70000]
+try:
+    vlen.decode_u64(b'\\xac')
+    raise AssertionError('expected ValueError')
+except ValueError:
+    pass
+",
+			None,
+			Some(&locals),
+		)
+		.unwrap();
+	});
+}
+
+#[test]
+#[cfg(feature = "arbitrary")]
+fn test_arbitrary_buffers() {
+	use arbitrary::{Arbitrary, Unstructured};
+	use vlen::arbitrary::{TruncatedBuffer, ValidBuffer};
+
+	fn check<T>(data: &[u8])
+	where
+		T: for<'a> Arbitrary<'a>
+			+ vlen::Encode
+			+ vlen::Decode
+			+ Copy
+			+ Default
+			+ PartialEq
+			+ fmt::Debug,
+	{
+		let mut u = Unstructured::new(data);
+		let valid = ValidBuffer::<T>::arbitrary(&mut u).unwrap();
+		let decoded = vlen::bulk_decode_values::<T>(&valid.bytes);
+		assert_eq!(decoded, Ok(valid.values));
+
+		let truncated = TruncatedBuffer::<T>::arbitrary(&mut u).unwrap();
+		let count = truncated.values.len();
+		let mut values = vec![T::default(); count + 1];
+		assert!(vlen::bulk_decode(&truncated.bytes, &mut values).is_err());
+		assert_eq!(values[..count], truncated.values);
+	}
+
+	let mut data = [0u8; 512];
+	for seed in 0..64u32 {
+		for (i, byte) in data.iter_mut().enumerate() {
+			*byte = (i as u32).wrapping_mul(seed * 2 + 1) as u8 ^ seed as u8;
+		}
+		check::<u16>(&data);
+		check::<u32>(&data);
+		check::<u64>(&data);
+		check::<u128>(&data);
+		check::<i32>(&data);
+		check::<i64>(&data);
+	}
+}
+
+#[test]
+#[cfg(feature = "rkyv")]
+fn test_rkyv_wrappers() {
+	use rkyv::rancor::Error;
+	use vlen::rkyv::ArchivedVlen;
+	use vlen::wrappers::{VlenF64, VlenI64, VlenU128, VlenU32};
+
+	#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+	#[derive(Debug, PartialEq)]
+	struct Record {
+		id: VlenU32,
+		delta: VlenI64,
+		total: VlenU128,
+		score: VlenF64,
+	}
+
+	let record = Record {
+		id: VlenU32(12345),
+		delta: VlenI64(-2),
+		total: VlenU128(u128::MAX),
+		score: VlenF64(1.5),
+	};
+	let bytes = rkyv::to_bytes::<Error>(&record).unwrap();
+	let archived = rkyv::access::<ArchivedRecord, Error>(&bytes).unwrap();
+	assert_eq!(archived.id.get(), Ok(12345));
+	assert_eq!(archived.id.as_bytes().len(), 2);
+	assert_eq!(archived.delta.get(), Ok(-2));
+	assert_eq!(archived.total.get(), Ok(u128::MAX));
+	assert_eq!(archived.score.get(), Ok(1.5));
+	assert_eq!(rkyv::from_bytes::<Record, Error>(&bytes).unwrap(), record);
+
+	// Small values archive to a single byte of payload.
+	let bytes = rkyv::to_bytes::<Error>(&VlenU32(7)).unwrap();
+	let archived = rkyv::access::<ArchivedVlen<u32>, Error>(&bytes).unwrap();
+	assert_eq!(archived.as_bytes(), [7]);
+}
+
+#[test]
+#[cfg(feature = "borsh")]
+fn test_borsh_wrappers() {
+	use vlen::wrappers::{VlenF64, VlenI64, VlenU128, VlenU16, VlenU32};
+
+	let record = (
+		VlenU16(7),
+		VlenU32(70000),
+		VlenI64(-2),
+		VlenU128(u128::MAX),
+		VlenF64(1.5),
+	);
+	let bytes = borsh::to_vec(&record).unwrap();
+	let expected = [
+		vlen::encode_to_vec(7u16).unwrap(),
+		vlen::encode_to_vec(70000u32).unwrap(),
+		vlen::encode_to_vec(-2i64).unwrap(),
+		vlen::encode_to_vec(u128::MAX).unwrap(),
+		vlen::encode_to_vec(1.5f64).unwrap(),
+	]
+	.concat();
+	assert_eq!(bytes, expected);
+	type Record = (VlenU16, VlenU32, VlenI64, VlenU128, VlenF64);
+	assert_eq!(borsh::from_slice::<Record>(&bytes).unwrap(), record);
+
+	// A value cut short is an error rather than a partial read.
+	assert!(borsh::from_slice::<VlenU32>(&[0xC1, 0x11]).is_err());
+}
+
+#[test]
+#[cfg(all(feature = "bincode", feature = "alloc"))]
+fn test_bincode_wrappers() {
+	use bincode::config;
+	use vlen::wrappers::{VlenF64, VlenI64, VlenU128, VlenU16, VlenU32};
+
+	type Record = (VlenU16, VlenU32, VlenI64, VlenU128, VlenF64);
+	let record: Record = (
+		VlenU16(7),
+		VlenU32(70000),
+		VlenI64(-2),
+		VlenU128(u128::MAX),
+		VlenF64(1.5),
+	);
+	let expected = [
+		vlen::encode_to_vec(7u16).unwrap(),
+		vlen::encode_to_vec(70000u32).unwrap(),
+		vlen::encode_to_vec(-2i64).unwrap(),
+		vlen::encode_to_vec(u128::MAX).unwrap(),
+		vlen::encode_to_vec(1.5f64).unwrap(),
+	]
+	.concat();
+
+	// The wrappers ignore the configured integer encoding.
+	let mut buf = [0u8; 64];
+	let len =
+		bincode::encode_into_slice(record, &mut buf, config::standard()).unwrap();
+	assert_eq!(buf[..len], expected);
+	let fixint = config::standard().with_fixed_int_encoding();
+	let len = bincode::encode_into_slice(record, &mut buf, fixint).unwrap();
+	assert_eq!(buf[..len], expected);
+	let (decoded, read): (Record, usize) =
+		bincode::decode_from_slice(&buf[..len], config::standard()).unwrap();
+	assert_eq!((decoded, read), (record, len));
+
+	// A value cut short is an error rather than a partial read.
+	let truncated = bincode::decode_from_slice::<VlenU32, _>(
+		&[0xC1, 0x11],
+		config::standard(),
+	);
+	assert!(truncated.is_err());
+}
+
+#[test]
+#[cfg(feature = "arrow")]
+fn test_arrow_arrays() {
+	use arrow_array::{Array, UInt32Array, UInt64Array};
+	use vlen::arrow::{
+		decode_u32_array, decode_u64_array, encode_u32_array, encode_u64_array,
+		NO_NULLS, NULLABLE,
+	};
+
+	let array = UInt32Array::from(vec![0, 1, 300, u32::MAX]);
+	let buf = encode_u32_array(&array).unwrap();
+	assert_eq!(buf[0], NO_NULLS);
+	assert_eq!(buf[1..], vlen::bulk_encode_to_vec(array.values()).unwrap());
+	assert_eq!(decode_u32_array(&buf).unwrap(), array);
+
+	let array = UInt32Array::from(vec![Some(0), None, Some(u32::MAX), None]);
+	let buf = encode_u32_array(&array).unwrap();
+	assert_eq!(buf[0], NULLABLE);
+	let decoded = decode_u32_array(&buf).unwrap();
+	assert_eq!(decoded, array);
+	assert_eq!(decoded.null_count(), 2);
+
+	let array = UInt64Array::from(vec![Some(u64::MAX), None, Some(7)]);
+	let buf = encode_u64_array(&array).unwrap();
+	assert_eq!(decode_u64_array(&buf).unwrap(), array);
+
+	// A nullable slot that does not fit the target type is rejected.
+	let mut buf = vec![NULLABLE];
+	vlen::encode_append(&mut buf, u64::from(u32::MAX) + 2).unwrap();
+	assert!(decode_u32_array(&buf).is_err());
+	assert!(decode_u32_array(&[]).is_err());
+	assert!(decode_u32_array(&[2]).is_err());
+}
+
+#[test]
+fn test_bulk_opt_u32() {
+	let values =
+		[Some(1), None, Some(300), None, None, None, None, None, Some(0)];
+	let mut buf = [0u8; 16];
+	let len = vlen::bulk_encode_opt_u32(&mut buf, &values).unwrap();
+	// Two bitmap bytes, then 1, 300 and 0.
+	assert_eq!(buf[..len], [0b0000_0101, 0b0000_0001, 0x01, 0xac, 0x04, 0x00]);
+
+	let mut decoded = [Some(7); 9];
+	assert_eq!(vlen::bulk_decode_opt_u32(&buf[..len], &mut decoded), Ok(len));
+	assert_eq!(decoded, values);
+
+	// All-absent columns are just the bitmap.
+	assert_eq!(vlen::bulk_encode_opt_u32(&mut buf, &[None; 3]), Ok(1));
+	assert_eq!(buf[0], 0);
+
+	assert!(vlen::bulk_encode_opt_u32(&mut buf[..2], &values).is_err());
+	assert!(vlen::bulk_decode_opt_u32(&buf[..1], &mut decoded).is_err());
+	assert!(vlen::bulk_decode_opt_u32(&[0b1, 0b0], &mut decoded).is_err());
+}
+
+#[test]
+fn test_sortedset() {
+	use vlen::sortedset;
+
+	fn encode(set: &[u64]) -> ([u8; 128], usize) {
+		let mut buf = [0u8; 128];
+		let len = sortedset::encode(&mut buf, set).unwrap();
+		(buf, len)
+	}
+
+	fn decode(buf: &[u8]) -> Vec<u64> {
+		sortedset::iter(buf).collect::<Result<_, _>>().unwrap()
+	}
+
+	let a_set = [0, 1, 2, 10, 300, 70000, u64::MAX];
+	let b_set = [1, 3, 10, 299, 70000, 1 << 40];
+	let (a, a_len) = encode(&a_set);
+	let (b, b_len) = encode(&b_set);
+	let (a, b) = (&a[..a_len], &b[..b_len]);
+	// Consecutive elements take a single zero byte.
+	assert_eq!(a[..3], [0, 0, 0]);
+	assert_eq!(decode(a), a_set);
+
+	let mut out = [0u8; 256];
+	let len = sortedset::union::<u64>(a, b, &mut out).unwrap();
+	assert!(len <= a.len() + b.len());
+	let expected = [0, 1, 2, 3, 10, 299, 300, 70000, 1 << 40, u64::MAX];
+	assert_eq!(decode(&out[..len]), expected);
+
+	let len = sortedset::intersect::<u64>(a, b, &mut out).unwrap();
+	assert_eq!(decode(&out[..len]), [1, 10, 70000]);
+	assert_eq!(sortedset::intersect::<u64>(a, &[], &mut out), Ok(0));
+	assert_eq!(sortedset::union::<u64>(&[], &[], &mut out), Ok(0));
+
+	assert_eq!(sortedset::contains(a, 300u64), Ok(true));
+	assert_eq!(sortedset::contains(a, 301u64), Ok(false));
+	assert_eq!(sortedset::contains(a, u64::MAX), Ok(true));
+	assert_eq!(sortedset::contains(&[], 0u64), Ok(false));
+
+	// Outputs only need room for the actual encoding.
+	let mut exact = [0u8; 4];
+	assert_eq!(sortedset::encode::<u32>(&mut exact, &[1, 2, 3, 4]), Ok(4));
+	assert!(sortedset::encode::<u32>(&mut exact[..3], &[1, 2, 3, 4]).is_err());
+	let mut out = vec![0u8; a.len() + b.len()];
+	let len = sortedset::union::<u64>(a, b, &mut out).unwrap();
+	assert_eq!(decode(&out[..len]), expected);
+	let mut out = vec![0u8; 1 + b.len()];
+	let len = sortedset::union::<u64>(&[0], b, &mut out).unwrap();
+	assert_eq!(len, 1 + b.len());
+
+	let mut buf = [0u8; 16];
+	assert!(sortedset::encode::<u32>(&mut buf, &[1, 1]).is_err());
+	assert!(sortedset::encode::<u32>(&mut buf, &[2, 1]).is_err());
+	assert!(sortedset::encode::<u32>(&mut buf[..1], &[1, 2]).is_err());
+
+	// A gap that overflows the element type is an error.
+	let len = sortedset::encode::<u32>(&mut buf, &[u32::MAX]).unwrap();
+	buf[len] = 0;
+	let mut iter = sortedset::iter::<u32>(&buf[..=len]);
+	assert_eq!(iter.next(), Some(Ok(u32::MAX)));
+	assert!(iter.next().unwrap().is_err());
+	assert_eq!(iter.next(), None);
+	// Lookups stop before reaching the bad gap.
+	assert_eq!(sortedset::contains(&buf[..=len], 0u32), Ok(false));
+}
+
+#[test]
+fn test_blocks() {
+	use vlen::blocks::{self, BlockHeader};
+
+	let values: Vec<i64> = (0..250).map(|i| (i * 37 % 101) - 50).collect();
+	let mut buf = vec![0u8; 2048];
+	let len = blocks::encode(&mut buf, &values, 100).unwrap();
+	let buf = &buf[..len];
+
+	let mut decoded = Vec::new();
+	let mut counts = Vec::new();
+	for (block, chunk) in blocks::blocks::<i64>(buf).zip(values.chunks(100)) {
+		let block = block.unwrap();
+		let BlockHeader { count, len, min, max } = block.header;
+		assert_eq!(len, block.data.len());
+		assert_eq!(min, *chunk.iter().min().unwrap());
+		assert_eq!(max, *chunk.iter().max().unwrap());
+		counts.push(count);
+		decoded.extend(block.values().map(Result::unwrap));
+	}
+	assert_eq!(counts, [100, 100, 50]);
+	assert_eq!(decoded, values);
+
+	assert_eq!(blocks::blocks::<i64>(&[]).count(), 0);
+	assert_eq!(blocks::encode::<i64>(&mut [], &[], 8), Ok(0));
+	assert!(blocks::encode(&mut [0u8; 64], &values[..4], 0).is_err());
+	assert!(blocks::encode(&mut [0u8; 64], &values, 100).is_err());
+
+	// A block whose values are cut short is an error, then iteration stops.
+	let mut iter = blocks::blocks::<i64>(&buf[..len - 1]);
+	assert!(iter.next().unwrap().is_ok());
+	assert!(iter.next().unwrap().is_ok());
+	assert!(iter.next().unwrap().is_err());
+	assert!(iter.next().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_index_seek() {
+	use vlen::index::Index;
+
+	let values: Vec<u32> = (0..1000).map(|i| i * 997 % 70001).collect();
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+
+	for every_n in [1, 7, 64, 1000, 5000] {
+		let index = Index::<u32>::build(&buf, every_n).unwrap();
+		assert_eq!(index.len(), values.len());
+		for n in [0, 1, 6, 7, 8, 500, 998, 999] {
+			let offset = index.seek(&buf, n).unwrap();
+			let value = vlen::decode_value::<u32>(&buf[offset..]);
+			assert_eq!(value, Ok(values[n]));
+		}
+		assert_eq!(index.seek(&buf, 1000), Ok(buf.len()));
+		assert!(index.seek(&buf, 1001).is_err());
+
+		let reloaded = Index::from_bytes(index.as_bytes().to_vec()).unwrap();
+		assert_eq!(reloaded, index);
+	}
+
+	let index = Index::<u32>::build(&[], 4).unwrap();
+	assert!(index.is_empty());
+	assert_eq!(index.seek(&[], 0), Ok(0));
+
+	assert!(Index::<u32>::build(&buf, 0).is_err());
+	assert!(Index::<u32>::build(&buf[..buf.len() - 1], 8).is_err());
+	assert!(Index::<u32>::from_bytes(vec![8, 100, 200]).is_err());
+
+	// Crafted sample gaps that sum past `usize::MAX` fail cleanly.
+	let mut crafted = vlen::bulk_encode_to_vec(&[1u64, 3, 0]).unwrap();
+	vlen::bulk_encode_append(&mut crafted, &[0, u64::MAX, u64::MAX]).unwrap();
+	let index = Index::<u32>::from_bytes(crafted).unwrap();
+	assert_eq!(index.seek(&buf, 2), Err("index offset out of range"));
+}
+
+#[test]
+fn test_rev_u64() {
+	use vlen::rev::{decode_u64_rev_from_end, encode_u64_rev, rev_iter_u64};
+
+	let values = [0, 1, 127, 128, 300, 70000, 1 << 40, u64::MAX];
+	let mut log = Vec::new();
+	for value in values {
+		let mut buf = [0u8; 9];
+		let len = encode_u64_rev(&mut buf, value);
+		assert_eq!(len, vlen::encoded_size(value).unwrap());
+		assert_eq!(decode_u64_rev_from_end(&buf[..len]), Ok((value, len)));
+		log.extend_from_slice(&buf[..len]);
+	}
+
+	let decoded: Result<Vec<u64>, _> = rev_iter_u64(&log).collect();
+	let mut expected = values.to_vec();
+	expected.reverse();
+	assert_eq!(decoded.unwrap(), expected);
+
+	// A buffer that starts partway through a value yields an error last.
+	let mut iter = rev_iter_u64(&[0x80, 0x01]);
+	assert_eq!(iter.next(), Some(Ok(1)));
+	assert!(iter.next().unwrap().is_err());
+	assert!(iter.next().is_none());
+	assert!(decode_u64_rev_from_end(&[]).is_err());
+	assert!(decode_u64_rev_from_end(&[0xFF]).is_err());
+}
+
+#[test]
+fn test_crc32c() {
+	use vlen::checksum::Crc32c;
+
+	assert_eq!(Crc32c::checksum(b""), 0);
+	assert_eq!(Crc32c::checksum(b"123456789"), 0xE306_9283);
+	assert_eq!(Crc32c::checksum(&[0u8; 32]), 0x8A91_36AA);
+
+	let mut crc = Crc32c::default();
+	for chunk in b"123456789".chunks(2) {
+		crc.update(chunk);
+	}
+	assert_eq!(crc.finish(), 0xE306_9283);
+}
+
+#[test]
+#[cfg(feature = "embedded-io")]
+fn test_embedded_io_checksum() {
+	use vlen::checksum::Crc32c;
+	use vlen::embedded_io::{
+		ChecksumReader, ChecksumWriter, VlenIoError, VlenRead, VlenWrite,
+	};
+
+	let mut buf = [0u8; 32];
+	let mut writer = ChecksumWriter::new(&mut buf[..]);
+	writer.write_vlen_u64(70000).unwrap();
+	writer.write_vlen(-3i32).unwrap();
+	let crc = writer.checksum();
+	let remaining = writer.finish().unwrap().len();
+	let len = buf.len() - remaining;
+
+	let payload_len = vlen::encoded_size(70000u64).unwrap() + 1;
+	assert_eq!(crc, Crc32c::checksum(&buf[..payload_len]));
+	assert_eq!(vlen::decode_value::<u32>(&buf[payload_len..len]), Ok(crc));
+
+	let mut reader = ChecksumReader::new(&buf[..len]);
+	assert_eq!(reader.read_vlen_u64().unwrap(), 70000);
+	assert_eq!(reader.read_vlen::<i32>().unwrap(), -3);
+	assert!(reader.verify().unwrap().is_empty());
+
+	// A corrupted payload byte is caught by the trailer.
+	buf[0] ^= 1;
+	let mut reader = ChecksumReader::new(&buf[..len]);
+	reader.read_vlen_u64().unwrap();
+	reader.read_vlen::<i32>().unwrap();
+	assert!(matches!(
+		reader.verify(),
+		Err(VlenIoError::Vlen("checksum mismatch"))
+	));
+}
+
+#[test]
+fn test_decode_checked() {
+	let mut buf = [0u8; 17];
+
+	// A u64 that does not fit in a u32 has a prefix `decode` rejects and a
+	// value `decode_checked` rejects.
+	let len = vlen::encode(&mut buf, 1u64 << 40).unwrap();
+	assert_eq!(
+		vlen::decode::<u32>(&buf[..len]),
+		Err("invalid vlen prefix byte")
+	);
+	assert!(vlen::decode_checked::<u32>(&buf[..len]).is_err());
+	assert!(vlen::decode_checked::<i32>(&buf[..len]).is_err());
+	assert!(vlen::decode_checked::<f32>(&buf[..len]).is_err());
+	assert_eq!(vlen::decode_checked::<u64>(&buf[..len]), Ok((1 << 40, len)));
+
+	let len = vlen::encode(&mut buf, u128::MAX).unwrap();
+	assert!(vlen::decode_checked::<u64>(&buf[..len]).is_err());
+	assert_eq!(vlen::decode_checked::<u128>(&buf[..len]), Ok((u128::MAX, len)));
+
+	// Prefixes wider than the type are rejected even if the high bytes are
+	// zero, exactly as `decode` rejects them.
+	let invalid = "invalid vlen prefix byte";
+	let overlong = [0xF7, 5, 0, 0, 0, 0, 0, 0, 0];
+	assert_eq!(vlen::decode_checked::<u32>(&overlong), Err(invalid));
+	assert_eq!(vlen::decode_checked::<u32>(&overlong[..8]), Err(invalid));
+	assert_eq!(vlen::decode_checked::<i32>(&overlong), Err(invalid));
+	assert_eq!(vlen::decode_checked::<u16>(&[0xC2, 5, 0, 0]), Err(invalid));
+	assert_eq!(vlen::decode_checked::<u64>(&overlong), Ok((5, 9)));
+
+	// Every canonical encoding passes the check.
+	for value in [0u32, 127, 128, 300, 70000, u32::MAX] {
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(vlen::decode_checked(&buf[..len]), Ok((value, len)));
+	}
+	for value in [0u16, 127, 128, 16383, 16384, u16::MAX] {
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(vlen::decode_checked(&buf[..len]), Ok((value, len)));
+	}
+}
+
+#[test]
+fn test_decode_fit() {
+	let mut buf = [0u8; 17];
+
+	let len = vlen::encode(&mut buf, 70000u64).unwrap();
+	assert_eq!(vlen::decode_fit::<u32>(&buf[..len]), Ok((70000, len)));
+	assert!(vlen::decode_fit::<u16>(&buf[..len]).is_err());
+
+	let len = vlen::encode(&mut buf, u64::from(u32::MAX) + 1).unwrap();
+	assert!(vlen::decode_fit::<u32>(&buf[..len]).is_err());
+	assert_eq!(
+		vlen::decode_fit::<u64>(&buf[..len]),
+		Ok((u64::from(u32::MAX) + 1, len))
+	);
+
+	let len = vlen::encode(&mut buf, u128::MAX).unwrap();
+	assert_eq!(vlen::decode_fit::<u128>(&buf[..len]), Ok((u128::MAX, len)));
+	assert!(vlen::decode_fit::<u64>(&buf[..len]).is_err());
+
+	for value in [0i64, -1, 1, i64::from(i32::MIN), i64::from(i32::MAX)] {
+		let len = vlen::encode(&mut buf, value).unwrap();
+		let expected = (value as i32, len);
+		assert_eq!(vlen::decode_fit::<i32>(&buf[..len]), Ok(expected));
+	}
+	let len = vlen::encode(&mut buf, i64::from(i32::MIN) - 1).unwrap();
+	assert!(vlen::decode_fit::<i32>(&buf[..len]).is_err());
+	let len = vlen::encode(&mut buf, -40000i32).unwrap();
+	assert!(vlen::decode_fit::<i16>(&buf[..len]).is_err());
+	assert!(vlen::decode_fit::<u32>(&[]).is_err());
+}
+
+#[test]
+fn test_validate() {
+	use vlen::ValidationError;
+
+	let mut buf = [0u8; 64];
+	let values = [0u64, 127, 128, 300, 70000, 1 << 40, u64::MAX];
+	let len = vlen::bulk_encode(&mut buf, &values).unwrap();
+	let report = vlen::validate_canonical::<u64>(&buf[..len]).unwrap();
+	assert_eq!(report.values, values.len());
+	assert_eq!(report.non_canonical, 0);
+	assert_eq!(vlen::validate::<u64>(&[]).unwrap().values, 0);
+
+	// Truncated tail.
+	let err = vlen::validate::<u64>(&buf[..len - 1]).unwrap_err();
+	assert_eq!(err.values, values.len() - 1);
+	assert_eq!(err.offset, len - 9);
+
+	// An overlong encoding of 5 is valid but not canonical.
+	let overlong = [0x01, 0xF7, 5, 0, 0, 0, 0, 0, 0, 0, 0x02];
+	let report = vlen::validate::<u64>(&overlong).unwrap();
+	assert_eq!(report.values, 3);
+	assert_eq!(report.non_canonical, 1);
+	let err = vlen::validate_canonical::<u64>(&overlong).unwrap_err();
+	assert_eq!(
+		err,
+		ValidationError {
+			offset: 1,
+			values: 1,
+			reason: "non-canonical vlen encoding",
+		}
+	);
+
+	// Prefixes longer than the type allows are illegal, whatever the value.
+	assert!(vlen::validate::<u32>(&[0xF7, 5, 0, 0, 0, 0, 0, 0, 0]).is_err());
+	let len = vlen::encode(&mut buf, 1u64 << 40).unwrap();
+	let err = vlen::validate::<u32>(&buf[..len]).unwrap_err();
+	assert_eq!(err.reason, "invalid vlen prefix byte");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_decoder_policy() {
+	use vlen::Decoder;
+
+	let mut buf = [0u8; 32];
+	let len = vlen::bulk_encode(&mut buf, &[1u64, 300, 70000]).unwrap();
+	let buf = &buf[..len];
+
+	let mut values = [0u64; 4];
+	assert_eq!(Decoder::new().decode_into(buf, &mut values), Ok((3, len)));
+	assert_eq!(values[..3], [1, 300, 70000]);
+	let mut values = [0u64; 2];
+	assert_eq!(Decoder::new().decode_into(buf, &mut values), Ok((2, 3)));
+
+	// Value limit.
+	let limited = Decoder::new().max_values(2);
+	let mut iter = limited.decode_iter::<u64>(buf);
+	assert_eq!(iter.next(), Some(Ok(1)));
+	assert_eq!(iter.next(), Some(Ok(300)));
+	assert!(iter.next().unwrap().is_err());
+	assert_eq!(iter.next(), None);
+	let mut iter = Decoder::new().max_values(3).decode_iter::<u64>(buf);
+	assert!(iter.all(|v| v.is_ok()));
+
+	// Truncated tail.
+	let cut = &buf[..len - 1];
+	let mut iter = Decoder::new().decode_iter::<u64>(cut);
+	assert_eq!(iter.by_ref().filter(Result::is_err).count(), 1);
+	let lenient = Decoder::new().allow_truncated_tail(true);
+	let mut iter = lenient.decode_iter::<u64>(cut);
+	assert_eq!(iter.by_ref().collect::<Result<Vec<_>, _>>(), Ok(vec![1, 300]));
+	assert_eq!(iter.remaining(), &buf[3..len - 1]);
+
+	// Overlong encodings and out-of-range values.
+	let overlong = [0xF7, 5, 0, 0, 0, 0, 0, 0, 0];
+	assert_eq!(Decoder::new().decode_all::<u64>(&overlong), Ok(vec![5]));
+	let strict = Decoder::new().reject_overlong(true);
+	assert!(strict.decode_all::<u64>(&overlong).is_err());
+	assert!(strict.decode_all::<u64>(buf).is_ok());
+	let wide = vlen::encode_to_vec(1u64 << 40).unwrap();
+	assert!(Decoder::new().decode_all::<u32>(&wide).is_err());
+}
+
+#[test]
+fn test_vlen_cursor() {
+	use vlen::VlenCursor;
+
+	let mut buf = [0u8; 64];
+	let mut len = vlen::encode(&mut buf, 7u16).unwrap();
+	len += vlen::encode(&mut buf[len..], 70000u32).unwrap();
+	len += vlen::encode(&mut buf[len..], -300i64).unwrap();
+	len += vlen::encode(&mut buf[len..], u128::MAX).unwrap();
+	len += vlen::encode(&mut buf[len..], 2.5f32).unwrap();
+	let buf = &buf[..len];
+
+	let mut cursor = VlenCursor::new(buf);
+	assert_eq!(cursor.read_u16(), Ok(7));
+	assert_eq!(cursor.position(), 1);
+	assert_eq!(cursor.read_u32(), Ok(70000));
+	assert_eq!(cursor.read_i64(), Ok(-300));
+	let before = cursor.position();
+	let skipped = cursor.skip_value::<u128>().unwrap();
+	assert_eq!(cursor.position(), before + skipped);
+	assert_eq!(cursor.remaining().len(), len - cursor.position());
+	assert_eq!(cursor.read_f32(), Ok(2.5));
+	assert!(cursor.is_empty());
+	assert!(cursor.read_u32().is_err());
+
+	// A failed read leaves the position unchanged.
+	let mut cursor = VlenCursor::new(&buf[..2]);
+	assert_eq!(cursor.read::<u16>(), Ok(7));
+	assert!(cursor.read_u32().is_err());
+	assert_eq!(cursor.position(), 1);
+	assert_eq!(cursor.remaining(), &buf[1..2]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_vlen_buf() {
+	use vlen::{VlenBuf, VlenCursor};
+
+	let mut buf = VlenBuf::with_capacity(4);
+	assert!(buf.is_empty());
+	assert_eq!(buf.push_u32(300), Ok(2));
+	assert_eq!(buf.push_i64(-1), Ok(1));
+	buf.push_f64(0.25).unwrap();
+	assert_eq!(buf.push_bytes(b""), Ok(1));
+	assert_eq!(buf.push_bytes(&[0xAB; 200]), Ok(202));
+	buf.push(u128::MAX).unwrap();
+	assert_eq!(buf.len_values(), 6);
+	assert_eq!(buf.len(), buf.as_slice().len());
+
+	let mut cursor = VlenCursor::new(buf.as_slice());
+	assert_eq!(cursor.read_u32(), Ok(300));
+	assert_eq!(cursor.read_i64(), Ok(-1));
+	assert_eq!(cursor.read_f64(), Ok(0.25));
+	assert_eq!(cursor.read_bytes(), Ok(&[][..]));
+	assert_eq!(cursor.read_bytes(), Ok(&[0xAB; 200][..]));
+	assert_eq!(cursor.read_u128(), Ok(u128::MAX));
+	assert!(cursor.is_empty());
+
+	// A byte string cut short is not consumed.
+	let bytes = buf.as_slice();
+	let mut cursor = VlenCursor::new(&bytes[..bytes.len() - 30]);
+	cursor.skip_value::<u32>().unwrap();
+	cursor.skip_value::<i64>().unwrap();
+	cursor.skip_value::<f64>().unwrap();
+	cursor.read_bytes().unwrap();
+	let position = cursor.position();
+	assert!(cursor.read_bytes().is_err());
+	assert_eq!(cursor.position(), position);
+
+	let vec: Vec<u8> = buf.clone().into();
+	assert_eq!(vec, buf.clone().into_vec());
+	buf.clear();
+	assert_eq!(buf.len_values(), 0);
+	assert!(buf.is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_edit_values() {
+	let mut buf = vlen::bulk_encode_to_vec(&[5u64, 300, 7]).unwrap();
+
+	// Growing, shrinking and same-length replacements shift the tail.
+	assert_eq!(vlen::replace_value(&mut buf, 0, u64::MAX), Ok(5));
+	assert_eq!(vlen::replace_value(&mut buf, 1, 1u64), Ok(300));
+	assert_eq!(vlen::replace_value(&mut buf, 2, 8u64), Ok(7));
+	assert_eq!(
+		vlen::bulk_decode_values::<u64>(&buf).unwrap(),
+		[u64::MAX, 1, 8]
+	);
+
+	assert_eq!(vlen::insert_value(&mut buf, 3, 300u64), Ok(2));
+	assert_eq!(vlen::insert_value(&mut buf, 0, 0u64), Ok(1));
+	assert_eq!(vlen::remove_value::<u64>(&mut buf, 1), Ok(u64::MAX));
+	assert_eq!(
+		vlen::bulk_decode_values::<u64>(&buf).unwrap(),
+		[0, 1, 8, 300]
+	);
+
+	let before = buf.clone();
+	assert!(vlen::replace_value(&mut buf, 4, 1u64).is_err());
+	assert!(vlen::remove_value::<u64>(&mut buf, 4).is_err());
+	assert!(vlen::insert_value(&mut buf, 5, 1u64).is_err());
+	assert_eq!(buf, before);
+
+	while !buf.is_empty() {
+		vlen::remove_value::<u64>(&mut buf, 0).unwrap();
+	}
+	assert_eq!(vlen::insert_value(&mut buf, 0, -3i32), Ok(1));
+	assert_eq!(vlen::decode_value::<i32>(&buf), Ok(-3));
+}
+
+#[test]
+fn test_protobuf_interop() {
+	use integer_encoding::VarInt;
+	use vlen::interop::protobuf::*;
+
+	let samples = [
+		0u64,
+		1,
+		127,
+		128,
+		150,
+		16383,
+		16384,
+		u32::MAX as u64,
+		1 << 55,
+		(1 << 56) - 1,
+		1 << 56,
+		1 << 63,
+		u64::MAX,
+	];
+	for &value in &samples {
+		let mut buf = [0u8; MAX_VARINT_LEN];
+		let len = encode_varint(&mut buf, value);
+		assert_eq!(buf[..len], value.encode_var_vec()[..], "{value}");
+		assert_eq!(varint_len(value), len);
+
+		// Decode through both the word-at-a-time and bytewise paths.
+		let mut padded = [0xFFu8; 16];
+		padded[..len].copy_from_slice(&buf[..len]);
+		assert_eq!(decode_varint(&padded), Ok((value, len)));
+		assert_eq!(decode_varint(&buf[..len]), Ok((value, len)));
+		assert!(decode_varint(&buf[..len - 1]).is_err());
+	}
+	assert!(decode_varint(&[0xFF; 9]).is_err());
+	let mut overflow = [0xFFu8; MAX_VARINT_LEN];
+	overflow[9] = 0x02;
+	assert!(decode_varint(&overflow).is_err());
+	assert!(decode_varint(&[0x80; 12]).is_err());
+
+	for value in [0i64, -1, 1, i64::MIN, i64::MAX] {
+		assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+	}
+	assert_eq!(zigzag_encode(-1), 1);
+	assert_eq!(zigzag_encode(1), 2);
+
+	// A message with a varint, a string and a fixed32 field.
+	let mut message = [0u8; 32];
+	let mut len = encode_varint_field(&mut message, 1, 150).unwrap();
+	let mut tag = [0u8; 5];
+	let tag_len = encode_tag(&mut tag, 2, WireType::Len).unwrap();
+	message[len..len + tag_len].copy_from_slice(&tag[..tag_len]);
+	len += tag_len;
+	message[len..len + 4].copy_from_slice(&[3, b'a', b'b', b'c']);
+	len += 4;
+	let tag_len =
+		encode_tag(&mut tag, MAX_FIELD_NUMBER, WireType::I32).unwrap();
+	assert_eq!(tag_len, 5);
+	message[len..len + tag_len].copy_from_slice(&tag[..tag_len]);
+	len += tag_len + 4;
+	assert_eq!(message[..3], [0x08, 0x96, 0x01]);
+
+	let mut offset = 0;
+	let mut fields = [0u32; 3];
+	for slot in &mut fields {
+		let (field, wire_type, tag_len) =
+			decode_tag(&message[offset..]).unwrap();
+		offset += tag_len;
+		offset += skip_field(&message[offset..len], wire_type).unwrap();
+		*slot = field;
+	}
+	assert_eq!(fields, [1, 2, MAX_FIELD_NUMBER]);
+	assert_eq!(offset, len);
+	assert!(skip_field(&message[..3], WireType::I64).is_err());
+	assert!(skip_field(&[0x05, 0x00], WireType::Len).is_err());
+	assert!(skip_field(&[], WireType::StartGroup).is_err());
+
+	assert!(encode_tag(&mut tag, 0, WireType::Varint).is_err());
+	assert!(
+		encode_tag(&mut tag, MAX_FIELD_NUMBER + 1, WireType::Varint).is_err()
+	);
+	assert!(decode_tag(&[0x00]).is_err());
+	assert!(decode_tag(&[0x0E]).is_err());
+	assert_eq!(WireType::try_from(5), Ok(WireType::I32));
+	assert!(WireType::try_from(6).is_err());
+	assert!(encode_varint_field(&mut [0u8; 2], 1, 150).is_err());
+
+	// Packed repeated field payloads.
+	let mut packed = [0u8; 128];
+	let packed_len = bulk_encode_varints(&mut packed, &samples).unwrap();
+	let mut values = [0u64; 16];
+	assert_eq!(
+		bulk_decode_varints(&packed[..packed_len], &mut values),
+		Ok((samples.len(), packed_len))
+	);
+	assert_eq!(values[..samples.len()], samples);
+	assert!(bulk_encode_varints(&mut packed[..4], &samples).is_err());
+
+	// Packed payloads convert to vlen and back without loss.
+	let mut vlen_buf = [0u8; 128];
+	let vlen_len =
+		bulk_varint_to_vlen(&packed[..packed_len], &mut vlen_buf).unwrap();
+	assert_eq!(
+		vlen::bulk_decode_values::<u64>(&vlen_buf[..vlen_len]).unwrap(),
+		samples
+	);
+	let mut back = [0u8; 128];
+	assert_eq!(
+		bulk_vlen_to_varint(&vlen_buf[..vlen_len], &mut back),
+		Ok(packed_len)
+	);
+	assert_eq!(back[..packed_len], packed[..packed_len]);
+	assert!(bulk_varint_to_vlen(&packed[..packed_len], &mut [0u8; 4]).is_err());
+	assert!(bulk_varint_to_vlen(&[0x96], &mut vlen_buf).is_err());
+
+	let mut out = [0u8; 9];
+	assert_eq!(varint_to_vlen(&[0x96, 0x01], &mut out), Ok((2, 2)));
+	assert_eq!(vlen::decode_value::<u64>(&out), Ok(150));
+	let mut out = [0u8; MAX_VARINT_LEN];
+	assert_eq!(vlen_to_varint(&vlen_buf[..vlen_len], &mut out), Ok((1, 1)));
+}
+
+#[test]
+fn test_git_offset_interop() {
+	use vlen::interop::git::*;
+
+	// Boundaries where the encoding gains a byte.
+	let cases: [(u64, &[u8]); 5] = [
+		(0, &[0x00]),
+		(127, &[0x7F]),
+		(128, &[0x80, 0x00]),
+		(16511, &[0xFF, 0x7F]),
+		(16512, &[0x80, 0x80, 0x00]),
+	];
+	for (value, encoded) in cases {
+		let mut buf = [0u8; MAX_OFFSET_LEN];
+		let len = encode_offset(&mut buf, value);
+		assert_eq!(&buf[..len], encoded, "{value}");
+		assert_eq!(offset_len(value), len);
+		assert_eq!(decode_offset(encoded), Ok((value, len)));
+	}
+
+	for value in [300u64, 1 << 32, (1 << 63) + 12345, u64::MAX] {
+		let mut buf = [0u8; MAX_OFFSET_LEN];
+		let len = encode_offset(&mut buf, value);
+		assert_eq!(offset_len(value), len);
+		assert_eq!(decode_offset(&buf[..len]), Ok((value, len)));
+		assert!(decode_offset(&buf[..len - 1]).is_err());
+
+		let mut vlen_buf = [0u8; 9];
+		let (read, written) = offset_to_vlen(&buf, &mut vlen_buf).unwrap();
+		assert_eq!(read, len);
+		assert_eq!(vlen::decode_value::<u64>(&vlen_buf[..written]), Ok(value));
+		let mut back = [0u8; MAX_OFFSET_LEN];
+		assert_eq!(
+			vlen_to_offset(&vlen_buf[..written], &mut back),
+			Ok((written, len))
+		);
+		assert_eq!(back[..len], buf[..len]);
+	}
+	assert_eq!(offset_len(u64::MAX), MAX_OFFSET_LEN);
+	assert!(decode_offset(&[0xFF; 11]).is_err());
+	assert!(decode_offset(&[]).is_err());
+}
+
+#[test]
+fn test_quic_interop() {
+	use vlen::interop::quic::*;
+
+	// Sample encodings from RFC 9000, appendix A.1.
+	let cases: [(u64, &[u8]); 4] = [
+		(
+			151_288_809_941_952_652,
+			&[0xC2, 0x19, 0x7C, 0x5E, 0xFF, 0x14, 0xE8, 0x8C],
+		),
+		(494_878_333, &[0x9D, 0x7F, 0x3E, 0x7D]),
+		(15293, &[0x7B, 0xBD]),
+		(37, &[0x25]),
+	];
+	for (value, encoded) in cases {
+		let mut buf = [0u8; 8];
+		let len = encode_varint(&mut buf, value).unwrap();
+		assert_eq!(&buf[..len], encoded, "{value}");
+		assert_eq!(varint_len(value), Ok(len));
+		assert_eq!(decode_varint(encoded), Ok((value, len)));
+		assert!(decode_varint(&encoded[..len - 1]).is_err());
+	}
+	// Longer encodings than necessary are valid QUIC.
+	assert_eq!(decode_varint(&[0x40, 0x25]), Ok((37, 2)));
+	assert_eq!(varint_len(MAX_VALUE), Ok(8));
+	assert!(varint_len(MAX_VALUE + 1).is_err());
+	assert!(encode_varint(&mut [0u8; 8], u64::MAX).is_err());
+
+	let values = [0u64, 63, 64, 16383, 16384, 1 << 30, MAX_VALUE];
+	let mut quic = [0u8; 64];
+	let mut quic_len = 0;
+	for &value in &values {
+		let mut buf = [0u8; 8];
+		let len = encode_varint(&mut buf, value).unwrap();
+		quic[quic_len..quic_len + len].copy_from_slice(&buf[..len]);
+		quic_len += len;
+	}
+	let mut vlen_buf = [0u8; 64];
+	let vlen_len =
+		bulk_varint_to_vlen(&quic[..quic_len], &mut vlen_buf).unwrap();
+	let mut decoded = [0u64; 7];
+	vlen::bulk_decode(&vlen_buf[..vlen_len], &mut decoded).unwrap();
+	assert_eq!(decoded, values);
+
+	let mut back = [0u8; 64];
+	assert_eq!(
+		bulk_vlen_to_varint(&vlen_buf[..vlen_len], &mut back),
+		Ok(quic_len)
+	);
+	assert_eq!(back[..quic_len], quic[..quic_len]);
+	assert!(bulk_varint_to_vlen(&quic[..quic_len], &mut [0u8; 4]).is_err());
+
+	let mut out = [0u8; 8];
+	assert_eq!(vlen_to_varint(&vlen_buf, &mut out), Ok((1, 1)));
+	let mut too_large_buf = [0u8; 9];
+	vlen::encode(&mut too_large_buf, u64::MAX).unwrap();
+	assert!(vlen_to_varint(&too_large_buf, &mut out).is_err());
+}
+
+#[test]
+fn test_cbor_interop() {
+	use vlen::interop::cbor::*;
+
+	// Sample encodings from RFC 8949, appendix A.
+	let cases: [(i64, &[u8]); 10] = [
+		(0, &[0x00]),
+		(23, &[0x17]),
+		(24, &[0x18, 0x18]),
+		(100, &[0x18, 0x64]),
+		(1000, &[0x19, 0x03, 0xE8]),
+		(1_000_000, &[0x1A, 0x00, 0x0F, 0x42, 0x40]),
+		(
+			1_000_000_000_000,
+			&[0x1B, 0x00, 0x00, 0x00, 0xE8, 0xD4, 0xA5, 0x10, 0x00],
+		),
+		(-1, &[0x20]),
+		(-100, &[0x38, 0x63]),
+		(-1000, &[0x39, 0x03, 0xE7]),
+	];
+	for (value, encoded) in cases {
+		let mut buf = [0u8; MAX_HEAD_LEN];
+		let len = encode_i64(&mut buf, value);
+		assert_eq!(&buf[..len], encoded, "{value}");
+		assert_eq!(decode_i64(encoded), Ok((value, len)));
+		assert!(decode_i64(&encoded[..len - 1]).is_err());
+	}
+	for value in [i64::MIN, i64::MAX] {
+		let mut buf = [0u8; MAX_HEAD_LEN];
+		let len = encode_i64(&mut buf, value);
+		assert_eq!(decode_i64(&buf[..len]), Ok((value, len)));
+	}
+	// 2^64 - 1 and -2^64 are valid CBOR but do not fit in an i64.
+	assert!(decode_i64(&[0x1B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])
+		.is_err());
+	assert!(decode_i64(&[0x3B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])
+		.is_err());
+	assert!(decode_i64(&[0x61, b'a']).is_err());
+
+	let mut buf = [0u8; MAX_HEAD_LEN];
+	let len = encode_head(&mut buf, MajorType::Map, u64::MAX);
+	assert_eq!(len, head_len(u64::MAX));
+	assert_eq!(decode_head(&buf), Ok((MajorType::Map, Some(u64::MAX), 9)));
+	assert_eq!(
+		decode_head(&[0xC1, 0x1A]),
+		Ok((MajorType::Tag, Some(1), 1))
+	);
+
+	// Indefinite lengths and the break code.
+	assert_eq!(encode_indefinite_head(&mut buf, MajorType::Array), Ok(1));
+	assert_eq!(buf[0], 0x9F);
+	assert_eq!(decode_head(&[0x9F]), Ok((MajorType::Array, None, 1)));
+	assert_eq!(decode_head(&[0xFF]), Ok((MajorType::Simple, None, 1)));
+	assert!(encode_indefinite_head(&mut buf, MajorType::Tag).is_err());
+	assert!(decode_head(&[0x1F]).is_err());
+	assert!(decode_head(&[0x1C]).is_err());
+	assert!(decode_head(&[]).is_err());
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn test_half_floats() {
+	use half::{bf16, f16};
+
+	let samples = [0.0f32, -0.0, 1.0, -1.5, 0.1, 65504.0, f32::INFINITY];
+	for &sample in &samples {
+		let value = f16::from_f32(sample);
+		let mut buf = [0u8; 3];
+		let len = vlen::half::encode_f16(&mut buf, value);
+		assert_eq!(vlen::half::decode_f16(&buf), (value, len));
+		assert_eq!(vlen::encoded_size(value), Ok(len));
+		assert_eq!(vlen::decode_value::<f16>(&buf[..len]), Ok(value));
+
+		let value = bf16::from_f32(sample);
+		let len = vlen::half::encode_bf16(&mut buf, value);
+		assert_eq!(vlen::half::decode_bf16(&buf), (value, len));
+		assert_eq!(vlen::decode_checked::<bf16>(&buf[..len]), Ok((value, len)));
+	}
+
+	// Values with short mantissas fit in a byte.
+	let mut buf = [0u8; 3];
+	assert_eq!(vlen::encode(&mut buf, f16::from_f32(2.0)), Ok(1));
+	assert_eq!(vlen::encode(&mut buf, bf16::from_f32(2.0)), Ok(1));
+	assert!(vlen::decode_value::<f16>(&[0x7E]).unwrap().is_nan());
+
+	let mut values = [f16::ZERO; 4];
+	let encoded = [f16::ONE, f16::NEG_ONE, f16::MAX, f16::MIN_POSITIVE];
+	let mut bulk = [0u8; 12];
+	let len = vlen::bulk_encode(&mut bulk, &encoded).unwrap();
+	vlen::bulk_decode(&bulk[..len], &mut values).unwrap();
+	assert_eq!(values, encoded);
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn test_bigint() {
+	use num_bigint::{BigInt, BigUint};
+
+	let mut buf = [0u8; 64];
+	let unsigned = [
+		BigUint::from(0u8),
+		BigUint::from(1u8),
+		BigUint::from(u64::MAX),
+		BigUint::from(u128::MAX) << 130,
+	];
+	for value in unsigned {
+		let len = vlen::encode(&mut buf, value.clone()).unwrap();
+		assert_eq!(vlen::encoded_size(value.clone()), Ok(len));
+		assert_eq!(vlen::decode::<BigUint>(&buf[..len]), Ok((value, len)));
+		if len > 1 {
+			assert!(vlen::decode::<BigUint>(&buf[..len - 1]).is_err());
+		}
+	}
+	assert_eq!(vlen::encode(&mut buf, BigUint::from(0u8)), Ok(1));
+	assert_eq!(buf[0], 0);
+
+	let signed = [
+		BigInt::from(0),
+		BigInt::from(-1),
+		BigInt::from(i64::MIN),
+		BigInt::from(i128::MAX) * BigInt::from(-7),
+		BigInt::from(1) << 1000,
+	];
+	for value in signed {
+		let mut buf = [0u8; 160];
+		let len = vlen::encode(&mut buf, value.clone()).unwrap();
+		assert_eq!(vlen::encoded_size(value.clone()), Ok(len));
+		assert_eq!(vlen::decode::<BigInt>(&buf[..len]), Ok((value, len)));
+	}
+
+	let big = BigInt::from(1) << 1000;
+	assert!(vlen::encode(&mut buf, big).is_err());
+	// A length prefix larger than the buffer must not be trusted.
+	let huge_len = [0xF7, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+	assert!(vlen::decode::<BigUint>(&huge_len).is_err());
+}
+
+#[test]
+fn test_decimal() {
+	use vlen::decimal::Decimal;
+
+	let samples = [
+		Decimal::new(0, 0),
+		Decimal::new(1999, 2),
+		Decimal::new(-5, 1),
+		Decimal::new(i128::MIN, u32::MAX),
+		Decimal::new(i128::MAX, 28),
+	];
+	for value in samples {
+		let mut buf = [0u8; 22];
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(vlen::encoded_size(value), Ok(len));
+		assert_eq!(vlen::decode::<Decimal>(&buf[..len]), Ok((value, len)));
+		assert_eq!(vlen::decode_checked::<Decimal>(&buf), Ok((value, len)));
+		assert!(vlen::decode::<Decimal>(&buf[..len - 1]).is_err());
+	}
+
+	assert_eq!(Decimal::new(150, 2).normalize(), Decimal::new(15, 1));
+	assert_eq!(Decimal::new(-1000, 3).normalize(), Decimal::new(-1, 0));
+	assert_eq!(Decimal::new(0, 9).normalize(), Decimal::new(0, 0));
+	assert_eq!(Decimal::new(7, 0).normalize(), Decimal::new(7, 0));
+
+	let mut buf = [0u8; 64];
+	let len = vlen::bulk_encode(&mut buf, &samples).unwrap();
+	let mut decoded = [Decimal::default(); 5];
+	vlen::bulk_decode(&buf[..len], &mut decoded).unwrap();
+	assert_eq!(decoded, samples);
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test]
+fn test_rust_decimal() {
+	use vlen::decimal::Decimal;
+
+	let samples = [
+		rust_decimal::Decimal::new(1999, 2),
+		rust_decimal::Decimal::new(-1, 28),
+		rust_decimal::Decimal::MAX,
+		rust_decimal::Decimal::MIN,
+		rust_decimal::Decimal::ZERO,
+	];
+	for value in samples {
+		let mut buf = [0u8; 22];
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(vlen::encoded_size(value), Ok(len));
+		let decoded = vlen::decode_value::<rust_decimal::Decimal>(&buf);
+		assert_eq!(decoded, Ok(value));
+		// The two types share an encoding.
+		assert_eq!(vlen::decode_value::<Decimal>(&buf), Ok(value.into()));
+	}
+
+	let mut buf = [0u8; 22];
+	let len = vlen::encode(&mut buf, Decimal::new(1, 29)).unwrap();
+	assert!(vlen::decode_value::<rust_decimal::Decimal>(&buf[..len]).is_err());
+	let len = vlen::encode(&mut buf, Decimal::new(1 << 96, 0)).unwrap();
+	assert!(vlen::decode_value::<rust_decimal::Decimal>(&buf[..len]).is_err());
+}
+
+#[test]
+fn test_f64_adaptive() {
+	use vlen::float::*;
+
+	// Normal f32 values, zeros and infinities encode as the f32 would.
+	let narrow = [
+		0.0f32,
+		-0.0,
+		1.0,
+		-1.5,
+		0.1,
+		12_345.678,
+		1e10,
+		f32::MAX,
+		f32::MIN_POSITIVE,
+		f32::INFINITY,
+		f32::NEG_INFINITY,
+	];
+	for value in narrow {
+		let mut f32_buf = [0u8; 5];
+		let f32_len = vlen::encode_f32(&mut f32_buf, value);
+		let mut buf = [0u8; 9];
+		let len = encode_f64_adaptive(&mut buf, f64::from(value));
+		assert_eq!(buf[..len], f32_buf[..f32_len], "{value}");
+		assert_eq!(decode_f64_adaptive(&buf), (f64::from(value), len));
+	}
+
+	let wide = [
+		0.1f64,
+		1e300,
+		-1e-300,
+		f64::MAX,
+		f64::MIN_POSITIVE,
+		f64::from_bits(1),
+		f64::from(f32::from_bits(1)),
+		core::f64::consts::PI,
+	];
+	for value in wide {
+		let mut buf = [0u8; 9];
+		let len = encode_f64_adaptive(&mut buf, value);
+		assert_eq!(encoded_size_f64_adaptive(value), len);
+		assert_eq!(decode_f64_adaptive(&buf), (value, len));
+		assert!(len > 5, "{value}");
+	}
+
+	// The encoding is a bijection on bit patterns, NaN payloads included.
+	let mut bits = 0x9E37_79B9_7F4A_7C15u64;
+	for _ in 0..10_000 {
+		bits = bits.rotate_left(17).wrapping_mul(0x2545_F491_4F6C_DD1D);
+		let value = f64::from_bits(bits);
+		let mut buf = [0u8; 9];
+		let len = encode_f64_adaptive(&mut buf, value);
+		assert_eq!(decode_f64_adaptive(&buf).0.to_bits(), bits);
+		assert_eq!(encoded_size_f64_adaptive(value), len);
+	}
+	let mut buf = [0u8; 9];
+	let _ = encode_f64_adaptive(&mut buf, f64::NAN);
+	assert!(decode_f64_adaptive(&buf).0.is_nan());
+
+	let values = [AdaptiveF64(0.5), AdaptiveF64(0.1), AdaptiveF64(-2.0)];
+	let mut bulk = [0u8; 27];
+	let len = vlen::bulk_encode(&mut bulk, &values).unwrap();
+	let mut decoded = [AdaptiveF64::default(); 3];
+	vlen::bulk_decode(&bulk[..len], &mut decoded).unwrap();
+	assert_eq!(decoded, values);
+}
+
+#[test]
+fn test_float_compact() {
+	use vlen::float::*;
+
+	for value in [0.0, 1.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+		let mut buf = [0u8; 9];
+		assert_eq!(encode_f64_compact(&mut buf, value), 1, "{value}");
+		assert_eq!(encoded_size_f64_compact(value), 1);
+		let (decoded, len) = decode_f64_compact(&buf);
+		assert_eq!(decoded.to_bits(), value.to_bits());
+		assert_eq!(len, 1);
+
+		let value = value as f32;
+		let mut buf = [0u8; 5];
+		assert_eq!(encode_f32_compact(&mut buf, value), 1, "{value}");
+		let (decoded, _) = decode_f32_compact(&buf);
+		assert_eq!(decoded.to_bits(), value.to_bits());
+	}
+
+	// Other values keep their default encoding.
+	for value in [0.5f64, -2.0, 0.1, 1e300, f64::MIN_POSITIVE] {
+		let mut buf = [0u8; 9];
+		let mut default_buf = [0u8; 9];
+		let len = encode_f64_compact(&mut buf, value);
+		let default_len = vlen::encode_f64(&mut default_buf, value);
+		assert_eq!(buf[..len], default_buf[..default_len]);
+	}
+
+	// The displaced tiny values and NaN payloads still round-trip.
+	let mut patterns = vec![0x7FF0_0000_0000_0001u64, 0xFFF8_0000_0000_0000];
+	patterns.extend((1..=6u64).map(|code| code << 56));
+	let mut bits = 0x0123_4567_89AB_CDEFu64;
+	for _ in 0..1000 {
+		bits = bits.rotate_left(13).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+		patterns.push(bits);
+	}
+	for pattern in patterns {
+		let mut buf = [0u8; 9];
+		let value = f64::from_bits(pattern);
+		let len = encode_f64_compact(&mut buf, value);
+		assert_eq!(encoded_size_f64_compact(value), len);
+		let (decoded, decoded_len) = decode_f64_compact(&buf);
+		assert_eq!((decoded.to_bits(), decoded_len), (pattern, len));
+
+		let pattern = (pattern >> 32) as u32;
+		let mut buf = [0u8; 5];
+		let _ = encode_f32_compact(&mut buf, f32::from_bits(pattern));
+		assert_eq!(decode_f32_compact(&buf).0.to_bits(), pattern);
+	}
+
+	let values =
+		[CompactF32(1.0), CompactF32(f32::NEG_INFINITY), CompactF32(0.25)];
+	let mut bulk = [0u8; 15];
+	let len = vlen::bulk_encode(&mut bulk, &values).unwrap();
+	assert_eq!(len, 5);
+	let mut decoded = [CompactF32::default(); 3];
+	vlen::bulk_decode(&bulk[..len], &mut decoded).unwrap();
+	assert_eq!(decoded, values);
+	let value = CompactF64(f64::NAN);
+	let mut buf = [0u8; 9];
+	assert_eq!(vlen::encode(&mut buf, value), Ok(1));
+	assert!(vlen::decode_value::<CompactF64>(&buf).unwrap().0.is_nan());
+}
+
+#[test]
+fn test_quantize_f32() {
+	use vlen::float::{quantize_decode_f32, quantize_encode_f32};
+
+	let values: Vec<f32> =
+		(0..1000).map(|i| 20.0 + (i as f32 * 0.01).sin() * 5.0).collect();
+	let mut buf = vec![0u8; values.len() * 9];
+	let len = quantize_encode_f32(&mut buf, &values, 100.0).unwrap();
+	assert!(len < values.len() * 2, "{len}");
+
+	let mut decoded = vec![0f32; values.len()];
+	assert_eq!(quantize_decode_f32(&buf[..len], &mut decoded, 100.0), Ok(len));
+	for (value, decoded) in values.iter().zip(&decoded) {
+		assert!((value - decoded).abs() <= 0.005 + 1e-5, "{value} {decoded}");
+	}
+
+	// Rounding goes to the nearest step, halves away from zero.
+	let mut buf = [0u8; 64];
+	let len =
+		quantize_encode_f32(&mut buf, &[0.5, -0.5, 1.4, -1.6, 0.0], 1.0)
+			.unwrap();
+	let mut decoded = [0f32; 5];
+	quantize_decode_f32(&buf[..len], &mut decoded, 1.0).unwrap();
+	assert_eq!(decoded, [1.0, -1.0, 1.0, -2.0, 0.0]);
+
+	assert!(quantize_encode_f32(&mut buf, &[1.0], 0.0).is_err());
+	assert!(quantize_encode_f32(&mut buf, &[1.0], f32::NAN).is_err());
+	assert!(quantize_encode_f32(&mut buf, &[f32::NAN], 1.0).is_err());
+	assert!(quantize_encode_f32(&mut buf, &[f32::INFINITY], 1.0).is_err());
+	assert!(quantize_encode_f32(&mut buf, &[1e30], 1.0).is_err());
+	assert!(quantize_encode_f32(&mut buf[..2], &[1.0, 1e9, 2.0], 1.0).is_err());
+	let mut decoded = [0f32; 6];
+	assert!(quantize_decode_f32(&buf[..len], &mut decoded, 1.0).is_err());
+}
+
+#[test]
+fn test_sign_bit_mode() {
+	use vlen::sign::{decode_signed, encode_signed, SignBit, SignMode};
+
+	let values = [0i64, 1, -1, 63, -63, -64, 64, i64::MAX, i64::MIN];
+	for mode in [SignMode::ZigZag, SignMode::SignBit] {
+		for value in values {
+			let mut buf = [0u8; 9];
+			let len = encode_signed(&mut buf, value, mode).unwrap();
+			assert_eq!(decode_signed::<i64>(&buf, mode), Ok((value, len)));
+		}
+	}
+
+	// Non-negative values encode identically in both modes.
+	for value in [0i32, 1, 63, 64, 8191, i32::MAX] {
+		let mut zigzag = [0u8; 5];
+		let mut sign_bit = [0u8; 5];
+		let len = encode_signed(&mut zigzag, value, SignMode::ZigZag).unwrap();
+		encode_signed(&mut sign_bit, value, SignMode::SignBit).unwrap();
+		assert_eq!(zigzag[..len], sign_bit[..len]);
+	}
+
+	let mut buf = [0u8; 17];
+	assert_eq!(vlen::encode(&mut buf, SignBit(-64i16)), Ok(2));
+	assert_eq!(vlen::encode(&mut buf, -64i16), Ok(1));
+	for value in [i16::MIN, -1, 0, i16::MAX] {
+		let len = vlen::encode(&mut buf, SignBit(value)).unwrap();
+		let decoded = vlen::decode_value::<SignBit<i16>>(&buf[..len]);
+		assert_eq!(decoded, Ok(SignBit(value)));
+	}
+	let len = vlen::encode(&mut buf, SignBit(i128::MIN)).unwrap();
+	assert_eq!((len, buf[0]), (1, 1));
+	let decoded = vlen::decode_value::<SignBit<i128>>(&buf[..len]);
+	assert_eq!(decoded, Ok(SignBit(i128::MIN)));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_transcode_widths() {
+	use vlen::transcode::*;
+
+	let values = [0u16, 0x7F, 0x3FFF, 0x4000, u16::MAX];
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+	let wide = widen_u16_to_u32(&buf).unwrap();
+	let expected: Vec<u32> = values.iter().map(|&v| v.into()).collect();
+	assert_eq!(wide, vlen::bulk_encode_to_vec(&expected).unwrap());
+	assert_eq!(narrow_u32_to_u16(&wide).unwrap(), buf);
+
+	let values = [0u32, 300, 0x1F_FFFF, u32::MAX];
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+	let wide = widen_u32_to_u64(&buf).unwrap();
+	assert_eq!(wide, buf);
+	assert_eq!(widen_u64_to_u128(&wide).unwrap(), buf);
+	assert_eq!(narrow_u128_to_u64(&buf).unwrap(), buf);
+	assert_eq!(narrow_u64_to_u32(&wide).unwrap(), buf);
+
+	let values = [i16::MIN, -1, 0, 1, i16::MAX];
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+	let wide = widen_i16_to_i32(&buf).unwrap();
+	let decoded = vlen::bulk_decode_values::<i32>(&wide).unwrap();
+	assert_eq!(decoded, [-32768, -1, 0, 1, 32767]);
+	let wide = widen_i64_to_i128(&widen_i32_to_i64(&wide).unwrap()).unwrap();
+	let wide = narrow_i64_to_i32(&narrow_i128_to_i64(&wide).unwrap());
+	assert_eq!(narrow_i32_to_i16(&wide.unwrap()).unwrap(), buf);
+
+	let buf = vlen::bulk_encode_to_vec(&[1u64, 1 << 40]).unwrap();
+	assert_eq!(narrow_u64_to_u32(&buf), Err("vlen value out of range"));
+	assert_eq!(widen_u32_to_u64(&buf[..1]).unwrap(), [1]);
+	assert!(widen_u32_to_u64(&[0xE0]).is_err());
+
+	// Non-canonical u16 binary forms are re-encoded, except 0xF0 and 0xF1.
+	let buf = [0xC1, 5, 0, 0xE1, 6, 0, 0xD0, 7, 0xF1, 8, 0, 0xF0, 9];
+	assert_eq!(vlen::bulk_decode_values::<u16>(&buf).unwrap(), [5, 6, 7, 8, 9]);
+	let wide = widen_u16_to_u32(&buf).unwrap();
+	let decoded = vlen::bulk_decode_values::<u32>(&wide).unwrap();
+	assert_eq!(decoded, [5, 6, 7, 8, 9]);
+	assert_eq!(&wide[3..], &buf[8..]);
+	let wide = widen_i16_to_i32(&buf).unwrap();
+	let decoded = vlen::bulk_decode_values::<i32>(&wide).unwrap();
+	assert_eq!(decoded, [-3, 3, -4, 4, -5]);
+}
+
+#[test]
+fn test_size_histogram() {
+	use vlen::stats::{analyze, SizeHistogram, MAX_TRACKED_LEN};
+
+	let values = [0u64, 0x7F, 0x80, 0x3FFF, 0x4000, 1 << 40, u64::MAX];
+	let stats = analyze(&values).unwrap();
+	assert_eq!(stats.values, 7);
+	assert_eq!(stats.counts[..4], [0, 2, 2, 1]);
+	assert_eq!(stats.counts[7], 1);
+	assert_eq!(stats.counts[9], 1);
+	let len = vlen::bulk_encode(&mut [0; 63], &values).unwrap();
+	assert_eq!(stats.encoded_size, len);
+	assert_eq!(stats.fixed_size, 56);
+	assert_eq!(stats.fixed_size_at(4), 28);
+	assert_eq!(stats.max_len(), 9);
+	assert!(stats.ratio() < 1.0);
+	assert_eq!(stats.mean_len(), stats.encoded_size as f64 / 7.0);
+
+	let mut stats = SizeHistogram::new();
+	assert_eq!((stats.max_len(), stats.ratio()), (0, 0.0));
+	assert_eq!(stats.record(u32::MAX), Ok(5));
+	assert_eq!(stats.record(-1i16), Ok(1));
+	assert_eq!(stats.fixed_size, 6);
+	assert_eq!(stats.ratio(), 1.0);
+	assert_eq!(stats.record(u128::MAX), Ok(17));
+	assert_eq!(stats.max_len(), 17);
+	assert_eq!(stats.counts[MAX_TRACKED_LEN], 0);
+}
+
+#[test]
+fn test_encode_by_reference() {
+	let values = [1u32, 300, 70000];
+	let refs: Vec<&u32> = values.iter().collect();
+	let mut expected = [0u8; 15];
+	let len = vlen::bulk_encode(&mut expected, &values).unwrap();
+	let mut buf = [0u8; 15];
+	assert_eq!(vlen::bulk_encode(&mut buf, &refs), Ok(len));
+	assert_eq!(buf[..len], expected[..len]);
+	assert_eq!(vlen::encoded_size(refs[2]), Ok(3));
+	assert_eq!(<&u64 as vlen::Encode>::MAX_ENCODED_SIZE, 9);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_encode_smart_pointers() {
+	use std::rc::Rc;
+	use std::sync::Arc;
+
+	let expected = vlen::encode_to_vec(-5i64).unwrap();
+	assert_eq!(vlen::encode_to_vec(Box::new(-5i64)).unwrap(), expected);
+	assert_eq!(vlen::encode_to_vec(Rc::new(-5i64)).unwrap(), expected);
+	assert_eq!(vlen::encode_to_vec(Arc::new(-5i64)).unwrap(), expected);
+
+	let boxed = [Box::new(1u16), Box::new(u16::MAX)];
+	let buf = vlen::bulk_encode_to_vec(&boxed).unwrap();
+	assert_eq!(vlen::bulk_decode_values::<u16>(&buf).unwrap(), [1, u16::MAX]);
+
+	let mut buf = vlen::VlenBuf::new();
+	buf.push(&Rc::new(7u32)).unwrap();
+	assert_eq!(buf.as_slice(), [7]);
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn test_bulk_encode_clone_values() {
+	use num_bigint::BigUint;
+
+	let values = [BigUint::from(1u8), BigUint::from(1u8) << 80u32];
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+	let decoded = vlen::bulk_decode_values::<BigUint>(&buf).unwrap();
+	assert_eq!(decoded, values);
+	let stats = vlen::stats::analyze(&values).unwrap();
+	assert_eq!(stats.encoded_size, buf.len());
+
+	let value = BigUint::from(300u16);
+	let buf = vlen::encode_to_vec(value.clone()).unwrap();
+	assert_eq!(buf.len(), 3);
+	assert_eq!(vlen::encode(&mut [0u8; 3], value), Ok(3));
+}
+
+#[test]
+fn test_dyn_encode() {
+	use vlen::DynEncode;
+
+	let fields: [&dyn DynEncode; 4] = [&7u16, &-2i64, &1.5f32, &u32::MAX];
+	let mut buf = [0u8; 32];
+	let len = vlen::bulk_encode_dyn(&mut buf, &fields).unwrap();
+
+	let mut cursor = vlen::VlenCursor::new(&buf[..len]);
+	assert_eq!(cursor.read_u16(), Ok(7));
+	assert_eq!(cursor.read_i64(), Ok(-2));
+	assert_eq!(cursor.read_f32(), Ok(1.5));
+	assert_eq!(cursor.read_u32(), Ok(u32::MAX));
+	assert!(cursor.is_empty());
+
+	let sizes: Vec<usize> = fields
+		.iter()
+		.map(|field| field.encoded_size_dyn().unwrap())
+		.collect();
+	assert_eq!(sizes.iter().sum::<usize>(), len);
+	assert_eq!(fields[1].max_encoded_size_dyn(), 9);
+	assert_eq!(vlen::bulk_encode_dyn(&mut buf[..4], &fields).ok(), None);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_dyn_encode_boxed() {
+	use vlen::DynEncode;
+
+	let fields: Vec<Box<dyn DynEncode>> =
+		vec![Box::new(300u32), Box::new(-1i16)];
+	let mut out = Vec::new();
+	for field in &fields {
+		let mut buf = vec![0u8; field.max_encoded_size_dyn()];
+		let len = field.encode_dyn(&mut buf).unwrap();
+		out.extend_from_slice(&buf[..len]);
+	}
+	let mut cursor = vlen::VlenCursor::new(&out);
+	assert_eq!(cursor.read_u32(), Ok(300));
+	assert_eq!(cursor.read_i16(), Ok(-1));
+}
+
+#[test]
+fn test_wrapping_and_saturating() {
+	use core::num::{Saturating, Wrapping};
+
+	let mut counter = Wrapping(u32::MAX);
+	counter += 2;
+	let mut buf = [0u8; 17];
+	assert_eq!(vlen::encode(&mut buf, counter), Ok(1));
+	assert_eq!(vlen::decode_value(&buf), Ok(Wrapping(1u32)));
+
+	let level = Saturating(i64::MIN) - Saturating(1);
+	let len = vlen::encode(&mut buf, level).unwrap();
+	assert_eq!(len, vlen::encoded_size(i64::MIN).unwrap());
+	let decoded = vlen::decode_value::<Saturating<i64>>(&buf[..len]);
+	assert_eq!(decoded, Ok(Saturating(i64::MIN)));
+
+	let values = [Wrapping(1u128), Wrapping(u128::MAX)];
+	let mut buf = [0u8; 34];
+	let len = vlen::bulk_encode(&mut buf, &values).unwrap();
+	let mut decoded = [Wrapping(0u128); 2];
+	assert_eq!(vlen::bulk_decode(&buf[..len], &mut decoded), Ok(len));
+	assert_eq!(decoded, values);
+	assert_eq!(<Wrapping<u16> as vlen::Decode>::MAX_ENCODED_SIZE, 3);
+	let err = <Saturating<u16> as vlen::Decode>::decode_checked(&[0xF7; 9]);
+	assert!(err.is_err());
+}
+
+#[test]
+fn test_wrapper_numeric_traits() {
+	use vlen::wrappers::{VlenF64, VlenI32, VlenU64};
+
+	let mut total = VlenU64::default();
+	total += VlenU64(40);
+	total = total * VlenU64(2) - VlenU64(80) + VlenU64(42);
+	assert_eq!(total, VlenU64(42));
+	assert_eq!(total.to_string(), "42");
+	assert_eq!(format!("{:>4}", VlenI32(-7)), "  -7");
+	assert_eq!(format!("{:.2}", VlenF64(0.5)), "0.50");
+
+	assert_eq!("-12".parse(), Ok(VlenI32(-12)));
+	assert!("-12".parse::<VlenU64>().is_err());
+	assert_eq!("2.5".parse::<VlenF64>(), Ok(VlenF64(2.5)));
+	assert_eq!(VlenF64::default(), VlenF64(0.0));
+}
+
+#[test]
+fn test_float_wrapper_total_order() {
+	use std::collections::{BTreeSet, HashSet};
+	use vlen::wrappers::{VlenF32, VlenF64};
+
+	let mut values = [
+		VlenF64(f64::NAN),
+		VlenF64(1.0),
+		VlenF64(0.0),
+		VlenF64(-0.0),
+		VlenF64(f64::NEG_INFINITY),
+	];
+	values.sort();
+	let bits: Vec<u64> = values.iter().map(|value| value.to_bits()).collect();
+	let expected = [f64::NEG_INFINITY, -0.0, 0.0, 1.0, f64::NAN];
+	assert_eq!(bits, expected.map(f64::to_bits));
+
+	assert_eq!(VlenF32(f32::NAN), VlenF32(f32::NAN));
+	assert_ne!(VlenF32(0.0), VlenF32(-0.0));
+	let set: HashSet<_> = [VlenF32(f32::NAN), VlenF32(f32::NAN)].into();
+	assert_eq!(set.len(), 1);
+	let set: BTreeSet<_> = [VlenF32(0.0), VlenF32(-0.0)].into();
+	assert_eq!(set.len(), 2);
+}
+
+#[test]
+#[cfg(feature = "schemars")]
+fn test_schemars_wrappers() {
+	use schemars::JsonSchema;
+	use vlen::serde::VlenSeq;
+	use vlen::wrappers::{VlenF64, VlenI16, VlenU128};
+
+	#[derive(JsonSchema)]
+	#[allow(dead_code)]
+	struct Record {
+		small: VlenI16,
+		large: VlenU128,
+		ratio: Option<VlenF64>,
+		values: VlenSeq<u32>,
+		#[schemars(with = "VlenSeq<i64>")]
+		deltas: Vec<i64>,
+	}
+
+	let schema = schemars::schema_for!(Record);
+	let properties = &schema.as_value()["properties"];
+	for name in ["small", "large", "values", "deltas"] {
+		assert_eq!(properties[name]["type"], "string");
+		assert_eq!(properties[name]["contentEncoding"], "base64");
+	}
+	assert_eq!(properties["small"]["maxLength"], 4);
+	assert_eq!(properties["large"]["maxLength"], 24);
+
+	// The schema matches what serde_json writes.
+	let json = serde_json::to_value(VlenU128(u128::MAX)).unwrap();
+	assert_eq!(json.as_str().unwrap().len(), 24);
+}
+
+#[test]
+fn test_encode_bools() {
+	use vlen::bits::{decode_bools, encode_bools, encoded_bools_size};
+
+	let flags: Vec<bool> = (0..1000).map(|i| i % 3 == 0).collect();
+	let mut buf = [0u8; 160];
+	let len = encode_bools(&mut buf, &flags).unwrap();
+	assert_eq!(len, 127);
+	assert_eq!(len, encoded_bools_size(flags.len()));
+	assert!(encode_bools(&mut buf[..126], &flags).is_err());
+
+	let mut decoded = [false; 1000];
+	assert_eq!(decode_bools(&buf[..len], &mut decoded), Ok((1000, len)));
+	assert_eq!(decoded[..], flags[..]);
+	assert!(decode_bools(&buf[..len], &mut decoded[..999]).is_err());
+	assert!(decode_bools(&buf[..len - 1], &mut decoded).is_err());
+
+	// Empty input encodes as a lone zero count.
+	assert_eq!(encode_bools(&mut buf, &[]), Ok(1));
+	assert_eq!(decode_bools(&buf[..1], &mut decoded), Ok((0, 1)));
+
+	// Set padding bits are rejected.
+	assert!(decode_bools(&[3, 0b0000_1000], &mut decoded).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_bit_set() {
+	use vlen::bits::{decode_bools, encode_bools, BitSet};
+
+	let flags = [true, false, false, true, true, false, true, false, true];
+	let set = BitSet::from(&flags[..]);
+	assert_eq!(set.len(), 9);
+	assert_eq!(set.count_ones(), 5);
+	assert_eq!(set.get(3), Some(true));
+	assert_eq!(set.get(9), None);
+	assert!(set.iter().eq(flags.iter().copied()));
+
+	// Both sides read what the other wrote.
+	let buf = vlen::encode_to_vec(set.clone()).unwrap();
+	let mut expected = [0u8; 8];
+	let len = encode_bools(&mut expected, &flags).unwrap();
+	assert_eq!(buf, expected[..len]);
+	let mut decoded = [false; 9];
+	assert_eq!(decode_bools(&buf, &mut decoded), Ok((9, len)));
+	assert_eq!(decoded, flags);
+	assert_eq!(vlen::decode_value::<BitSet>(&buf), Ok(set.clone()));
+
+	let mut cleared = set;
+	for i in 0..cleared.len() {
+		cleared.set(i, false);
+	}
+	assert_eq!(cleared, BitSet::with_len(9));
+	assert!(vlen::decode_value::<BitSet>(&[9, 0, 2]).is_err());
+}
+
+#[test]
+fn test_encode_char() {
+	let mut buf = [0u8; 5];
+	let cases = [('a', 1), ('é', 2), ('€', 2), ('😀', 3), (char::MAX, 3)];
+	for (value, len) in cases {
+		assert_eq!(vlen::encode(&mut buf, value), Ok(len));
+		assert_eq!(vlen::encoded_size(value), Ok(len));
+		assert_eq!(vlen::decode::<char>(&buf), Ok((value, len)));
+		assert_eq!(vlen::decode::<u32>(&buf), Ok((u32::from(value), len)));
+	}
+
+	// A buffer of exactly `MAX_ENCODED_SIZE` bytes holds any `char`.
+	let mut exact = [0u8; <char as vlen::Encode>::MAX_ENCODED_SIZE];
+	assert_eq!(vlen::encode(&mut exact, 'a'), Ok(1));
+	assert_eq!(vlen::encode(&mut exact, char::MAX), Ok(3));
+	assert_eq!(vlen::decode_value::<char>(&exact), Ok(char::MAX));
+	assert!(vlen::encode(&mut exact[..2], char::MAX).is_err());
+
+	// Surrogates and values past `char::MAX` are not scalar values.
+	for invalid in [0xD800u32, 0xDFFF, 0x11_0000, u32::MAX] {
+		let len = vlen::encode(&mut buf, invalid).unwrap();
+		assert!(vlen::decode::<char>(&buf[..len]).is_err());
+		assert!(vlen::decode_checked::<char>(&buf[..len]).is_err());
+	}
+
+	let text: Vec<char> = "vlen ✓".chars().collect();
+	let mut encoded = [0u8; 16];
+	let len = vlen::bulk_encode(&mut encoded, &text).unwrap();
+	assert_eq!(len, 7);
+	let mut decoded = ['\0'; 6];
+	assert_eq!(vlen::bulk_decode(&encoded[..len], &mut decoded), Ok(len));
+	assert_eq!(decoded[..], text[..]);
+}
+
+#[test]
+fn test_encode_network_addresses() {
+	use core::net::{
+		IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
+	};
+
+	let mut buf = [0u8; 32];
+	let v4 = Ipv4Addr::new(192, 168, 1, 20);
+	assert_eq!(vlen::encode(&mut buf, v4), Ok(5));
+	assert_eq!(vlen::decode::<u32>(&buf), Ok((u32::from(v4), 5)));
+	assert_eq!(vlen::decode::<Ipv4Addr>(&buf), Ok((v4, 5)));
+	assert_eq!(vlen::encode(&mut buf, Ipv4Addr::new(0, 0, 0, 7)), Ok(1));
+
+	let v6 = Ipv6Addr::LOCALHOST;
+	assert_eq!(vlen::encode(&mut buf, v6), Ok(1));
+	assert_eq!(vlen::decode::<Ipv6Addr>(&buf), Ok((v6, 1)));
+
+	let addrs: [SocketAddr; 3] = [
+		SocketAddrV4::new(v4, 443).into(),
+		SocketAddrV6::new("2001:db8::1".parse().unwrap(), 8080, 7, 3).into(),
+		SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0).into(),
+	];
+	for addr in addrs {
+		let len = vlen::encode(&mut buf, addr).unwrap();
+		assert_eq!(vlen::encoded_size(addr), Ok(len));
+		assert_eq!(vlen::decode::<SocketAddr>(&buf[..len]), Ok((addr, len)));
+		assert!(vlen::decode::<SocketAddr>(&buf[..len - 1]).is_err());
+		assert!(vlen::encode(&mut buf[..len - 1], addr).is_err());
+	}
+
+	let ip = IpAddr::V6(Ipv6Addr::LOCALHOST);
+	assert_eq!(vlen::encode(&mut buf, ip), Ok(2));
+	assert_eq!(buf[..2], [6, 1]);
+	assert_eq!(vlen::decode_checked::<IpAddr>(&buf[..2]), Ok((ip, 2)));
+	assert!(vlen::decode::<IpAddr>(&[5, 1]).is_err());
+}
+
+#[test]
+fn test_encode_frame() {
+	use vlen::frame::{decode_frame, encode_frame, frame_len};
+
+	let payload = [7u8; 200];
+	let mut buf = [0u8; 256];
+	let len = encode_frame(&mut buf, &payload).unwrap();
+	assert_eq!(len, frame_len(payload.len()));
+	assert_eq!(buf[..2], [0x80 | 200 & 0x3F, 200 >> 6]);
+	assert!(encode_frame(&mut buf[..len - 1], &payload).is_err());
+
+	assert_eq!(decode_frame(&buf[..len]), Ok(Some((&payload[..], len))));
+	for partial in [0, 1, 2, len - 1] {
+		assert_eq!(decode_frame(&buf[..partial]), Ok(None));
+	}
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_frame_reader_writer() {
+	use vlen::frame::{FrameReader, FrameWriter};
+
+	let messages: Vec<Vec<u8>> =
+		[0, 1, 127, 128, 20_000].iter().map(|&n| vec![n as u8; n]).collect();
+	let mut writer = FrameWriter::new();
+	for message in &messages {
+		writer.write_frame(message).unwrap();
+	}
+
+	// Partial writes of three bytes, then reads of one byte at a time.
+	let mut wire = Vec::new();
+	while !writer.is_empty() {
+		let pending = writer.pending();
+		let chunk = pending[..3.min(pending.len())].to_vec();
+		writer.consume(chunk.len());
+		wire.extend_from_slice(&chunk);
+	}
+	let mut reader = FrameReader::new();
+	let mut received = Vec::new();
+	for &byte in &wire {
+		reader.extend(&[byte]);
+		while let Some(frame) = reader.next_frame().unwrap() {
+			received.push(frame.to_vec());
+		}
+	}
+	assert_eq!(received, messages);
+	assert_eq!(reader.buffered(), 0);
+
+	// Oversized frames fail once the prefix arrives.
+	let mut reader = FrameReader::new().max_frame_len(127);
+	reader.extend(&wire[..132]);
+	assert_eq!(reader.next_frame(), Ok(Some(&[][..])));
+	assert_eq!(reader.next_frame(), Ok(Some(&[1][..])));
+	assert_eq!(reader.next_frame().unwrap().map(<[u8]>::len), Some(127));
+	assert_eq!(reader.next_frame(), Ok(None));
+	reader.extend(&wire[132..133]);
+	assert!(reader.next_frame().is_err());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_frame_std_io() {
+	use std::io::{self, Read, Write};
+	use vlen::frame::{FrameReader, FrameWriter};
+
+	// Accepts or returns at most five bytes per call.
+	struct Trickle(Vec<u8>, usize);
+
+	impl Read for Trickle {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			let len = buf.len().min(5).min(self.0.len() - self.1);
+			buf[..len].copy_from_slice(&self.0[self.1..self.1 + len]);
+			self.1 += len;
+			Ok(len)
+		}
+	}
+
+	impl Write for Trickle {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			let len = buf.len().min(5);
+			self.0.extend_from_slice(&buf[..len]);
+			Ok(len)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	let mut writer = FrameWriter::new();
+	writer.write_frame(b"first message").unwrap();
+	writer.write_frame(&[9u8; 1000]).unwrap();
+	let mut stream = Trickle(Vec::new(), 0);
+	while !writer.is_empty() {
+		assert!(writer.write_to(&mut stream).unwrap() <= 5);
+	}
+
+	let mut reader = FrameReader::new();
+	let mut frames = Vec::new();
+	while reader.read_from(&mut stream).unwrap() > 0 {
+		while let Some(frame) = reader.next_frame().unwrap() {
+			frames.push(frame.to_vec());
+		}
+	}
+	assert_eq!(frames, [b"first message".to_vec(), vec![9u8; 1000]]);
+
+	// Accepts up to 10 bytes per call, however they are split.
+	#[derive(Default)]
+	struct Vectored(Vec<u8>);
+
+	impl Write for Vectored {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.write_vectored(&[io::IoSlice::new(buf)])
+		}
+
+		fn write_vectored(
+			&mut self,
+			bufs: &[io::IoSlice<'_>],
+		) -> io::Result<usize> {
+			let start = self.0.len();
+			bufs.iter().for_each(|buf| self.0.extend_from_slice(buf));
+			self.0.truncate(start + 10);
+			Ok(self.0.len() - start)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	// Interleave queuing and partial sends so the ring buffer wraps around.
+	let mut writer = FrameWriter::with_capacity(64);
+	let mut expected = Vec::new();
+	let mut sink = Vectored::default();
+	let mut wrapped = false;
+	for round in 0..20u8 {
+		let payload = vec![round; 10 + usize::from(round % 7)];
+		writer.write_frame(&payload).unwrap();
+		expected.push(payload.len() as u8);
+		expected.extend_from_slice(&payload);
+		wrapped |= !writer.pending_slices().1.is_empty();
+		writer.write_to(&mut sink).unwrap();
+	}
+	while !writer.is_empty() {
+		writer.write_to(&mut sink).unwrap();
+	}
+	assert!(wrapped);
+	assert_eq!(sink.0, expected);
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn test_vlen_stream() {
+	use std::pin::Pin;
+	use std::task::{Context, Poll};
+
+	use futures_util::io::{AsyncRead, Cursor};
+	use futures_util::{FutureExt, StreamExt};
+	use vlen::async_io::VlenStream;
+
+	/// Yields at most three bytes per read to split values across reads.
+	struct Trickle(Vec<u8>);
+
+	impl AsyncRead for Trickle {
+		fn poll_read(
+			mut self: Pin<&mut Self>,
+			_cx: &mut Context<'_>,
+			buf: &mut [u8],
+		) -> Poll<std::io::Result<usize>> {
+			let len = buf.len().min(self.0.len()).min(3);
+			buf[..len].copy_from_slice(&self.0[..len]);
+			self.0.drain(..len);
+			Poll::Ready(Ok(len))
+		}
+	}
+
+	let values: Vec<i64> = (0..5000).map(|i| (i - 2500) * i * i).collect();
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+	let decoded: Vec<i64> = VlenStream::<_, i64>::new(Trickle(buf.clone()))
+		.map(Result::unwrap)
+		.collect::<Vec<_>>()
+		.now_or_never()
+		.unwrap();
+	assert_eq!(decoded, values);
+
+	// A value cut short by the end of the reader is an error.
+	let mut stream = VlenStream::<_, u32>::new(Cursor::new(vec![1, 0xC0, 0]));
+	async {
+		assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+		let err = stream.next().await.unwrap().unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+		assert!(stream.next().await.is_none());
+	}
+	.now_or_never()
+	.unwrap();
+}
+
+#[test]
+fn test_from_vlen() {
+	use vlen::FromVlen;
+
+	assert_eq!(u32::try_from_vlen(&[0xAC, 0x04, 0xFF]), Ok(300));
+	assert_eq!(u32::try_from_vlen_exact(&[0xAC, 0x04]), Ok(300));
+	assert!(u32::try_from_vlen_exact(&[0xAC, 0x04, 0x00]).is_err());
+	assert!(u32::try_from_vlen_exact(&[0xAC]).is_err());
+	assert!(u32::try_from_vlen(&[]).is_err());
+
+	// The exact variant range-checks like `decode_checked`.
+	let mut buf = [0u8; 9];
+	let len = vlen::encode(&mut buf, 1u64 << 40).unwrap();
+	assert!(u32::try_from_vlen_exact(&buf[..len]).is_err());
+	assert_eq!(u64::try_from_vlen_exact(&buf[..len]), Ok(1 << 40));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_to_vlen() {
+	use vlen::{FromVlen, ToVlen};
+
+	for value in [0u64, 127, 128, u64::MAX] {
+		let bytes = value.to_vlen().unwrap();
+		assert_eq!(bytes, vlen::encode_to_vec(value).unwrap());
+		assert_eq!(u64::try_from_vlen_exact(&bytes), Ok(value));
+	}
+	let bytes = 'λ'.to_vlen().unwrap();
+	assert_eq!(char::try_from_vlen_exact(&bytes), Ok('λ'));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_try_encode_to_vec() {
+	let values = [1u64, 300, u64::MAX];
+	assert_eq!(
+		vlen::try_bulk_encode_to_vec(&values),
+		vlen::bulk_encode_to_vec(&values)
+	);
+	assert_eq!(vlen::try_encode_to_vec(-2i32), Ok(vec![0x03]));
+
+	let mut vec = vec![0xFF];
+	assert_eq!(vlen::try_encode_append(&mut vec, 300u32), Ok(2));
+	assert_eq!(vlen::try_bulk_encode_append(&mut vec, &[1u16, 2]), Ok(2));
+	assert_eq!(vec, [0xFF, 0xAC, 0x04, 1, 2]);
+
+	/// Claims an encoded size no allocation can satisfy.
+	#[derive(Clone)]
+	struct Huge;
+
+	impl vlen::Encode for Huge {
+		fn encode(_: &mut [u8], _: Self) -> Result<usize, &'static str> {
+			unreachable!("the buffer is never allocated")
+		}
+
+		fn encoded_size(_: Self) -> Result<usize, &'static str> {
+			Ok(usize::MAX / 2 + 1)
+		}
+
+		const MAX_ENCODED_SIZE: usize = usize::MAX;
+	}
+
+	assert!(vlen::try_encode_to_vec(Huge).is_err());
+	assert!(vlen::try_bulk_encode_to_vec(&[Huge, Huge]).is_err());
+	assert!(vlen::try_encode_append(&mut vec, Huge).is_err());
+	assert_eq!(vec.len(), 5);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decode_in_chunks() {
+	let values: Vec<u64> = (0..1000).map(|i| i * i * 1000).collect();
+	let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+
+	let mut decoded = Vec::new();
+	let mut chunk_lens = Vec::new();
+	let count = vlen::decode_in_chunks(&buf, 300, |chunk: &[u64]| {
+		decoded.extend_from_slice(chunk);
+		chunk_lens.push(chunk.len());
+	});
+	assert_eq!(count, Ok(1000));
+	assert_eq!(decoded, values);
+	assert_eq!(chunk_lens, [300, 300, 300, 100]);
+
+	let mut calls = 0;
+	let empty = vlen::decode_in_chunks(&[], 8, |_: &[u64]| calls += 1);
+	assert_eq!((empty, calls), (Ok(0), 0));
+
+	// Complete chunks before a truncated value are still delivered.
+	let mut seen = 0;
+	let truncated = &buf[..buf.len() - 1];
+	let result = vlen::decode_in_chunks(truncated, 300, |chunk: &[u64]| {
+		seen += chunk.len();
+	});
+	assert_eq!(result, Err("truncated vlen value"));
+	assert_eq!(seen, 900);
+}
+
+#[test]
+#[cfg(feature = "simd")]
+fn test_delta_bulk_u32() {
+	let values: Vec<u32> = (0..1000).map(|i| 50_000 + i * 3).collect();
+	let mut buf = vec![0u8; values.len() * 5];
+	let len = vlen::delta_bulk_encode_u32_safe(&mut buf, &values).unwrap();
+	// Every gap fits in a byte or two, far below the 3 bytes per value.
+	assert!(len < values.len() * 2 + 8);
+
+	let mut decoded = vec![0u32; values.len()];
+	let decoded_len =
+		vlen::delta_bulk_decode_u32_safe(&buf[..len], &mut decoded).unwrap();
+	assert_eq!(decoded_len, len);
+	assert_eq!(decoded, values);
+
+	// Unsorted input still round trips through wrapping differences.
+	let values = [10u32, 3, u32::MAX, 0, 7];
+	let len = vlen::delta_bulk_encode_u32_safe(&mut buf, &values).unwrap();
+	let mut decoded = [0u32; 5];
+	vlen::delta_bulk_decode_u32_safe(&buf[..len], &mut decoded).unwrap();
+	assert_eq!(decoded, values);
+
+	assert_eq!(
+		vlen::delta_bulk_encode_u32_safe(&mut [0u8; 4], &values),
+		Err("buffer too small for bulk encoding")
+	);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_dict_encoding() {
+	use vlen::dict::{self, Dictionary};
+
+	let values = [500u64, 1 << 40, 500, 7, 1 << 40, 500];
+	let dictionary = Dictionary::build(&values);
+	assert_eq!(dictionary.values(), [500, 1 << 40, 7]);
+	assert_eq!(dictionary.index_of(7), Some(2));
+	assert_eq!(dictionary.index_of(8), None);
+	assert_eq!(dictionary.get(1), Some(1 << 40));
+
+	let buf = dict::encode(&values).unwrap();
+	assert_eq!(buf[0], 3);
+	assert_eq!(&buf[buf.len() - 7..], [6, 0, 1, 0, 2, 1, 0]);
+	assert_eq!(dict::decode(&buf), Ok((values.to_vec(), buf.len())));
+
+	// Values outside the dictionary are rejected before writing.
+	let mut vec = vec![0xFF];
+	assert_eq!(
+		dictionary.encode_append(&mut vec, &[500, 8]),
+		Err("value not in dictionary")
+	);
+	assert_eq!(vec, [0xFF]);
+
+	let empty = dict::encode(&[]).unwrap();
+	assert_eq!(empty, [0, 0]);
+	assert_eq!(dict::decode(&empty), Ok((vec![], 2)));
+
+	// One dictionary entry, indexed out of range.
+	assert_eq!(
+		dict::decode(&[1, 9, 2, 0, 1]),
+		Err("dictionary index out of range")
+	);
+	assert!(dict::decode(&buf[..buf.len() - 1]).is_err());
+}
+
+#[test]
+fn test_pfor_encoding() {
+	use vlen::pfor;
+
+	fn round_trip(values: &[u32]) -> usize {
+		let mut buf = [0u8; pfor::encoded_size_bound(300)];
+		let len = pfor::encode(&mut buf, values).unwrap();
+		let mut decoded = [0u32; 300];
+		let result = pfor::decode(&buf[..len], &mut decoded);
+		assert_eq!(result, Ok((values.len(), len)));
+		assert_eq!(decoded[..values.len()], *values);
+		len
+	}
+
+	// Zeros pack at width 0: a header and an exception count per block.
+	assert_eq!(round_trip(&[0; 300]), 2 + 3 * 2);
+	assert_eq!(round_trip(&[]), 1);
+
+	// Small values with outliers patched in as exceptions.
+	let values: Vec<u32> = (0..300)
+		.map(|i| if i % 97 == 0 { u32::MAX - i } else { i % 16 })
+		.collect();
+	assert!(round_trip(&values) < 200);
+
+	// Full-width values pack at 32 bits, below 5 bytes each as vlen.
+	let wide: Vec<u32> = (0..300).map(|i| 0xF000_0000 | (i * 7919)).collect();
+	assert!(round_trip(&wide) < 300 * 4 + 12);
+
+	// Mixed sizes that do not pack well stay plain vlen.
+	let mixed: Vec<u32> =
+		(0..300).map(|i| [1, 200, 70_000, 1 << 29][i % 4]).collect();
+	let mut buf = [0u8; pfor::encoded_size_bound(300)];
+	let len = pfor::encode(&mut buf, &mixed).unwrap();
+	assert_eq!(buf[2], 0xFF);
+	let mut decoded = [0u32; 300];
+	pfor::decode(&buf[..len], &mut decoded).unwrap();
+	assert_eq!(decoded[..], mixed[..]);
+
+	assert_eq!(
+		pfor::encode(&mut buf[..100], &mixed),
+		Err("buffer too small for pfor encoding")
+	);
+	assert_eq!(
+		pfor::decode(&buf[..len], &mut decoded[..299]),
+		Err("buffer holds more values than expected")
+	);
+	assert!(pfor::decode(&buf[..len - 1], &mut decoded).is_err());
+	assert_eq!(
+		pfor::decode(&[1, 40], &mut decoded),
+		Err("invalid pfor block header")
+	);
+	// One exception at position 1 of a one-value block.
+	assert_eq!(
+		pfor::decode(&[1, 0, 1, 1, 1], &mut decoded),
+		Err("invalid pfor exception position")
+	);
+}
+
+#[test]
+fn test_xor_encode_u64() {
+	// A file offset whose low bits move both ways.
+	let values: Vec<u64> = (0..100)
+		.map(|i: u64| 0x7FFF_1234_5600_0000 | (i * 37 % 200))
+		.collect();
+	let mut buf = [0u8; 900];
+	let len = vlen::xor_encode_u64(&mut buf, &values).unwrap();
+	// Only the first value is large.
+	assert!(len <= 9 + 99 * 2);
+
+	let mut decoded = [0u64; 100];
+	assert_eq!(vlen::xor_decode_u64(&buf[..len], &mut decoded), Ok(len));
+	assert_eq!(decoded[..], values[..]);
+
+	assert_eq!(vlen::xor_encode_u64(&mut buf, &[5, 5, 4]), Ok(3));
+	assert_eq!(buf[..3], [5, 0, 1]);
+	assert_eq!(
+		vlen::xor_decode_u64(&buf[..3], &mut decoded[..4]),
+		Err("buffer holds fewer values than expected")
+	);
+	assert_eq!(
+		vlen::xor_encode_u64(&mut [], &[1]),
+		Err("buffer too small for bulk encoding")
+	);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_diff_encode_apply() {
+	let old = [10u64, 20, 30, 40, 50];
+	let new = [10u64, 21, 30, 40, 50, 0, 7];
+	let patch = vlen::diff_encode(&old, &new);
+	// Length, count, then (gap, xor) for positions 1, 5 and 6.
+	assert_eq!(patch, [7, 3, 1, 20 ^ 21, 3, 0, 0, 7]);
+	assert_eq!(vlen::diff_apply(&old, &patch), Ok(new.to_vec()));
+
+	// Shrinking needs no changes at all.
+	let patch = vlen::diff_encode(&old, &old[..2]);
+	assert_eq!(patch, [2, 0]);
+	assert_eq!(vlen::diff_apply(&old, &patch), Ok(vec![10, 20]));
+	assert_eq!(vlen::diff_encode(&old, &old), [5, 0]);
+
+	let mut values = old.to_vec();
+	let errors = [
+		(&[5, 1, 5, 1][..], "diff position out of range"),
+		(&[7, 1, 5, 1][..], "diff does not cover appended values"),
+		(&[5, 0, 0][..], "trailing bytes after diff patch"),
+		(&[5, 9, 0, 1][..], "sequence count exceeds buffer length"),
+	];
+	for (patch, err) in errors {
+		assert_eq!(vlen::diff_apply_in_place(&mut values, patch), Err(err));
+	}
+	assert_eq!(values, old);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_record_batch() {
+	use vlen::batch::{ColumnType, RecordBatch, RecordBatchEncoder};
+
+	let ids = [1u32, 300, 70_000];
+	let offsets = [-1i16, 0, 1];
+	let ratios = [0.5f32, -2.0, 1e9];
+	let mut encoder = RecordBatchEncoder::new();
+	encoder
+		.push_column(&ids)
+		.unwrap()
+		.push_column(&offsets)
+		.unwrap()
+		.push_column(&ratios)
+		.unwrap();
+	assert_eq!(
+		encoder.push_column(&[1u64, 2]).unwrap_err(),
+		"column length does not match batch"
+	);
+	assert_eq!(encoder.num_columns(), 3);
+
+	// Two batches back to back, the second one empty.
+	let mut buf = encoder.finish();
+	let first_len = buf.len();
+	let empty_len = RecordBatchEncoder::new().finish_into(&mut buf);
+	assert_eq!(&buf[first_len..], [2, 0, 0]);
+	assert_eq!(buf.len(), first_len + empty_len);
+
+	let (batch, len) = RecordBatch::decode(&buf).unwrap();
+	assert_eq!(len, first_len);
+	assert_eq!((batch.num_rows(), batch.num_columns()), (3, 3));
+	assert_eq!(batch.column_type(1), Some(ColumnType::I16));
+	assert_eq!(batch.column_type(3), None);
+	let id_bytes = vlen::bulk_encode_to_vec(&ids).unwrap();
+	assert_eq!(batch.column_bytes(0), Some(&id_bytes[..]));
+	assert_eq!(batch.column::<u32>(0), Ok(ids.to_vec()));
+	assert_eq!(batch.column::<i16>(1), Ok(offsets.to_vec()));
+	assert_eq!(batch.column::<f32>(2), Ok(ratios.to_vec()));
+	let iter = batch.column_iter::<i16>(1).unwrap();
+	assert_eq!(iter.collect::<Result<Vec<_>, _>>(), Ok(offsets.to_vec()));
+	assert_eq!(batch.column::<i32>(1), Err("column type mismatch"));
+	assert_eq!(batch.column::<u32>(3), Err("column index out of range"));
+
+	let (empty, len) = RecordBatch::decode(&buf[first_len..]).unwrap();
+	assert_eq!((empty.num_rows(), empty.num_columns(), len), (0, 0, 3));
+
+	let errors = [
+		(&[4, 0, 1, 9, 0][..], "invalid column type"),
+		(&[4, 0, 1, 2, 1][..], "column lengths do not match batch length"),
+		(&[3, 0, 9, 2][..], "sequence count exceeds buffer length"),
+	];
+	for (buf, err) in errors {
+		assert_eq!(RecordBatch::decode(buf).unwrap_err(), err);
+	}
+	assert_eq!(
+		RecordBatch::decode(&buf[..first_len - 1]).unwrap_err(),
+		"truncated vlen value"
+	);
+}
+
+#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+#[test]
+fn test_compression_hooks() {
+	use vlen::compress::{compress, decompress, decompress_limited, Compression};
+	use vlen::frame::{FrameReader, FrameWriter};
+
+	let values: Vec<f64> = (0..2000).map(|i| f64::from(i % 50) / 8.0).collect();
+	let encoded = vlen::bulk_encode_to_vec(&values).unwrap();
+
+	let mut codecs = vec![Compression::None];
+	#[cfg(feature = "zstd")]
+	codecs.push(Compression::Zstd(0));
+	#[cfg(feature = "lz4_flex")]
+	codecs.push(Compression::Lz4);
+	for compression in codecs {
+		let compressed = compress(&encoded, compression).unwrap();
+		assert_eq!(compressed[0], compression.tag());
+		assert_eq!(decompress(&compressed).unwrap(), encoded);
+		if compression == Compression::None {
+			assert_eq!(compressed.len(), encoded.len() + 1);
+		} else {
+			assert!(compressed.len() < encoded.len() / 4);
+		}
+		assert_eq!(
+			decompress_limited(&compressed, encoded.len() - 1),
+			Err("decompressed length exceeds limit")
+		);
+
+		let mut writer = FrameWriter::new();
+		writer.write_compressed_frame(&encoded, compression).unwrap();
+		writer.write_compressed_frame(b"", compression).unwrap();
+		let mut reader = FrameReader::new();
+		reader.extend(writer.pending());
+		let frame = reader.next_decompressed_frame().unwrap().unwrap();
+		assert_eq!(frame, encoded);
+		let frame = reader.next_decompressed_frame().unwrap().unwrap();
+		assert!(frame.is_empty());
+		assert_eq!(reader.next_decompressed_frame(), Ok(None));
+
+		let mut buf = Vec::new();
+		let len = vlen::blocks::encode_compressed_append(
+			&mut buf,
+			&values,
+			512,
+			compression,
+		);
+		assert_eq!(len, Ok(buf.len()));
+		let mut decoded = Vec::new();
+		for block in vlen::blocks::blocks::<f64>(&buf) {
+			let block = block.unwrap();
+			assert_eq!((block.header.min, block.header.max), (0.0, 6.125));
+			let data = block.decompressed_data().unwrap();
+			let iter = vlen::decode_iter::<f64>(&data);
+			decoded.extend(iter.map(Result::unwrap));
+		}
+		assert_eq!(decoded, values);
+	}
+
+	assert_eq!(decompress(&[]), Err("truncated vlen value"));
+	assert_eq!(decompress(&[9, 0]), Err("unsupported compression tag"));
+	#[cfg(feature = "lz4_flex")]
+	{
+		let mut corrupt = compress(&encoded, Compression::Lz4).unwrap();
+		corrupt.truncate(corrupt.len() / 2);
+		assert_eq!(decompress(&corrupt), Err("invalid compressed payload"));
+
+		// Declares 16 TiB from three bytes; rejected before allocating.
+		let bomb = [2, 0xF5, 0, 0, 0, 0, 0, 0x10, 0x10, 0x41];
+		assert_eq!(decompress(&bomb), Err("invalid compressed payload"));
+	}
+	#[cfg(feature = "zstd")]
+	{
+		let mut bomb = vec![Compression::TAG_ZSTD];
+		vlen::encode_append(&mut bomb, 1u64 << 44).unwrap();
+		bomb.extend_from_slice(&[0x28, 0xB5, 0x2F, 0xFD]);
+		assert_eq!(decompress(&bomb), Err("invalid compressed payload"));
+	}
+}
+
+#[test]
+fn test_format_header() {
+	use vlen::header::{negotiate_version, Header, FORMAT_VERSION};
+
+	let mut buf = [0u8; vlen::header::MAX_HEADER_LEN];
+	let flags = Header::FLAG_DELTA | Header::FLAG_COMPRESSED;
+	let len = vlen::write_header(&mut buf, flags).unwrap();
+	assert_eq!(&buf[..len], b"VLEN\x01\x06");
+	let (header, read_len) = vlen::read_header(&buf[..len]).unwrap();
+	assert_eq!(header.version, vlen::format_version());
+	assert_eq!(header.flags, flags);
+	assert!(header.has_flag(Header::FLAG_DELTA));
+	assert!(!header.has_flag(Header::FLAG_SIGN_BIT));
+	assert_eq!(read_len, len);
+
+	let len = vlen::write_header(&mut buf, 1 << 40);
+	assert_eq!(len, Err("unknown header flags"));
+	assert_eq!(
+		vlen::write_header(&mut buf[..5], 0),
+		Err("buffer too small for header")
+	);
+	let errors = [
+		(&b"VLEX\x01\x00"[..], "invalid header magic"),
+		(&b"VLE"[..], "truncated vlen value"),
+		(&b"VLEN\x00\x00"[..], "unsupported format version"),
+		(&b"VLEN\x02\x00"[..], "unsupported format version"),
+		(&b"VLEN\x01\x08"[..], "unknown header flags"),
+		(&b"VLEN\x01"[..], "buffer too small for u64 decoding"),
+	];
+	for (buf, err) in errors {
+		assert_eq!(vlen::read_header(buf), Err(err));
+	}
+
+	assert_eq!(negotiate_version(FORMAT_VERSION), Ok(FORMAT_VERSION));
+	assert_eq!(negotiate_version(u32::MAX), Ok(FORMAT_VERSION));
+	assert_eq!(negotiate_version(0), Err("unsupported format version"));
+}
+
+#[test]
+fn test_encoded_len_per_width() {
+	use vlen::{encoded_len_u128, encoded_len_u16, encoded_len_u32};
+	use vlen::{encoded_len_u64, Decode};
+
+	let invalid = Err("invalid vlen prefix byte");
+	assert_eq!(encoded_len_u16(0x7F), Ok(1));
+	assert_eq!(encoded_len_u16(0xBF), Ok(2));
+	assert_eq!(encoded_len_u16(0xDE), Ok(3));
+	assert_eq!(encoded_len_u16(0xC1), Ok(3));
+	assert_eq!(encoded_len_u16(0xC2), invalid);
+	assert_eq!(encoded_len_u16(0xF3), invalid);
+	assert_eq!(encoded_len_u32(0xEF), Ok(4));
+	assert_eq!(encoded_len_u32(0xF3), Ok(5));
+	assert_eq!(encoded_len_u32(0xF4), invalid);
+	assert_eq!(encoded_len_u32(0xF9), invalid);
+	assert_eq!(encoded_len_u64(0xF7), Ok(9));
+	assert_eq!(encoded_len_u64(0xF8), invalid);
+	assert_eq!(encoded_len_u128(0xFF), Ok(17));
+
+	// The checked lengths agree with the encoder for every width.
+	let mut buf = [0u8; 17];
+	for shift in 0..128 {
+		let value = 1u128 << shift;
+		let len = vlen::encode(&mut buf, value).unwrap();
+		assert_eq!(encoded_len_u128(buf[0]), Ok(len));
+		if let Ok(value) = u64::try_from(value) {
+			let len = vlen::encode(&mut buf, value).unwrap();
+			assert_eq!(encoded_len_u64(buf[0]), Ok(len));
+		}
+		if let Ok(value) = u32::try_from(value) {
+			let len = vlen::encode(&mut buf, value).unwrap();
+			assert_eq!(encoded_len_u32(buf[0]), Ok(len));
+		}
+		if let Ok(value) = u16::try_from(value) {
+			let len = vlen::encode(&mut buf, value).unwrap();
+			assert_eq!(encoded_len_u16(buf[0]), Ok(len));
+		}
+	}
+
+	// Scanners reject a prefix too wide for the stream's type instead of
+	// skipping over it.
+	let mut stream = [0u8; 12];
+	stream[..2].copy_from_slice(&[1, 0xF9]);
+	assert_eq!(vlen::count_values::<u32>(&stream), invalid);
+	assert_eq!(vlen::skip_values::<i32>(&stream, 2), invalid);
+	assert_eq!(vlen::skip_values::<u32>(&stream, 1), Ok(1));
+	assert_eq!(vlen::count_values::<u128>(&stream), Ok(2));
+	assert_eq!(char::check_prefix(0xF4), invalid.map(drop));
+	let err = vlen::validate::<f32>(&stream[1..]).unwrap_err();
+	assert_eq!(err.reason, "invalid vlen prefix byte");
+	assert_eq!(vlen::frame::decode_frame(&[0xF8]), invalid.map(|_| None));
+}
+
+#[test]
+fn test_decode_any() {
+	let mut buf = [0u8; 17];
+	let len = vlen::encode(&mut buf, -3i64).unwrap();
+	assert_eq!(vlen::decode_any(&buf[..len]), Ok((5, 1)));
+	let len = vlen::encode(&mut buf, 1u64 << 40).unwrap();
+	assert_eq!(vlen::decode_any(&buf[..len]), Ok((1 << 40, 7)));
+	let len = vlen::encode(&mut buf, 1u128 << 100).unwrap();
+	assert_eq!(vlen::decode_any(&buf), Ok((1 << 100, len)));
+	assert_eq!(vlen::decode_any(&[]), Err("truncated vlen value"));
+
+	// Every prefix with every length of input either decodes exactly the
+	// announced bytes or fails.
+	let input = [0xA5u8; 17];
+	for prefix in 0..=u8::MAX {
+		for end in 1..=input.len() {
+			let mut buf = input;
+			buf[0] = prefix;
+			match vlen::decode_any(&buf[..end]) {
+				Ok((value, len)) => {
+					assert_eq!(len, vlen::encoded_len(prefix));
+					assert!(len <= end);
+					let (wide, _) = vlen::decode::<u128>(&buf).unwrap();
+					assert_eq!(value, wide);
+				},
+				Err(err) => {
+					assert_eq!(err, "truncated vlen value");
+					assert!(end < vlen::encoded_len(prefix));
+				},
+			}
+		}
+	}
+}
+
+#[test]
+fn test_encoded_len_table() {
+	use vlen::{encoded_len, ENCODED_LEN_TABLE};
+
+	for prefix in 0..=u8::MAX {
+		let len = usize::from(ENCODED_LEN_TABLE[usize::from(prefix)]);
+		assert_eq!(encoded_len(prefix), len);
+		let expected = match prefix {
+			0x00..=0x7F => 1,
+			0x80..=0xBF => 2,
+			0xC0..=0xDF => 3,
+			0xE0..=0xEF => 4,
+			_ => usize::from(prefix & 0x0F) + 2,
+		};
+		assert_eq!(len, expected);
+	}
+
+	// The table agrees with the length every decoder reports.
+	let mut buf = [0u8; 17];
+	for shift in 0..128 {
+		let len = vlen::encode(&mut buf, 1u128 << shift).unwrap();
+		assert_eq!(encoded_len(buf[0]), len);
+		assert_eq!(vlen::decode_u128(&buf).1, len);
+	}
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_vlen_map() {
+	use vlen::VlenMap;
+
+	let mut map = VlenMap::<u64, u32>::new();
+	assert!(map.is_empty());
+	assert_eq!(map.get(1), Ok(None));
+	for key in 0..200u64 {
+		map.insert(key * 0x1_0000_0001, key as u32).unwrap();
+	}
+	map.insert(5 * 0x1_0000_0001, 0xFFFF_FFFF).unwrap();
+	assert_eq!(map.len(), 201);
+	assert_eq!(map.get(5 * 0x1_0000_0001), Ok(Some(0xFFFF_FFFF)));
+	assert_eq!(map.get(6 * 0x1_0000_0001), Ok(Some(6)));
+	assert_eq!(map.get(6), Ok(None));
+	assert_eq!(map.contains_key(0), Ok(true));
+
+	// Indexed lookups agree with the linear scan, including after inserts.
+	let mut indexed = map.clone();
+	indexed.build_index().unwrap();
+	assert!(indexed.is_indexed());
+	indexed.insert(6 * 0x1_0000_0001, 60).unwrap();
+	indexed.insert(u64::MAX, 1).unwrap();
+	map.insert(6 * 0x1_0000_0001, 60).unwrap();
+	map.insert(u64::MAX, 1).unwrap();
+	assert_eq!(indexed.as_bytes(), map.as_bytes());
+	for key in [0, 5, 6, 7, 199, 200, u64::MAX, 3] {
+		assert_eq!(indexed.get(key), map.get(key));
+		assert_eq!(indexed.contains_key(key), map.contains_key(key));
+	}
+
+	let entries: Vec<_> = (&map).into_iter().collect();
+	assert_eq!(entries.len(), map.len());
+	assert_eq!(entries[201], Ok((6 * 0x1_0000_0001, 60)));
+
+	let small = VlenMap::<u16, i64>::from_bytes(vec![3, 1, 7, 4]).unwrap();
+	assert_eq!(small.len(), 2);
+	assert_eq!(small.get(3), Ok(Some(-1)));
+	assert_eq!(small.get(7), Ok(Some(2)));
+
+	let torn = VlenMap::<u16, i64>::from_bytes(vec![3, 1, 4]);
+	assert_eq!(torn, Err("buffer too small for i64 decoding"));
+}
+
+#[cfg(feature = "integer-encoding")]
+#[test]
+fn test_integer_encoding_adapter() {
+	use integer_encoding::VarInt;
+	use vlen::integer_encoding::Vlen;
+
+	for value in [0u64, 127, 128, 16383, 1 << 40, u64::MAX] {
+		let encoded = Vlen(value).encode_var_vec();
+		assert_eq!(encoded, vlen::encode_to_vec(value).unwrap());
+		assert_eq!(Vlen(value).required_space(), encoded.len());
+		assert_eq!(
+			Vlen::<u64>::decode_var(&encoded),
+			Some((Vlen(value), encoded.len()))
+		);
+		let truncated = &encoded[..encoded.len() - 1];
+		assert_eq!(Vlen::<u64>::decode_var(truncated), None);
+	}
+	for value in [0i32, -1, 63, -64, i32::MIN, i32::MAX] {
+		let mut buf = [0u8; 5];
+		let len = Vlen(value).encode_var(&mut buf);
+		assert_eq!(vlen::decode_value::<i32>(&buf[..len]), Ok(value));
+		assert_eq!(Vlen::<i32>::decode_var(&buf), Some((Vlen(value), len)));
+	}
+	assert_eq!(Vlen::from(7u16).into_inner(), 7);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_column_file() {
+	use vlen::column::{ColumnReader, ColumnWriter, MAGIC};
+
+	let mut writer = ColumnWriter::new(Vec::new(), 4).unwrap();
+	for value in [5i64, -3, 9, 0, 100, -100, 7] {
+		writer.append(value).unwrap();
+	}
+	assert_eq!(writer.num_blocks(), 1);
+	writer.flush().unwrap();
+	assert_eq!(writer.num_blocks(), 2);
+	writer.append(42).unwrap();
+	let file = writer.finish().unwrap();
+	assert_eq!(file[..4], MAGIC);
+	assert_eq!(file[file.len() - 4..], MAGIC);
+
+	let reader = ColumnReader::<i64>::new(&file).unwrap();
+	assert_eq!(reader.num_blocks(), 3);
+	assert_eq!(reader.num_values(), 8);
+	let block = reader.read_block(1).unwrap();
+	assert_eq!((block.header.min, block.header.max), (-100, 100));
+	let values: Result<Vec<i64>, _> = block.values().collect();
+	assert_eq!(values.unwrap(), [100, -100, 7]);
+	assert_eq!(reader.read_block(3).err(), Some("block index out of range"));
+
+	let empty = ColumnWriter::<_, u32>::new(Vec::new(), 8)
+		.unwrap()
+		.finish()
+		.unwrap();
+	assert_eq!(ColumnReader::<u32>::new(&empty).unwrap().num_blocks(), 0);
+
+	assert!(ColumnReader::<i64>::new(&file[..file.len() - 1]).is_err());
+	let mut corrupt = file.clone();
+	corrupt[file.len() - 8] ^= 0x40;
+	assert!(ColumnReader::<i64>::new(&corrupt).is_err());
+	assert!(ColumnWriter::<_, u32>::new(Vec::new(), 0).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_column_pushdown() {
+	use core::ops::{Bound, RangeBounds};
+	use vlen::column::{ColumnReader, ColumnWriter};
+
+	let values: Vec<i32> = (0..500).map(|i| (i * 7) % 250 - 100).collect();
+	let mut writer = ColumnWriter::new(Vec::new(), 64).unwrap();
+	for &value in &values {
+		writer.append(value).unwrap();
+	}
+	let file = writer.finish().unwrap();
+	let reader = ColumnReader::<i32>::new(&file).unwrap();
+
+	let mut decoded = 0;
+	for block in reader.blocks_where(|&min, &max| min <= -95 && -95 <= max) {
+		let block = block.unwrap();
+		assert!(block.header.min <= -95 && -95 <= block.header.max);
+		decoded += 1;
+	}
+	assert!(decoded > 0);
+	assert_eq!(reader.blocks_where(|_, &max| max > 1000).count(), 0);
+
+	let ranges: [(Bound<i32>, Bound<i32>); 4] = [
+		(Bound::Included(-100), Bound::Excluded(0)),
+		(Bound::Excluded(10), Bound::Included(149)),
+		(Bound::Unbounded, Bound::Included(-50)),
+		(Bound::Included(500), Bound::Unbounded),
+	];
+	for range in ranges {
+		let expected =
+			values.iter().filter(|value| range.contains(value)).count();
+		assert_eq!(reader.count_matching(range), Ok(expected));
+	}
+	assert_eq!(reader.count_matching(..), Ok(values.len()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_column_resume() {
+	use std::fs::OpenOptions;
+	use vlen::column::{ColumnReader, ColumnWriter};
+
+	let path = std::env::temp_dir()
+		.join(format!("vlen-test-{}.vlcf", std::process::id()));
+	let open = || {
+		OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.open(&path)
+			.unwrap()
+	};
+	std::fs::write(&path, b"VL").unwrap();
+	let mut writer = ColumnWriter::<_, u64>::resume(open(), 3).unwrap();
+	for value in 0..7 {
+		writer.append(value * 1000).unwrap();
+	}
+	writer.flush().unwrap();
+	drop(writer);
+
+	// Tear the last block.
+	let len = std::fs::metadata(&path).unwrap().len();
+	open().set_len(len - 2).unwrap();
+	let mut writer = ColumnWriter::<_, u64>::resume(open(), 3).unwrap();
+	assert_eq!(writer.num_blocks(), 2);
+	writer.append(6000).unwrap();
+	writer.finish().unwrap();
+
+	// Resuming a finished file drops the footer.
+	let mut writer = ColumnWriter::<_, u64>::resume(open(), 3).unwrap();
+	assert_eq!(writer.num_blocks(), 3);
+	writer.append(7000).unwrap();
+	writer.finish().unwrap();
+
+	let file = std::fs::read(&path).unwrap();
+	let reader = ColumnReader::<u64>::new(&file).unwrap();
+	let mut values = Vec::new();
+	for index in 0..reader.num_blocks() {
+		let block = reader.read_block(index).unwrap();
+		values.extend(block.values().map(Result::unwrap));
+	}
+	assert_eq!(values, (0..8).map(|i| i * 1000).collect::<Vec<u64>>());
+
+	let mut corrupt = file.clone();
+	corrupt[6] ^= 1;
+	let reader = ColumnReader::<u64>::new(&corrupt).unwrap();
+	assert_eq!(reader.read_block(0).err(), Some("checksum mismatch"));
+
+	std::fs::write(&path, b"not a column file").unwrap();
+	assert!(ColumnWriter::<_, u64>::resume(open(), 3).is_err());
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_sortedset_search() {
+	use vlen::sortedset::{self, SetIndex};
+
+	let set: Vec<u64> = (0..1000u64).map(|i| 2 * i * i + 5).collect();
+	let mut buf = vec![0u8; 4096];
+	let len = sortedset::encode(&mut buf, &set).unwrap();
+	let buf = &buf[..len];
+
+	for every_n in [1, 7, 64, 2000] {
+		let index = SetIndex::<u64>::build(buf, every_n).unwrap();
+		assert_eq!(index.len(), set.len());
+		for (position, &value) in set.iter().enumerate() {
+			let found = sortedset::search(buf, &index, value);
+			assert_eq!(found, Ok(Some(position)));
+			assert_eq!(sortedset::search(buf, &index, value + 1), Ok(None));
+		}
+		assert_eq!(sortedset::search(buf, &index, 0), Ok(None));
+	}
+
+	let empty = SetIndex::<u64>::build(&[], 16).unwrap();
+	assert!(empty.is_empty());
+	assert_eq!(sortedset::search(&[], &empty, 5), Ok(None));
+	assert!(SetIndex::<u64>::build(buf, 0).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_sink() {
+	use vlen::sink::IoSink;
+	use vlen::{encode_into, Sink, VlenBuf};
+
+	let values = [0u64, 300, 1 << 40, u64::MAX];
+	let expected = vlen::bulk_encode_to_vec(&values).unwrap();
+
+	let mut buf = [0u8; 32];
+	let mut slice = &mut buf[..];
+	let mut vec = Vec::new();
+	let mut vlen_buf = VlenBuf::new();
+	let mut io = IoSink::new(Vec::new());
+	for &value in &values {
+		encode_into(&mut slice, value).unwrap();
+		encode_into(&mut vec, value).unwrap();
+		encode_into(&mut vlen_buf, value).unwrap();
+		encode_into(&mut io, value).unwrap();
+	}
+	let written = 32 - slice.len();
+	assert_eq!(buf[..written], expected);
+	assert_eq!(vec, expected);
+	assert_eq!(vlen_buf.as_slice(), expected);
+	assert_eq!(vlen_buf.len_values(), values.len());
+	assert_eq!(io.into_inner(), expected);
+
+	let mut small = [0u8; 2];
+	let mut slice = &mut small[..];
+	assert!(encode_into(&mut slice, 70000u32).is_err());
+	assert_eq!(slice.len(), 2);
+	assert!(slice.write_bytes(b"abc").is_err());
+	assert_eq!(encode_into(&mut slice, 5u16), Ok(1));
+	assert_eq!(slice.len(), 1);
+
+	let mut one = [0u8; 1];
+	let mut full = IoSink::new(&mut one[..]);
+	assert_eq!(full.write_bytes(b"ab"), Err("sink write failed"));
+	assert!(full.take_error().is_some());
+
+	let mut framed = IoSink::new(Vec::new());
+	vlen::frame::encode_frame_into(&mut framed, b"hello").unwrap();
+	assert_eq!(framed.get_ref(), b"\x05hello");
+
+	let block_values: Vec<i32> = (-50..50).collect();
+	let mut blocks_vec = Vec::new();
+	vlen::blocks::encode_append(&mut blocks_vec, &block_values, 16).unwrap();
+	let mut blocks_buf = [0u8; 256];
+	let mut slice = &mut blocks_buf[..];
+	let len =
+		vlen::blocks::encode_into(&mut slice, &block_values, 16).unwrap();
+	assert_eq!(blocks_buf[..len], blocks_vec);
+}
+
+#[test]
+fn test_decode_never_reports_more_than_buffer() {
+	// Binary prefixes wider than the type used to decode with the length
+	// of the prefix, past the end of the buffer.
+	let wide = [0xFF, 0, 0, 0, 0];
+	assert_eq!(vlen::decode::<u32>(&wide), Err("invalid vlen prefix byte"));
+	assert_eq!(vlen::decode::<u64>(&wide), Err("invalid vlen prefix byte"));
+	assert_eq!(vlen::decode::<u16>(&wide), Err("invalid vlen prefix byte"));
+	assert_eq!(vlen::decode::<u128>(&wide), Err("truncated vlen value"));
+	assert_eq!(
+		vlen::decode::<u64>(&[0xF7, 1, 0, 0, 0, 0, 0, 0]),
+		Err("truncated vlen value")
+	);
+
+	let mut iter = vlen::decode_iter::<u32>(&wide);
+	assert_eq!(iter.size_hint(), (1, Some(5)));
+	assert!(iter.next().unwrap().is_err());
+	assert!(iter.remaining().is_empty());
+	assert_eq!(iter.size_hint(), (0, Some(0)));
+
+	let mut values = [0u32; 4];
+	assert!(vlen::decode::bulk_decode_counted(&wide, &mut values).is_err());
+	let mut stream = [0u8; 6];
+	stream[0] = 3;
+	stream[1..].copy_from_slice(&wide);
+	assert!(vlen::decode::bulk_decode_counted(&stream, &mut values).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_decode_seq_rejects_wide_prefix() {
+	assert!(vlen::decode_seq_to_vec::<u32>(&[2, 0xFF, 0, 0, 0, 0]).is_err());
+	assert!(vlen::decode_seq_to_vec::<u16>(&[1, 0xC5, 0, 0]).is_err());
+	// A count prefix longer than the buffer.
+	assert!(vlen::decode_seq_to_vec::<u32>(&[0xF7, 1]).is_err());
+	assert!(vlen::decode_seq_to_vec::<u32>(&[0xFF, 0, 0, 0, 0]).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_column_resume_torn_trailer() {
+	use std::fs::OpenOptions;
+	use vlen::column::{ColumnWriter, MAGIC};
+
+	let path = std::env::temp_dir()
+		.join(format!("vlen-test-{}-torn.vlcf", std::process::id()));
+	let open = || {
+		OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.open(&path)
+			.unwrap()
+	};
+
+	// A file holding one block, without its checksum trailer.
+	let mut file = MAGIC.to_vec();
+	vlen::blocks::encode_append(&mut file, &[7u32], 8).unwrap();
+	let block_end = file.len();
+
+	let mut state = 0x2545_F491u32;
+	for first in 0..=255u8 {
+		for trailer_len in 1..=5 {
+			let mut torn = file.clone();
+			torn.push(first);
+			for _ in 1..trailer_len {
+				state ^= state << 13;
+				state ^= state >> 17;
+				state ^= state << 5;
+				torn.push(state as u8);
+			}
+			std::fs::write(&path, &torn).unwrap();
+			let writer = ColumnWriter::<_, u32>::resume(open(), 8).unwrap();
+			let len = std::fs::metadata(&path).unwrap().len() as usize;
+			match writer.num_blocks() {
+				0 => assert_eq!(len, 4),
+				1 => assert!(len > block_end && len <= torn.len()),
+				_ => unreachable!(),
+			}
+		}
+	}
+	// Wide prefixes in front of the right checksum bytes.
+	let checksum = vlen::checksum::Crc32c::checksum(&file[4..]);
+	for first in 0xF0..=0xFF {
+		let mut torn = file.clone();
+		torn.push(first);
+		torn.extend_from_slice(&checksum.to_le_bytes());
+		std::fs::write(&path, &torn).unwrap();
+		let writer = ColumnWriter::<_, u32>::resume(open(), 8).unwrap();
+		assert_eq!(writer.num_blocks(), usize::from(first == 0xF3));
+	}
+	std::fs::remove_file(&path).unwrap();
+}
+
 #[test]
 fn test_decode_value() {
 	let mut buf = [0u8; 5];
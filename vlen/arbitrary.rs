@@ -0,0 +1,123 @@
+//! Fuzzing support via `arbitrary`
+//!
+//! [`ValidBuffer`] and [`TruncatedBuffer`] turn unstructured fuzzer input
+//! into encoded buffers with a known expected outcome, so protocol fuzzers
+//! spend their time on interesting inputs instead of rejected noise.
+//! `Arbitrary` is also implemented for the [`Vlen*`](crate::wrappers)
+//! wrapper types.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use vlen::arbitrary::{TruncatedBuffer, ValidBuffer};
+//!
+//! let mut u = Unstructured::new(&[7, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+//! let valid = ValidBuffer::<u32>::arbitrary(&mut u).unwrap();
+//! assert_eq!(vlen::bulk_decode_values::<u32>(&valid.bytes), Ok(valid.values));
+//!
+//! let truncated = TruncatedBuffer::<u32>::arbitrary(&mut u).unwrap();
+//! assert!(vlen::bulk_decode_values::<u32>(&truncated.bytes).is_err());
+//! ```
+
+use alloc::vec::Vec;
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::encode::Encode;
+
+/// A buffer holding the valid encoding of `values`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidBuffer<T> {
+	/// The encoded values, in order.
+	pub values: Vec<T>,
+	/// The encoding of `values`.
+	pub bytes: Vec<u8>,
+}
+
+impl<'a, T> Arbitrary<'a> for ValidBuffer<T>
+where
+	T: Arbitrary<'a> + Encode + Copy,
+{
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let values: Vec<T> = u.arbitrary()?;
+		let bytes = encode_values(&values)?;
+		Ok(Self { values, bytes })
+	}
+}
+
+/// A buffer of valid values followed by a value that is cut short.
+///
+/// For the built-in types, decoding every value in `bytes` always fails
+/// after `values` have been decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncatedBuffer<T> {
+	/// The complete values at the start of the buffer.
+	pub values: Vec<T>,
+	/// The encoding of `values` followed by an incomplete value.
+	pub bytes: Vec<u8>,
+}
+
+impl<'a, T> Arbitrary<'a> for TruncatedBuffer<T>
+where
+	T: Arbitrary<'a> + Encode + Copy,
+{
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let values: Vec<T> = u.arbitrary()?;
+		let mut bytes = encode_values(&values)?;
+
+		let mut tail = [0u8; 32];
+		let tail_value: T = u.arbitrary()?;
+		let tail_len = if T::MAX_ENCODED_SIZE <= tail.len() {
+			T::encode(&mut tail, tail_value).unwrap_or(0)
+		} else {
+			0
+		};
+		if tail_len >= 2 {
+			let cut = u.int_in_range(1..=tail_len - 1)?;
+			bytes.extend_from_slice(&tail[..cut]);
+		} else {
+			// A two-byte prefix with no payload is incomplete for every type.
+			bytes.push(0x80);
+		}
+		Ok(Self { values, bytes })
+	}
+}
+
+fn encode_values<T>(values: &[T]) -> Result<Vec<u8>>
+where
+	T: Encode + Copy,
+{
+	crate::bulk_encode_to_vec(values)
+		.map_err(|_| ::arbitrary::Error::IncorrectFormat)
+}
+
+/// Implements `Arbitrary` for wrapper types by delegating to the inner type.
+macro_rules! impl_arbitrary_wrapper {
+	($($wrapper:ident($inner:ty)),* $(,)?) => {$(
+		impl<'a> Arbitrary<'a> for crate::wrappers::$wrapper {
+			#[inline]
+			fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+				Ok(Self(u.arbitrary()?))
+			}
+
+			#[inline]
+			fn size_hint(depth: usize) -> (usize, Option<usize>) {
+				<$inner as Arbitrary<'a>>::size_hint(depth)
+			}
+		}
+	)*};
+}
+
+impl_arbitrary_wrapper!(
+	VlenU16(u16),
+	VlenU32(u32),
+	VlenU64(u64),
+	VlenU128(u128),
+	VlenI16(i16),
+	VlenI32(i32),
+	VlenI64(i64),
+	VlenI128(i128),
+	VlenF32(f32),
+	VlenF64(f64),
+);
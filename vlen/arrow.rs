@@ -0,0 +1,92 @@
+//! Apache Arrow interop
+//!
+//! Encodes `UInt32Array` and `UInt64Array` into a vlen buffer and decodes
+//! it back into Arrow buffers. The buffer starts with a layout byte:
+//!
+//! - [`NO_NULLS`]: the values follow as a plain vlen stream.
+//! - [`NULLABLE`]: each slot follows as a value of the next wider type,
+//!   `0` for a null and `value + 1` otherwise, so the validity bitmap
+//!   travels with the values.
+//!
+//! Arrays without nulls always use [`NO_NULLS`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use arrow_array::UInt32Array;
+//! use vlen::arrow::{decode_u32_array, encode_u32_array};
+//!
+//! let array = UInt32Array::from(vec![Some(1), None, Some(300)]);
+//! let buf = encode_u32_array(&array).unwrap();
+//! assert_eq!(decode_u32_array(&buf).unwrap(), array);
+//! ```
+
+use alloc::vec::Vec;
+
+use arrow_array::types::{UInt32Type, UInt64Type};
+use arrow_array::{Array, PrimitiveArray};
+use arrow_buffer::NullBuffer;
+
+/// Layout byte for an array without nulls.
+pub const NO_NULLS: u8 = 0;
+
+/// Layout byte for an array with a validity bitmap.
+pub const NULLABLE: u8 = 1;
+
+/// Generates the encode and decode functions for an Arrow type.
+macro_rules! arrow_functions {
+	($arrow:ty, $t:ty, $wide:ty, $encode:ident, $decode:ident) => {
+		#[doc = concat!("Encodes a `PrimitiveArray<", stringify!($arrow), ">` into a new buffer.")]
+		pub fn $encode(
+			array: &PrimitiveArray<$arrow>,
+		) -> Result<Vec<u8>, &'static str> {
+			let mut buf = Vec::new();
+			if array.null_count() == 0 {
+				buf.push(NO_NULLS);
+				crate::bulk_encode_append(&mut buf, array.values())?;
+				return Ok(buf);
+			}
+			buf.push(NULLABLE);
+			for slot in array {
+				let slot = slot.map_or(0, |value| <$wide>::from(value) + 1);
+				crate::encode_append(&mut buf, slot)?;
+			}
+			Ok(buf)
+		}
+
+		#[doc = concat!("Decodes a buffer written by [`", stringify!($encode), "`].")]
+		pub fn $decode(
+			buf: &[u8],
+		) -> Result<PrimitiveArray<$arrow>, &'static str> {
+			let (&layout, buf) =
+				buf.split_first().ok_or("truncated vlen value")?;
+			match layout {
+				NO_NULLS => {
+					let values: Vec<$t> = crate::bulk_decode_values(buf)?;
+					Ok(PrimitiveArray::new(values.into(), None))
+				},
+				NULLABLE => {
+					let slots: Vec<$wide> = crate::bulk_decode_values(buf)?;
+					let mut values = Vec::with_capacity(slots.len());
+					let mut validity = Vec::with_capacity(slots.len());
+					for slot in slots {
+						let value = match slot.checked_sub(1) {
+							Some(value) => <$t>::try_from(value).map_err(|_| {
+								concat!("value out of range for ", stringify!($t))
+							})?,
+							None => 0,
+						};
+						values.push(value);
+						validity.push(slot != 0);
+					}
+					let nulls = NullBuffer::from(validity);
+					Ok(PrimitiveArray::new(values.into(), Some(nulls)))
+				},
+				_ => Err("unknown arrow layout byte"),
+			}
+		}
+	};
+}
+
+arrow_functions!(UInt32Type, u32, u64, encode_u32_array, decode_u32_array);
+arrow_functions!(UInt64Type, u64, u128, encode_u64_array, decode_u64_array);
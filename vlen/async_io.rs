@@ -0,0 +1,269 @@
+//! Asynchronous streaming over `futures` I/O traits
+//!
+//! [`AsyncVlenReadExt`] and [`AsyncVlenWriteExt`] extend any
+//! [`AsyncRead`] or [`AsyncWrite`] implementation. Reads fetch the prefix byte
+//! first and then exactly the remaining bytes of the value, so values split
+//! across partial reads are reassembled correctly and no bytes past the value
+//! are consumed. Decoding errors are reported as
+//! [`io::ErrorKind::InvalidData`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use futures_util::io::Cursor;
+//! use futures_util::FutureExt;
+//! use vlen::async_io::{AsyncVlenReadExt, AsyncVlenWriteExt};
+//!
+//! let mut cursor = Cursor::new(Vec::new());
+//! cursor.write_vlen_u64(70000).now_or_never().unwrap().unwrap();
+//!
+//! cursor.set_position(0);
+//! let value = cursor.read_vlen_u64().now_or_never().unwrap().unwrap();
+//! assert_eq!(value, 70000);
+//! ```
+//!
+//! [`VlenStream`] turns a reader into a [`Stream`] of decoded values:
+//!
+//! ```rust
+//! use futures_util::io::Cursor;
+//! use futures_util::{FutureExt, StreamExt};
+//! use vlen::async_io::VlenStream;
+//!
+//! let buf = vlen::bulk_encode_to_vec(&[1u32, 300, 70000]).unwrap();
+//! let mut stream = VlenStream::<_, u32>::new(Cursor::new(buf));
+//! async {
+//!     while let Some(value) = stream.next().await {
+//!         println!("{}", value.unwrap());
+//!     }
+//! }
+//! .now_or_never()
+//! .unwrap();
+//! ```
+
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::boxed::Box;
+use std::io;
+
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures_util::stream::Stream;
+
+use crate::decode::{prefix_encoded_len, Decode};
+use crate::encode::Encode;
+
+fn invalid_data(err: &'static str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+macro_rules! read_methods {
+	($($name:ident: $t:ty),* $(,)?) => {$(
+		#[doc = concat!("Reads a vlen-encoded `", stringify!($t), "`.")]
+		#[inline]
+		fn $name(&mut self) -> impl Future<Output = io::Result<$t>> + '_ {
+			self.read_vlen::<$t>()
+		}
+	)*};
+}
+
+macro_rules! write_methods {
+	($($name:ident: $t:ty),* $(,)?) => {$(
+		#[doc = concat!(
+			"Writes a vlen-encoded `", stringify!($t), "`, returning the ",
+			"number of bytes written."
+		)]
+		#[inline]
+		fn $name(
+			&mut self,
+			value: $t,
+		) -> impl Future<Output = io::Result<usize>> + '_ {
+			self.write_vlen(value)
+		}
+	)*};
+}
+
+/// Extension trait for reading vlen values from an [`AsyncRead`].
+pub trait AsyncVlenReadExt: AsyncRead + Unpin {
+	/// Reads a single vlen-encoded value.
+	///
+	/// `T` must encode as a single vlen value, as the built-in numeric types
+	/// do; composite types such as `Duration` are not supported.
+	fn read_vlen<T: Decode>(
+		&mut self,
+	) -> impl Future<Output = io::Result<T>> + '_ {
+		async move {
+			let mut buf = [0u8; 17];
+			self.read_exact(&mut buf[..1]).await?;
+			let len = prefix_encoded_len(buf[0]);
+			self.read_exact(&mut buf[1..len]).await?;
+			let (value, decoded_len) =
+				T::decode(&buf[..len]).map_err(invalid_data)?;
+			if decoded_len != len {
+				return Err(invalid_data("invalid vlen prefix byte"));
+			}
+			Ok(value)
+		}
+	}
+
+	read_methods! {
+		read_vlen_u16: u16,
+		read_vlen_u32: u32,
+		read_vlen_u64: u64,
+		read_vlen_u128: u128,
+		read_vlen_i16: i16,
+		read_vlen_i32: i32,
+		read_vlen_i64: i64,
+		read_vlen_i128: i128,
+		read_vlen_f32: f32,
+		read_vlen_f64: f64,
+	}
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> AsyncVlenReadExt for R {}
+
+/// Extension trait for writing vlen values to an [`AsyncWrite`].
+pub trait AsyncVlenWriteExt: AsyncWrite + Unpin {
+	/// Writes a vlen-encoded value, returning the number of bytes written.
+	fn write_vlen<T: Encode>(
+		&mut self,
+		value: T,
+	) -> impl Future<Output = io::Result<usize>> + '_ {
+		let mut buf = [0u8; 32];
+		let encoded = buf
+			.get_mut(..T::MAX_ENCODED_SIZE)
+			.ok_or("value too large for streaming")
+			.and_then(|buf| T::encode(buf, value))
+			.map_err(invalid_data);
+		async move {
+			let len = encoded?;
+			self.write_all(&buf[..len]).await?;
+			Ok(len)
+		}
+	}
+
+	write_methods! {
+		write_vlen_u16: u16,
+		write_vlen_u32: u32,
+		write_vlen_u64: u64,
+		write_vlen_u128: u128,
+		write_vlen_i16: i16,
+		write_vlen_i32: i32,
+		write_vlen_i64: i64,
+		write_vlen_i128: i128,
+		write_vlen_f32: f32,
+		write_vlen_f64: f64,
+	}
+}
+
+impl<W: AsyncWrite + Unpin + ?Sized> AsyncVlenWriteExt for W {}
+
+/// Size of the read buffer of a [`VlenStream`].
+const STREAM_BUF_LEN: usize = 4096;
+
+/// A [`Stream`] of values decoded from an [`AsyncRead`].
+///
+/// Bytes are read in blocks into an internal buffer, and a value split
+/// across reads is completed by the next read. The stream ends when the
+/// reader does; ending partway through a value yields an
+/// [`io::ErrorKind::UnexpectedEof`] error. As with
+/// [`read_vlen`](AsyncVlenReadExt::read_vlen), `T` must encode as a single
+/// vlen value. After a decoding error the stream ends.
+pub struct VlenStream<R, T> {
+	reader: R,
+	buf: Box<[u8; STREAM_BUF_LEN]>,
+	start: usize,
+	end: usize,
+	done: bool,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<R, T> VlenStream<R, T>
+where
+	R: AsyncRead + Unpin,
+	T: Decode,
+{
+	/// Creates a stream that decodes values read from `reader`.
+	#[inline]
+	pub fn new(reader: R) -> Self {
+		Self {
+			reader,
+			buf: Box::new([0; STREAM_BUF_LEN]),
+			start: 0,
+			end: 0,
+			done: false,
+			_marker: PhantomData,
+		}
+	}
+
+	/// Returns a reference to the underlying reader.
+	#[inline]
+	pub fn get_ref(&self) -> &R {
+		&self.reader
+	}
+
+	/// Returns the underlying reader.
+	///
+	/// Bytes already read but not yet decoded are lost.
+	#[inline]
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+
+	/// Decodes the value at the front of the buffer, if it is complete.
+	fn decode_buffered(&mut self) -> Option<io::Result<T>> {
+		let pending = &self.buf[self.start..self.end];
+		let len = prefix_encoded_len(*pending.first()?);
+		let encoding = pending.get(..len)?;
+		self.start += len;
+		let result = match T::decode(encoding) {
+			Ok((value, decoded_len)) if decoded_len == len => Ok(value),
+			Ok(_) => Err(invalid_data("invalid vlen prefix byte")),
+			Err(err) => Err(invalid_data(err)),
+		};
+		self.done = result.is_err();
+		Some(result)
+	}
+}
+
+impl<R, T> Stream for VlenStream<R, T>
+where
+	R: AsyncRead + Unpin,
+	T: Decode,
+{
+	type Item = io::Result<T>;
+
+	fn poll_next(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+	) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			if this.done {
+				return Poll::Ready(None);
+			}
+			if let Some(result) = this.decode_buffered() {
+				return Poll::Ready(Some(result));
+			}
+			this.buf.copy_within(this.start..this.end, 0);
+			this.end -= this.start;
+			this.start = 0;
+			let read = Pin::new(&mut this.reader)
+				.poll_read(cx, &mut this.buf[this.end..]);
+			match read {
+				Poll::Pending => return Poll::Pending,
+				Poll::Ready(Ok(0)) => {
+					this.done = true;
+					if this.end > 0 {
+						let eof = io::ErrorKind::UnexpectedEof;
+						return Poll::Ready(Some(Err(eof.into())));
+					}
+				},
+				Poll::Ready(Ok(read)) => this.end += read,
+				Poll::Ready(Err(err))
+					if err.kind() == io::ErrorKind::Interrupted => {},
+				Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err))),
+			}
+		}
+	}
+}
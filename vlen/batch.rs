@@ -0,0 +1,329 @@
+//! Multi-column record batches
+//!
+//! A record batch stores several equally long columns of different types in
+//! one buffer, in struct-of-arrays layout. [`RecordBatchEncoder`] takes the
+//! columns as typed slices and writes the batch as a
+//! [frame](crate::frame), so batches can follow each other in a stream.
+//! [`RecordBatch`] reads one back and decodes any column on its own, without
+//! touching the others.
+//!
+//! The frame payload starts with a schema header: the row count and the
+//! column count as vlen `u64`s, a [`ColumnType`] tag byte per column and the
+//! encoded length of each column as a vlen `u64`. The columns follow, each a
+//! run of vlen values, at offsets given by summing the lengths.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::batch::{ColumnType, RecordBatch, RecordBatchEncoder};
+//!
+//! let ids = [1u32, 2, 3];
+//! let deltas = [-5i64, 0, 12];
+//! let prices = [9.5f64, 10.25, 8.0];
+//!
+//! let mut encoder = RecordBatchEncoder::new();
+//! encoder.push_column(&ids)?.push_column(&deltas)?.push_column(&prices)?;
+//! let buf = encoder.finish();
+//!
+//! let (batch, len) = RecordBatch::decode(&buf)?;
+//! assert_eq!(len, buf.len());
+//! assert_eq!(batch.num_rows(), 3);
+//! assert_eq!(batch.column_type(2), Some(ColumnType::F64));
+//! assert_eq!(batch.column::<f64>(2)?, prices);
+//! assert_eq!(batch.column::<u32>(0)?, ids);
+//! # Ok::<(), &'static str>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::decode::{Decode, DecodeIter};
+use crate::encode::Encode;
+use crate::sink::{encode_into, Sink};
+
+/// The type of the values in a column, stored as a tag byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnType {
+	/// `u16` values.
+	U16 = 1,
+	/// `u32` values.
+	U32 = 2,
+	/// `u64` values.
+	U64 = 3,
+	/// `i16` values.
+	I16 = 4,
+	/// `i32` values.
+	I32 = 5,
+	/// `i64` values.
+	I64 = 6,
+	/// `f32` values.
+	F32 = 7,
+	/// `f64` values.
+	F64 = 8,
+}
+
+impl ColumnType {
+	/// Returns the column type with the tag byte `tag`.
+	#[must_use]
+	pub const fn from_tag(tag: u8) -> Option<Self> {
+		Some(match tag {
+			1 => ColumnType::U16,
+			2 => ColumnType::U32,
+			3 => ColumnType::U64,
+			4 => ColumnType::I16,
+			5 => ColumnType::I32,
+			6 => ColumnType::I64,
+			7 => ColumnType::F32,
+			8 => ColumnType::F64,
+			_ => return None,
+		})
+	}
+
+	/// Returns the tag byte of the column type.
+	#[inline]
+	#[must_use]
+	pub const fn tag(self) -> u8 {
+		self as u8
+	}
+}
+
+/// Types that can be stored in a record batch column.
+pub trait ColumnValue: Encode + Decode + Copy {
+	/// The column type written for columns of this type.
+	const COLUMN_TYPE: ColumnType;
+}
+
+macro_rules! impl_column_value {
+	($($t:ty => $column_type:ident),* $(,)?) => {$(
+		impl ColumnValue for $t {
+			const COLUMN_TYPE: ColumnType = ColumnType::$column_type;
+		}
+	)*};
+}
+
+impl_column_value! {
+	u16 => U16,
+	u32 => U32,
+	u64 => U64,
+	i16 => I16,
+	i32 => I32,
+	i64 => I64,
+	f32 => F32,
+	f64 => F64,
+}
+
+/// Builds a record batch from typed column slices.
+///
+/// Columns are encoded as they are pushed, so the slices need not outlive
+/// the call. Every column must have as many values as the first.
+#[derive(Debug, Clone, Default)]
+pub struct RecordBatchEncoder {
+	rows: Option<usize>,
+	types: Vec<ColumnType>,
+	lens: Vec<usize>,
+	data: Vec<u8>,
+}
+
+impl RecordBatchEncoder {
+	/// Creates an encoder with no columns.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			rows: None,
+			types: Vec::new(),
+			lens: Vec::new(),
+			data: Vec::new(),
+		}
+	}
+
+	/// Appends a column.
+	///
+	/// Fails if the column's length differs from the columns already
+	/// pushed, leaving the encoder unchanged.
+	pub fn push_column<T>(
+		&mut self,
+		values: &[T],
+	) -> Result<&mut Self, &'static str>
+	where
+		T: ColumnValue,
+	{
+		if self.rows.is_some_and(|rows| rows != values.len()) {
+			return Err("column length does not match batch");
+		}
+		let len = crate::bulk_encode_append(&mut self.data, values)?;
+		self.rows = Some(values.len());
+		self.types.push(T::COLUMN_TYPE);
+		self.lens.push(len);
+		Ok(self)
+	}
+
+	/// Returns the number of columns pushed so far.
+	#[inline]
+	#[must_use]
+	pub fn num_columns(&self) -> usize {
+		self.types.len()
+	}
+
+	/// Appends the batch as a frame to `vec`, returning the number of bytes
+	/// written.
+	pub fn finish_into(&self, vec: &mut Vec<u8>) -> usize {
+		self.write_to(vec).expect("vectors accept every write")
+	}
+
+	/// Writes the batch as a frame into `sink`, returning the number of
+	/// bytes written.
+	pub fn write_to<S>(&self, sink: &mut S) -> Result<usize, &'static str>
+	where
+		S: Sink + ?Sized,
+	{
+		let mut header = Vec::new();
+		encode_into(&mut header, self.rows.unwrap_or(0) as u64)?;
+		encode_into(&mut header, self.types.len() as u64)?;
+		header.extend(self.types.iter().map(|column_type| column_type.tag()));
+		for &len in &self.lens {
+			encode_into(&mut header, len as u64)?;
+		}
+		let payload_len = header.len() + self.data.len();
+		let prefix_len = encode_into(sink, payload_len as u64)?;
+		sink.write_bytes(&header)?;
+		sink.write_bytes(&self.data)?;
+		Ok(prefix_len + payload_len)
+	}
+
+	/// Returns the batch as a frame in a newly allocated vector.
+	#[must_use]
+	pub fn finish(&self) -> Vec<u8> {
+		let mut vec = Vec::new();
+		self.finish_into(&mut vec);
+		vec
+	}
+}
+
+/// A record batch read from a buffer, borrowing its column data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordBatch<'a> {
+	rows: usize,
+	columns: Vec<(ColumnType, &'a [u8])>,
+}
+
+impl<'a> RecordBatch<'a> {
+	/// Reads the batch framed at the start of `buf`, returning it and the
+	/// number of bytes consumed.
+	///
+	/// Only the schema header is decoded; the columns are decoded on demand.
+	pub fn decode(buf: &'a [u8]) -> Result<(Self, usize), &'static str> {
+		let (payload, frame_len) =
+			crate::frame::decode_frame(buf)?.ok_or("truncated vlen value")?;
+		let (rows, mut offset) = u64::decode(payload)?;
+		let rows =
+			usize::try_from(rows).map_err(|_| "vlen value out of range")?;
+		let (count, len) = u64::decode(&payload[offset..])?;
+		offset += len;
+		// Every column takes a tag byte and at least one length byte.
+		if count > ((payload.len() - offset) / 2) as u64 {
+			return Err("sequence count exceeds buffer length");
+		}
+		let count = count as usize;
+		let tags = &payload[offset..offset + count];
+		offset += count;
+
+		let mut columns = Vec::with_capacity(count);
+		let mut data_len = 0usize;
+		for &tag in tags {
+			let column_type =
+				ColumnType::from_tag(tag).ok_or("invalid column type")?;
+			let (len, len_len) = u64::decode(&payload[offset..])?;
+			offset += len_len;
+			let len =
+				usize::try_from(len).map_err(|_| "vlen value out of range")?;
+			columns.push((column_type, len));
+			data_len =
+				data_len.checked_add(len).ok_or("vlen value out of range")?;
+		}
+		if data_len != payload.len() - offset {
+			return Err("column lengths do not match batch length");
+		}
+
+		let columns = columns
+			.into_iter()
+			.map(|(column_type, len)| {
+				let bytes = &payload[offset..offset + len];
+				offset += len;
+				(column_type, bytes)
+			})
+			.collect();
+		Ok((Self { rows, columns }, frame_len))
+	}
+
+	/// Returns the number of rows.
+	#[inline]
+	#[must_use]
+	pub fn num_rows(&self) -> usize {
+		self.rows
+	}
+
+	/// Returns the number of columns.
+	#[inline]
+	#[must_use]
+	pub fn num_columns(&self) -> usize {
+		self.columns.len()
+	}
+
+	/// Returns the type of the column at `index`, or `None` if out of
+	/// bounds.
+	#[inline]
+	#[must_use]
+	pub fn column_type(&self, index: usize) -> Option<ColumnType> {
+		self.columns.get(index).map(|&(column_type, _)| column_type)
+	}
+
+	/// Returns the encoded values of the column at `index`, or `None` if
+	/// out of bounds.
+	#[inline]
+	#[must_use]
+	pub fn column_bytes(&self, index: usize) -> Option<&'a [u8]> {
+		self.columns.get(index).map(|&(_, bytes)| bytes)
+	}
+
+	/// Decodes the column at `index` into a newly allocated vector.
+	///
+	/// Fails if `T` is not the column's type or the column does not hold
+	/// exactly one value per row.
+	pub fn column<T>(&self, index: usize) -> Result<Vec<T>, &'static str>
+	where
+		T: ColumnValue,
+	{
+		let bytes = self.typed_column_bytes::<T>(index)?;
+		crate::bulk_decode_values_exact(bytes, self.rows)
+	}
+
+	/// Returns an iterator that lazily decodes the column at `index`.
+	///
+	/// Fails if `T` is not the column's type.
+	pub fn column_iter<T>(
+		&self,
+		index: usize,
+	) -> Result<DecodeIter<'a, T>, &'static str>
+	where
+		T: ColumnValue,
+	{
+		let bytes = self.typed_column_bytes::<T>(index)?;
+		Ok(crate::decode::decode_iter(bytes))
+	}
+
+	fn typed_column_bytes<T>(
+		&self,
+		index: usize,
+	) -> Result<&'a [u8], &'static str>
+	where
+		T: ColumnValue,
+	{
+		match self.columns.get(index) {
+			Some(&(column_type, bytes)) if column_type == T::COLUMN_TYPE => {
+				Ok(bytes)
+			},
+			Some(_) => Err("column type mismatch"),
+			None => Err("column index out of range"),
+		}
+	}
+}
@@ -0,0 +1,139 @@
+//! Arbitrary-precision integer encoding for vlen
+//!
+//! A `BigUint` is written as its length in bytes as a vlen `u64`, followed
+//! by its magnitude as little-endian bytes with no trailing zeros; zero is
+//! the single byte `0x00`. A `BigInt` shifts the length left by one and
+//! stores the sign in the low bit, so small values of either type take only
+//! a byte more than their magnitude.
+//!
+//! The encoded size of a big integer has no upper bound, so both types set
+//! `MAX_ENCODED_SIZE` to `usize::MAX` and accept any buffer that holds the
+//! encoding.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use num_bigint::BigInt;
+//!
+//! let value = -(BigInt::from(1u8) << 100u32);
+//! let mut buf = [0u8; 32];
+//! let len = vlen::encode(&mut buf, value.clone()).unwrap();
+//! assert_eq!(len, 14);
+//! assert_eq!(vlen::decode_value::<BigInt>(&buf[..len]), Ok(value));
+//! ```
+
+use alloc::vec::Vec;
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::decode::Decode;
+use crate::encode::{encode_u64, encoded_size_u64, Encode};
+
+/// Writes a length prefix and magnitude bytes.
+fn write_prefixed(
+	buf: &mut [u8],
+	prefix: u64,
+	magnitude: &[u8],
+) -> Result<usize, &'static str> {
+	let mut prefix_buf = [0u8; 9];
+	let prefix_len = encode_u64(&mut prefix_buf, prefix);
+	let len = prefix_len + magnitude.len();
+	let dst = buf
+		.get_mut(..len)
+		.ok_or("buffer too small for big integer encoding")?;
+	dst[..prefix_len].copy_from_slice(&prefix_buf[..prefix_len]);
+	dst[prefix_len..].copy_from_slice(magnitude);
+	Ok(len)
+}
+
+/// Returns the magnitude of `value` as little-endian bytes, empty for zero.
+fn magnitude_bytes(value: &BigUint) -> Vec<u8> {
+	if value.bits() == 0 {
+		Vec::new()
+	} else {
+		value.to_bytes_le()
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "num-bigint")))]
+impl Encode for BigUint {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		let magnitude = magnitude_bytes(&value);
+		write_prefixed(buf, magnitude.len() as u64, &magnitude)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		let len = value.bits().div_ceil(8);
+		Ok(encoded_size_u64(len) + len as usize)
+	}
+
+	const MAX_ENCODED_SIZE: usize = usize::MAX;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "num-bigint")))]
+impl Decode for BigUint {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (len, prefix_len) = u64::decode(buf)?;
+		let magnitude = magnitude_slice(buf, prefix_len, len)?;
+		let value = BigUint::from_bytes_le(magnitude);
+		Ok((value, prefix_len + magnitude.len()))
+	}
+
+	const MAX_ENCODED_SIZE: usize = usize::MAX;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "num-bigint")))]
+impl Encode for BigInt {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		let magnitude = magnitude_bytes(value.magnitude());
+		let negative = u64::from(value.sign() == Sign::Minus);
+		write_prefixed(
+			buf,
+			(magnitude.len() as u64) << 1 | negative,
+			&magnitude,
+		)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		let len = value.bits().div_ceil(8);
+		let negative = u64::from(value.sign() == Sign::Minus);
+		Ok(encoded_size_u64(len << 1 | negative) + len as usize)
+	}
+
+	const MAX_ENCODED_SIZE: usize = usize::MAX;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "num-bigint")))]
+impl Decode for BigInt {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (prefix, prefix_len) = u64::decode(buf)?;
+		let magnitude = magnitude_slice(buf, prefix_len, prefix >> 1)?;
+		let sign = if prefix & 1 == 1 {
+			Sign::Minus
+		} else {
+			Sign::Plus
+		};
+		let value = BigInt::from_bytes_le(sign, magnitude);
+		Ok((value, prefix_len + magnitude.len()))
+	}
+
+	const MAX_ENCODED_SIZE: usize = usize::MAX;
+}
+
+/// Returns the `len` magnitude bytes that follow a prefix.
+fn magnitude_slice(
+	buf: &[u8],
+	prefix_len: usize,
+	len: u64,
+) -> Result<&[u8], &'static str> {
+	usize::try_from(len)
+		.ok()
+		.and_then(|len| buf.get(prefix_len..prefix_len.checked_add(len)?))
+		.ok_or("truncated vlen value")
+}
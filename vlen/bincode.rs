@@ -0,0 +1,95 @@
+//! `bincode` 2 support for the wrapper types
+//!
+//! bincode 2 selects its integer encoding from a closed set (`Fixint` or
+//! `Varint`) and does not accept third-party encodings, so vlen cannot be
+//! plugged in as a whole-config integer encoding. Instead, the
+//! [`Vlen*`](crate::wrappers) wrapper types implement `bincode::Encode` and
+//! `bincode::Decode` by writing their vlen byte form, and switching a field
+//! to vlen is a change to its type. The wrappers encode the same way under
+//! every configuration.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use bincode::config;
+//! use vlen::wrappers::{VlenI64, VlenU64};
+//!
+//! let mut buf = [0u8; 16];
+//! let value = (VlenU64(300), VlenI64(-2));
+//! let len =
+//!     bincode::encode_into_slice(value, &mut buf, config::standard()).unwrap();
+//! assert_eq!(buf[..len], [0xac, 0x04, 0x03]);
+//!
+//! let (decoded, _): ((VlenU64, VlenI64), usize) =
+//!     bincode::decode_from_slice(&buf[..len], config::standard()).unwrap();
+//! assert_eq!(decoded, value);
+//! ```
+
+use ::bincode::de::read::Reader;
+use ::bincode::de::Decoder;
+use ::bincode::enc::write::Writer;
+use ::bincode::enc::Encoder;
+use ::bincode::error::{DecodeError, EncodeError};
+use ::bincode::{impl_borrow_decode, Decode, Encode};
+
+use crate::decode::prefix_encoded_len;
+use crate::wrappers::{
+	VlenF32, VlenF64, VlenI128, VlenI16, VlenI32, VlenI64, VlenU128, VlenU16,
+	VlenU32, VlenU64,
+};
+
+/// Writes the vlen encoding of `value`.
+fn write_vlen<T, E>(value: T, encoder: &mut E) -> Result<(), EncodeError>
+where
+	T: crate::encode::Encode,
+	E: Encoder,
+{
+	let mut buf = [0u8; 17];
+	let len = T::encode(&mut buf, value).map_err(EncodeError::Other)?;
+	encoder.writer().write(&buf[..len])
+}
+
+/// Reads exactly the bytes of one vlen value and decodes it.
+fn read_vlen<T, D>(decoder: &mut D) -> Result<T, DecodeError>
+where
+	T: crate::decode::Decode,
+	D: Decoder,
+{
+	let mut buf = [0u8; 17];
+	decoder.reader().read(&mut buf[..1])?;
+	let len = prefix_encoded_len(buf[0]);
+	decoder.claim_bytes_read(len)?;
+	decoder.reader().read(&mut buf[1..len])?;
+	let (value, decoded_len) =
+		T::decode(&buf[..len]).map_err(DecodeError::Other)?;
+	if decoded_len != len {
+		return Err(DecodeError::Other("invalid vlen prefix byte"));
+	}
+	Ok(value)
+}
+
+/// Implements the `bincode` traits for wrapper types.
+macro_rules! impl_bincode_wrapper {
+	($($wrapper:ident),* $(,)?) => {$(
+		impl Encode for $wrapper {
+			#[inline]
+			fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+				write_vlen(self.0, encoder)
+			}
+		}
+
+		impl<Context> Decode<Context> for $wrapper {
+			#[inline]
+			fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+				read_vlen(decoder).map(Self)
+			}
+		}
+
+		impl_borrow_decode!($wrapper);
+	)*};
+}
+
+impl_bincode_wrapper!(
+	VlenU16, VlenU32, VlenU64, VlenU128, VlenI16, VlenI32, VlenI64, VlenI128,
+	VlenF32, VlenF64,
+);
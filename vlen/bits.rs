@@ -0,0 +1,291 @@
+//! Packed boolean encoding
+//!
+//! A run of booleans is stored as its length as a vlen `u64` followed by the
+//! values packed eight to a byte, least significant bit first. The unused
+//! high bits of the last byte must be zero, so every sequence has exactly one
+//! encoding. A thousand flags take 127 bytes instead of the 1000 that one
+//! byte per `bool` would need.
+//!
+//! [`encode_bools`] and [`decode_bools`] work on slices of `bool`. With the
+//! `alloc` feature, [`BitSet`] keeps the values packed in memory and encodes
+//! to the same format, so either side can read what the other wrote.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::bits::{decode_bools, encode_bools};
+//!
+//! let flags = [true, false, true, true, false, false, false, false, true];
+//! let mut buf = [0u8; 8];
+//! let len = encode_bools(&mut buf, &flags).unwrap();
+//! assert_eq!(&buf[..len], [9, 0b0000_1101, 0b0000_0001]);
+//!
+//! let mut decoded = [false; 16];
+//! let (count, decoded_len) = decode_bools(&buf[..len], &mut decoded).unwrap();
+//! assert_eq!((count, decoded_len), (9, len));
+//! assert_eq!(decoded[..count], flags);
+//! ```
+
+use crate::decode::Decode;
+use crate::encode::{encode_u64, encoded_size_u64};
+
+/// Returns the encoded size of `count` booleans.
+#[inline]
+#[must_use]
+pub fn encoded_bools_size(count: usize) -> usize {
+	encoded_size_u64(count as u64) + count.div_ceil(8)
+}
+
+/// Encodes booleans as a length prefix and packed bits, returning the
+/// encoded length.
+pub fn encode_bools(
+	buf: &mut [u8],
+	values: &[bool],
+) -> Result<usize, &'static str> {
+	write_packed(buf, values.len(), |packed| {
+		for (byte, chunk) in packed.iter_mut().zip(values.chunks(8)) {
+			*byte = chunk
+				.iter()
+				.enumerate()
+				.fold(0, |byte, (bit, &value)| byte | u8::from(value) << bit);
+		}
+	})
+}
+
+/// Writes the length prefix of `count` booleans and lets `pack` fill in the
+/// packed bytes, returning the encoded length.
+fn write_packed(
+	buf: &mut [u8],
+	count: usize,
+	pack: impl FnOnce(&mut [u8]),
+) -> Result<usize, &'static str> {
+	let mut prefix_buf = [0u8; 9];
+	let prefix_len = encode_u64(&mut prefix_buf, count as u64);
+	let len = prefix_len + count.div_ceil(8);
+	let dst = buf
+		.get_mut(..len)
+		.ok_or("buffer too small for bool encoding")?;
+	dst[..prefix_len].copy_from_slice(&prefix_buf[..prefix_len]);
+	pack(&mut dst[prefix_len..]);
+	Ok(len)
+}
+
+/// Splits an encoding into the number of booleans, the packed bytes and the
+/// encoded length, checking the padding bits.
+fn packed_bits(buf: &[u8]) -> Result<(usize, &[u8], usize), &'static str> {
+	let (count, prefix_len) = u64::decode(buf)?;
+	let count =
+		usize::try_from(count).map_err(|_| "vlen value out of range")?;
+	let packed = buf[prefix_len..]
+		.get(..count.div_ceil(8))
+		.ok_or("truncated vlen value")?;
+	if let Some(&last) = packed.last() {
+		if !count.is_multiple_of(8) && last >> (count % 8) != 0 {
+			return Err("nonzero padding bits in bool encoding");
+		}
+	}
+	Ok((count, packed, prefix_len + packed.len()))
+}
+
+/// Decodes booleans written by [`encode_bools`] into `values`.
+///
+/// Returns the number of booleans decoded and the number of bytes consumed.
+/// Fails if `values` is shorter than the encoded sequence.
+pub fn decode_bools(
+	buf: &[u8],
+	values: &mut [bool],
+) -> Result<(usize, usize), &'static str> {
+	let (count, packed, len) = packed_bits(buf)?;
+	let values = values
+		.get_mut(..count)
+		.ok_or("buffer holds more values than expected")?;
+	for (i, value) in values.iter_mut().enumerate() {
+		*value = packed[i / 8] >> (i % 8) & 1 != 0;
+	}
+	Ok((count, len))
+}
+
+/// Booleans packed eight to a byte.
+///
+/// Encodes to the same format as [`encode_bools`] through the [`Encode`]
+/// and [`Decode`] traits. Like the big integer types, its encoded size has
+/// no upper bound, so `MAX_ENCODED_SIZE` is `usize::MAX`.
+///
+/// [`Encode`]: crate::encode::Encode
+///
+/// ```rust
+/// use vlen::bits::BitSet;
+///
+/// let mut set = BitSet::with_len(10);
+/// set.set(3, true);
+/// set.push(true);
+/// assert_eq!(set.len(), 11);
+/// assert_eq!(set.ones().collect::<Vec<_>>(), [3, 10]);
+///
+/// let buf = vlen::encode_to_vec(set.clone()).unwrap();
+/// assert_eq!(buf, [11, 0b0000_1000, 0b0000_0100]);
+/// assert_eq!(vlen::decode_value::<BitSet>(&buf), Ok(set));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitSet {
+	bytes: alloc::vec::Vec<u8>,
+	len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl BitSet {
+	/// Creates an empty set.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			bytes: alloc::vec::Vec::new(),
+			len: 0,
+		}
+	}
+
+	/// Creates a set of `len` booleans, all `false`.
+	#[inline]
+	#[must_use]
+	pub fn with_len(len: usize) -> Self {
+		Self {
+			bytes: alloc::vec![0; len.div_ceil(8)],
+			len,
+		}
+	}
+
+	/// Returns the number of booleans.
+	#[inline]
+	#[must_use]
+	pub const fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the set holds no booleans.
+	#[inline]
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the boolean at `index`, or `None` if out of bounds.
+	#[inline]
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<bool> {
+		(index < self.len)
+			.then(|| self.bytes[index / 8] >> (index % 8) & 1 != 0)
+	}
+
+	/// Sets the boolean at `index`.
+	///
+	/// # Panics
+	///
+	/// Panics if `index` is out of bounds.
+	#[inline]
+	pub fn set(&mut self, index: usize, value: bool) {
+		assert!(index < self.len, "bit index out of bounds");
+		let mask = 1 << (index % 8);
+		if value {
+			self.bytes[index / 8] |= mask;
+		} else {
+			self.bytes[index / 8] &= !mask;
+		}
+	}
+
+	/// Appends a boolean.
+	#[inline]
+	pub fn push(&mut self, value: bool) {
+		if self.len.is_multiple_of(8) {
+			self.bytes.push(0);
+		}
+		self.len += 1;
+		self.set(self.len - 1, value);
+	}
+
+	/// Returns the number of `true` values.
+	#[inline]
+	#[must_use]
+	pub fn count_ones(&self) -> usize {
+		self.bytes
+			.iter()
+			.map(|byte| byte.count_ones() as usize)
+			.sum()
+	}
+
+	/// Returns an iterator over the booleans.
+	#[inline]
+	pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+		(0..self.len).map(|i| self.bytes[i / 8] >> (i % 8) & 1 != 0)
+	}
+
+	/// Returns an iterator over the indices of the `true` values.
+	#[inline]
+	pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+		self.iter()
+			.enumerate()
+			.filter_map(|(i, value)| value.then_some(i))
+	}
+
+	/// Returns the packed bytes, least significant bit first.
+	#[inline]
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<bool> for BitSet {
+	fn from_iter<I>(iter: I) -> Self
+	where
+		I: IntoIterator<Item = bool>,
+	{
+		let mut set = Self::new();
+		iter.into_iter().for_each(|value| set.push(value));
+		set
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<&[bool]> for BitSet {
+	#[inline]
+	fn from(values: &[bool]) -> Self {
+		values.iter().copied().collect()
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+impl crate::encode::Encode for BitSet {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		write_packed(buf, value.len, |packed| {
+			packed.copy_from_slice(&value.bytes);
+		})
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(encoded_bools_size(value.len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = usize::MAX;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+impl Decode for BitSet {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (count, packed, len) = packed_bits(buf)?;
+		let set = Self {
+			bytes: packed.to_vec(),
+			len: count,
+		};
+		Ok((set, len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = usize::MAX;
+}
@@ -0,0 +1,350 @@
+//! Block-based columnar layout with per-block min/max metadata
+//!
+//! [`encode`] splits a value stream into blocks of a fixed number of values.
+//! Each block starts with a header of four vlen values: the value count, the
+//! byte length of the block's values, and the smallest and largest value in
+//! the block. Readers walk the headers with [`blocks`] and decode only the
+//! blocks whose range can match a query, zone-map style.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::blocks;
+//!
+//! let values: Vec<u32> = (0..1000).collect();
+//! let mut buf = vec![0u8; 4096];
+//! let len = blocks::encode(&mut buf, &values, 128).unwrap();
+//!
+//! // Only the block holding 500 is decoded.
+//! let mut found = Vec::new();
+//! for block in blocks::blocks::<u32>(&buf[..len]) {
+//!     let block = block.unwrap();
+//!     if block.header.min <= 500 && 500 <= block.header.max {
+//!         found.extend(block.values().map(Result::unwrap));
+//!     }
+//! }
+//! assert_eq!(found, (384..512).collect::<Vec<u32>>());
+//! ```
+
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::decode::{decode_iter, Decode, DecodeIter};
+use crate::encode::Encode;
+use crate::sink::Sink;
+
+/// Metadata stored at the start of each block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockHeader<T> {
+	/// Number of values in the block.
+	pub count: usize,
+	/// Byte length of the block's encoded values.
+	pub len: usize,
+	/// The smallest value in the block.
+	pub min: T,
+	/// The largest value in the block.
+	pub max: T,
+}
+
+/// Encodes `values` as blocks of up to `block_size` values, returning the
+/// encoded length.
+///
+/// Minimum and maximum are found with `PartialOrd`, so a NaN only becomes a
+/// bound when it is the first value of its block.
+pub fn encode<T>(
+	buf: &mut [u8],
+	values: &[T],
+	block_size: usize,
+) -> Result<usize, &'static str>
+where
+	T: Encode + Copy + PartialOrd,
+{
+	if block_size == 0 {
+		return Err("block size must be non-zero");
+	}
+	let mut offset = 0;
+	for block in values.chunks(block_size) {
+		let (min, max) = bounds(block);
+		let mut len = 0;
+		for &value in block {
+			len += T::encoded_size(value)?;
+		}
+		offset += encode_at(buf, offset, block.len() as u64)?;
+		offset += encode_at(buf, offset, len as u64)?;
+		offset += encode_at(buf, offset, min)?;
+		offset += encode_at(buf, offset, max)?;
+		for &value in block {
+			offset += encode_at(buf, offset, value)?;
+		}
+	}
+	Ok(offset)
+}
+
+/// Encodes `values` as blocks like [`encode`] into `sink`, returning the
+/// number of bytes written.
+pub fn encode_into<S, T>(
+	sink: &mut S,
+	values: &[T],
+	block_size: usize,
+) -> Result<usize, &'static str>
+where
+	S: Sink + ?Sized,
+	T: Encode + Copy + PartialOrd,
+{
+	if block_size == 0 {
+		return Err("block size must be non-zero");
+	}
+	let mut written = 0;
+	for block in values.chunks(block_size) {
+		let mut len = 0;
+		for &value in block {
+			len += T::encoded_size(value)?;
+		}
+		written += write_header(sink, block, len)?;
+		for &value in block {
+			written += sink.write_value(value)?;
+		}
+	}
+	Ok(written)
+}
+
+/// Encodes `values` as blocks like [`encode`] onto the end of `vec`,
+/// returning the number of bytes written.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn encode_append<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	values: &[T],
+	block_size: usize,
+) -> Result<usize, &'static str>
+where
+	T: Encode + Copy + PartialOrd,
+{
+	encode_into(vec, values, block_size)
+}
+
+/// Encodes `values` as blocks like [`encode`], compressing the values of
+/// each block, onto the end of `vec`. Returns the number of bytes written.
+///
+/// The headers are left uncompressed, so blocks can still be skipped by
+/// their range, and their length is that of the compressed values. Read
+/// the values with [`Block::decompressed_data`].
+///
+/// ```rust
+/// use vlen::blocks;
+/// use vlen::compress::Compression;
+///
+/// let values: Vec<u64> = (0..1000).map(|i| i / 10 * 1_000_003).collect();
+/// # #[cfg(feature = "lz4_flex")]
+/// # let compression = Compression::Lz4;
+/// # #[cfg(not(feature = "lz4_flex"))]
+/// # let compression = Compression::Zstd(3);
+/// let mut buf = Vec::new();
+/// blocks::encode_compressed_append(&mut buf, &values, 500, compression)
+///     .unwrap();
+///
+/// let block = blocks::blocks::<u64>(&buf).nth(1).unwrap().unwrap();
+/// assert_eq!(block.header.min, 50 * 1_000_003);
+/// let data = block.decompressed_data().unwrap();
+/// let decoded: Result<Vec<u64>, _> = vlen::decode_iter(&data).collect();
+/// assert_eq!(decoded.unwrap(), values[500..]);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(any(feature = "zstd", feature = "lz4_flex"))))]
+#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+pub fn encode_compressed_append<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	values: &[T],
+	block_size: usize,
+	compression: crate::compress::Compression,
+) -> Result<usize, &'static str>
+where
+	T: Encode + Copy + PartialOrd,
+{
+	if block_size == 0 {
+		return Err("block size must be non-zero");
+	}
+	let start = vec.len();
+	for block in values.chunks(block_size) {
+		let data = crate::bulk_encode_to_vec(block)?;
+		let data = crate::compress::compress(&data, compression)?;
+		write_header(vec, block, data.len())?;
+		vec.extend_from_slice(&data);
+	}
+	Ok(vec.len() - start)
+}
+
+/// Writes the header of a non-empty block whose values take `len` bytes,
+/// returning the header's encoded length.
+fn write_header<S, T>(
+	sink: &mut S,
+	block: &[T],
+	len: usize,
+) -> Result<usize, &'static str>
+where
+	S: Sink + ?Sized,
+	T: Encode + Copy + PartialOrd,
+{
+	let (min, max) = bounds(block);
+	Ok(sink.write_value(block.len() as u64)?
+		+ sink.write_value(len as u64)?
+		+ sink.write_value(min)?
+		+ sink.write_value(max)?)
+}
+
+/// Returns the smallest and largest value in a non-empty block.
+#[inline]
+fn bounds<T>(block: &[T]) -> (T, T)
+where
+	T: Copy + PartialOrd,
+{
+	let (mut min, mut max) = (block[0], block[0]);
+	for &value in block {
+		if value < min {
+			min = value;
+		}
+		if value > max {
+			max = value;
+		}
+	}
+	(min, max)
+}
+
+/// Encodes a value at `offset`, returning its encoded length.
+#[inline]
+fn encode_at<T>(
+	buf: &mut [u8],
+	offset: usize,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: Encode,
+{
+	let buf = buf
+		.get_mut(offset..)
+		.ok_or("buffer too small for block encoding")?;
+	T::encode(buf, value)
+}
+
+/// Decodes a value at `offset`, advancing it past the value.
+#[inline]
+fn decode_at<T>(buf: &[u8], offset: &mut usize) -> Result<T, &'static str>
+where
+	T: Decode,
+{
+	let (value, len) = T::decode(&buf[*offset..])?;
+	*offset += len;
+	Ok(value)
+}
+
+/// A block found by [`blocks`].
+#[derive(Debug, Clone, Copy)]
+pub struct Block<'a, T> {
+	/// The block's metadata.
+	pub header: BlockHeader<T>,
+	/// The block's encoded values.
+	pub data: &'a [u8],
+}
+
+impl<'a, T> Block<'a, T>
+where
+	T: Decode,
+{
+	/// Returns an iterator that decodes the block's values.
+	#[inline]
+	pub fn values(&self) -> DecodeIter<'a, T> {
+		decode_iter(self.data)
+	}
+
+	/// Returns the block's encoded values from a buffer written by
+	/// [`encode_compressed_append`], decompressing them.
+	#[cfg_attr(docsrs, doc(cfg(any(feature = "zstd", feature = "lz4_flex"))))]
+	#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+	pub fn decompressed_data(
+		&self,
+	) -> Result<alloc::borrow::Cow<'a, [u8]>, &'static str> {
+		crate::compress::decompress(self.data)
+	}
+}
+
+/// Returns an iterator over the blocks in a buffer.
+#[inline]
+pub fn blocks<T>(buf: &[u8]) -> Blocks<'_, T>
+where
+	T: Decode,
+{
+	Blocks {
+		buf,
+		offset: 0,
+		_marker: PhantomData,
+	}
+}
+
+/// Iterator over the blocks in a buffer.
+///
+/// Reads only block headers; the values of a block are not decoded until
+/// [`Block::values`] is called. Yields an error and then stops if the buffer
+/// ends partway through a block. Created by [`blocks`].
+#[derive(Debug, Clone)]
+pub struct Blocks<'a, T> {
+	buf: &'a [u8],
+	offset: usize,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> Blocks<'a, T>
+where
+	T: Decode,
+{
+	/// Returns the number of bytes read so far.
+	#[inline]
+	#[must_use]
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	fn read_block(&mut self) -> Result<Block<'a, T>, &'static str> {
+		let mut offset = self.offset;
+		let count = decode_at::<u64>(self.buf, &mut offset)?;
+		let len = decode_at::<u64>(self.buf, &mut offset)?;
+		let count =
+			usize::try_from(count).map_err(|_| "block count out of range")?;
+		let len =
+			usize::try_from(len).map_err(|_| "block length out of range")?;
+		let min = decode_at(self.buf, &mut offset)?;
+		let max = decode_at(self.buf, &mut offset)?;
+		let data = offset
+			.checked_add(len)
+			.and_then(|end| self.buf.get(offset..end))
+			.ok_or("truncated vlen block")?;
+		self.offset = offset + len;
+		Ok(Block {
+			header: BlockHeader {
+				count,
+				len,
+				min,
+				max,
+			},
+			data,
+		})
+	}
+}
+
+impl<'a, T> Iterator for Blocks<'a, T>
+where
+	T: Decode,
+{
+	type Item = Result<Block<'a, T>, &'static str>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.offset >= self.buf.len() {
+			return None;
+		}
+		let block = self.read_block();
+		if block.is_err() {
+			self.offset = self.buf.len();
+		}
+		Some(block)
+	}
+}
+
+impl<T> FusedIterator for Blocks<'_, T> where T: Decode {}
@@ -0,0 +1,75 @@
+//! `borsh` support for the wrapper types
+//!
+//! The [`Vlen*`](crate::wrappers) wrapper types serialize as their vlen byte
+//! form instead of borsh's fixed-width little-endian integers, so individual
+//! fields of a borsh schema can opt into the compact encoding.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::wrappers::{VlenI64, VlenU64};
+//!
+//! let bytes = borsh::to_vec(&(VlenU64(300), VlenI64(-2))).unwrap();
+//! assert_eq!(bytes, [0xac, 0x04, 0x03]);
+//!
+//! let decoded: (VlenU64, VlenI64) = borsh::from_slice(&bytes).unwrap();
+//! assert_eq!(decoded, (VlenU64(300), VlenI64(-2)));
+//! ```
+
+use ::borsh::io::{Error, ErrorKind, Read, Result, Write};
+use ::borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::decode::{prefix_encoded_len, Decode};
+use crate::encode::Encode;
+use crate::wrappers::{
+	VlenF32, VlenF64, VlenI128, VlenI16, VlenI32, VlenI64, VlenU128, VlenU16,
+	VlenU32, VlenU64,
+};
+
+fn invalid_data(err: &'static str) -> Error {
+	Error::new(ErrorKind::InvalidData, err)
+}
+
+/// Writes the vlen encoding of `value`.
+fn write_vlen<T: Encode, W: Write>(value: T, writer: &mut W) -> Result<()> {
+	let mut buf = [0u8; 17];
+	let len = T::encode(&mut buf, value).map_err(invalid_data)?;
+	writer.write_all(&buf[..len])
+}
+
+/// Reads exactly the bytes of one vlen value and decodes it.
+fn read_vlen<T: Decode, R: Read>(reader: &mut R) -> Result<T> {
+	let mut buf = [0u8; 17];
+	reader.read_exact(&mut buf[..1])?;
+	let len = prefix_encoded_len(buf[0]);
+	reader.read_exact(&mut buf[1..len])?;
+	let (value, decoded_len) = T::decode(&buf[..len]).map_err(invalid_data)?;
+	if decoded_len != len {
+		return Err(invalid_data("invalid vlen prefix byte"));
+	}
+	Ok(value)
+}
+
+/// Implements the `borsh` traits for wrapper types.
+macro_rules! impl_borsh_wrapper {
+	($($wrapper:ident),* $(,)?) => {$(
+		impl BorshSerialize for $wrapper {
+			#[inline]
+			fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+				write_vlen(self.0, writer)
+			}
+		}
+
+		impl BorshDeserialize for $wrapper {
+			#[inline]
+			fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+				read_vlen(reader).map(Self)
+			}
+		}
+	)*};
+}
+
+impl_borsh_wrapper!(
+	VlenU16, VlenU32, VlenU64, VlenU128, VlenI16, VlenI32, VlenI64, VlenI128,
+	VlenF32, VlenF64,
+);
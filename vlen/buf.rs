@@ -0,0 +1,177 @@
+//! Growable buffer for writing multi-field messages
+//!
+//! A [`VlenBuf`] appends encoded values to a `Vec<u8>` that grows as needed
+//! and counts the values written. It is the write-side counterpart of
+//! [`VlenCursor`](crate::VlenCursor).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::{VlenBuf, VlenCursor};
+//!
+//! let mut buf = VlenBuf::new();
+//! buf.push_u32(300).unwrap();
+//! buf.push_f64(1.5).unwrap();
+//! buf.push_bytes(b"hello").unwrap();
+//! assert_eq!(buf.len_values(), 3);
+//!
+//! let mut cursor = VlenCursor::new(buf.as_slice());
+//! assert_eq!(cursor.read_u32(), Ok(300));
+//! assert_eq!(cursor.read_f64(), Ok(1.5));
+//! assert_eq!(cursor.read_bytes(), Ok(&b"hello"[..]));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::encode::Encode;
+use crate::sink::Sink;
+
+/// Growable buffer of encoded values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VlenBuf {
+	bytes: Vec<u8>,
+	values: usize,
+}
+
+macro_rules! impl_push {
+	($($name:ident($t:ty);)*) => {
+		$(
+			#[doc = concat!("Appends a `", stringify!($t), "`, returning its encoded length.")]
+			#[inline]
+			pub fn $name(&mut self, value: $t) -> Result<usize, &'static str> {
+				self.push(value)
+			}
+		)*
+	};
+}
+
+impl VlenBuf {
+	/// Creates an empty buffer.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			bytes: Vec::new(),
+			values: 0,
+		}
+	}
+
+	/// Creates an empty buffer with room for `capacity` bytes.
+	#[inline]
+	#[must_use]
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			bytes: Vec::with_capacity(capacity),
+			values: 0,
+		}
+	}
+
+	/// Appends a value, returning its encoded length.
+	#[inline]
+	pub fn push<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode + Clone,
+	{
+		let len = crate::encode_append(&mut self.bytes, value)?;
+		self.values += 1;
+		Ok(len)
+	}
+
+	impl_push! {
+		push_u16(u16);
+		push_u32(u32);
+		push_u64(u64);
+		push_u128(u128);
+		push_i16(i16);
+		push_i32(i32);
+		push_i64(i64);
+		push_i128(i128);
+		push_f32(f32);
+		push_f64(f64);
+	}
+
+	/// Appends a byte string prefixed with its length as a `u64`, returning
+	/// the total number of bytes appended.
+	///
+	/// The byte string counts as one value.
+	pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<usize, &'static str> {
+		let len = crate::encode_append(&mut self.bytes, bytes.len() as u64)?;
+		self.bytes.extend_from_slice(bytes);
+		self.values += 1;
+		Ok(len + bytes.len())
+	}
+
+	/// Returns the number of values written.
+	#[inline]
+	#[must_use]
+	pub fn len_values(&self) -> usize {
+		self.values
+	}
+
+	/// Returns the number of bytes written.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	/// Returns `true` if nothing has been written.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// Returns the encoded bytes.
+	#[inline]
+	#[must_use]
+	pub fn as_slice(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	/// Removes every value, keeping the allocation.
+	#[inline]
+	pub fn clear(&mut self) {
+		self.bytes.clear();
+		self.values = 0;
+	}
+
+	/// Returns the encoded bytes as a vector.
+	#[inline]
+	#[must_use]
+	pub fn into_vec(self) -> Vec<u8> {
+		self.bytes
+	}
+}
+
+impl AsRef<[u8]> for VlenBuf {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		&self.bytes
+	}
+}
+
+impl From<VlenBuf> for Vec<u8> {
+	#[inline]
+	fn from(buf: VlenBuf) -> Self {
+		buf.bytes
+	}
+}
+
+/// Raw bytes are appended without counting as a value; encoded values count
+/// as one each.
+impl Sink for VlenBuf {
+	#[inline]
+	fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+		self.bytes.extend_from_slice(bytes);
+		Ok(())
+	}
+
+	#[inline]
+	fn write_value<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode + Clone,
+	{
+		self.push(value)
+	}
+}
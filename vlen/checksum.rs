@@ -0,0 +1,89 @@
+//! CRC32C checksums for integrity-checked streams
+//!
+//! [`Crc32c`] is a `no_std` CRC-32C (Castagnoli) accumulator. With the
+//! `embedded-io` feature, [`ChecksumWriter`](crate::embedded_io::ChecksumWriter)
+//! and [`ChecksumReader`](crate::embedded_io::ChecksumReader) layer it over a
+//! stream and carry the checksum as a vlen-encoded `u32` trailer.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::checksum::Crc32c;
+//!
+//! let mut crc = Crc32c::new();
+//! crc.update(b"1234");
+//! crc.update(b"56789");
+//! assert_eq!(crc.finish(), 0xE306_9283);
+//! ```
+
+/// The reflected CRC-32C polynomial.
+const POLY: u32 = 0x82F6_3B78;
+
+/// Lookup table for one byte of input.
+const TABLE: [u32; 256] = {
+	let mut table = [0u32; 256];
+	let mut i = 0;
+	while i < 256 {
+		let mut crc = i as u32;
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 1 != 0 {
+				(crc >> 1) ^ POLY
+			} else {
+				crc >> 1
+			};
+			bit += 1;
+		}
+		table[i] = crc;
+		i += 1;
+	}
+	table
+};
+
+/// Incremental CRC-32C (Castagnoli) checksum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32c {
+	state: u32,
+}
+
+impl Crc32c {
+	/// Creates an accumulator over no bytes.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { state: !0 }
+	}
+
+	/// Adds `bytes` to the checksum.
+	#[inline]
+	pub fn update(&mut self, bytes: &[u8]) {
+		let mut crc = self.state;
+		for &byte in bytes {
+			crc = TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8);
+		}
+		self.state = crc;
+	}
+
+	/// Returns the checksum of the bytes added so far.
+	#[inline]
+	#[must_use]
+	pub const fn finish(&self) -> u32 {
+		!self.state
+	}
+
+	/// Returns the checksum of `bytes`.
+	#[inline]
+	#[must_use]
+	pub fn checksum(bytes: &[u8]) -> u32 {
+		let mut crc = Self::new();
+		crc.update(bytes);
+		crc.finish()
+	}
+}
+
+impl Default for Crc32c {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,503 @@
+//! Column files with a footer index of blocks
+//!
+//! A column file stores one stream of values as a sequence of
+//! [blocks](crate::blocks), so it can be written once and read back a block
+//! at a time. [`ColumnWriter`] buffers appended values and writes each full
+//! block to any `std::io::Write`; [`ColumnWriter::finish`] ends the file with
+//! a footer listing where every block starts. [`ColumnReader`] reads the
+//! footer of a file held in memory or mapped with the `mmap` feature, and
+//! decodes any block without touching the others. Queries skip blocks by
+//! their smallest and largest value with [`ColumnReader::blocks_where`] and
+//! [`ColumnReader::count_matching`].
+//!
+//! A writer interrupted by a crash leaves a file without a footer, possibly
+//! ending in a torn block. [`ColumnWriter::resume`] reopens such a file,
+//! keeps every block whose checksum matches, truncates the rest and carries
+//! on appending.
+//!
+//! The file starts with the magic bytes [`MAGIC`], followed by the blocks in
+//! the layout of [`blocks::encode`](crate::blocks::encode). Each block is
+//! followed by the CRC-32C of its bytes as a vlen `u32` trailer. The footer
+//! holds the block count, then the offset and value count of each block,
+//! all as vlen `u64`s. The footer's length as a little-endian `u32` and the
+//! magic bytes again close the file, so a reader finds the footer from the
+//! end.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::column::{ColumnReader, ColumnWriter};
+//!
+//! let mut writer = ColumnWriter::new(Vec::new(), 100)?;
+//! for value in 0..250u32 {
+//!     writer.append(value)?;
+//! }
+//! let file = writer.finish()?;
+//!
+//! let reader = ColumnReader::<u32>::new(&file).unwrap();
+//! assert_eq!(reader.num_blocks(), 3);
+//! assert_eq!(reader.num_values(), 250);
+//! let block = reader.read_block(2).unwrap();
+//! assert_eq!(block.header.min, 200);
+//! assert_eq!(block.values().count(), 50);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::blocks::{self, Block, BlockHeader};
+use crate::checksum::Crc32c;
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// Bytes every column file starts and ends with.
+pub const MAGIC: [u8; 4] = *b"VLCF";
+
+/// Length of the fixed-size tail: the footer length and the magic bytes.
+const TAIL_LEN: usize = 4 + MAGIC.len();
+
+/// Returns an [`io::ErrorKind::InvalidData`] error for an encoding failure.
+fn invalid_data(err: &'static str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Writes values to a column file, one block at a time.
+///
+/// Values are buffered until a block is full or [`flush`](Self::flush) is
+/// called. Call [`finish`](Self::finish) to write the footer; a file without
+/// one cannot be read by [`ColumnReader`].
+#[derive(Debug)]
+pub struct ColumnWriter<W, T> {
+	writer: W,
+	block_size: usize,
+	pending: Vec<T>,
+	scratch: Vec<u8>,
+	offset: u64,
+	blocks: Vec<(u64, u64)>,
+}
+
+impl<W, T> ColumnWriter<W, T>
+where
+	W: io::Write,
+	T: Encode + Copy + PartialOrd,
+{
+	/// Starts a column file in `writer` with blocks of up to `block_size`
+	/// values, writing the magic bytes.
+	pub fn new(mut writer: W, block_size: usize) -> io::Result<Self> {
+		check_block_size(block_size)?;
+		writer.write_all(&MAGIC)?;
+		Ok(Self::with_blocks(
+			writer,
+			block_size,
+			MAGIC.len(),
+			Vec::new(),
+		))
+	}
+
+	/// Continues a file whose blocks end at `offset`.
+	fn with_blocks(
+		writer: W,
+		block_size: usize,
+		offset: usize,
+		blocks: Vec<(u64, u64)>,
+	) -> Self {
+		Self {
+			writer,
+			block_size,
+			pending: Vec::with_capacity(block_size),
+			scratch: Vec::new(),
+			offset: offset as u64,
+			blocks,
+		}
+	}
+
+	/// Appends a value, writing the current block once it is full.
+	pub fn append(&mut self, value: T) -> io::Result<()> {
+		self.pending.push(value);
+		if self.pending.len() == self.block_size {
+			self.write_block()?;
+		}
+		Ok(())
+	}
+
+	/// Writes the buffered values as a block, even if it is not full, and
+	/// flushes the underlying writer.
+	pub fn flush(&mut self) -> io::Result<()> {
+		self.write_block()?;
+		self.writer.flush()
+	}
+
+	/// Returns the number of blocks written so far.
+	#[inline]
+	#[must_use]
+	pub fn num_blocks(&self) -> usize {
+		self.blocks.len()
+	}
+
+	/// Returns a reference to the underlying writer.
+	#[inline]
+	#[must_use]
+	pub fn get_ref(&self) -> &W {
+		&self.writer
+	}
+
+	/// Writes the buffered values and the footer, returning the underlying
+	/// writer.
+	pub fn finish(mut self) -> io::Result<W> {
+		self.write_block()?;
+		self.scratch.clear();
+		crate::encode_append(&mut self.scratch, self.blocks.len() as u64)
+			.map_err(invalid_data)?;
+		for &(offset, count) in &self.blocks {
+			crate::encode_append(&mut self.scratch, offset)
+				.map_err(invalid_data)?;
+			crate::encode_append(&mut self.scratch, count)
+				.map_err(invalid_data)?;
+		}
+		let footer_len = u32::try_from(self.scratch.len())
+			.map_err(|_| invalid_data("column footer too large"))?;
+		self.scratch.extend_from_slice(&footer_len.to_le_bytes());
+		self.scratch.extend_from_slice(&MAGIC);
+		self.writer.write_all(&self.scratch)?;
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+
+	fn write_block(&mut self) -> io::Result<()> {
+		if self.pending.is_empty() {
+			return Ok(());
+		}
+		self.scratch.clear();
+		blocks::encode_append(&mut self.scratch, &self.pending, usize::MAX)
+			.map_err(invalid_data)?;
+		let checksum = Crc32c::checksum(&self.scratch);
+		crate::encode_append(&mut self.scratch, checksum)
+			.map_err(invalid_data)?;
+		self.writer.write_all(&self.scratch)?;
+		self.blocks.push((self.offset, self.pending.len() as u64));
+		self.offset += self.scratch.len() as u64;
+		self.pending.clear();
+		Ok(())
+	}
+}
+
+impl<T> ColumnWriter<File, T>
+where
+	T: Encode + Decode + Copy + PartialOrd,
+{
+	/// Reopens a column file to append more values, with blocks of up to
+	/// `block_size` values.
+	///
+	/// The file is read from the start and cut after the last block whose
+	/// checksum matches, dropping a torn block or a footer left by
+	/// [`finish`](Self::finish). An empty file, or one that ends within the
+	/// magic bytes, is started afresh. Fails if the file is not a column
+	/// file.
+	///
+	/// ```rust,no_run
+	/// use std::fs::OpenOptions;
+	/// use vlen::column::ColumnWriter;
+	///
+	/// let file = OpenOptions::new()
+	///     .read(true)
+	///     .write(true)
+	///     .create(true)
+	///     .truncate(false)
+	///     .open("events.vlcf")?;
+	/// let mut writer = ColumnWriter::<_, u64>::resume(file, 1024)?;
+	/// writer.append(1_700_000_000)?;
+	/// writer.flush()?;
+	/// # Ok::<(), std::io::Error>(())
+	/// ```
+	pub fn resume(mut file: File, block_size: usize) -> io::Result<Self> {
+		check_block_size(block_size)?;
+		let mut buf = Vec::new();
+		file.seek(SeekFrom::Start(0))?;
+		file.read_to_end(&mut buf)?;
+		if buf.len() < MAGIC.len() && MAGIC.starts_with(&buf) {
+			file.set_len(0)?;
+			file.seek(SeekFrom::Start(0))?;
+			return Self::new(file, block_size);
+		}
+		if !buf.starts_with(&MAGIC) {
+			return Err(invalid_data("invalid column file magic"));
+		}
+		let mut offset = MAGIC.len();
+		let mut blocks = Vec::new();
+		// Any block that fails to read, however it is torn, marks the end.
+		while let Ok((block, len)) = checked_block::<T>(&buf[offset..]) {
+			blocks.push((offset as u64, block.header.count as u64));
+			offset += len;
+		}
+		file.set_len(offset as u64)?;
+		file.seek(SeekFrom::Start(offset as u64))?;
+		Ok(Self::with_blocks(file, block_size, offset, blocks))
+	}
+}
+
+/// Returns an [`io::ErrorKind::InvalidInput`] error for an empty block size.
+fn check_block_size(block_size: usize) -> io::Result<()> {
+	if block_size == 0 {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"block size must be non-zero",
+		));
+	}
+	Ok(())
+}
+
+/// Reads the block at the start of `buf` and its checksum trailer,
+/// returning the block and the length of both.
+fn checked_block<T>(buf: &[u8]) -> Result<(Block<'_, T>, usize), &'static str>
+where
+	T: Decode,
+{
+	let mut iter = blocks::blocks::<T>(buf);
+	let block = iter.next().ok_or("truncated vlen block")??;
+	let len = iter.offset();
+	let (checksum, checksum_len) = u32::decode_checked(&buf[len..])?;
+	if len + checksum_len > buf.len() {
+		return Err("truncated vlen value");
+	}
+	if checksum != Crc32c::checksum(&buf[..len]) {
+		return Err("checksum mismatch");
+	}
+	Ok((block, len + checksum_len))
+}
+
+/// Reads the blocks of a column file held in memory.
+#[derive(Debug, Clone)]
+pub struct ColumnReader<'a, T> {
+	buf: &'a [u8],
+	data_end: usize,
+	blocks: Vec<(usize, usize)>,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ColumnReader<'a, T>
+where
+	T: Decode,
+{
+	/// Reads the footer of the column file in `buf`.
+	///
+	/// Fails if the magic bytes are missing or the footer does not describe
+	/// blocks inside the file.
+	pub fn new(buf: &'a [u8]) -> Result<Self, &'static str> {
+		if buf.len() < MAGIC.len() + TAIL_LEN {
+			return Err("truncated column file");
+		}
+		let (tail, magic) = buf.split_at(buf.len() - MAGIC.len());
+		if buf[..MAGIC.len()] != MAGIC || magic != MAGIC {
+			return Err("invalid column file magic");
+		}
+		let (rest, footer_len) = tail.split_at(tail.len() - 4);
+		let footer_len = u32::from_le_bytes(footer_len.try_into().unwrap());
+		let data_end = usize::try_from(footer_len)
+			.ok()
+			.and_then(|len| rest.len().checked_sub(len))
+			.filter(|&end| end >= MAGIC.len())
+			.ok_or("invalid column footer")?;
+		let footer = &rest[data_end..];
+
+		let (count, mut offset) = u64::decode(footer)?;
+		// Every block takes at least one byte for its offset and count.
+		if count > ((footer.len() - offset) / 2) as u64 {
+			return Err("sequence count exceeds buffer length");
+		}
+		let mut blocks = Vec::with_capacity(count as usize);
+		let mut block_end = MAGIC.len();
+		for _ in 0..count {
+			let (start, len) = u64::decode(&footer[offset..])?;
+			offset += len;
+			let (values, len) = u64::decode(&footer[offset..])?;
+			offset += len;
+			let start = usize::try_from(start)
+				.ok()
+				.filter(|&start| start >= block_end && start < data_end)
+				.ok_or("invalid column footer")?;
+			let values = usize::try_from(values)
+				.map_err(|_| "vlen value out of range")?;
+			blocks.push((start, values));
+			block_end = start + 1;
+		}
+		if offset != footer.len() {
+			return Err("invalid column footer");
+		}
+		Ok(Self {
+			buf,
+			data_end,
+			blocks,
+			_marker: PhantomData,
+		})
+	}
+
+	/// Returns the number of blocks.
+	#[inline]
+	#[must_use]
+	pub fn num_blocks(&self) -> usize {
+		self.blocks.len()
+	}
+
+	/// Returns the number of values in all blocks.
+	#[must_use]
+	pub fn num_values(&self) -> usize {
+		self.blocks.iter().map(|&(_, count)| count).sum()
+	}
+
+	/// Reads the header of the block at `index`; its values are decoded on
+	/// demand.
+	///
+	/// Fails if the block's checksum does not match, or the block does not
+	/// span exactly the bytes up to the next one or holds a different number
+	/// of values than the footer says.
+	pub fn read_block(
+		&self,
+		index: usize,
+	) -> Result<Block<'a, T>, &'static str> {
+		let &(start, count) =
+			self.blocks.get(index).ok_or("block index out of range")?;
+		let end = self
+			.blocks
+			.get(index + 1)
+			.map_or(self.data_end, |&(next, _)| next);
+		let bytes = &self.buf[start..end];
+		let (block, len) = checked_block::<T>(bytes)?;
+		if len != bytes.len() || block.header.count != count {
+			return Err("invalid column footer");
+		}
+		Ok(block)
+	}
+
+	/// Returns an iterator over the blocks whose smallest and largest value
+	/// satisfy `predicate`.
+	///
+	/// Only block headers are read to evaluate the predicate, so the values
+	/// of skipped blocks are never decoded.
+	///
+	/// ```rust
+	/// use vlen::column::{ColumnReader, ColumnWriter};
+	///
+	/// let mut writer = ColumnWriter::new(Vec::new(), 100)?;
+	/// for value in 0..1000u32 {
+	///     writer.append(value)?;
+	/// }
+	/// let file = writer.finish()?;
+	///
+	/// let reader = ColumnReader::<u32>::new(&file).unwrap();
+	/// let hits: Vec<_> = reader
+	///     .blocks_where(|&min, &max| min <= 420 && 420 <= max)
+	///     .map(|block| block.unwrap().header.min)
+	///     .collect();
+	/// assert_eq!(hits, [400]);
+	/// assert_eq!(reader.count_matching(250..420), Ok(170));
+	/// # Ok::<(), std::io::Error>(())
+	/// ```
+	#[inline]
+	pub fn blocks_where<F>(&self, predicate: F) -> BlocksWhere<'_, 'a, T, F>
+	where
+		F: FnMut(&T, &T) -> bool,
+	{
+		BlocksWhere {
+			reader: self,
+			index: 0,
+			predicate,
+		}
+	}
+
+	/// Returns the number of values inside `range`.
+	///
+	/// Blocks entirely inside or outside the range are counted from their
+	/// header; only blocks straddling a bound are decoded.
+	pub fn count_matching<R>(&self, range: R) -> Result<usize, &'static str>
+	where
+		T: PartialOrd,
+		R: RangeBounds<T>,
+	{
+		let mut count = 0;
+		for index in 0..self.blocks.len() {
+			let block = self.read_block(index)?;
+			let BlockHeader { min, max, .. } = &block.header;
+			if range.contains(min) && range.contains(max) {
+				count += block.header.count;
+			} else if overlaps(&range, min, max) {
+				for value in block.values() {
+					if range.contains(&value?) {
+						count += 1;
+					}
+				}
+			}
+		}
+		Ok(count)
+	}
+}
+
+/// Returns `true` if `range` may hold values between `min` and `max`.
+fn overlaps<T, R>(range: &R, min: &T, max: &T) -> bool
+where
+	T: PartialOrd,
+	R: RangeBounds<T>,
+{
+	let above_start = match range.start_bound() {
+		Bound::Included(start) => max >= start,
+		Bound::Excluded(start) => max > start,
+		Bound::Unbounded => true,
+	};
+	let below_end = match range.end_bound() {
+		Bound::Included(end) => min <= end,
+		Bound::Excluded(end) => min < end,
+		Bound::Unbounded => true,
+	};
+	above_start && below_end
+}
+
+/// Iterator over the blocks of a column file that match a predicate on
+/// their bounds.
+///
+/// Yields an error and then stops if a block cannot be read. Created by
+/// [`ColumnReader::blocks_where`].
+#[derive(Debug, Clone)]
+pub struct BlocksWhere<'r, 'a, T, F> {
+	reader: &'r ColumnReader<'a, T>,
+	index: usize,
+	predicate: F,
+}
+
+impl<'a, T, F> Iterator for BlocksWhere<'_, 'a, T, F>
+where
+	T: Decode,
+	F: FnMut(&T, &T) -> bool,
+{
+	type Item = Result<Block<'a, T>, &'static str>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.index < self.reader.num_blocks() {
+			let block = self.reader.read_block(self.index);
+			self.index += 1;
+			match block {
+				Ok(block) => {
+					let header = &block.header;
+					if (self.predicate)(&header.min, &header.max) {
+						return Some(Ok(block));
+					}
+				},
+				Err(err) => {
+					self.index = self.reader.num_blocks();
+					return Some(Err(err));
+				},
+			}
+		}
+		None
+	}
+}
+
+impl<T, F> FusedIterator for BlocksWhere<'_, '_, T, F>
+where
+	T: Decode,
+	F: FnMut(&T, &T) -> bool,
+{
+}
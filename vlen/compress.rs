@@ -0,0 +1,193 @@
+//! General-purpose compression of encoded payloads
+//!
+//! vlen shrinks small integers, but the encoding of floats, hashes or
+//! repetitive data still compresses well with a general-purpose codec. With
+//! the `zstd` or `lz4_flex` feature, [`compress`] wraps an encoded payload in
+//! the chosen codec and [`decompress`] undoes it, reading the codec from the
+//! payload itself. [`FrameWriter`](crate::frame::FrameWriter) and
+//! [`blocks`](crate::blocks) build on these to compress frames and blocks,
+//! and their readers decompress whatever codec each one was written with.
+//!
+//! A compressed payload starts with a [`Compression`] tag byte. Without
+//! compression the payload follows unchanged. Otherwise the uncompressed
+//! length follows as a vlen `u64`, then the codec's output, so a reader can
+//! reject an oversized payload before decompressing it.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::compress::{compress, decompress, Compression};
+//!
+//! let prices: Vec<f64> = (0..1000).map(|i| f64::from(i % 10) * 0.1).collect();
+//! let encoded = vlen::bulk_encode_to_vec(&prices).unwrap();
+//!
+//! # #[cfg(feature = "lz4_flex")]
+//! # let compression = Compression::Lz4;
+//! # #[cfg(not(feature = "lz4_flex"))]
+//! # let compression = Compression::Zstd(3);
+//! let compressed = compress(&encoded, compression).unwrap();
+//! assert!(compressed.len() < encoded.len() / 10);
+//! assert_eq!(decompress(&compressed).unwrap(), encoded);
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::decode::Decode;
+use crate::encode::encode_u64;
+
+/// Error returned for payloads a codec cannot decompress.
+const INVALID: &str = "invalid compressed payload";
+
+/// Most bytes one compressed byte can expand to with Zstandard, whose
+/// smallest block, an RLE block of four bytes, yields at most 128 KiB.
+#[cfg(feature = "zstd")]
+const ZSTD_MAX_RATIO: usize = 1 << 15;
+
+/// Most bytes one compressed byte can expand to with LZ4, where each
+/// extra match length byte adds at most 255 bytes of output.
+#[cfg(feature = "lz4_flex")]
+const LZ4_MAX_RATIO: usize = 255;
+
+/// The codec a payload is compressed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Compression {
+	/// The payload is stored as is.
+	#[default]
+	None,
+	/// Zstandard at the given level, where 0 selects the codec's default.
+	#[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+	#[cfg(feature = "zstd")]
+	Zstd(i32),
+	/// LZ4 block compression.
+	#[cfg_attr(docsrs, doc(cfg(feature = "lz4_flex")))]
+	#[cfg(feature = "lz4_flex")]
+	Lz4,
+}
+
+impl Compression {
+	/// Tag byte of an uncompressed payload.
+	pub const TAG_NONE: u8 = 0;
+	/// Tag byte of a Zstandard payload.
+	pub const TAG_ZSTD: u8 = 1;
+	/// Tag byte of an LZ4 payload.
+	pub const TAG_LZ4: u8 = 2;
+
+	/// Returns the tag byte written before payloads compressed this way.
+	#[inline]
+	#[must_use]
+	pub const fn tag(self) -> u8 {
+		match self {
+			Compression::None => Self::TAG_NONE,
+			#[cfg(feature = "zstd")]
+			Compression::Zstd(_) => Self::TAG_ZSTD,
+			#[cfg(feature = "lz4_flex")]
+			Compression::Lz4 => Self::TAG_LZ4,
+		}
+	}
+}
+
+/// Compresses `payload` into a newly allocated vector.
+pub fn compress(
+	payload: &[u8],
+	compression: Compression,
+) -> Result<Vec<u8>, &'static str> {
+	let mut vec = Vec::new();
+	compress_append(&mut vec, payload, compression)?;
+	Ok(vec)
+}
+
+/// Compresses `payload` onto the end of `vec`, returning the number of bytes
+/// written.
+pub fn compress_append(
+	vec: &mut Vec<u8>,
+	payload: &[u8],
+	compression: Compression,
+) -> Result<usize, &'static str> {
+	let start = vec.len();
+	vec.push(compression.tag());
+	if compression == Compression::None {
+		vec.extend_from_slice(payload);
+		return Ok(vec.len() - start);
+	}
+	let mut len_buf = [0u8; 9];
+	let len_len = encode_u64(&mut len_buf, payload.len() as u64);
+	vec.extend_from_slice(&len_buf[..len_len]);
+	match compression {
+		Compression::None => {},
+		#[cfg(feature = "zstd")]
+		Compression::Zstd(level) => {
+			let compressed = zstd::bulk::compress(payload, level)
+				.map_err(|_| "zstd compression failed")?;
+			vec.extend_from_slice(&compressed);
+		},
+		#[cfg(feature = "lz4_flex")]
+		Compression::Lz4 => {
+			vec.extend_from_slice(&lz4_flex::block::compress(payload));
+		},
+	}
+	Ok(vec.len() - start)
+}
+
+/// Decompresses a payload written by [`compress`].
+///
+/// An uncompressed payload is borrowed from `buf`. Fails if the payload was
+/// compressed with a codec whose feature is not enabled, or declares a
+/// length the codec could not produce from its compressed bytes, so the
+/// input never drives the allocation size.
+pub fn decompress(buf: &[u8]) -> Result<Cow<'_, [u8]>, &'static str> {
+	decompress_limited(buf, usize::MAX)
+}
+
+/// Like [`decompress`], but rejects payloads that decompress to more than
+/// `max_len` bytes before allocating for them.
+pub fn decompress_limited(
+	buf: &[u8],
+	max_len: usize,
+) -> Result<Cow<'_, [u8]>, &'static str> {
+	let (&tag, rest) = buf.split_first().ok_or("truncated vlen value")?;
+	if tag == Compression::TAG_NONE {
+		if rest.len() > max_len {
+			return Err("decompressed length exceeds limit");
+		}
+		return Ok(Cow::Borrowed(rest));
+	}
+	let (len, len_len) = u64::decode(rest)?;
+	let len = usize::try_from(len).map_err(|_| "vlen value out of range")?;
+	if len > max_len {
+		return Err("decompressed length exceeds limit");
+	}
+	let compressed = &rest[len_len..];
+	let payload = match tag {
+		#[cfg(feature = "zstd")]
+		Compression::TAG_ZSTD => {
+			check_expansion(len, compressed, ZSTD_MAX_RATIO)?;
+			zstd::bulk::decompress(compressed, len).map_err(|_| INVALID)?
+		},
+		#[cfg(feature = "lz4_flex")]
+		Compression::TAG_LZ4 => {
+			check_expansion(len, compressed, LZ4_MAX_RATIO)?;
+			lz4_flex::block::decompress(compressed, len).map_err(|_| INVALID)?
+		},
+		_ => return Err("unsupported compression tag"),
+	};
+	if payload.len() != len {
+		return Err(INVALID);
+	}
+	Ok(Cow::Owned(payload))
+}
+
+/// Rejects a declared length that `compressed` could not expand to at
+/// `max_ratio` output bytes per input byte.
+#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+#[inline]
+fn check_expansion(
+	len: usize,
+	compressed: &[u8],
+	max_ratio: usize,
+) -> Result<(), &'static str> {
+	if len > compressed.len().saturating_mul(max_ratio) {
+		return Err(INVALID);
+	}
+	Ok(())
+}
@@ -157,3 +157,21 @@ pub const fn decode_i128(buf: &[u8; 17]) -> (i128, usize) {
 	let value = ((zigzag >> ZIGZAG_SHIFT) as i128) ^ (-((zigzag & 1) as i128));
 	(value, len)
 }
+
+// Floating-point
+
+/// Decodes an `f32` from a buffer, returning the value and encoded length.
+#[inline]
+#[must_use]
+pub const fn decode_f32(buf: &[u8; 5]) -> (f32, usize) {
+	let (swapped, len) = decode_u32(buf);
+	(f32::from_bits(swapped.swap_bytes()), len)
+}
+
+/// Decodes an `f64` from a buffer, returning the value and encoded length.
+#[inline]
+#[must_use]
+pub const fn decode_f64(buf: &[u8; 9]) -> (f64, usize) {
+	let (swapped, len) = decode_u64(buf);
+	(f64::from_bits(swapped.swap_bytes()), len)
+}
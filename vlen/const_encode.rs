@@ -2,6 +2,14 @@
 
 use crate::helpers::const_copy_slice;
 
+// The unsigned size calculators in `encode` are already `const fn`.
+pub use crate::encode::{
+	encoded_size_u128,
+	encoded_size_u16,
+	encoded_size_u32,
+	encoded_size_u64,
+};
+
 /// Encodes a `u16` into a buffer, returning the encoded length.
 #[inline]
 #[must_use]
@@ -137,3 +145,182 @@ pub const fn encode_i128(buf: &mut [u8; 17], value: i128) -> usize {
     let zigzag = ((value >> ZIGZAG_SHIFT) as u128) ^ ((value << 1) as u128);
     encode_u128(buf, zigzag)
 }
+
+// Floating-point
+
+/// Encodes an `f32` into a buffer, returning the encoded length.
+#[inline]
+#[must_use]
+pub const fn encode_f32(buf: &mut [u8; 5], value: f32) -> usize {
+	encode_u32(buf, value.to_bits().swap_bytes())
+}
+
+/// Encodes an `f64` into a buffer, returning the encoded length.
+#[inline]
+#[must_use]
+pub const fn encode_f64(buf: &mut [u8; 9], value: f64) -> usize {
+	encode_u64(buf, value.to_bits().swap_bytes())
+}
+
+// Encoded sizes
+
+/// Calculates the encoded size of an `i16` value without encoding it.
+#[inline]
+#[must_use]
+pub const fn encoded_size_i16(value: i16) -> usize {
+	encoded_size_u16(((value >> 15) as u16) ^ ((value << 1) as u16))
+}
+
+/// Calculates the encoded size of an `i32` value without encoding it.
+#[inline]
+#[must_use]
+pub const fn encoded_size_i32(value: i32) -> usize {
+	encoded_size_u32(((value >> 31) as u32) ^ ((value << 1) as u32))
+}
+
+/// Calculates the encoded size of an `i64` value without encoding it.
+#[inline]
+#[must_use]
+pub const fn encoded_size_i64(value: i64) -> usize {
+	encoded_size_u64(((value >> 63) as u64) ^ ((value << 1) as u64))
+}
+
+/// Calculates the encoded size of an `i128` value without encoding it.
+#[inline]
+#[must_use]
+pub const fn encoded_size_i128(value: i128) -> usize {
+	encoded_size_u128(((value >> 127) as u128) ^ ((value << 1) as u128))
+}
+
+/// Calculates the encoded size of an `f32` value without encoding it.
+#[inline]
+#[must_use]
+pub const fn encoded_size_f32(value: f32) -> usize {
+	encoded_size_u32(value.to_bits().swap_bytes())
+}
+
+/// Calculates the encoded size of an `f64` value without encoding it.
+#[inline]
+#[must_use]
+pub const fn encoded_size_f64(value: f64) -> usize {
+	encoded_size_u64(value.to_bits().swap_bytes())
+}
+
+// By-value encoding
+
+macro_rules! encode_array {
+	($($name:ident: $ty:ty => $encode:ident, $n:literal;)*) => {$(
+		#[doc = concat!(
+			"Encodes a `", stringify!($ty), "` into a new array, returning the ",
+			"array and the encoded length."
+		)]
+		#[inline]
+		#[must_use]
+		pub const fn $name(value: $ty) -> ([u8; $n], usize) {
+			let mut buf = [0u8; $n];
+			let len = $encode(&mut buf, value);
+			(buf, len)
+		}
+	)*};
+}
+
+encode_array! {
+	encode_u16_array: u16 => encode_u16, 3;
+	encode_u32_array: u32 => encode_u32, 5;
+	encode_u64_array: u64 => encode_u64, 9;
+	encode_u128_array: u128 => encode_u128, 17;
+	encode_i16_array: i16 => encode_i16, 3;
+	encode_i32_array: i32 => encode_i32, 5;
+	encode_i64_array: i64 => encode_i64, 9;
+	encode_i128_array: i128 => encode_i128, 17;
+	encode_f32_array: f32 => encode_f32, 5;
+	encode_f64_array: f64 => encode_f64, 9;
+}
+
+// Compile-time tables
+
+/// Expands to a `[u8; N]` holding the concatenated encodings of the given
+/// values, computed entirely at compile time.
+///
+/// The first token names the value type; every built-in integer and float
+/// type is supported. Values must be constant expressions.
+///
+/// ```rust
+/// const TABLE: [u8; 6] = vlen::vlen_const!(u32: 1, 300, 70000);
+/// const MAGIC: &[u8] = &vlen::vlen_const!(i64: -1, 1 << 40);
+///
+/// assert_eq!(TABLE, [0x01, 0xAC, 0x04, 0xD0, 0x8B, 0x08]);
+/// assert_eq!(MAGIC.len(), 1 + 7);
+/// ```
+#[macro_export]
+macro_rules! vlen_const {
+	(u16: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(u16, encoded_size_u16, encode_u16, 3; $($value),*)
+	};
+	(u32: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(u32, encoded_size_u32, encode_u32, 5; $($value),*)
+	};
+	(u64: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(u64, encoded_size_u64, encode_u64, 9; $($value),*)
+	};
+	(u128: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(
+			u128, encoded_size_u128, encode_u128, 17; $($value),*
+		)
+	};
+	(i16: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(i16, encoded_size_i16, encode_i16, 3; $($value),*)
+	};
+	(i32: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(i32, encoded_size_i32, encode_i32, 5; $($value),*)
+	};
+	(i64: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(i64, encoded_size_i64, encode_i64, 9; $($value),*)
+	};
+	(i128: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(
+			i128, encoded_size_i128, encode_i128, 17; $($value),*
+		)
+	};
+	(f32: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(f32, encoded_size_f32, encode_f32, 5; $($value),*)
+	};
+	(f64: $($value:expr),* $(,)?) => {
+		$crate::__vlen_const_table!(f64, encoded_size_f64, encode_f64, 9; $($value),*)
+	};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __vlen_const_table {
+	($ty:ty, $size:ident, $encode:ident, $max:expr; $($value:expr),*) => {{
+		const VALUES: &[$ty] = &[$($value),*];
+		const LEN: usize = {
+			let mut len = 0;
+			let mut i = 0;
+			while i < VALUES.len() {
+				len += $crate::const_encode::$size(VALUES[i]);
+				i += 1;
+			}
+			len
+		};
+		const TABLE: [u8; LEN] = {
+			let mut table = [0u8; LEN];
+			let mut offset = 0;
+			let mut i = 0;
+			while i < VALUES.len() {
+				let mut scratch = [0u8; $max];
+				let len = $crate::const_encode::$encode(&mut scratch, VALUES[i]);
+				let mut j = 0;
+				while j < len {
+					table[offset + j] = scratch[j];
+					j += 1;
+				}
+				offset += len;
+				i += 1;
+			}
+			table
+		};
+		TABLE
+	}};
+}
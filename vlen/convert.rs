@@ -0,0 +1,69 @@
+//! One-shot conversions between values and encoded bytes
+//!
+//! [`FromVlen`] and, with the `alloc` feature, `ToVlen` add methods to
+//! every [`Decode`] and [`Encode`](crate::encode::Encode) type, so a single
+//! value can be converted without sizing a buffer or discarding the encoded
+//! length, as in tests and command-line tools.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::FromVlen;
+//!
+//! assert_eq!(u64::try_from_vlen(&[0xAC, 0x04]), Ok(300));
+//!
+//! // The exact variant rejects bytes after the value.
+//! assert_eq!(u64::try_from_vlen(&[0xAC, 0x04, 0x00]), Ok(300));
+//! assert!(u64::try_from_vlen_exact(&[0xAC, 0x04, 0x00]).is_err());
+//! ```
+
+use crate::decode::Decode;
+#[cfg(feature = "alloc")]
+use crate::encode::Encode;
+
+/// Decodes a single value from a byte slice.
+pub trait FromVlen: Decode {
+	/// Decodes the value at the start of `buf`, ignoring any bytes after it.
+	#[inline]
+	fn try_from_vlen(buf: &[u8]) -> Result<Self, &'static str> {
+		let (value, _) = Self::decode(buf)?;
+		Ok(value)
+	}
+
+	/// Decodes a value that must fill `buf` exactly.
+	///
+	/// The value is range-checked as by [`Decode::decode_checked`], and
+	/// bytes left over after it are an error.
+	#[inline]
+	fn try_from_vlen_exact(buf: &[u8]) -> Result<Self, &'static str> {
+		let (value, len) = Self::decode_checked(buf)?;
+		if len != buf.len() {
+			return Err("trailing bytes after vlen value");
+		}
+		Ok(value)
+	}
+}
+
+impl<T> FromVlen for T where T: Decode {}
+
+/// Encodes a single value into a newly allocated vector.
+///
+/// ```rust
+/// use vlen::{FromVlen, ToVlen};
+///
+/// let bytes = (-2i32).to_vlen().unwrap();
+/// assert_eq!(bytes, [0x03]);
+/// assert_eq!(i32::try_from_vlen_exact(&bytes), Ok(-2));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub trait ToVlen: Encode + Clone {
+	/// Returns the encoding of the value.
+	#[inline]
+	fn to_vlen(&self) -> Result<alloc::vec::Vec<u8>, &'static str> {
+		crate::encode_to_vec(self.clone())
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<T> ToVlen for T where T: Encode + Clone {}
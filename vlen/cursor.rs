@@ -0,0 +1,139 @@
+//! Positioned reads for multi-field messages
+//!
+//! A [`VlenCursor`] decodes consecutive fields of different types from one
+//! buffer and tracks the offset itself, so parsing a message does not need
+//! manual offset bookkeeping around the free functions. A failed read leaves
+//! the position unchanged.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::VlenCursor;
+//!
+//! let mut buf = [0u8; 32];
+//! let mut len = vlen::encode(&mut buf, 300u32).unwrap();
+//! len += vlen::encode(&mut buf[len..], -5i64).unwrap();
+//! len += vlen::encode(&mut buf[len..], 1.5f64).unwrap();
+//!
+//! let mut cursor = VlenCursor::new(&buf[..len]);
+//! assert_eq!(cursor.read_u32(), Ok(300));
+//! assert_eq!(cursor.read_i64(), Ok(-5));
+//! cursor.skip_value::<f64>().unwrap();
+//! assert!(cursor.is_empty());
+//! ```
+
+use crate::decode::Decode;
+
+/// Cursor that decodes values from a buffer at an advancing position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VlenCursor<'a> {
+	buf: &'a [u8],
+	position: usize,
+}
+
+macro_rules! impl_read {
+	($($name:ident($t:ty);)*) => {
+		$(
+			#[doc = concat!("Decodes the next value as a `", stringify!($t), "`.")]
+			#[inline]
+			pub fn $name(&mut self) -> Result<$t, &'static str> {
+				self.read::<$t>()
+			}
+		)*
+	};
+}
+
+impl<'a> VlenCursor<'a> {
+	/// Creates a cursor at the start of `buf`.
+	#[inline]
+	#[must_use]
+	pub const fn new(buf: &'a [u8]) -> Self {
+		Self { buf, position: 0 }
+	}
+
+	/// Decodes the next value as a `T` and advances past it.
+	#[inline]
+	pub fn read<T>(&mut self) -> Result<T, &'static str>
+	where
+		T: Decode,
+	{
+		let (value, len) = self.peek::<T>()?;
+		self.position += len;
+		Ok(value)
+	}
+
+	impl_read! {
+		read_u16(u16);
+		read_u32(u32);
+		read_u64(u64);
+		read_u128(u128);
+		read_i16(i16);
+		read_i32(i32);
+		read_i64(i64);
+		read_i128(i128);
+		read_f32(f32);
+		read_f64(f64);
+	}
+
+	/// Reads a byte string prefixed with its length as a `u64`, as written by
+	/// `VlenBuf::push_bytes`.
+	pub fn read_bytes(&mut self) -> Result<&'a [u8], &'static str> {
+		let (len, prefix_len) = self.peek::<u64>()?;
+		let start = self.position + prefix_len;
+		let bytes = usize::try_from(len)
+			.ok()
+			.and_then(|len| self.buf.get(start..start.checked_add(len)?))
+			.ok_or("truncated vlen value")?;
+		self.position = start + bytes.len();
+		Ok(bytes)
+	}
+
+	/// Advances past the next value without returning it, returning its
+	/// encoded length.
+	#[inline]
+	pub fn skip_value<T>(&mut self) -> Result<usize, &'static str>
+	where
+		T: Decode,
+	{
+		let (_, len) = self.peek::<T>()?;
+		self.position += len;
+		Ok(len)
+	}
+
+	/// Returns the number of bytes read so far.
+	#[inline]
+	#[must_use]
+	pub const fn position(&self) -> usize {
+		self.position
+	}
+
+	/// Returns the bytes that have not been read yet.
+	#[inline]
+	#[must_use]
+	pub fn remaining(&self) -> &'a [u8] {
+		&self.buf[self.position..]
+	}
+
+	/// Returns `true` if every byte has been read.
+	#[inline]
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.position >= self.buf.len()
+	}
+
+	/// Decodes the next value without advancing.
+	fn peek<T>(&self) -> Result<(T, usize), &'static str>
+	where
+		T: Decode,
+	{
+		let rest = self.remaining();
+		if rest.is_empty() {
+			return Err("truncated vlen value");
+		}
+		let (value, len) = T::decode(rest)?;
+		if len > rest.len() {
+			return Err("truncated vlen value");
+		}
+		Ok((value, len))
+	}
+}
@@ -0,0 +1,168 @@
+//! Exact scaled decimal encoding
+//!
+//! A [`Decimal`] is the value `mantissa / 10^scale`. It is encoded as the
+//! scale as a vlen `u32` followed by the mantissa as a zigzag vlen `i128`,
+//! so amounts such as prices keep every digit and typically take two to
+//! four bytes instead of the nine of an `f64`.
+//!
+//! With the `rust_decimal` feature, [`Encode`] and [`Decode`] are also
+//! implemented for `rust_decimal::Decimal`, which converts to and from
+//! [`Decimal`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::decimal::Decimal;
+//!
+//! let price = Decimal::new(1999, 2); // 19.99
+//! let mut buf = [0u8; 22];
+//! let len = vlen::encode(&mut buf, price).unwrap();
+//! assert_eq!(len, 3);
+//! assert_eq!(vlen::decode_value::<Decimal>(&buf[..len]), Ok(price));
+//! ```
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// A decimal value `mantissa / 10^scale`.
+///
+/// Equality compares the representation, so `1.50` (mantissa 150, scale 2)
+/// and `1.5` differ; call [`Decimal::normalize`] first to compare values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+	/// The unscaled digits.
+	pub mantissa: i128,
+	/// The number of digits after the decimal point.
+	pub scale: u32,
+}
+
+impl Decimal {
+	/// Creates the decimal `mantissa / 10^scale`.
+	#[inline]
+	#[must_use]
+	pub const fn new(mantissa: i128, scale: u32) -> Self {
+		Self { mantissa, scale }
+	}
+
+	/// Returns the same value with trailing zero digits removed from the
+	/// mantissa, which gives the shortest encoding.
+	#[inline]
+	#[must_use]
+	pub const fn normalize(self) -> Self {
+		let mut mantissa = self.mantissa;
+		let mut scale = self.scale;
+		while scale > 0 && mantissa % 10 == 0 {
+			mantissa /= 10;
+			scale -= 1;
+		}
+		if mantissa == 0 {
+			scale = 0;
+		}
+		Self { mantissa, scale }
+	}
+}
+
+impl Encode for Decimal {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		let scale_len = u32::encode(buf, value.scale)?;
+		let mantissa_len = i128::encode(
+			buf.get_mut(scale_len..).unwrap_or_default(),
+			value.mantissa,
+		)?;
+		Ok(scale_len + mantissa_len)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(u32::encoded_size(value.scale)?
+			+ i128::encoded_size(value.mantissa)?)
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<u32 as Encode>::MAX_ENCODED_SIZE + <i128 as Encode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+impl Decode for Decimal {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (scale, scale_len) = u32::decode(buf)?;
+		let rest = buf.get(scale_len..).ok_or("truncated vlen value")?;
+		let (mantissa, mantissa_len) = i128::decode(rest)?;
+		Ok((Self { mantissa, scale }, scale_len + mantissa_len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (scale, scale_len) = u32::decode_checked(buf)?;
+		let rest = buf.get(scale_len..).ok_or("truncated vlen value")?;
+		let (mantissa, mantissa_len) = i128::decode_checked(rest)?;
+		Ok((Self { mantissa, scale }, scale_len + mantissa_len))
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<u32 as Decode>::MAX_ENCODED_SIZE + <i128 as Decode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for Decimal {
+	#[inline]
+	fn from(value: rust_decimal::Decimal) -> Self {
+		Self::new(value.mantissa(), value.scale())
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+#[cfg(feature = "rust_decimal")]
+impl TryFrom<Decimal> for rust_decimal::Decimal {
+	type Error = &'static str;
+
+	/// Fails if the mantissa exceeds 96 bits or the scale exceeds 28.
+	#[inline]
+	fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+		rust_decimal::Decimal::try_from_i128_with_scale(
+			value.mantissa,
+			value.scale,
+		)
+		.map_err(|_| "decimal out of range for rust_decimal")
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+#[cfg(feature = "rust_decimal")]
+impl Encode for rust_decimal::Decimal {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		Decimal::encode(buf, value.into())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Decimal::encoded_size(value.into())
+	}
+
+	const MAX_ENCODED_SIZE: usize = <Decimal as Encode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+#[cfg(feature = "rust_decimal")]
+impl Decode for rust_decimal::Decimal {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = Decimal::decode(buf)?;
+		Ok((value.try_into()?, len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = Decimal::decode_checked(buf)?;
+		Ok((value.try_into()?, len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <Decimal as Decode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
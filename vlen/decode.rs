@@ -1,19 +1,47 @@
 //! Decoding functions for vlen
 
-/// Macro for casting buffer to smaller type
-macro_rules! cast_buffer_ref {
-	($buf:expr, $from_size:expr, $to_size:expr) => {
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::encode::{
+	encoded_len_u128, encoded_len_u16, encoded_len_u32, encoded_len_u64,
+};
+use crate::helpers::array_prefix;
+
+/// Macro for reading aligned/unaligned values from a buffer at offset 1
+#[cfg(not(feature = "safe"))]
+macro_rules! read_aligned_at_offset {
+	($buf:expr, $T:ty) => {
 		unsafe {
-			&*(crate::helpers::ptr_from_ref::<[u8; $from_size]>($buf)
-				.cast::<[u8; $to_size]>())
+			let ptr = $buf.as_ptr().add(1).cast::<$T>();
+			if ptr.is_aligned() {
+				<$T>::from_le(ptr.read())
+			} else {
+				<$T>::from_le(ptr.read_unaligned())
+			}
 		}
 	};
 }
 
+/// Macro for reading little-endian values from a buffer at offset 1
+#[cfg(feature = "safe")]
+macro_rules! read_aligned_at_offset {
+	($buf:expr, $T:ty) => {
+		<$T>::from_le_bytes(
+			*$buf[1..]
+				.first_chunk::<{ core::mem::size_of::<$T>() }>()
+				.unwrap(),
+		)
+	};
+}
+
 /// Macro for delegating to smaller type decoder
 macro_rules! decode_delegate {
 	($buf:expr, $smaller_fn:ident, $from_size:expr, $to_size:expr) => {{
-		let buf_smaller = cast_buffer_ref!($buf, $from_size, $to_size);
+		let buf_smaller = array_prefix::<$from_size, $to_size>($buf);
 		$smaller_fn(buf_smaller)
 	}};
 }
@@ -28,15 +56,8 @@ macro_rules! decode_binary_length_prefix {
 		} else {
 			<$T>::MAX >> (($size - payload_bytes) * 8)
 		};
-		let value = unsafe {
-			let ptr = $buf.as_ptr().add(1).cast::<$T>();
-			if ptr.is_aligned() {
-				ptr.read()
-			} else {
-				ptr.read_unaligned()
-			}
-		};
-		(<$T>::from_le(value) & mask, (len + 2) as usize)
+		let value = read_aligned_at_offset!($buf, $T);
+		(value & mask, (len + 2) as usize)
 	}};
 }
 
@@ -58,6 +79,12 @@ macro_rules! decode_large_int {
 	};
 }
 
+// The scalar decoders branch on the prefix class rather than reading it from
+// `ENCODED_LEN_TABLE`. In a decode loop the next offset depends on the length
+// of the current value, and a table lookup puts a load on that chain, while
+// predicted branches let the CPU start on the next value early. On the
+// `decode_u64_mixed` benchmark the table-driven version was about 2.5x slower.
+
 /// Decodes a `u16` from a buffer, returning the value and encoded length.
 #[inline]
 #[must_use]
@@ -181,11 +208,154 @@ where
 	T::decode(buf)
 }
 
+/// Returns the total encoded length implied by the first byte of a value.
+///
+/// The result is exact for every type except `u16`, whose `0xC0..=0xDD`
+/// prefixes are decoded as binary length prefixes.
+#[inline]
+#[must_use]
+#[cfg_attr(
+	not(any(
+		feature = "async",
+		feature = "bincode",
+		feature = "borsh",
+		feature = "embedded-io"
+	)),
+	allow(dead_code)
+)]
+pub(crate) const fn prefix_encoded_len(first: u8) -> usize {
+	crate::encode::encoded_len(first)
+}
+
+/// Generic checked decoding function that rejects values too wide for `T`.
+///
+/// See [`Decode::decode_checked`].
+#[inline]
+pub fn decode_checked<T>(buf: &[u8]) -> Result<(T, usize), &'static str>
+where
+	T: Decode,
+{
+	T::decode_checked(buf)
+}
+
+/// Decodes the value at the front of `buf` at full width, without the caller
+/// choosing a type.
+///
+/// Returns the value as a `u128` and its encoded length, which is the
+/// length class the writer used. Signed integers come back in their zigzag
+/// form and floats as their encoded bits. As with [`decode_fit`], the input
+/// must have been written by a 32-bit or wider type. Never reads past the
+/// value, and fails instead of panicking on any input, so it suits fuzzers
+/// and tools that inspect data of unknown type.
+///
+/// ```rust
+/// let mut buf = [0u8; 17];
+/// let len = vlen::encode(&mut buf, 70_000u32).unwrap();
+/// assert_eq!(vlen::decode_any(&buf[..len]), Ok((70_000, 3)));
+///
+/// let len = vlen::encode(&mut buf, u128::MAX).unwrap();
+/// assert_eq!(vlen::decode_any(&buf[..len]), Ok((u128::MAX, 17)));
+/// assert_eq!(vlen::decode_any(&buf[..4]), Err("truncated vlen value"));
+/// ```
+pub fn decode_any(buf: &[u8]) -> Result<(u128, usize), &'static str> {
+	let &first = buf.first().ok_or("truncated vlen value")?;
+	let len = encoded_len_u128(first)?;
+	let encoding = buf.get(..len).ok_or("truncated vlen value")?;
+	let mut temp_buf = [0u8; 17];
+	temp_buf[..len].copy_from_slice(encoding);
+	Ok(decode_u128(&temp_buf))
+}
+
+/// Decodes a value at full 128-bit width and narrows it to `T`.
+///
+/// Returns a `vlen value out of range` error if the value does not fit, so
+/// readers can keep a narrow type for a field that writers have widened.
+/// The input must have been written by a 32-bit or wider type; `u16` and
+/// `i16` writers use their own three-byte form.
+#[inline]
+pub fn decode_fit<T>(buf: &[u8]) -> Result<(T, usize), &'static str>
+where
+	T: DecodeFit,
+{
+	let (wide, len) = T::Wide::decode(buf)?;
+	let value = T::fit(wide).ok_or("vlen value out of range")?;
+	Ok((value, len))
+}
+
+/// Integer types that [`decode_fit`] can narrow into.
+pub trait DecodeFit: Sized {
+	/// The full-width type values are decoded as.
+	type Wide: Decode;
+
+	/// Narrows a full-width value, returning `None` if it does not fit.
+	fn fit(wide: Self::Wide) -> Option<Self>;
+}
+
+macro_rules! impl_decode_fit {
+	($wide:ty: $($t:ty),* $(,)?) => {$(
+		impl DecodeFit for $t {
+			type Wide = $wide;
+
+			#[inline]
+			fn fit(wide: Self::Wide) -> Option<Self> {
+				<$t>::try_from(wide).ok()
+			}
+		}
+	)*};
+}
+
+impl_decode_fit!(u128: u16, u32, u64, u128);
+impl_decode_fit!(i128: i16, i32, i64, i128);
+
+/// Decodes a value with `decode_fn`, accepting buffers shorter than `N` as
+/// long as they hold the complete encoding.
+///
+/// `len_fn` gives the encoded length for the prefix byte and rejects
+/// prefixes wider than the type, so the returned length never exceeds
+/// `buf.len()`.
+#[inline]
+fn decode_prefix<T, const N: usize>(
+	buf: &[u8],
+	decode_fn: fn(&[u8; N]) -> (T, usize),
+	len_fn: fn(u8) -> Result<usize, &'static str>,
+	too_small: &'static str,
+) -> Result<(T, usize), &'static str> {
+	let first = *buf.first().ok_or(too_small)?;
+	if len_fn(first)? > buf.len() {
+		return Err("truncated vlen value");
+	}
+	if let Some(buf_array) = buf.first_chunk::<N>() {
+		return Ok(decode_fn(buf_array));
+	}
+	let mut temp_buf = [0u8; N];
+	temp_buf[..buf.len()].copy_from_slice(buf);
+	Ok(decode_fn(&temp_buf))
+}
+
 /// Bulk decoding function for multiple values.
+///
+/// Returns the number of bytes consumed. Use [`bulk_decode_counted`] to also
+/// learn how many slots of `values` were filled.
 pub fn bulk_decode<T>(
 	buf: &[u8],
 	values: &mut [T],
 ) -> Result<usize, &'static str>
+where
+	T: Decode,
+{
+	let (_, offset) = bulk_decode_counted(buf, values)?;
+	Ok(offset)
+}
+
+/// Bulk decoding function that reports how much was decoded.
+///
+/// Decodes until `values` is full or `buf` is exhausted, returning the number
+/// of values decoded and the number of bytes consumed. Returns an error if
+/// the buffer ends partway through a value.
+pub fn bulk_decode_counted<T>(
+	buf: &[u8],
+	values: &mut [T],
+) -> Result<(usize, usize), &'static str>
 where
 	T: Decode,
 {
@@ -197,34 +367,319 @@ where
 		offset += len;
 		i += 1;
 	}
+	Ok((i, offset))
+}
+
+/// Bulk decodes values into uninitialized memory.
+///
+/// Behaves like [`bulk_decode_counted`], returning the number of values
+/// decoded and the number of bytes consumed, but does not need `values` to
+/// be initialized first, which saves zero-filling a large output array. On
+/// success the first `count` slots of `values` are initialized. On error,
+/// values already written are left in place and never dropped.
+pub fn bulk_decode_uninit<T>(
+	buf: &[u8],
+	values: &mut [MaybeUninit<T>],
+) -> Result<(usize, usize), &'static str>
+where
+	T: Decode,
+{
+	let mut offset = 0;
+	let mut i = 0;
+	while i < values.len() && offset < buf.len() {
+		let (value, len) = T::decode(&buf[offset..])?;
+		values[i].write(value);
+		offset += len;
+		i += 1;
+	}
+	Ok((i, offset))
+}
+
+/// Decodes every value in `buf` a chunk at a time, passing each chunk to
+/// `f`.
+///
+/// At most `chunk_len` values are held at once, in a buffer reused for
+/// every chunk, so a buffer of any size is processed in constant memory.
+/// Returns the number of values decoded. Fails if the buffer ends partway
+/// through a value, after `f` has seen every complete chunk before it.
+///
+/// ```rust
+/// let mut buf = [0u8; 64];
+/// let values: Vec<u32> = (0..10).map(|i| i * 100).collect();
+/// let len = vlen::bulk_encode(&mut buf, &values).unwrap();
+///
+/// let mut sum = 0;
+/// let mut chunks = 0;
+/// let count = vlen::decode_in_chunks(&buf[..len], 4, |chunk: &[u32]| {
+///     sum += chunk.iter().sum::<u32>();
+///     chunks += 1;
+/// })
+/// .unwrap();
+/// assert_eq!((count, sum, chunks), (10, 4500, 3));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is zero.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn decode_in_chunks<T, F>(
+	buf: &[u8],
+	chunk_len: usize,
+	mut f: F,
+) -> Result<usize, &'static str>
+where
+	T: Decode,
+	F: FnMut(&[T]),
+{
+	assert!(chunk_len > 0, "chunk length must be nonzero");
+	let mut chunk = Vec::with_capacity(chunk_len.min(buf.len()));
+	let mut offset = 0;
+	let mut count = 0;
+	while offset < buf.len() {
+		chunk.clear();
+		while chunk.len() < chunk_len && offset < buf.len() {
+			let (value, len) = T::decode(&buf[offset..])?;
+			chunk.push(value);
+			offset += len;
+		}
+		count += chunk.len();
+		f(&chunk);
+	}
+	Ok(count)
+}
+
+/// Bulk decodes optional values written by
+/// [`bulk_encode_opt_u32`](crate::encode::bulk_encode_opt_u32).
+///
+/// Fills every slot of `values`, returning the number of bytes consumed.
+pub fn bulk_decode_opt_u32(
+	buf: &[u8],
+	values: &mut [Option<u32>],
+) -> Result<usize, &'static str> {
+	let bitmap_len = values.len().div_ceil(8);
+	let bitmap = buf
+		.get(..bitmap_len)
+		.ok_or("buffer holds fewer values than expected")?;
+	let mut offset = bitmap_len;
+	for (i, slot) in values.iter_mut().enumerate() {
+		*slot = if bitmap[i / 8] & (1 << (i % 8)) != 0 {
+			if offset >= buf.len() {
+				return Err("buffer holds fewer values than expected");
+			}
+			let (value, len) = u32::decode(&buf[offset..])?;
+			offset += len;
+			Some(value)
+		} else {
+			None
+		};
+	}
 	Ok(offset)
 }
 
+/// Decodes values written by
+/// [`xor_encode_u64`](crate::encode::xor_encode_u64).
+///
+/// Fills every slot of `values`, returning the number of bytes consumed.
+pub fn xor_decode_u64(
+	buf: &[u8],
+	values: &mut [u64],
+) -> Result<usize, &'static str> {
+	let mut offset = 0;
+	let mut previous = 0;
+	for slot in values {
+		if offset >= buf.len() {
+			return Err("buffer holds fewer values than expected");
+		}
+		let (xored, len) = u64::decode(&buf[offset..])?;
+		previous ^= xored;
+		*slot = previous;
+		offset += len;
+	}
+	Ok(offset)
+}
+
+/// Counts the values in a buffer without storing them.
+///
+/// Returns an error if the buffer ends partway through a value or a value
+/// starts with a prefix byte invalid for `T`.
+pub fn count_values<T>(buf: &[u8]) -> Result<usize, &'static str>
+where
+	T: Decode,
+{
+	let mut count = 0;
+	let mut offset = 0;
+	while offset < buf.len() {
+		T::check_prefix(buf[offset])?;
+		let (_, len) = T::decode(&buf[offset..])?;
+		offset += len;
+		count += 1;
+	}
+	Ok(count)
+}
+
+/// Skips over `n` values, returning the number of bytes they occupy.
+///
+/// Returns an error if the buffer holds fewer than `n` values or a value
+/// starts with a prefix byte invalid for `T`.
+pub fn skip_values<T>(buf: &[u8], n: usize) -> Result<usize, &'static str>
+where
+	T: Decode,
+{
+	let mut offset = 0;
+	for _ in 0..n {
+		if offset >= buf.len() {
+			return Err("buffer holds fewer values than expected");
+		}
+		T::check_prefix(buf[offset])?;
+		let (_, len) = T::decode(&buf[offset..])?;
+		offset += len;
+	}
+	Ok(offset)
+}
+
+/// Returns an iterator that lazily decodes values from a buffer.
+#[inline]
+pub fn decode_iter<T>(buf: &[u8]) -> DecodeIter<'_, T>
+where
+	T: Decode,
+{
+	DecodeIter {
+		buf,
+		offset: 0,
+		_marker: PhantomData,
+	}
+}
+
+/// Iterator that lazily decodes values from a buffer.
+///
+/// Yields an error and then stops if the buffer ends partway through a
+/// value. Created by [`decode_iter`].
+#[derive(Debug, Clone)]
+pub struct DecodeIter<'a, T> {
+	buf: &'a [u8],
+	offset: usize,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> DecodeIter<'a, T> {
+	/// Returns the number of bytes decoded so far.
+	#[inline]
+	#[must_use]
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	/// Returns the bytes that have not been decoded yet.
+	#[inline]
+	#[must_use]
+	pub fn remaining(&self) -> &'a [u8] {
+		&self.buf[self.offset..]
+	}
+}
+
+impl<T> Iterator for DecodeIter<'_, T>
+where
+	T: Decode,
+{
+	type Item = Result<T, &'static str>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.offset >= self.buf.len() {
+			return None;
+		}
+		match T::decode(&self.buf[self.offset..]) {
+			Ok((value, len)) => {
+				self.offset += len;
+				Some(Ok(value))
+			},
+			Err(err) => {
+				self.offset = self.buf.len();
+				Some(Err(err))
+			},
+		}
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.buf.len() - self.offset;
+		(
+			remaining.min(1),
+			Some(remaining / T::MIN_ENCODED_SIZE.max(1)),
+		)
+	}
+}
+
+impl<T> core::iter::FusedIterator for DecodeIter<'_, T> where T: Decode {}
+
 /// Trait for types that can be decoded using vlen.
 pub trait Decode: Sized {
 	/// Decodes the value from the provided buffer.
+	///
+	/// Returns an error if the buffer ends before the encoded value does.
 	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str>;
 
+	/// Decodes the value, rejecting encodings whose value does not fit in
+	/// `Self`.
+	///
+	/// The built-in types already reject a binary length prefix wider than
+	/// the type, such as a 9-byte encoding decoded as a `u32`, in
+	/// [`Decode::decode`], so for them the two agree. Implementations whose
+	/// `decode` is more lenient override this to validate fully, which
+	/// matters when decoding untrusted input into narrow types.
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		Self::decode(buf)
+	}
+
+	/// Checks that a value of this type can start with the byte `first`.
+	///
+	/// Validators and scanners call this before reading the rest of a
+	/// value, so a prefix announcing more bytes than the type has, such as
+	/// `0xF9` in a `u32` stream, fails with an `invalid vlen prefix byte`
+	/// error instead of being skipped over. The default accepts every byte.
+	#[inline]
+	fn check_prefix(first: u8) -> Result<(), &'static str> {
+		let _ = first;
+		Ok(())
+	}
+
 	/// The maximum possible encoded size for this type.
+	///
+	/// Buffers of at least this length can hold any encoded value, so it can
+	/// be used to size scratch buffers generically. Shorter buffers are
+	/// accepted as long as they contain the complete encoding.
 	const MAX_ENCODED_SIZE: usize;
+
+	/// The minimum possible encoded size for this type.
+	///
+	/// Every encoded value occupies at least this many bytes, which bounds
+	/// the number of values a buffer can hold.
+	const MIN_ENCODED_SIZE: usize = 1;
 }
 
 /// Macro to generate Decode implementation for unsigned integers
 macro_rules! impl_decode_unsigned {
-	($t:ty, $buf_size:expr, $decode_fn:ident) => {
+	($t:ty, $buf_size:expr, $decode_fn:ident, $len_fn:ident) => {
 		impl Decode for $t {
 			#[inline]
 			fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
-				if buf.len() < $buf_size {
-					return Err(concat!(
+				decode_prefix::<$t, $buf_size>(
+					buf,
+					$decode_fn,
+					$len_fn,
+					concat!(
 						"buffer too small for ",
 						stringify!($t),
 						" decoding"
-					));
-				}
-				let buf_array =
-					unsafe { &*(buf.as_ptr() as *const [u8; $buf_size]) };
-				Ok($decode_fn(buf_array))
+					),
+				)
+			}
+
+			#[inline]
+			fn check_prefix(first: u8) -> Result<(), &'static str> {
+				$len_fn(first).map(drop)
 			}
 
 			const MAX_ENCODED_SIZE: usize = $buf_size;
@@ -234,20 +689,25 @@ macro_rules! impl_decode_unsigned {
 
 /// Macro to generate Decode implementation for signed integers
 macro_rules! impl_decode_signed {
-	($t:ty, $buf_size:expr, $decode_fn:ident) => {
+	($t:ty, $buf_size:expr, $decode_fn:ident, $len_fn:ident) => {
 		impl Decode for $t {
 			#[inline]
 			fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
-				if buf.len() < $buf_size {
-					return Err(concat!(
+				decode_prefix::<$t, $buf_size>(
+					buf,
+					$decode_fn,
+					$len_fn,
+					concat!(
 						"buffer too small for ",
 						stringify!($t),
 						" decoding"
-					));
-				}
-				let buf_array =
-					unsafe { &*(buf.as_ptr() as *const [u8; $buf_size]) };
-				Ok($decode_fn(buf_array))
+					),
+				)
+			}
+
+			#[inline]
+			fn check_prefix(first: u8) -> Result<(), &'static str> {
+				$len_fn(first).map(drop)
 			}
 
 			const MAX_ENCODED_SIZE: usize = $buf_size;
@@ -257,20 +717,25 @@ macro_rules! impl_decode_signed {
 
 /// Macro to generate Decode implementation for floating-point types
 macro_rules! impl_decode_float {
-	($t:ty, $buf_size:expr, $decode_fn:ident) => {
+	($t:ty, $buf_size:expr, $decode_fn:ident, $len_fn:ident) => {
 		impl Decode for $t {
 			#[inline]
 			fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
-				if buf.len() < $buf_size {
-					return Err(concat!(
+				decode_prefix::<$t, $buf_size>(
+					buf,
+					$decode_fn,
+					$len_fn,
+					concat!(
 						"buffer too small for ",
 						stringify!($t),
 						" decoding"
-					));
-				}
-				let buf_array =
-					unsafe { &*(buf.as_ptr() as *const [u8; $buf_size]) };
-				Ok($decode_fn(buf_array))
+					),
+				)
+			}
+
+			#[inline]
+			fn check_prefix(first: u8) -> Result<(), &'static str> {
+				$len_fn(first).map(drop)
 			}
 
 			const MAX_ENCODED_SIZE: usize = $buf_size;
@@ -278,15 +743,80 @@ macro_rules! impl_decode_float {
 	};
 }
 
-impl_decode_unsigned!(u16, 3, decode_u16);
-impl_decode_unsigned!(u32, 5, decode_u32);
-impl_decode_unsigned!(u64, 9, decode_u64);
-impl_decode_unsigned!(u128, 17, decode_u128);
+impl_decode_unsigned!(u16, 3, decode_u16, encoded_len_u16);
+impl_decode_unsigned!(u32, 5, decode_u32, encoded_len_u32);
+impl_decode_unsigned!(u64, 9, decode_u64, encoded_len_u64);
+impl_decode_unsigned!(u128, 17, decode_u128, encoded_len_u128);
+
+impl_decode_signed!(i16, 3, decode_i16, encoded_len_u16);
+impl_decode_signed!(i32, 5, decode_i32, encoded_len_u32);
+impl_decode_signed!(i64, 9, decode_i64, encoded_len_u64);
+impl_decode_signed!(i128, 17, decode_i128, encoded_len_u128);
+
+impl_decode_float!(f32, 5, decode_f32, encoded_len_u32);
+impl_decode_float!(f64, 9, decode_f64, encoded_len_u64);
+
+/// Decodes a `char` from its Unicode scalar value, rejecting surrogates and
+/// values above `char::MAX`.
+impl Decode for char {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = u32::decode(buf)?;
+		let value =
+			char::from_u32(value).ok_or("invalid unicode scalar value")?;
+		Ok((value, len))
+	}
 
-impl_decode_signed!(i16, 3, decode_i16);
-impl_decode_signed!(i32, 5, decode_i32);
-impl_decode_signed!(i64, 9, decode_i64);
-impl_decode_signed!(i128, 17, decode_i128);
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = u32::decode_checked(buf)?;
+		let value =
+			char::from_u32(value).ok_or("invalid unicode scalar value")?;
+		Ok((value, len))
+	}
+
+	#[inline]
+	fn check_prefix(first: u8) -> Result<(), &'static str> {
+		u32::check_prefix(first)
+	}
+
+	const MAX_ENCODED_SIZE: usize = 3;
+}
+
+/// Macro to generate Decode implementation for `core::num` newtypes
+macro_rules! impl_decode_num_wrapper {
+	($($wrapper:ident),*) => {
+		$(
+			impl<T> Decode for core::num::$wrapper<T>
+			where
+				T: Decode,
+			{
+				#[inline]
+				fn decode(
+					buf: &[u8],
+				) -> Result<(Self, usize), &'static str> {
+					let (value, len) = T::decode(buf)?;
+					Ok((core::num::$wrapper(value), len))
+				}
+
+				#[inline]
+				fn decode_checked(
+					buf: &[u8],
+				) -> Result<(Self, usize), &'static str> {
+					let (value, len) = T::decode_checked(buf)?;
+					Ok((core::num::$wrapper(value), len))
+				}
+
+				#[inline]
+				fn check_prefix(first: u8) -> Result<(), &'static str> {
+					T::check_prefix(first)
+				}
+
+				const MAX_ENCODED_SIZE: usize = T::MAX_ENCODED_SIZE;
+				const MIN_ENCODED_SIZE: usize = T::MIN_ENCODED_SIZE;
+			}
+		)*
+	};
+}
 
-impl_decode_float!(f32, 5, decode_f32);
-impl_decode_float!(f64, 9, decode_f64);
+impl_decode_num_wrapper!(Wrapping, Saturating);
@@ -0,0 +1,205 @@
+//! Configurable decoding policy
+//!
+//! A [`Decoder`] gathers the strictness and resource limits for decoding
+//! untrusted buffers in one place. Every value is range-checked as by
+//! [`Decode::decode_checked`]; the builder methods add a limit on the
+//! number of values, rejection of overlong encodings, and tolerance of a
+//! truncated final value.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::Decoder;
+//!
+//! let decoder = Decoder::new().max_values(2).reject_overlong(true);
+//!
+//! let mut values = [0u32; 4];
+//! assert_eq!(decoder.decode_into(&[0x01, 0x02], &mut values), Ok((2, 2)));
+//! assert_eq!(values[..2], [1, 2]);
+//! assert!(decoder.decode_into(&[0x01, 0x02, 0x03], &mut values).is_err());
+//!
+//! let lenient = Decoder::new().allow_truncated_tail(true);
+//! assert_eq!(lenient.decode_into(&[0x01, 0x80], &mut values), Ok((1, 1)));
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+use crate::validate::is_canonical;
+
+/// Decoding policy built with chained setters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decoder {
+	max_values: Option<usize>,
+	reject_overlong: bool,
+	allow_truncated_tail: bool,
+}
+
+impl Decoder {
+	/// Creates a decoder with no value limit that accepts overlong encodings
+	/// and rejects a truncated final value.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			max_values: None,
+			reject_overlong: false,
+			allow_truncated_tail: false,
+		}
+	}
+
+	/// Rejects buffers holding more than `max` values.
+	#[inline]
+	#[must_use]
+	pub const fn max_values(mut self, max: usize) -> Self {
+		self.max_values = Some(max);
+		self
+	}
+
+	/// Rejects encodings longer than the encoder would have written.
+	#[inline]
+	#[must_use]
+	pub const fn reject_overlong(mut self, reject: bool) -> Self {
+		self.reject_overlong = reject;
+		self
+	}
+
+	/// Ends decoding without an error when the buffer stops partway through
+	/// its last value, as when reading a stream that is still being written.
+	#[inline]
+	#[must_use]
+	pub const fn allow_truncated_tail(mut self, allow: bool) -> Self {
+		self.allow_truncated_tail = allow;
+		self
+	}
+
+	/// Returns an iterator that lazily decodes values under this policy.
+	#[inline]
+	pub fn decode_iter<T>(self, buf: &[u8]) -> DecoderIter<'_, T>
+	where
+		T: Decode + Encode + Copy,
+	{
+		DecoderIter {
+			decoder: self,
+			buf,
+			offset: 0,
+			count: 0,
+			done: false,
+			_marker: PhantomData,
+		}
+	}
+
+	/// Decodes every value in `buf`.
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	#[cfg(feature = "alloc")]
+	pub fn decode_all<T>(self, buf: &[u8]) -> Result<Vec<T>, &'static str>
+	where
+		T: Decode + Encode + Copy,
+	{
+		self.decode_iter(buf).collect()
+	}
+
+	/// Decodes values into `values` until it is full or `buf` is exhausted,
+	/// returning the number of values decoded and bytes consumed.
+	pub fn decode_into<T>(
+		self,
+		buf: &[u8],
+		values: &mut [T],
+	) -> Result<(usize, usize), &'static str>
+	where
+		T: Decode + Encode + Copy,
+	{
+		let mut iter = self.decode_iter(buf);
+		let mut count = 0;
+		for slot in values.iter_mut() {
+			match iter.next() {
+				Some(value) => *slot = value?,
+				None => break,
+			}
+			count += 1;
+		}
+		Ok((count, iter.offset()))
+	}
+}
+
+/// Iterator that decodes values under a [`Decoder`] policy.
+///
+/// Yields an error and then stops at the first value that breaks the
+/// policy. Created by [`Decoder::decode_iter`].
+#[derive(Debug, Clone)]
+pub struct DecoderIter<'a, T> {
+	decoder: Decoder,
+	buf: &'a [u8],
+	offset: usize,
+	count: usize,
+	done: bool,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> DecoderIter<'a, T> {
+	/// Returns the number of bytes decoded so far.
+	#[inline]
+	#[must_use]
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	/// Returns the bytes that have not been decoded yet.
+	///
+	/// After decoding stops at a truncated tail, this is the tail.
+	#[inline]
+	#[must_use]
+	pub fn remaining(&self) -> &'a [u8] {
+		&self.buf[self.offset..]
+	}
+}
+
+impl<T> DecoderIter<'_, T>
+where
+	T: Decode + Encode + Copy,
+{
+	fn decode_next(&mut self) -> Result<Option<T>, &'static str> {
+		if self.decoder.max_values.is_some_and(|max| self.count >= max) {
+			return Err("buffer holds more values than allowed");
+		}
+		let rest = &self.buf[self.offset..];
+		let (value, len) = match T::decode_checked(rest) {
+			Ok((value, len)) if len <= rest.len() => (value, len),
+			Ok(_) | Err("truncated vlen value")
+				if self.decoder.allow_truncated_tail =>
+			{
+				return Ok(None);
+			},
+			Ok(_) => return Err("truncated vlen value"),
+			Err(err) => return Err(err),
+		};
+		if self.decoder.reject_overlong && !is_canonical(&rest[..len], value) {
+			return Err("non-canonical vlen encoding");
+		}
+		self.offset += len;
+		self.count += 1;
+		Ok(Some(value))
+	}
+}
+
+impl<T> Iterator for DecoderIter<'_, T>
+where
+	T: Decode + Encode + Copy,
+{
+	type Item = Result<T, &'static str>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done || self.offset >= self.buf.len() {
+			return None;
+		}
+		let result = self.decode_next();
+		self.done = !matches!(result, Ok(Some(_)));
+		result.transpose()
+	}
+}
+
+impl<T> FusedIterator for DecoderIter<'_, T> where T: Decode + Encode + Copy {}
@@ -0,0 +1,150 @@
+//! Dictionary encoding for low-cardinality columns
+//!
+//! A column with few distinct values, such as foreign keys or status codes,
+//! is stored as a dictionary of its distinct values followed by the index of
+//! each value in the dictionary. Both parts are sequences in the format of
+//! [`encode_seq`](crate::encode::encode_seq): a vlen count, then the vlen
+//! elements. The dictionary lists the most frequent values first, so the
+//! common values get the smallest indexes and take a single byte however
+//! large they are.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let ids: Vec<u64> = (0..1000).map(|i| 7_000_000_000 + i % 3).collect();
+//! let buf = vlen::dict::encode(&ids).unwrap();
+//! assert!(buf.len() < vlen::bulk_encode_to_vec(&ids).unwrap().len() / 5);
+//!
+//! assert_eq!(vlen::dict::decode(&buf), Ok((ids, buf.len())));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// The distinct values of a column, most frequent first.
+///
+/// Built by [`Dictionary::build`] and written with the data it indexes by
+/// [`Dictionary::encode_append`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Dictionary {
+	values: Vec<u64>,
+	indexes: BTreeMap<u64, usize>,
+}
+
+impl Dictionary {
+	/// Builds the dictionary of the distinct values in `values`.
+	///
+	/// Values are ordered by descending frequency, and values equally
+	/// frequent by ascending value, so the result is deterministic.
+	pub fn build(values: &[u64]) -> Self {
+		let mut counts = BTreeMap::new();
+		for &value in values {
+			*counts.entry(value).or_insert(0usize) += 1;
+		}
+		let mut entries: Vec<(u64, usize)> = counts.into_iter().collect();
+		entries.sort_by_key(|&(_, count)| core::cmp::Reverse(count));
+		let values: Vec<u64> =
+			entries.into_iter().map(|(value, _)| value).collect();
+		let indexes = values
+			.iter()
+			.enumerate()
+			.map(|(index, &value)| (value, index))
+			.collect();
+		Self { values, indexes }
+	}
+
+	/// Returns the number of distinct values.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Returns `true` if the dictionary holds no values.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	/// Returns the distinct values in index order.
+	#[inline]
+	#[must_use]
+	pub fn values(&self) -> &[u64] {
+		&self.values
+	}
+
+	/// Returns the index of `value`, or `None` if it is not in the
+	/// dictionary.
+	#[inline]
+	#[must_use]
+	pub fn index_of(&self, value: u64) -> Option<usize> {
+		self.indexes.get(&value).copied()
+	}
+
+	/// Returns the value at `index`, or `None` if out of bounds.
+	#[inline]
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<u64> {
+		self.values.get(index).copied()
+	}
+
+	/// Appends the dictionary followed by the index of each of `values`,
+	/// returning the number of bytes written.
+	///
+	/// Fails if a value is not in the dictionary, leaving `vec` unchanged.
+	pub fn encode_append(
+		&self,
+		vec: &mut Vec<u8>,
+		values: &[u64],
+	) -> Result<usize, &'static str> {
+		let indexes = values
+			.iter()
+			.map(|&value| {
+				self.index_of(value)
+					.map(|index| index as u64)
+					.ok_or("value not in dictionary")
+			})
+			.collect::<Result<Vec<u64>, _>>()?;
+		let start = vec.len();
+		crate::encode_append(vec, self.values.len() as u64)?;
+		crate::bulk_encode_append(vec, &self.values)?;
+		crate::encode_append(vec, indexes.len() as u64)?;
+		crate::bulk_encode_append(vec, &indexes)?;
+		Ok(vec.len() - start)
+	}
+}
+
+/// Dictionary encodes `values` into a newly allocated vector.
+pub fn encode(values: &[u64]) -> Result<Vec<u8>, &'static str> {
+	let mut vec = Vec::new();
+	encode_append(&mut vec, values)?;
+	Ok(vec)
+}
+
+/// Dictionary encodes `values` onto the end of `vec`, returning the number
+/// of bytes written.
+pub fn encode_append(
+	vec: &mut Vec<u8>,
+	values: &[u64],
+) -> Result<usize, &'static str> {
+	Dictionary::build(values).encode_append(vec, values)
+}
+
+/// Decodes a column written by [`encode`], replacing each index with its
+/// dictionary value.
+///
+/// Returns the values and the number of bytes consumed. Fails if an index
+/// is outside the dictionary.
+pub fn decode(buf: &[u8]) -> Result<(Vec<u64>, usize), &'static str> {
+	let (dictionary, dictionary_len) = crate::decode_seq_to_vec::<u64>(buf)?;
+	let (mut values, indexes_len) =
+		crate::decode_seq_to_vec::<u64>(&buf[dictionary_len..])?;
+	for value in &mut values {
+		*value = usize::try_from(*value)
+			.ok()
+			.and_then(|index| dictionary.get(index).copied())
+			.ok_or("dictionary index out of range")?;
+	}
+	Ok((values, dictionary_len + indexes_len))
+}
@@ -0,0 +1,145 @@
+//! Patches between two versions of a `u64` array
+//!
+//! [`diff_encode`] compares an old and a new version of an array and writes
+//! only the positions that changed, so a replica holding the old version can
+//! be brought up to date with [`diff_apply`] without resending the column.
+//!
+//! A patch is the new length and the number of changes, as vlen `u64`s,
+//! followed by each change in order of position: the gap since the previous
+//! change as a vlen `u64`, then the new value XORed with the old one. A run
+//! of neighbouring changes costs one byte per gap, and a value that changed
+//! only in its low bits encodes short however large it is. Positions past
+//! the end of the old array compare against zero and are always listed, so
+//! a patch can grow the array by no more than it describes.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let old: Vec<u64> = (0..10_000).map(|i| i * 1_000_000).collect();
+//! let mut new = old.clone();
+//! new[17] += 1;
+//! new[9_000] += 4;
+//! new.push(1 << 40);
+//!
+//! let patch = vlen::diff_encode(&old, &new);
+//! assert!(patch.len() < 20);
+//! assert_eq!(vlen::diff_apply(&old, &patch), Ok(new));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::decode::Decode;
+use crate::encode::encode_u64;
+
+/// Writes the patch that turns `old` into `new`.
+#[must_use]
+pub fn diff_encode(old: &[u64], new: &[u64]) -> Vec<u8> {
+	let mut changes = Vec::new();
+	let mut count = 0u64;
+	let mut next_position = 0;
+	for (position, &value) in new.iter().enumerate() {
+		let old_value = old.get(position).copied();
+		if old_value == Some(value) {
+			continue;
+		}
+		let gap = (position - next_position) as u64;
+		let xored = value ^ old_value.unwrap_or(0);
+		push_u64(&mut changes, gap);
+		push_u64(&mut changes, xored);
+		count += 1;
+		next_position = position + 1;
+	}
+	let mut patch = Vec::with_capacity(changes.len() + 18);
+	push_u64(&mut patch, new.len() as u64);
+	push_u64(&mut patch, count);
+	patch.extend_from_slice(&changes);
+	patch
+}
+
+/// Appends the encoding of `value` to `vec`.
+#[inline]
+fn push_u64(vec: &mut Vec<u8>, value: u64) {
+	let mut buf = [0u8; 9];
+	let len = encode_u64(&mut buf, value);
+	vec.extend_from_slice(&buf[..len]);
+}
+
+/// Applies a patch written by [`diff_encode`] to `old`, returning the new
+/// version.
+pub fn diff_apply(old: &[u64], patch: &[u8]) -> Result<Vec<u64>, &'static str> {
+	let mut values = old.to_vec();
+	diff_apply_in_place(&mut values, patch)?;
+	Ok(values)
+}
+
+/// Applies a patch written by [`diff_encode`] to `values` in place.
+///
+/// The whole patch is checked before `values` is modified, so on error it is
+/// left unchanged.
+pub fn diff_apply_in_place(
+	values: &mut Vec<u64>,
+	patch: &[u8],
+) -> Result<(), &'static str> {
+	let (new_len, count, start) = parse_header(patch)?;
+	let old_len = values.len();
+	let mut appended = 0;
+	let end = for_each_change(patch, start, count, |position, _| {
+		if position >= new_len {
+			return Err("diff position out of range");
+		}
+		appended += usize::from(position >= old_len);
+		Ok(())
+	})?;
+	if end != patch.len() {
+		return Err("trailing bytes after diff patch");
+	}
+	if appended != new_len.saturating_sub(old_len) {
+		return Err("diff does not cover appended values");
+	}
+
+	values.resize(new_len, 0);
+	for_each_change(patch, start, count, |position, xored| {
+		values[position] ^= xored;
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Reads the new length and the number of changes, returning them with the
+/// offset of the first change.
+fn parse_header(patch: &[u8]) -> Result<(usize, usize, usize), &'static str> {
+	let (new_len, new_len_len) = u64::decode(patch)?;
+	let (count, count_len) = u64::decode(&patch[new_len_len..])?;
+	let start = new_len_len + count_len;
+	// Every change takes at least two bytes.
+	if count > ((patch.len() - start) / 2) as u64 {
+		return Err("sequence count exceeds buffer length");
+	}
+	let new_len =
+		usize::try_from(new_len).map_err(|_| "vlen value out of range")?;
+	Ok((new_len, count as usize, start))
+}
+
+/// Calls `f` with the position and XORed value of each of the `count`
+/// changes starting at `offset`, returning the offset after the last one.
+fn for_each_change(
+	patch: &[u8],
+	mut offset: usize,
+	count: usize,
+	mut f: impl FnMut(usize, u64) -> Result<(), &'static str>,
+) -> Result<usize, &'static str> {
+	let mut next_position = 0usize;
+	for _ in 0..count {
+		let (gap, gap_len) = u64::decode(&patch[offset..])?;
+		offset += gap_len;
+		let (xored, xored_len) = u64::decode(&patch[offset..])?;
+		offset += xored_len;
+		let position = usize::try_from(gap)
+			.ok()
+			.and_then(|gap| next_position.checked_add(gap))
+			.ok_or("diff position out of range")?;
+		f(position, xored)?;
+		next_position = position + 1;
+	}
+	Ok(offset)
+}
@@ -0,0 +1,90 @@
+//! In-place edits of encoded buffers
+//!
+//! [`replace_value`], [`insert_value`] and [`remove_value`] locate the n-th
+//! value of a buffer of `T` values and rewrite it, shifting the tail of the
+//! buffer when the encoded length changes. Locating a value decodes every
+//! value before it, so each edit is linear in the size of the buffer.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let mut buf = vlen::bulk_encode_to_vec(&[1u32, 2, 3]).unwrap();
+//!
+//! assert_eq!(vlen::replace_value(&mut buf, 1, 70000u32), Ok(2));
+//! vlen::insert_value(&mut buf, 0, 0u32).unwrap();
+//! assert_eq!(vlen::remove_value::<u32>(&mut buf, 3), Ok(3));
+//!
+//! assert_eq!(vlen::bulk_decode_values::<u32>(&buf).unwrap(), [0, 1, 70000]);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::decode::{skip_values, Decode};
+use crate::encode::Encode;
+
+/// Replaces the value at `index` with `new_value`, returning the old value.
+pub fn replace_value<T>(
+	buf: &mut Vec<u8>,
+	index: usize,
+	new_value: T,
+) -> Result<T, &'static str>
+where
+	T: Decode + Encode + Copy,
+{
+	let (start, old_value, old_len) = locate::<T>(buf, index)?;
+	let encoded = crate::encode_to_vec(new_value)?;
+	buf.splice(start..start + old_len, encoded);
+	Ok(old_value)
+}
+
+/// Inserts `value` before the value at `index`, returning its encoded
+/// length.
+///
+/// `index` may equal the number of values, appending `value`.
+pub fn insert_value<T>(
+	buf: &mut Vec<u8>,
+	index: usize,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: Decode + Encode + Copy,
+{
+	let start = skip_values::<T>(buf, index)?;
+	let encoded = crate::encode_to_vec(value)?;
+	let len = encoded.len();
+	buf.splice(start..start, encoded);
+	Ok(len)
+}
+
+/// Removes the value at `index`, returning it.
+pub fn remove_value<T>(
+	buf: &mut Vec<u8>,
+	index: usize,
+) -> Result<T, &'static str>
+where
+	T: Decode,
+{
+	let (start, value, len) = locate::<T>(buf, index)?;
+	buf.drain(start..start + len);
+	Ok(value)
+}
+
+/// Returns the offset, value and encoded length of the value at `index`.
+fn locate<T>(
+	buf: &[u8],
+	index: usize,
+) -> Result<(usize, T, usize), &'static str>
+where
+	T: Decode,
+{
+	let start = skip_values::<T>(buf, index)?;
+	let rest = &buf[start..];
+	if rest.is_empty() {
+		return Err("buffer holds fewer values than expected");
+	}
+	let (value, len) = T::decode(rest)?;
+	if len > rest.len() {
+		return Err("truncated vlen value");
+	}
+	Ok((start, value, len))
+}
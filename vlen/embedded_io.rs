@@ -0,0 +1,260 @@
+//! Streaming over `embedded-io` transports
+//!
+//! [`VlenRead`] and [`VlenWrite`] extend any [`embedded_io::Read`] or
+//! [`embedded_io::Write`] implementation, so firmware can exchange vlen values
+//! over UART, SPI and similar transports without `std`. Reads consume exactly
+//! the bytes of one value, using its prefix byte to find the length.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::embedded_io::{VlenRead, VlenWrite};
+//!
+//! let mut buf = [0u8; 16];
+//! let mut writer = &mut buf[..];
+//! writer.write_vlen_u64(70000).unwrap();
+//! writer.write_vlen(-3i32).unwrap();
+//!
+//! let mut reader = &buf[..];
+//! assert_eq!(reader.read_vlen_u64().unwrap(), 70000);
+//! assert_eq!(reader.read_vlen::<i32>().unwrap(), -3);
+//! ```
+//!
+//! [`ChecksumWriter`] and [`ChecksumReader`] add a CRC-32C trailer to a frame
+//! or stream:
+//!
+//! ```rust
+//! use vlen::embedded_io::{ChecksumReader, ChecksumWriter, VlenRead, VlenWrite};
+//!
+//! let mut buf = [0u8; 16];
+//! let mut writer = ChecksumWriter::new(&mut buf[..]);
+//! writer.write_vlen_u64(70000).unwrap();
+//! writer.finish().unwrap();
+//!
+//! let mut reader = ChecksumReader::new(&buf[..]);
+//! assert_eq!(reader.read_vlen_u64().unwrap(), 70000);
+//! reader.verify().unwrap();
+//! ```
+
+use ::embedded_io::{ErrorType, Read, ReadExactError, Write};
+
+use crate::checksum::Crc32c;
+use crate::decode::{prefix_encoded_len, Decode};
+use crate::encode::Encode;
+
+/// Error returned when streaming vlen values over `embedded-io`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VlenIoError<E> {
+	/// The transport reported an error.
+	Io(E),
+	/// The transport ended partway through a value.
+	UnexpectedEof,
+	/// The bytes read were not a valid encoding, or the value could not be
+	/// encoded.
+	Vlen(&'static str),
+}
+
+impl<E> From<ReadExactError<E>> for VlenIoError<E> {
+	fn from(err: ReadExactError<E>) -> Self {
+		match err {
+			ReadExactError::UnexpectedEof => VlenIoError::UnexpectedEof,
+			ReadExactError::Other(err) => VlenIoError::Io(err),
+		}
+	}
+}
+
+macro_rules! read_methods {
+	($($name:ident: $t:ty),* $(,)?) => {$(
+		#[doc = concat!("Reads a vlen-encoded `", stringify!($t), "`.")]
+		#[inline]
+		fn $name(&mut self) -> Result<$t, VlenIoError<Self::Error>> {
+			self.read_vlen::<$t>()
+		}
+	)*};
+}
+
+macro_rules! write_methods {
+	($($name:ident: $t:ty),* $(,)?) => {$(
+		#[doc = concat!(
+			"Writes a vlen-encoded `", stringify!($t), "`, returning the ",
+			"number of bytes written."
+		)]
+		#[inline]
+		fn $name(
+			&mut self,
+			value: $t,
+		) -> Result<usize, VlenIoError<Self::Error>> {
+			self.write_vlen(value)
+		}
+	)*};
+}
+
+/// Extension trait for reading vlen values from an [`embedded_io::Read`].
+pub trait VlenRead: Read {
+	/// Reads a single vlen-encoded value.
+	///
+	/// `T` must encode as a single vlen value, as the built-in numeric types
+	/// do; composite types such as `Duration` are not supported.
+	fn read_vlen<T: Decode>(&mut self) -> Result<T, VlenIoError<Self::Error>> {
+		let mut buf = [0u8; 17];
+		self.read_exact(&mut buf[..1])?;
+		let len = prefix_encoded_len(buf[0]);
+		self.read_exact(&mut buf[1..len])?;
+		let (value, decoded_len) =
+			T::decode(&buf[..len]).map_err(VlenIoError::Vlen)?;
+		if decoded_len != len {
+			return Err(VlenIoError::Vlen("invalid vlen prefix byte"));
+		}
+		Ok(value)
+	}
+
+	read_methods! {
+		read_vlen_u16: u16,
+		read_vlen_u32: u32,
+		read_vlen_u64: u64,
+		read_vlen_u128: u128,
+		read_vlen_i16: i16,
+		read_vlen_i32: i32,
+		read_vlen_i64: i64,
+		read_vlen_i128: i128,
+		read_vlen_f32: f32,
+		read_vlen_f64: f64,
+	}
+}
+
+impl<R: Read + ?Sized> VlenRead for R {}
+
+/// Extension trait for writing vlen values to an [`embedded_io::Write`].
+pub trait VlenWrite: Write {
+	/// Writes a vlen-encoded value, returning the number of bytes written.
+	fn write_vlen<T: Encode>(
+		&mut self,
+		value: T,
+	) -> Result<usize, VlenIoError<Self::Error>> {
+		let mut buf = [0u8; 32];
+		let buf = buf
+			.get_mut(..T::MAX_ENCODED_SIZE)
+			.ok_or(VlenIoError::Vlen("value too large for streaming"))?;
+		let len = T::encode(buf, value).map_err(VlenIoError::Vlen)?;
+		self.write_all(&buf[..len]).map_err(VlenIoError::Io)?;
+		Ok(len)
+	}
+
+	write_methods! {
+		write_vlen_u16: u16,
+		write_vlen_u32: u32,
+		write_vlen_u64: u64,
+		write_vlen_u128: u128,
+		write_vlen_i16: i16,
+		write_vlen_i32: i32,
+		write_vlen_i64: i64,
+		write_vlen_i128: i128,
+		write_vlen_f32: f32,
+		write_vlen_f64: f64,
+	}
+}
+
+impl<W: Write + ?Sized> VlenWrite for W {}
+
+/// A writer that checksums everything written through it.
+///
+/// [`ChecksumWriter::finish`] appends the CRC-32C of the written bytes as a
+/// vlen-encoded `u32`. Wrap each frame in a new writer to checksum frames
+/// individually.
+#[derive(Debug)]
+pub struct ChecksumWriter<W> {
+	inner: W,
+	crc: Crc32c,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+	/// Wraps `inner`, starting a new checksum.
+	#[inline]
+	pub fn new(inner: W) -> Self {
+		Self {
+			inner,
+			crc: Crc32c::new(),
+		}
+	}
+
+	/// Returns the checksum of the bytes written so far.
+	#[inline]
+	#[must_use]
+	pub fn checksum(&self) -> u32 {
+		self.crc.finish()
+	}
+
+	/// Writes the checksum trailer and returns the inner writer.
+	pub fn finish(mut self) -> Result<W, VlenIoError<W::Error>> {
+		self.inner.write_vlen_u32(self.crc.finish())?;
+		Ok(self.inner)
+	}
+}
+
+impl<W: ErrorType> ErrorType for ChecksumWriter<W> {
+	type Error = W::Error;
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+	#[inline]
+	fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+		let len = self.inner.write(buf)?;
+		self.crc.update(&buf[..len]);
+		Ok(len)
+	}
+
+	#[inline]
+	fn flush(&mut self) -> Result<(), Self::Error> {
+		self.inner.flush()
+	}
+}
+
+/// A reader that checksums everything read through it.
+///
+/// [`ChecksumReader::verify`] reads the trailer written by
+/// [`ChecksumWriter::finish`] and compares it with the bytes read so far.
+#[derive(Debug)]
+pub struct ChecksumReader<R> {
+	inner: R,
+	crc: Crc32c,
+}
+
+impl<R: Read> ChecksumReader<R> {
+	/// Wraps `inner`, starting a new checksum.
+	#[inline]
+	pub fn new(inner: R) -> Self {
+		Self {
+			inner,
+			crc: Crc32c::new(),
+		}
+	}
+
+	/// Returns the checksum of the bytes read so far.
+	#[inline]
+	#[must_use]
+	pub fn checksum(&self) -> u32 {
+		self.crc.finish()
+	}
+
+	/// Reads the checksum trailer and returns the inner reader if it matches.
+	pub fn verify(mut self) -> Result<R, VlenIoError<R::Error>> {
+		let expected = self.crc.finish();
+		if self.inner.read_vlen_u32()? != expected {
+			return Err(VlenIoError::Vlen("checksum mismatch"));
+		}
+		Ok(self.inner)
+	}
+}
+
+impl<R: ErrorType> ErrorType for ChecksumReader<R> {
+	type Error = R::Error;
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+	#[inline]
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+		let len = self.inner.read(buf)?;
+		self.crc.update(&buf[..len]);
+		Ok(len)
+	}
+}
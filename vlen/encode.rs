@@ -1,16 +1,20 @@
 //! Encoding functions for vlen
 
-use crate::helpers::ptr_from_mut;
+use core::mem::MaybeUninit;
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, rc::Rc};
+
+use crate::helpers::array_prefix_mut;
 
 /// Macro for writing aligned/unaligned values to a buffer at offset 1
+#[cfg(not(feature = "safe"))]
 macro_rules! write_aligned_at_offset {
 	($buf:expr, $value:expr, $ut:ident, $shift:expr) => {
 		unsafe {
-			let ptr =
-				ptr_from_mut::<[u8; core::mem::size_of::<$ut>() + 1]>($buf)
-					.cast::<u8>()
-					.add(1)
-					.cast::<$ut>();
+			let ptr = $buf.as_mut_ptr().add(1).cast::<$ut>();
 			if ptr.is_aligned() {
 				ptr.write(($value >> $shift).to_le());
 			} else {
@@ -20,6 +24,15 @@ macro_rules! write_aligned_at_offset {
 	};
 }
 
+/// Macro for writing little-endian values to a buffer at offset 1
+#[cfg(feature = "safe")]
+macro_rules! write_aligned_at_offset {
+	($buf:expr, $value:expr, $ut:ident, $shift:expr) => {
+		$buf[1..=core::mem::size_of::<$ut>()]
+			.copy_from_slice(&($value >> $shift).to_le_bytes())
+	};
+}
+
 /// Unified macro for size calculation and encoding of large integers
 macro_rules! encode_large_int {
 	($(#[$docs:meta])* $size_fn:ident, $encode_fn:ident ( $ut:ident, $smaller_ut:ident, $smaller_size_fn:ident, $smaller_encode_fn:ident, $max_smaller:expr, $buf_size:expr, $smaller_buf_size:expr ) ) => {
@@ -43,9 +56,7 @@ macro_rules! encode_large_int {
 		pub fn $encode_fn(buf: &mut [u8; $buf_size], value: $ut) -> usize {
 			match value {
 				_ if value <= $max_smaller as $ut => {
-				let buf_smaller = unsafe {
-						&mut *(ptr_from_mut::<[u8; $buf_size]>(buf).cast::<[u8; $smaller_buf_size]>())
-				};
+					let buf_smaller = array_prefix_mut::<$buf_size, $smaller_buf_size>(buf);
 					$smaller_encode_fn(buf_smaller, value as $smaller_ut)
 			},
 			_ => {
@@ -60,18 +71,80 @@ macro_rules! encode_large_int {
 	};
 }
 
+/// Encoded length of a value, indexed by its prefix byte.
+///
+/// Scanners can step over values with a table lookup instead of branching
+/// on the prefix; [`encoded_len`] reads from this table.
+pub const ENCODED_LEN_TABLE: [u8; 256] = {
+	let mut table = [0u8; 256];
+	let mut b = 0;
+	while b < table.len() {
+		table[b] = match b as u8 {
+			0x00..=0x7F => 1,
+			0x80..=0xBF => 2,
+			0xC0..=0xDF => 3,
+			0xE0..=0xEF => 4,
+			prefix => (prefix & 0x0F) + 2,
+		};
+		b += 1;
+	}
+	table
+};
+
 /// Returns the encoded length in a `vlen` prefix byte.
+#[inline]
 #[must_use]
 pub const fn encoded_len(b: u8) -> usize {
+	ENCODED_LEN_TABLE[b as usize] as usize
+}
+
+/// Error returned for prefix bytes that no value of a type starts with.
+const INVALID_PREFIX: &str = "invalid vlen prefix byte";
+
+/// Returns the encoded length of a `u16` or `i16` with prefix byte `b`.
+///
+/// `u16` decodes every prefix from `0xC0` except `0xDE` as a binary length
+/// prefix, and fails if it announces more than two payload bytes.
+#[inline]
+pub const fn encoded_len_u16(b: u8) -> Result<usize, &'static str> {
+	match b {
+		_ if b < 0x80 => Ok(1),
+		_ if b < 0xC0 => Ok(2),
+		0xDE => Ok(3),
+		_ if b & 0x0F < 2 => Ok(((b & 0x0F) + 2) as usize),
+		_ => Err(INVALID_PREFIX),
+	}
+}
+
+/// Returns the encoded length of a `u32`, `i32` or `f32` with prefix byte
+/// `b`, failing for binary length prefixes wider than four bytes.
+#[inline]
+pub const fn encoded_len_u32(b: u8) -> Result<usize, &'static str> {
 	match b {
-		_ if b < 0x80 => 1,
-		_ if b < 0xC0 => 2,
-		_ if b < 0xE0 => 3,
-		_ if b < 0xF0 => 4,
-		_ => ((b & 0x0F) + 2) as usize,
+		0xF4..=0xFF => Err(INVALID_PREFIX),
+		_ => Ok(encoded_len(b)),
 	}
 }
 
+/// Returns the encoded length of a `u64`, `i64` or `f64` with prefix byte
+/// `b`, failing for binary length prefixes wider than eight bytes.
+#[inline]
+pub const fn encoded_len_u64(b: u8) -> Result<usize, &'static str> {
+	match b {
+		0xF8..=0xFF => Err(INVALID_PREFIX),
+		_ => Ok(encoded_len(b)),
+	}
+}
+
+/// Returns the encoded length of a `u128` or `i128` with prefix byte `b`.
+///
+/// Every byte is a valid `u128` prefix; this exists for symmetry with the
+/// narrower widths.
+#[inline]
+pub const fn encoded_len_u128(b: u8) -> Result<usize, &'static str> {
+	Ok(encoded_len(b))
+}
+
 /// Calculates the encoded size of a u16 value without encoding it.
 #[inline]
 #[must_use]
@@ -135,12 +208,7 @@ pub fn encode_u16(buf: &mut [u8; 3], value: u16) -> usize {
 #[must_use]
 pub fn encode_u32(buf: &mut [u8; 5], value: u32) -> usize {
 	match value {
-		_ if value < 0x4000 => {
-			let buf_u16 = unsafe {
-				&mut *(ptr_from_mut::<[u8; 5]>(buf).cast::<[u8; 3]>())
-			};
-			encode_u16(buf_u16, value as u16)
-		},
+		_ if value < 0x4000 => encode_u16(array_prefix_mut(buf), value as u16),
 		_ if value < 0x200000 => {
 			buf[0] = 0xC0 | ((value & 0x1F) as u8);
 			buf[1] = (value >> 5) as u8;
@@ -240,19 +308,179 @@ pub fn bulk_encode<T>(
 	values: &[T],
 ) -> Result<usize, &'static str>
 where
-	T: Encode + Copy,
+	T: Encode + Clone,
 {
 	let mut offset = 0;
-	for &value in values {
+	for value in values {
 		if offset >= buf.len() {
 			return Err("buffer too small for bulk encoding");
 		}
-		let len = T::encode(&mut buf[offset..], value)?;
+		let len = T::encode(&mut buf[offset..], value.clone())?;
 		offset += len;
 	}
 	Ok(offset)
 }
 
+/// Bulk encodes optional values as a validity bitmap and the present values.
+///
+/// The bitmap takes `values.len().div_ceil(8)` bytes, with bit `i % 8` of
+/// byte `i / 8` set when `values[i]` is present. The present values follow
+/// in order; absent values take no space.
+pub fn bulk_encode_opt_u32(
+	buf: &mut [u8],
+	values: &[Option<u32>],
+) -> Result<usize, &'static str> {
+	let bitmap_len = values.len().div_ceil(8);
+	let bitmap = buf
+		.get_mut(..bitmap_len)
+		.ok_or("buffer too small for bulk encoding")?;
+	bitmap.fill(0);
+	for (i, value) in values.iter().enumerate() {
+		if value.is_some() {
+			bitmap[i / 8] |= 1 << (i % 8);
+		}
+	}
+	let mut offset = bitmap_len;
+	for &value in values.iter().flatten() {
+		if offset >= buf.len() {
+			return Err("buffer too small for bulk encoding");
+		}
+		offset += u32::encode(&mut buf[offset..], value)?;
+	}
+	Ok(offset)
+}
+
+/// Bulk encodes `u64` values each XORed with the value before it, the first
+/// with zero.
+///
+/// Values that differ from their predecessor only in the low bits, such as
+/// gauge readings and file offsets, XOR to small numbers and encode in a
+/// byte or two whatever their magnitude. Unlike a delta, the XOR costs the
+/// same whichever way the value moves.
+pub fn xor_encode_u64(
+	buf: &mut [u8],
+	values: &[u64],
+) -> Result<usize, &'static str> {
+	let mut offset = 0;
+	let mut previous = 0;
+	for &value in values {
+		if offset >= buf.len() {
+			return Err("buffer too small for bulk encoding");
+		}
+		offset += u64::encode(&mut buf[offset..], value ^ previous)?;
+		previous = value;
+	}
+	Ok(offset)
+}
+
+/// Encodes a sequence as a vlen element count followed by the elements.
+///
+/// Unlike [`bulk_encode`], the output is self-describing: the decoder does
+/// not need to know the number of elements in advance.
+pub fn encode_seq<T>(
+	buf: &mut [u8],
+	values: &[T],
+) -> Result<usize, &'static str>
+where
+	T: Encode + Clone,
+{
+	let count_len = u64::encode(buf, values.len() as u64)?;
+	let values_len = bulk_encode(&mut buf[count_len..], values)?;
+	Ok(count_len + values_len)
+}
+
+/// Encodes a value into uninitialized memory, returning the encoded length.
+///
+/// On success the first `len` bytes of `buf` are initialized. The buffer only
+/// needs room for the actual encoding when `T::MAX_ENCODED_SIZE` is at most
+/// 32 bytes, which holds for every built-in type. With the `safe` feature,
+/// larger types are rejected.
+pub fn encode_uninit<T>(
+	buf: &mut [MaybeUninit<u8>],
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: Encode,
+{
+	let mut temp_buf = [0u8; 32];
+	if T::MAX_ENCODED_SIZE <= temp_buf.len() {
+		let len = T::encode(&mut temp_buf, value)?;
+		let dst = buf
+			.get_mut(..len)
+			.ok_or("buffer too small for uninit encoding")?;
+		for (dst, &byte) in dst.iter_mut().zip(&temp_buf[..len]) {
+			dst.write(byte);
+		}
+		return Ok(len);
+	}
+	encode_uninit_large(buf, value)
+}
+
+/// Encodes a value larger than the scratch buffer into uninitialized memory.
+#[cfg(not(feature = "safe"))]
+fn encode_uninit_large<T>(
+	buf: &mut [MaybeUninit<u8>],
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: Encode,
+{
+	let dst = buf
+		.get_mut(..T::MAX_ENCODED_SIZE)
+		.ok_or("buffer too small for uninit encoding")?;
+	for byte in dst.iter_mut() {
+		byte.write(0);
+	}
+	// SAFETY: every byte of `dst` was initialized above, and `MaybeUninit<u8>`
+	// has the same layout as `u8`.
+	let dst = unsafe {
+		core::slice::from_raw_parts_mut(
+			dst.as_mut_ptr().cast::<u8>(),
+			dst.len(),
+		)
+	};
+	let len = T::encode(dst, value)?;
+	// Callers rely on exactly `len` bytes being initialized, so a length
+	// past the zeroed span cannot be trusted, whatever `T` reports.
+	if len > dst.len() {
+		return Err("encoded length exceeds buffer");
+	}
+	Ok(len)
+}
+
+/// Encodes a value larger than the scratch buffer into uninitialized memory.
+///
+/// Reborrowing uninitialized memory as bytes requires `unsafe`, so the `safe`
+/// feature does not support these types.
+#[cfg(feature = "safe")]
+fn encode_uninit_large<T>(
+	_buf: &mut [MaybeUninit<u8>],
+	_value: T,
+) -> Result<usize, &'static str>
+where
+	T: Encode,
+{
+	Err("encoding too large for uninit buffer")
+}
+
+/// Bulk encodes values into uninitialized memory, returning the encoded
+/// length.
+///
+/// On success the first `len` bytes of `buf` are initialized.
+pub fn bulk_encode_uninit<T>(
+	buf: &mut [MaybeUninit<u8>],
+	values: &[T],
+) -> Result<usize, &'static str>
+where
+	T: Encode + Clone,
+{
+	let mut offset = 0;
+	for value in values {
+		offset += encode_uninit(&mut buf[offset..], value.clone())?;
+	}
+	Ok(offset)
+}
+
 /// Trait for types that can be encoded using vlen.
 pub trait Encode: Sized {
 	/// Encodes the value into the provided buffer.
@@ -262,7 +490,68 @@ pub trait Encode: Sized {
 	fn encoded_size(value: Self) -> Result<usize, &'static str>;
 
 	/// The maximum possible encoded size for this type.
+	///
+	/// Buffers passed to [`encode`](Self::encode) must be at least this long,
+	/// so it can be used to size scratch buffers generically.
 	const MAX_ENCODED_SIZE: usize;
+
+	/// The minimum possible encoded size for this type.
+	///
+	/// Every encoded value occupies at least this many bytes, which bounds
+	/// the number of values a buffer can hold.
+	const MIN_ENCODED_SIZE: usize = 1;
+}
+
+/// Object-safe companion to [`Encode`].
+///
+/// Implemented for every [`Encode`] type that is [`Clone`], so values of
+/// different types can be held as `&dyn DynEncode` or `Box<dyn DynEncode>`
+/// and encoded one after another.
+pub trait DynEncode {
+	/// Encodes the value into the provided buffer.
+	fn encode_dyn(&self, buf: &mut [u8]) -> Result<usize, &'static str>;
+
+	/// Calculates the encoded size of the value without encoding it.
+	fn encoded_size_dyn(&self) -> Result<usize, &'static str>;
+
+	/// Returns the maximum possible encoded size of the value's type.
+	fn max_encoded_size_dyn(&self) -> usize;
+}
+
+impl<T> DynEncode for T
+where
+	T: Encode + Clone,
+{
+	#[inline]
+	fn encode_dyn(&self, buf: &mut [u8]) -> Result<usize, &'static str> {
+		T::encode(buf, self.clone())
+	}
+
+	#[inline]
+	fn encoded_size_dyn(&self) -> Result<usize, &'static str> {
+		T::encoded_size(self.clone())
+	}
+
+	#[inline]
+	fn max_encoded_size_dyn(&self) -> usize {
+		T::MAX_ENCODED_SIZE
+	}
+}
+
+/// Encodes values of mixed types one after another, returning the total
+/// encoded length.
+pub fn bulk_encode_dyn(
+	buf: &mut [u8],
+	values: &[&dyn DynEncode],
+) -> Result<usize, &'static str> {
+	let mut offset = 0;
+	for value in values {
+		if offset >= buf.len() {
+			return Err("buffer too small for bulk encoding");
+		}
+		offset += value.encode_dyn(&mut buf[offset..])?;
+	}
+	Ok(offset)
 }
 
 /// Macro to generate Encode implementation for unsigned integers
@@ -274,15 +563,12 @@ macro_rules! impl_encode_unsigned {
 				buf: &mut [u8],
 				value: Self,
 			) -> Result<usize, &'static str> {
-				if buf.len() < $buf_size {
-					return Err(concat!(
+				let buf_array =
+					buf.first_chunk_mut::<$buf_size>().ok_or(concat!(
 						"buffer too small for ",
 						stringify!($t),
 						" encoding"
-					));
-				}
-				let buf_array =
-					unsafe { &mut *(buf.as_mut_ptr() as *mut [u8; $buf_size]) };
+					))?;
 				Ok($encode_fn(buf_array, value))
 			}
 
@@ -305,15 +591,12 @@ macro_rules! impl_encode_signed {
 				buf: &mut [u8],
 				value: Self,
 			) -> Result<usize, &'static str> {
-				if buf.len() < $buf_size {
-					return Err(concat!(
+				let buf_array =
+					buf.first_chunk_mut::<$buf_size>().ok_or(concat!(
 						"buffer too small for ",
 						stringify!($t),
 						" encoding"
-					));
-				}
-				let buf_array =
-					unsafe { &mut *(buf.as_mut_ptr() as *mut [u8; $buf_size]) };
+					))?;
 				Ok($encode_fn(buf_array, value))
 			}
 
@@ -341,15 +624,12 @@ macro_rules! impl_encode_float {
 				buf: &mut [u8],
 				value: Self,
 			) -> Result<usize, &'static str> {
-				if buf.len() < $buf_size {
-					return Err(concat!(
+				let buf_array =
+					buf.first_chunk_mut::<$buf_size>().ok_or(concat!(
 						"buffer too small for ",
 						stringify!($t),
 						" encoding"
-					));
-				}
-				let buf_array =
-					unsafe { &mut *(buf.as_mut_ptr() as *mut [u8; $buf_size]) };
+					))?;
 				Ok($encode_fn(buf_array, value))
 			}
 
@@ -375,3 +655,112 @@ impl_encode_signed!(i128, 17, encode_i128, encoded_size_u128, u128);
 
 impl_encode_float!(f32, 5, encode_f32, encoded_size_u32);
 impl_encode_float!(f64, 9, encode_f64, encoded_size_u64);
+
+/// Encodes a `char` as its Unicode scalar value, taking at most three bytes.
+impl Encode for char {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		// `encode_u32` needs room for any `u32`, but scalar values never
+		// take more than three bytes.
+		let mut temp_buf = [0u8; 5];
+		let len = encode_u32(&mut temp_buf, u32::from(value));
+		buf.get_mut(..len)
+			.ok_or("buffer too small for char encoding")?
+			.copy_from_slice(&temp_buf[..len]);
+		Ok(len)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(encoded_size_u32(u32::from(value)))
+	}
+
+	const MAX_ENCODED_SIZE: usize = 3;
+}
+
+impl<T> Encode for &T
+where
+	T: Encode + Clone,
+{
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		T::encode(buf, value.clone())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		T::encoded_size(value.clone())
+	}
+
+	const MAX_ENCODED_SIZE: usize = T::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = T::MIN_ENCODED_SIZE;
+}
+
+/// Macro to generate Encode implementation for smart pointers
+#[cfg(feature = "alloc")]
+macro_rules! impl_encode_pointer {
+	($($(#[$attr:meta])* $ptr:ident($unwrap:expr)),* $(,)?) => {
+		$(
+			$(#[$attr])*
+			#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+			impl<T> Encode for $ptr<T>
+			where
+				T: Encode + Clone,
+			{
+				#[inline]
+				fn encode(
+					buf: &mut [u8],
+					value: Self,
+				) -> Result<usize, &'static str> {
+					T::encode(buf, ($unwrap)(value))
+				}
+
+				#[inline]
+				fn encoded_size(value: Self) -> Result<usize, &'static str> {
+					T::encoded_size(($unwrap)(value))
+				}
+
+				const MAX_ENCODED_SIZE: usize = T::MAX_ENCODED_SIZE;
+				const MIN_ENCODED_SIZE: usize = T::MIN_ENCODED_SIZE;
+			}
+		)*
+	};
+}
+
+#[cfg(feature = "alloc")]
+impl_encode_pointer! {
+	Box(|value: Box<T>| *value),
+	Rc(Rc::unwrap_or_clone),
+	#[cfg(target_has_atomic = "ptr")]
+	Arc(Arc::unwrap_or_clone),
+}
+
+/// Macro to generate Encode implementation for `core::num` newtypes
+macro_rules! impl_encode_num_wrapper {
+	($($wrapper:ident),*) => {
+		$(
+			impl<T> Encode for core::num::$wrapper<T>
+			where
+				T: Encode,
+			{
+				#[inline]
+				fn encode(
+					buf: &mut [u8],
+					value: Self,
+				) -> Result<usize, &'static str> {
+					T::encode(buf, value.0)
+				}
+
+				#[inline]
+				fn encoded_size(value: Self) -> Result<usize, &'static str> {
+					T::encoded_size(value.0)
+				}
+
+				const MAX_ENCODED_SIZE: usize = T::MAX_ENCODED_SIZE;
+				const MIN_ENCODED_SIZE: usize = T::MIN_ENCODED_SIZE;
+			}
+		)*
+	};
+}
+
+impl_encode_num_wrapper!(Wrapping, Saturating);
@@ -0,0 +1,111 @@
+//! Buffer inspection for debugging and wire-format tooling
+//!
+//! [`explain`] walks a buffer value by value without knowing the encoded
+//! type, reporting where each value starts, its prefix byte, its encoded
+//! length, and its value decoded as a `u128`. Signed and floating-point
+//! values are reported in their zigzag or byte-swapped unsigned form.
+//!
+//! `u16` values in the three-byte `0xDE` form use a different layout than
+//! wider types, so their reported value is only meaningful for wider types.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let buf = [0x05, 0xAC, 0x04, 0xF3, 0x78, 0x56, 0x34, 0x12];
+//! for token in vlen::explain(&buf) {
+//!     println!("{token}");
+//! }
+//!
+//! let values: Vec<u128> = vlen::explain(&buf).map(|t| t.value).collect();
+//! assert_eq!(values, [5, 300, 0x12345678]);
+//! ```
+
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::decode::decode_u128;
+use crate::encode::encoded_len;
+
+/// Returns an iterator that describes each value in a buffer.
+#[inline]
+pub fn explain(buf: &[u8]) -> Explain<'_> {
+	Explain { buf, offset: 0 }
+}
+
+/// A single encoded value found by [`explain`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Token {
+	/// Byte offset of the value within the buffer.
+	pub offset: usize,
+	/// The first byte of the value.
+	pub prefix: u8,
+	/// Encoded length implied by the prefix byte.
+	pub len: usize,
+	/// The value decoded as a `u128`.
+	///
+	/// Missing bytes of a truncated value are treated as zero.
+	pub value: u128,
+	/// Whether the buffer ends before the value does.
+	pub truncated: bool,
+}
+
+impl fmt::Display for Token {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"offset {}: prefix {:#04x}, length {}, value {}",
+			self.offset, self.prefix, self.len, self.value
+		)?;
+		if self.truncated {
+			f.write_str(" (truncated)")?;
+		}
+		Ok(())
+	}
+}
+
+/// Iterator over the values in a buffer, created by [`explain`].
+///
+/// Stops after the first truncated value.
+#[derive(Debug, Clone)]
+pub struct Explain<'a> {
+	buf: &'a [u8],
+	offset: usize,
+}
+
+impl Iterator for Explain<'_> {
+	type Item = Token;
+
+	fn next(&mut self) -> Option<Token> {
+		let rest = self.buf.get(self.offset..).filter(|r| !r.is_empty())?;
+		let prefix = rest[0];
+		let len = encoded_len(prefix);
+		let truncated = len > rest.len();
+
+		let mut temp_buf = [0u8; 17];
+		let copy_len = len.min(rest.len());
+		temp_buf[..copy_len].copy_from_slice(&rest[..copy_len]);
+		let (value, _) = decode_u128(&temp_buf);
+
+		let token = Token {
+			offset: self.offset,
+			prefix,
+			len,
+			value,
+			truncated,
+		};
+		self.offset = if truncated {
+			self.buf.len()
+		} else {
+			self.offset + len
+		};
+		Some(token)
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.buf.len() - self.offset;
+		(remaining.min(1), Some(remaining))
+	}
+}
+
+impl FusedIterator for Explain<'_> {}
@@ -0,0 +1,213 @@
+//! C-compatible FFI surface
+//!
+//! Every function returns an `isize`: a non-negative value is the number of
+//! bytes written or consumed, and a negative value is one of the `VLEN_ERR_*`
+//! codes. The codes are part of the stable ABI and will not be renumbered.
+//! The matching declarations are in `include/vlen.h`.
+//!
+//! The `vlen-ffi` package in `ffi/` builds this module as a static and a
+//! dynamic library for C and C++ callers:
+//!
+//! ```text
+//! cargo build --release -p vlen-ffi
+//! ```
+
+use core::slice;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// A required pointer argument was null.
+pub const VLEN_ERR_NULL_POINTER: isize = -1;
+
+/// The output buffer is too small for the encoded values.
+pub const VLEN_ERR_BUFFER_TOO_SMALL: isize = -2;
+
+/// The input ends partway through a value.
+pub const VLEN_ERR_TRUNCATED: isize = -3;
+
+/// The input is not a valid encoding.
+pub const VLEN_ERR_INVALID: isize = -4;
+
+/// Maps an encoding or decoding error to its FFI error code.
+fn error_code(err: &'static str) -> isize {
+	if err.starts_with("buffer too small") {
+		VLEN_ERR_BUFFER_TOO_SMALL
+	} else if err == "truncated vlen value" {
+		VLEN_ERR_TRUNCATED
+	} else {
+		VLEN_ERR_INVALID
+	}
+}
+
+/// Converts a result to the FFI return convention.
+fn to_code(result: Result<usize, &'static str>) -> isize {
+	match result {
+		Ok(len) => len as isize,
+		Err(err) => error_code(err),
+	}
+}
+
+/// Reborrows a pointer and length as a slice, allowing null when empty.
+///
+/// # Safety
+///
+/// A non-null `ptr` must be valid for reads of `len` elements.
+unsafe fn slice_from_raw<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+	match (ptr.is_null(), len) {
+		(true, 0) => Some(&[]),
+		(true, _) => None,
+		(false, _) => Some(slice::from_raw_parts(ptr, len)),
+	}
+}
+
+/// Mutably reborrows a pointer and length as a slice, allowing null when
+/// empty.
+///
+/// # Safety
+///
+/// A non-null `ptr` must be valid for reads and writes of `len` elements.
+unsafe fn slice_from_raw_mut<'a, T>(
+	ptr: *mut T,
+	len: usize,
+) -> Option<&'a mut [T]> {
+	match (ptr.is_null(), len) {
+		(true, 0) => Some(&mut []),
+		(true, _) => None,
+		(false, _) => Some(slice::from_raw_parts_mut(ptr, len)),
+	}
+}
+
+/// Generates the FFI functions for an unsigned integer type.
+macro_rules! ffi_functions {
+	($t:ty, $encode:ident, $decode:ident, $size:ident, $bulk_encode:ident, $bulk_decode:ident) => {
+		#[doc = concat!("Encodes a `", stringify!($t), "` into `buf`, returning the encoded length.")]
+		///
+		/// # Safety
+		///
+		/// `buf` must be valid for writes of `buf_len` bytes.
+		#[no_mangle]
+		pub unsafe extern "C" fn $encode(
+			buf: *mut u8,
+			buf_len: usize,
+			value: $t,
+		) -> isize {
+			let Some(buf) = slice_from_raw_mut(buf, buf_len) else {
+				return VLEN_ERR_NULL_POINTER;
+			};
+			to_code(<$t>::encode(buf, value))
+		}
+
+		#[doc = concat!("Decodes a `", stringify!($t), "` from `buf` into `*value`, returning the encoded length.")]
+		///
+		/// # Safety
+		///
+		/// `buf` must be valid for reads of `buf_len` bytes, and `value` must
+		/// be valid for writes.
+		#[no_mangle]
+		pub unsafe extern "C" fn $decode(
+			buf: *const u8,
+			buf_len: usize,
+			value: *mut $t,
+		) -> isize {
+			let Some(buf) = slice_from_raw(buf, buf_len) else {
+				return VLEN_ERR_NULL_POINTER;
+			};
+			if value.is_null() {
+				return VLEN_ERR_NULL_POINTER;
+			}
+			match <$t>::decode(buf) {
+				Ok((decoded, len)) => {
+					value.write(decoded);
+					len as isize
+				},
+				Err(err) => error_code(err),
+			}
+		}
+
+		#[doc = concat!("Returns the encoded size of a `", stringify!($t), "`.")]
+		#[no_mangle]
+		pub extern "C" fn $size(value: $t) -> isize {
+			to_code(<$t>::encoded_size(value))
+		}
+
+		#[doc = concat!("Encodes `count` `", stringify!($t), "` values into `buf`, returning the encoded length.")]
+		///
+		/// # Safety
+		///
+		/// `buf` must be valid for writes of `buf_len` bytes, and `values`
+		/// must be valid for reads of `count` values.
+		#[no_mangle]
+		pub unsafe extern "C" fn $bulk_encode(
+			buf: *mut u8,
+			buf_len: usize,
+			values: *const $t,
+			count: usize,
+		) -> isize {
+			let (Some(buf), Some(values)) =
+				(slice_from_raw_mut(buf, buf_len), slice_from_raw(values, count))
+			else {
+				return VLEN_ERR_NULL_POINTER;
+			};
+			let mut offset = 0;
+			for &value in values {
+				match <$t>::encode(&mut buf[offset..], value) {
+					Ok(len) => offset += len,
+					Err(err) => return error_code(err),
+				}
+			}
+			offset as isize
+		}
+
+		#[doc = concat!("Decodes up to `capacity` `", stringify!($t), "` values from `buf`, returning the number of bytes consumed.")]
+		///
+		/// Decoding stops when `values` is full or `buf` is exhausted. The
+		/// number of decoded values is stored in `*decoded` unless it is null.
+		///
+		/// # Safety
+		///
+		/// `buf` must be valid for reads of `buf_len` bytes, `values` must be
+		/// valid for writes of `capacity` values, and a non-null `decoded`
+		/// must be valid for writes.
+		#[no_mangle]
+		pub unsafe extern "C" fn $bulk_decode(
+			buf: *const u8,
+			buf_len: usize,
+			values: *mut $t,
+			capacity: usize,
+			decoded: *mut usize,
+		) -> isize {
+			let (Some(buf), Some(values)) =
+				(slice_from_raw(buf, buf_len), slice_from_raw_mut(values, capacity))
+			else {
+				return VLEN_ERR_NULL_POINTER;
+			};
+			match crate::decode::bulk_decode_counted(buf, values) {
+				Ok((count, offset)) => {
+					if !decoded.is_null() {
+						decoded.write(count);
+					}
+					offset as isize
+				},
+				Err(err) => error_code(err),
+			}
+		}
+	};
+}
+
+ffi_functions!(
+	u32,
+	vlen_encode_u32,
+	vlen_decode_u32,
+	vlen_encoded_size_u32,
+	vlen_bulk_encode_u32,
+	vlen_bulk_decode_u32
+);
+ffi_functions!(
+	u64,
+	vlen_encode_u64,
+	vlen_decode_u64,
+	vlen_encoded_size_u64,
+	vlen_bulk_encode_u64,
+	vlen_bulk_decode_u64
+);
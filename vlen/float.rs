@@ -0,0 +1,403 @@
+//! Alternative encodings for floating-point values
+//!
+//! The default `f64` encoding byte-swaps the bits so that short mantissas
+//! encode compactly, but a value that is exactly representable as an `f32`
+//! still spends its first bytes on the wider exponent. The adaptive encoding
+//! rearranges the bits of an `f64` so that such a value is written exactly
+//! as [`encode_f32`](crate::encode_f32) would write it, taking at most five
+//! bytes, while any other value takes at most nine. No tag byte is needed:
+//! the rearrangement is a bijection, and the vlen length prefix already
+//! tells the two cases apart.
+//!
+//! The compact encodings give one-byte forms to the values that dominate
+//! telemetry: `1.0`, `-1.0`, NaN and both infinities, alongside `0.0`, which
+//! is a single byte already. Each special value trades places with one of
+//! five tiny values (below `1e-35` for `f32` and `1e-283` for `f64`) whose
+//! default encoding is a single byte, so every other value encodes as before
+//! and no bits are lost. Only the canonical NaN, `f32::NAN` or `f64::NAN`,
+//! takes the short form; other NaN payloads round-trip unchanged at their
+//! usual length.
+//!
+//! [`AdaptiveF64`], [`CompactF32`] and [`CompactF64`] implement [`Encode`]
+//! and [`Decode`] with these encodings, for use with the bulk and streaming
+//! functions.
+//!
+//! [`quantize_encode_f32`] is a lossy bulk codec for time series: each value
+//! is multiplied by a scale factor and rounded to an integer, and the
+//! differences between consecutive integers are written as zigzag `i64`
+//! values. A slowly changing series at a precision of, say, two decimal
+//! places (`scale` 100) then takes a byte or two per value, and every
+//! decoded value is within `0.5 / scale` of the original.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::float::{decode_f64_adaptive, encode_f64_adaptive};
+//!
+//! let value = f64::from(0.1f32);
+//! let mut buf = [0u8; 9];
+//! let len = encode_f64_adaptive(&mut buf, value);
+//! assert_eq!(len, vlen::encoded_size(0.1f32).unwrap());
+//! assert!(len < vlen::encoded_size(value).unwrap());
+//! assert_eq!(decode_f64_adaptive(&buf), (value, len));
+//!
+//! let len = vlen::float::encode_f64_compact(&mut buf, f64::INFINITY);
+//! assert_eq!(len, 1);
+//! ```
+
+use crate::decode::{decode_u32, decode_u64, Decode};
+use crate::encode::{
+	encode_u32, encode_u64, encoded_size_u32, encoded_size_u64, Encode,
+};
+
+/// Bits of an `f64` mantissa that an `f32` does not have.
+const EXTRA_MANTISSA_BITS: u32 = 29;
+const EXTRA_MANTISSA_MASK: u64 = (1 << EXTRA_MANTISSA_BITS) - 1;
+
+/// Maps an `f64` exponent so that every `f32` exponent lands below 256.
+///
+/// Zero and subnormal (0) and infinite and NaN (2047) keep their meaning,
+/// the exponents of normal `f32` values take 1 to 254, and the rest follow.
+#[inline]
+const fn narrow_exponent(exponent: u64) -> u64 {
+	match exponent {
+		0 => 0,
+		2047 => 255,
+		897..=1150 => exponent - 896,
+		1..=896 => exponent + 255,
+		_ => exponent + 1,
+	}
+}
+
+/// Reverses [`narrow_exponent`].
+#[inline]
+const fn widen_exponent(exponent: u64) -> u64 {
+	match exponent {
+		0 => 0,
+		255 => 2047,
+		1..=254 => exponent + 896,
+		256..=1151 => exponent - 255,
+		_ => exponent - 1,
+	}
+}
+
+/// Rearranges the bits of an `f64` into the adaptive payload.
+#[inline]
+const fn to_adaptive(value: f64) -> u64 {
+	let bits = value.to_bits();
+	let exponent = narrow_exponent((bits >> 52) & 0x7FF);
+	let mantissa = bits & ((1 << 52) - 1);
+	let narrow = ((bits >> 63) << 31) as u32
+		| ((exponent & 0xFF) << 23) as u32
+		| (mantissa >> EXTRA_MANTISSA_BITS) as u32;
+	let rest = ((exponent >> 8) << EXTRA_MANTISSA_BITS) as u32
+		| (mantissa & EXTRA_MANTISSA_MASK) as u32;
+	narrow.swap_bytes() as u64 | (rest.swap_bytes() as u64) << 32
+}
+
+/// Reverses [`to_adaptive`].
+#[inline]
+const fn from_adaptive(payload: u64) -> f64 {
+	let narrow = (payload as u32).swap_bytes() as u64;
+	let rest = ((payload >> 32) as u32).swap_bytes() as u64;
+	let exponent = widen_exponent(
+		((narrow >> 23) & 0xFF) | (rest >> EXTRA_MANTISSA_BITS) << 8,
+	);
+	let mantissa = (narrow & 0x7F_FFFF) << EXTRA_MANTISSA_BITS
+		| rest & EXTRA_MANTISSA_MASK;
+	f64::from_bits((narrow >> 31) << 63 | exponent << 52 | mantissa)
+}
+
+/// Encodes an `f64` with the adaptive encoding, returning the encoded
+/// length.
+#[inline]
+#[must_use]
+pub fn encode_f64_adaptive(buf: &mut [u8; 9], value: f64) -> usize {
+	encode_u64(buf, to_adaptive(value))
+}
+
+/// Decodes an `f64` written by [`encode_f64_adaptive`], returning the value
+/// and encoded length.
+#[inline]
+#[must_use]
+pub fn decode_f64_adaptive(buf: &[u8; 9]) -> (f64, usize) {
+	let (payload, len) = decode_u64(buf);
+	(from_adaptive(payload), len)
+}
+
+/// Returns the length [`encode_f64_adaptive`] writes for `value`.
+#[inline]
+#[must_use]
+pub const fn encoded_size_f64_adaptive(value: f64) -> usize {
+	encoded_size_u64(to_adaptive(value))
+}
+
+/// An `f64` that encodes with [`encode_f64_adaptive`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[repr(transparent)]
+pub struct AdaptiveF64(pub f64);
+
+impl From<f64> for AdaptiveF64 {
+	#[inline]
+	fn from(value: f64) -> Self {
+		Self(value)
+	}
+}
+
+impl From<AdaptiveF64> for f64 {
+	#[inline]
+	fn from(value: AdaptiveF64) -> Self {
+		value.0
+	}
+}
+
+impl Encode for AdaptiveF64 {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		u64::encode(buf, to_adaptive(value.0))
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(encoded_size_f64_adaptive(value.0))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u64 as Encode>::MAX_ENCODED_SIZE;
+}
+
+impl Decode for AdaptiveF64 {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (payload, len) = u64::decode(buf)?;
+		Ok((Self(from_adaptive(payload)), len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (payload, len) = u64::decode_checked(buf)?;
+		Ok((Self(from_adaptive(payload)), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u64 as Decode>::MAX_ENCODED_SIZE;
+}
+
+macro_rules! impl_compact {
+	(
+		$wrapper:ident, $ft:ident, $ut:ident, $buf_size:expr,
+		$encode:ident, $decode:ident, $size:ident, $swap:ident,
+		$encode_fn:ident, $decode_fn:ident, $size_fn:ident
+	) => {
+		/// Exchanges the default payloads of the special values with the
+		/// one-byte codes 1 to 5.
+		#[inline]
+		const fn $swap(payload: $ut) -> $ut {
+			let specials = [
+				(1.0 as $ft).to_bits().swap_bytes(),
+				(-1.0 as $ft).to_bits().swap_bytes(),
+				$ft::NAN.to_bits().swap_bytes(),
+				$ft::INFINITY.to_bits().swap_bytes(),
+				$ft::NEG_INFINITY.to_bits().swap_bytes(),
+			];
+			let mut i = 0;
+			while i < specials.len() {
+				let code = i as $ut + 1;
+				if payload == code {
+					return specials[i];
+				}
+				if payload == specials[i] {
+					return code;
+				}
+				i += 1;
+			}
+			payload
+		}
+
+		#[doc = concat!("Encodes an `", stringify!($ft), "` with the compact encoding, returning the")]
+		/// encoded length.
+		#[inline]
+		#[must_use]
+		pub fn $encode(buf: &mut [u8; $buf_size], value: $ft) -> usize {
+			$encode_fn(buf, $swap(value.to_bits().swap_bytes()))
+		}
+
+		#[doc = concat!("Decodes an `", stringify!($ft), "` written by [`", stringify!($encode), "`], returning")]
+		/// the value and encoded length.
+		#[inline]
+		#[must_use]
+		pub fn $decode(buf: &[u8; $buf_size]) -> ($ft, usize) {
+			let (payload, len) = $decode_fn(buf);
+			($ft::from_bits($swap(payload).swap_bytes()), len)
+		}
+
+		#[doc = concat!("Returns the length [`", stringify!($encode), "`] writes for `value`.")]
+		#[inline]
+		#[must_use]
+		pub const fn $size(value: $ft) -> usize {
+			$size_fn($swap(value.to_bits().swap_bytes()))
+		}
+
+		#[doc = concat!("An `", stringify!($ft), "` that encodes with [`", stringify!($encode), "`].")]
+		#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+		#[repr(transparent)]
+		pub struct $wrapper(pub $ft);
+
+		impl From<$ft> for $wrapper {
+			#[inline]
+			fn from(value: $ft) -> Self {
+				Self(value)
+			}
+		}
+
+		impl From<$wrapper> for $ft {
+			#[inline]
+			fn from(value: $wrapper) -> Self {
+				value.0
+			}
+		}
+
+		impl Encode for $wrapper {
+			#[inline]
+			fn encode(
+				buf: &mut [u8],
+				value: Self,
+			) -> Result<usize, &'static str> {
+				$ut::encode(buf, $swap(value.0.to_bits().swap_bytes()))
+			}
+
+			#[inline]
+			fn encoded_size(value: Self) -> Result<usize, &'static str> {
+				Ok($size(value.0))
+			}
+
+			const MAX_ENCODED_SIZE: usize = $buf_size;
+		}
+
+		impl Decode for $wrapper {
+			#[inline]
+			fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+				let (payload, len) = $ut::decode(buf)?;
+				Ok((Self($ft::from_bits($swap(payload).swap_bytes())), len))
+			}
+
+			#[inline]
+			fn decode_checked(
+				buf: &[u8],
+			) -> Result<(Self, usize), &'static str> {
+				let (payload, len) = $ut::decode_checked(buf)?;
+				Ok((Self($ft::from_bits($swap(payload).swap_bytes())), len))
+			}
+
+			#[inline]
+			fn check_prefix(first: u8) -> Result<(), &'static str> {
+				$ut::check_prefix(first)
+			}
+
+			const MAX_ENCODED_SIZE: usize = $buf_size;
+		}
+	};
+}
+
+impl_compact!(
+	CompactF32,
+	f32,
+	u32,
+	5,
+	encode_f32_compact,
+	decode_f32_compact,
+	encoded_size_f32_compact,
+	swap_special_f32,
+	encode_u32,
+	decode_u32,
+	encoded_size_u32
+);
+impl_compact!(
+	CompactF64,
+	f64,
+	u64,
+	9,
+	encode_f64_compact,
+	decode_f64_compact,
+	encoded_size_f64_compact,
+	swap_special_f64,
+	encode_u64,
+	decode_u64,
+	encoded_size_u64
+);
+
+/// Largest magnitude of a quantized value, beyond which `f64` no longer
+/// represents every integer.
+const MAX_QUANTIZED: f64 = 9_007_199_254_740_992.0;
+
+/// Returns `scale` as an `f64` if it is positive and finite.
+fn check_scale(scale: f32) -> Result<f64, &'static str> {
+	if scale > 0.0 && scale.is_finite() {
+		Ok(f64::from(scale))
+	} else {
+		Err("quantization scale must be positive and finite")
+	}
+}
+
+/// Scales `value` and rounds it to the nearest integer, halves away from
+/// zero.
+fn quantize(value: f32, scale: f64) -> Result<i64, &'static str> {
+	let scaled = f64::from(value) * scale;
+	if scaled.is_nan() || scaled.abs() > MAX_QUANTIZED {
+		return Err("value out of range for quantization");
+	}
+	let truncated = scaled as i64;
+	let fraction = scaled - truncated as f64;
+	Ok(if fraction >= 0.5 {
+		truncated + 1
+	} else if fraction <= -0.5 {
+		truncated - 1
+	} else {
+		truncated
+	})
+}
+
+/// Quantizes and delta-encodes `values`, returning the number of bytes
+/// written.
+///
+/// Returns an error if `scale` is not positive and finite, or if a scaled
+/// value is not finite or exceeds 2^53 in magnitude.
+pub fn quantize_encode_f32(
+	buf: &mut [u8],
+	values: &[f32],
+	scale: f32,
+) -> Result<usize, &'static str> {
+	let scale = check_scale(scale)?;
+	let mut offset = 0;
+	let mut previous = 0i64;
+	for &value in values {
+		let quantized = quantize(value, scale)?;
+		if offset >= buf.len() {
+			return Err("buffer too small for bulk encoding");
+		}
+		offset += i64::encode(&mut buf[offset..], quantized - previous)?;
+		previous = quantized;
+	}
+	Ok(offset)
+}
+
+/// Decodes values written by [`quantize_encode_f32`] with the same `scale`,
+/// filling every slot of `values` and returning the number of bytes
+/// consumed.
+pub fn quantize_decode_f32(
+	buf: &[u8],
+	values: &mut [f32],
+	scale: f32,
+) -> Result<usize, &'static str> {
+	let scale = check_scale(scale)?;
+	let mut offset = 0;
+	let mut previous = 0i64;
+	for slot in values {
+		if offset >= buf.len() {
+			return Err("buffer holds fewer values than expected");
+		}
+		let (delta, len) = i64::decode(&buf[offset..])?;
+		previous = previous.wrapping_add(delta);
+		*slot = (previous as f64 / scale) as f32;
+		offset += len;
+	}
+	Ok(offset)
+}
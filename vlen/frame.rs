@@ -0,0 +1,373 @@
+//! Length-prefixed message framing
+//!
+//! A frame is a message preceded by its length in bytes as a vlen `u64`.
+//! [`encode_frame`] and [`decode_frame`] work on whole buffers. With the
+//! `alloc` feature, [`FrameReader`] collects bytes as they arrive, however
+//! the transport splits them, and yields each message once it is complete,
+//! while [`FrameWriter`] queues outgoing frames in a ring buffer and tracks
+//! how much of them a partial write has sent.
+//!
+//! Both sides are independent of any I/O API: feed the reader whatever a
+//! read returned and hand the writer's pending bytes to a write. With the
+//! `std` feature, [`FrameReader::read_from`] and [`FrameWriter::write_to`]
+//! do this for `std::io` streams, the latter with a single vectored write of
+//! every queued frame.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::frame::{decode_frame, encode_frame};
+//!
+//! let mut buf = [0u8; 16];
+//! let len = encode_frame(&mut buf, b"hello").unwrap();
+//! assert_eq!(&buf[..len], b"\x05hello");
+//!
+//! assert_eq!(decode_frame(&buf[..3]), Ok(None));
+//! assert_eq!(decode_frame(&buf[..len]), Ok(Some((&b"hello"[..], len))));
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::decode::Decode;
+use crate::encode::{encode_u64, encoded_len_u64, encoded_size_u64};
+use crate::sink::Sink;
+
+/// Returns the encoded size of a frame holding `payload_len` bytes.
+#[inline]
+#[must_use]
+pub fn frame_len(payload_len: usize) -> usize {
+	encoded_size_u64(payload_len as u64) + payload_len
+}
+
+/// Writes `payload` as a frame, returning the encoded length.
+pub fn encode_frame(
+	buf: &mut [u8],
+	payload: &[u8],
+) -> Result<usize, &'static str> {
+	let mut prefix_buf = [0u8; 9];
+	let prefix_len = encode_u64(&mut prefix_buf, payload.len() as u64);
+	let len = prefix_len + payload.len();
+	let dst = buf
+		.get_mut(..len)
+		.ok_or("buffer too small for frame encoding")?;
+	dst[..prefix_len].copy_from_slice(&prefix_buf[..prefix_len]);
+	dst[prefix_len..].copy_from_slice(payload);
+	Ok(len)
+}
+
+/// Writes `payload` as a frame into `sink`, returning the encoded length.
+pub fn encode_frame_into<S>(
+	sink: &mut S,
+	payload: &[u8],
+) -> Result<usize, &'static str>
+where
+	S: Sink + ?Sized,
+{
+	let prefix_len = sink.write_value(payload.len() as u64)?;
+	sink.write_bytes(payload)?;
+	Ok(prefix_len + payload.len())
+}
+
+/// Reads the frame at the start of `buf`.
+///
+/// Returns the payload and the encoded length of the frame, or `None` if
+/// `buf` ends before the frame does. Fails if the length prefix is invalid.
+pub fn decode_frame(
+	buf: &[u8],
+) -> Result<Option<(&[u8], usize)>, &'static str> {
+	decode_frame_limited(buf, usize::MAX)
+}
+
+/// Like [`decode_frame`], but rejects payloads longer than `max_len` as
+/// soon as the length prefix is complete.
+fn decode_frame_limited(
+	buf: &[u8],
+	max_len: usize,
+) -> Result<Option<(&[u8], usize)>, &'static str> {
+	let Some(&first) = buf.first() else {
+		return Ok(None);
+	};
+	let Some(prefix) = buf.get(..encoded_len_u64(first)?) else {
+		return Ok(None);
+	};
+	let (payload_len, prefix_len) = u64::decode_checked(prefix)?;
+	let payload_len =
+		usize::try_from(payload_len).map_err(|_| "vlen value out of range")?;
+	if payload_len > max_len {
+		return Err("frame length exceeds limit");
+	}
+	Ok(buf[prefix_len..]
+		.get(..payload_len)
+		.map(|payload| (payload, prefix_len + payload_len)))
+}
+
+/// Reassembles frames from bytes that arrive in pieces.
+///
+/// Bytes are appended with [`extend`](Self::extend) and complete frames are
+/// taken with [`next_frame`](Self::next_frame). A frame split across any
+/// number of reads, including inside its length prefix, is returned once
+/// its last byte arrives.
+///
+/// ```rust
+/// use vlen::frame::{FrameReader, FrameWriter};
+///
+/// let mut writer = FrameWriter::new();
+/// writer.write_frame(b"hello").unwrap();
+/// writer.write_frame(&[0u8; 300]).unwrap();
+/// let wire = writer.pending().to_vec();
+/// writer.consume(wire.len());
+///
+/// // The bytes arrive in arbitrary pieces.
+/// let mut reader = FrameReader::new();
+/// reader.extend(&wire[..4]);
+/// assert_eq!(reader.next_frame(), Ok(None));
+/// reader.extend(&wire[4..100]);
+/// assert_eq!(reader.next_frame(), Ok(Some(&b"hello"[..])));
+/// assert_eq!(reader.next_frame(), Ok(None));
+/// reader.extend(&wire[100..]);
+/// assert_eq!(reader.next_frame().unwrap().map(<[u8]>::len), Some(300));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct FrameReader {
+	buf: Vec<u8>,
+	start: usize,
+	max_frame_len: Option<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl FrameReader {
+	/// Creates a reader with no limit on the frame length.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			buf: Vec::new(),
+			start: 0,
+			max_frame_len: None,
+		}
+	}
+
+	/// Rejects frames whose payload is longer than `max` bytes.
+	///
+	/// The check happens as soon as the length prefix arrives, before the
+	/// payload is buffered.
+	#[inline]
+	#[must_use]
+	pub const fn max_frame_len(mut self, max: usize) -> Self {
+		self.max_frame_len = Some(max);
+		self
+	}
+
+	/// Appends bytes received from the transport.
+	pub fn extend(&mut self, bytes: &[u8]) {
+		self.compact();
+		self.buf.extend_from_slice(bytes);
+	}
+
+	/// Returns the next complete frame's payload, or `None` if more bytes
+	/// are needed.
+	///
+	/// After an error the stream is out of sync, so the reader should be
+	/// discarded.
+	pub fn next_frame(&mut self) -> Result<Option<&[u8]>, &'static str> {
+		let pending = &self.buf[self.start..];
+		let max_len = self.max_frame_len.unwrap_or(usize::MAX);
+		let Some((payload, len)) = decode_frame_limited(pending, max_len)?
+		else {
+			return Ok(None);
+		};
+		let payload_len = payload.len();
+		self.start += len;
+		Ok(Some(&self.buf[self.start - payload_len..self.start]))
+	}
+
+	/// Returns the next complete frame's payload, decompressed, or `None` if
+	/// more bytes are needed.
+	///
+	/// The frame must have been written by
+	/// [`FrameWriter::write_compressed_frame`]. The maximum frame length, if
+	/// set, also limits the decompressed length.
+	#[cfg_attr(docsrs, doc(cfg(any(feature = "zstd", feature = "lz4_flex"))))]
+	#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+	pub fn next_decompressed_frame(
+		&mut self,
+	) -> Result<Option<alloc::borrow::Cow<'_, [u8]>>, &'static str> {
+		let max_len = self.max_frame_len.unwrap_or(usize::MAX);
+		match self.next_frame()? {
+			Some(payload) => {
+				crate::compress::decompress_limited(payload, max_len).map(Some)
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Returns the number of bytes received but not yet returned in a frame.
+	#[inline]
+	#[must_use]
+	pub fn buffered(&self) -> usize {
+		self.buf.len() - self.start
+	}
+
+	/// Drops the bytes of frames already returned.
+	fn compact(&mut self) {
+		if self.start > 0 {
+			self.buf.drain(..self.start);
+			self.start = 0;
+		}
+	}
+
+	/// Performs one read from `reader` and appends what it returned.
+	///
+	/// Returns the number of bytes read, which is zero at the end of the
+	/// stream.
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	#[cfg(feature = "std")]
+	pub fn read_from<R>(&mut self, reader: &mut R) -> std::io::Result<usize>
+	where
+		R: std::io::Read + ?Sized,
+	{
+		const CHUNK_LEN: usize = 8192;
+		self.compact();
+		let filled = self.buf.len();
+		self.buf.resize(filled + CHUNK_LEN, 0);
+		let result = reader.read(&mut self.buf[filled..]);
+		let read = *result.as_ref().unwrap_or(&0);
+		self.buf.truncate(filled + read);
+		result
+	}
+}
+
+/// Queues outgoing frames until the transport accepts them.
+///
+/// Frames are appended with [`write_frame`](Self::write_frame) to a ring
+/// buffer, so queuing and sending can interleave without moving the unsent
+/// bytes. The bytes still to be sent are in at most two slices, returned by
+/// [`pending_slices`](Self::pending_slices) for vectored writes, and after a
+/// write, [`consume`](Self::consume) drops however many bytes the transport
+/// took.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct FrameWriter {
+	buf: VecDeque<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl FrameWriter {
+	/// Creates an empty writer.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			buf: VecDeque::new(),
+		}
+	}
+
+	/// Creates an empty writer with room for `capacity` pending bytes.
+	#[inline]
+	#[must_use]
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			buf: VecDeque::with_capacity(capacity),
+		}
+	}
+
+	/// Queues `payload` as a frame, returning the encoded length.
+	pub fn write_frame(
+		&mut self,
+		payload: &[u8],
+	) -> Result<usize, &'static str> {
+		let mut prefix_buf = [0u8; 9];
+		let prefix_len = encode_u64(&mut prefix_buf, payload.len() as u64);
+		self.buf.extend(&prefix_buf[..prefix_len]);
+		self.buf.extend(payload);
+		Ok(prefix_len + payload.len())
+	}
+
+	/// Queues `payload` compressed with `compression` as a frame, returning
+	/// the encoded length.
+	///
+	/// Read it back with [`FrameReader::next_decompressed_frame`].
+	#[cfg_attr(docsrs, doc(cfg(any(feature = "zstd", feature = "lz4_flex"))))]
+	#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+	pub fn write_compressed_frame(
+		&mut self,
+		payload: &[u8],
+		compression: crate::compress::Compression,
+	) -> Result<usize, &'static str> {
+		let compressed = crate::compress::compress(payload, compression)?;
+		self.write_frame(&compressed)
+	}
+
+	/// Returns the bytes not yet sent as one slice.
+	///
+	/// This moves the pending bytes if the ring buffer has wrapped around;
+	/// [`pending_slices`](Self::pending_slices) never does.
+	#[inline]
+	#[must_use]
+	pub fn pending(&mut self) -> &[u8] {
+		self.buf.make_contiguous()
+	}
+
+	/// Returns the bytes not yet sent as two slices, in order.
+	#[inline]
+	#[must_use]
+	pub fn pending_slices(&self) -> (&[u8], &[u8]) {
+		self.buf.as_slices()
+	}
+
+	/// Returns the number of bytes not yet sent.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.buf.len()
+	}
+
+	/// Marks the first `len` pending bytes as sent.
+	///
+	/// # Panics
+	///
+	/// Panics if `len` is larger than the number of pending bytes.
+	#[inline]
+	pub fn consume(&mut self, len: usize) {
+		assert!(len <= self.buf.len(), "consumed more than pending");
+		self.buf.drain(..len);
+	}
+
+	/// Returns `true` if every queued frame has been sent.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.buf.is_empty()
+	}
+
+	/// Returns the pending bytes as [`IoSlice`](std::io::IoSlice)s for a
+	/// vectored write.
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	#[cfg(feature = "std")]
+	#[inline]
+	#[must_use]
+	pub fn io_slices(&self) -> [std::io::IoSlice<'_>; 2] {
+		let (front, back) = self.buf.as_slices();
+		[std::io::IoSlice::new(front), std::io::IoSlice::new(back)]
+	}
+
+	/// Performs one vectored write of the pending bytes to `writer`.
+	///
+	/// Returns the number of bytes written, which have been consumed.
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	#[cfg(feature = "std")]
+	pub fn write_to<W>(&mut self, writer: &mut W) -> std::io::Result<usize>
+	where
+		W: std::io::Write + ?Sized,
+	{
+		let written = writer.write_vectored(&self.io_slices())?;
+		self.consume(written);
+		Ok(written)
+	}
+}
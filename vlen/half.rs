@@ -0,0 +1,83 @@
+//! 16-bit float encoding for vlen
+//!
+//! `f16` and `bf16` values are encoded like `f32` and `f64`: the bits are
+//! byte-swapped so that the sign and exponent land in the low byte, then
+//! written through the `u16` path. Common values with a short mantissa,
+//! such as small integers and halves, therefore take one or two bytes.
+//!
+//! [`Encode`] and [`Decode`] are implemented for `half::f16` and
+//! `half::bf16`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use half::f16;
+//!
+//! let mut buf = [0u8; 3];
+//! let len = vlen::half::encode_f16(&mut buf, f16::from_f32(1.5));
+//! assert_eq!(vlen::half::decode_f16(&buf), (f16::from_f32(1.5), len));
+//! ```
+
+use ::half::{bf16, f16};
+
+use crate::decode::{decode_u16, Decode};
+use crate::encode::{encode_u16, encoded_size_u16, Encode};
+
+macro_rules! impl_half {
+	($t:ident, $encode_fn:ident, $decode_fn:ident) => {
+		#[doc = concat!("Encodes an `", stringify!($t), "` into a buffer, returning the encoded length.")]
+		#[inline]
+		#[must_use]
+		pub fn $encode_fn(buf: &mut [u8; 3], value: $t) -> usize {
+			encode_u16(buf, value.to_bits().swap_bytes())
+		}
+
+		#[doc = concat!("Decodes an `", stringify!($t), "` from a buffer, returning the value and encoded length.")]
+		#[inline]
+		#[must_use]
+		pub fn $decode_fn(buf: &[u8; 3]) -> ($t, usize) {
+			let (swapped, len) = decode_u16(buf);
+			($t::from_bits(swapped.swap_bytes()), len)
+		}
+
+		#[cfg_attr(docsrs, doc(cfg(feature = "half")))]
+		impl Encode for $t {
+			#[inline]
+			fn encode(
+				buf: &mut [u8],
+				value: Self,
+			) -> Result<usize, &'static str> {
+				u16::encode(buf, value.to_bits().swap_bytes())
+			}
+
+			#[inline]
+			fn encoded_size(value: Self) -> Result<usize, &'static str> {
+				Ok(encoded_size_u16(value.to_bits().swap_bytes()))
+			}
+
+			const MAX_ENCODED_SIZE: usize = <u16 as Encode>::MAX_ENCODED_SIZE;
+		}
+
+		#[cfg_attr(docsrs, doc(cfg(feature = "half")))]
+		impl Decode for $t {
+			#[inline]
+			fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+				let (swapped, len) = u16::decode(buf)?;
+				Ok(($t::from_bits(swapped.swap_bytes()), len))
+			}
+
+			#[inline]
+			fn decode_checked(
+				buf: &[u8],
+			) -> Result<(Self, usize), &'static str> {
+				let (swapped, len) = u16::decode_checked(buf)?;
+				Ok(($t::from_bits(swapped.swap_bytes()), len))
+			}
+
+			const MAX_ENCODED_SIZE: usize = <u16 as Decode>::MAX_ENCODED_SIZE;
+		}
+	};
+}
+
+impl_half!(f16, encode_f16, decode_f16);
+impl_half!(bf16, encode_bf16, decode_bf16);
@@ -0,0 +1,153 @@
+//! Wire-format version header for stored data
+//!
+//! Data kept for a long time may be read by a later version of this crate,
+//! or written by one. [`write_header`] starts a stream with the magic bytes
+//! [`MAGIC`], the [`format_version`] and a set of flags describing how the
+//! stream was encoded, and [`read_header`] checks them before anything else
+//! is decoded, so a reader fails cleanly on data it does not understand
+//! instead of misreading it.
+//!
+//! The version and flags are stored as vlen `u32` and `u64` after the four
+//! magic bytes. The version only increases when the encoding of existing
+//! types changes; readers accept every version up to their own. Flags the
+//! reader does not know are rejected, since they may change how the rest of
+//! the stream is decoded. Two peers agree on a version with
+//! [`negotiate_version`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::header::{read_header, write_header, Header, MAX_HEADER_LEN};
+//!
+//! let mut buf = [0u8; MAX_HEADER_LEN];
+//! let len = write_header(&mut buf, Header::FLAG_SIGN_BIT).unwrap();
+//! assert_eq!(&buf[..4], b"VLEN");
+//!
+//! let (header, read_len) = read_header(&buf[..len]).unwrap();
+//! assert_eq!(header.version, vlen::format_version());
+//! assert!(header.has_flag(Header::FLAG_SIGN_BIT));
+//! assert_eq!(read_len, len);
+//! ```
+
+use crate::decode::Decode;
+use crate::encode::{encode_u32, encode_u64};
+
+/// Bytes every header starts with.
+pub const MAGIC: [u8; 4] = *b"VLEN";
+
+/// The wire-format version written by this crate.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The oldest wire-format version this crate reads.
+pub const MIN_FORMAT_VERSION: u32 = 1;
+
+/// The largest encoded size of a header.
+pub const MAX_HEADER_LEN: usize = MAGIC.len() + 5 + 9;
+
+/// Returns the wire-format version written by this crate.
+#[inline]
+#[must_use]
+pub const fn format_version() -> u32 {
+	FORMAT_VERSION
+}
+
+/// A decoded stream header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Header {
+	/// The wire-format version the stream was written with.
+	pub version: u32,
+	/// How the stream was encoded, as a set of `FLAG_` bits.
+	pub flags: u64,
+}
+
+impl Header {
+	/// Signed integers use the sign-bit representation of
+	/// [`SignMode::SignBit`](crate::sign::SignMode::SignBit) instead of
+	/// zigzag.
+	pub const FLAG_SIGN_BIT: u64 = 1 << 0;
+	/// Values are delta coded against the previous value.
+	pub const FLAG_DELTA: u64 = 1 << 1;
+	/// The payload is wrapped by `compress::compress` and starts with a
+	/// compression tag.
+	pub const FLAG_COMPRESSED: u64 = 1 << 2;
+	/// Every flag this crate understands.
+	pub const KNOWN_FLAGS: u64 =
+		Self::FLAG_SIGN_BIT | Self::FLAG_DELTA | Self::FLAG_COMPRESSED;
+
+	/// Returns `true` if every bit of `flag` is set.
+	#[inline]
+	#[must_use]
+	pub const fn has_flag(&self, flag: u64) -> bool {
+		self.flags & flag == flag
+	}
+}
+
+/// Writes a header with the current format version and `flags`, returning
+/// the encoded length.
+///
+/// A buffer of [`MAX_HEADER_LEN`] bytes is always large enough. Fails if
+/// `flags` holds bits outside [`Header::KNOWN_FLAGS`].
+pub fn write_header(buf: &mut [u8], flags: u64) -> Result<usize, &'static str> {
+	if flags & !Header::KNOWN_FLAGS != 0 {
+		return Err("unknown header flags");
+	}
+	let mut version_buf = [0u8; 5];
+	let version_len = encode_u32(&mut version_buf, FORMAT_VERSION);
+	let mut flags_buf = [0u8; 9];
+	let flags_len = encode_u64(&mut flags_buf, flags);
+	let len = MAGIC.len() + version_len + flags_len;
+	let dst = buf.get_mut(..len).ok_or("buffer too small for header")?;
+	let (magic, rest) = dst.split_at_mut(MAGIC.len());
+	let (version, rest) = rest.split_at_mut(version_len);
+	magic.copy_from_slice(&MAGIC);
+	version.copy_from_slice(&version_buf[..version_len]);
+	rest.copy_from_slice(&flags_buf[..flags_len]);
+	Ok(len)
+}
+
+/// Reads the header at the start of `buf`, returning it and the number of
+/// bytes consumed.
+///
+/// Fails if the magic bytes are missing, the version is outside
+/// [`MIN_FORMAT_VERSION`]`..=`[`FORMAT_VERSION`], or a flag is unknown.
+pub fn read_header(buf: &[u8]) -> Result<(Header, usize), &'static str> {
+	match buf.get(..MAGIC.len()) {
+		Some(magic) if magic == MAGIC => {},
+		Some(_) => return Err("invalid header magic"),
+		None => return Err("truncated vlen value"),
+	}
+	let mut offset = MAGIC.len();
+	let (version, len) = u32::decode(&buf[offset..])?;
+	offset += len;
+	if !(MIN_FORMAT_VERSION..=FORMAT_VERSION).contains(&version) {
+		return Err("unsupported format version");
+	}
+	let (flags, len) = u64::decode(&buf[offset..])?;
+	offset += len;
+	if flags & !Header::KNOWN_FLAGS != 0 {
+		return Err("unknown header flags");
+	}
+	Ok((Header { version, flags }, offset))
+}
+
+/// Returns the newest format version both this crate and a peer that reads
+/// versions up to `peer_version` understand.
+///
+/// Fails if the peer is older than [`MIN_FORMAT_VERSION`].
+///
+/// ```rust
+/// use vlen::header::{negotiate_version, FORMAT_VERSION};
+///
+/// assert_eq!(negotiate_version(FORMAT_VERSION + 3), Ok(FORMAT_VERSION));
+/// assert_eq!(negotiate_version(0), Err("unsupported format version"));
+/// ```
+pub const fn negotiate_version(peer_version: u32) -> Result<u32, &'static str> {
+	if peer_version < MIN_FORMAT_VERSION {
+		return Err("unsupported format version");
+	}
+	if peer_version < FORMAT_VERSION {
+		Ok(peer_version)
+	} else {
+		Ok(FORMAT_VERSION)
+	}
+}
@@ -0,0 +1,99 @@
+//! Fixed-capacity collection support via `heapless`
+//!
+//! These mirror the `alloc` convenience functions (`encode_to_vec`,
+//! `bulk_decode_values`, ...) using [`heapless::Vec`](::heapless::Vec), so
+//! `no_std` targets without an allocator get the same ergonomics. Every
+//! function returns an error instead of growing past the vector's capacity.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::heapless::{bulk_decode_values_heapless, encode_append_heapless};
+//!
+//! let mut buf = heapless::Vec::<u8, 16>::new();
+//! encode_append_heapless(&mut buf, 300u32).unwrap();
+//! encode_append_heapless(&mut buf, 7u32).unwrap();
+//!
+//! let values = bulk_decode_values_heapless::<u32, 4>(&buf).unwrap();
+//! assert_eq!(values, [300, 7]);
+//! ```
+
+use ::heapless::Vec;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+const CAPACITY_EXCEEDED: &str = "heapless vector capacity exceeded";
+
+/// Encodes a value into a new fixed-capacity vector.
+#[inline]
+pub fn encode_to_heapless<T, const N: usize>(
+	value: T,
+) -> Result<Vec<u8, N>, &'static str>
+where
+	T: Encode,
+{
+	let mut vec = Vec::new();
+	encode_append_heapless(&mut vec, value)?;
+	Ok(vec)
+}
+
+/// Encodes a value onto the end of a fixed-capacity vector, returning the
+/// number of bytes appended.
+///
+/// The vector is left unchanged if the encoding does not fit.
+pub fn encode_append_heapless<T, const N: usize>(
+	vec: &mut Vec<u8, N>,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: Encode,
+{
+	let mut temp_buf = [0u8; 32];
+	if T::MAX_ENCODED_SIZE <= temp_buf.len() {
+		let len = T::encode(&mut temp_buf, value)?;
+		vec.extend_from_slice(&temp_buf[..len])
+			.map_err(|()| CAPACITY_EXCEEDED)?;
+		return Ok(len);
+	}
+	// Types with larger encodings are written in place.
+	let start = vec.len();
+	vec.resize(start + T::MAX_ENCODED_SIZE, 0)
+		.map_err(|()| CAPACITY_EXCEEDED)?;
+	let result = T::encode(&mut vec[start..], value);
+	vec.truncate(start + *result.as_ref().unwrap_or(&0));
+	result
+}
+
+/// Encodes multiple values into a new fixed-capacity vector.
+pub fn bulk_encode_to_heapless<T, const N: usize>(
+	values: &[T],
+) -> Result<Vec<u8, N>, &'static str>
+where
+	T: Encode + Clone,
+{
+	let mut vec = Vec::new();
+	for value in values {
+		encode_append_heapless(&mut vec, value.clone())?;
+	}
+	Ok(vec)
+}
+
+/// Decodes every value in a slice into a new fixed-capacity vector.
+///
+/// Returns an error if the slice holds more than `N` values.
+pub fn bulk_decode_values_heapless<T, const N: usize>(
+	buf: &[u8],
+) -> Result<Vec<T, N>, &'static str>
+where
+	T: Decode,
+{
+	let mut values = Vec::new();
+	let mut offset = 0;
+	while offset < buf.len() {
+		let (value, len) = T::decode(&buf[offset..])?;
+		values.push(value).map_err(|_| CAPACITY_EXCEEDED)?;
+		offset += len;
+	}
+	Ok(values)
+}
@@ -1,15 +1,43 @@
-//! Shared helpers for vlen (pointer utilities)
+//! Shared helpers for vlen (array prefix reborrows and const copies)
 
-/// Returns a pointer to a reference, useful for unsafe operations.
+/// Reborrows the first `M` bytes of an array as a smaller array.
+#[cfg(not(feature = "safe"))]
 #[inline]
-pub const fn ptr_from_ref<T>(r: &T) -> *const T {
-	r as *const T
+pub fn array_prefix<const N: usize, const M: usize>(buf: &[u8; N]) -> &[u8; M] {
+	const { assert!(M <= N) };
+	// SAFETY: `M <= N`, so the prefix lies within `buf`, and `[u8; M]` has
+	// alignment 1.
+	unsafe { &*(buf as *const [u8; N]).cast::<[u8; M]>() }
 }
 
-/// Returns a pointer to a mutable reference, useful for unsafe operations.
+/// Reborrows the first `M` bytes of an array as a smaller array.
+#[cfg(feature = "safe")]
 #[inline]
-pub const fn ptr_from_mut<T>(r: &mut T) -> *mut T {
-	r as *mut T
+pub fn array_prefix<const N: usize, const M: usize>(buf: &[u8; N]) -> &[u8; M] {
+	const { assert!(M <= N) };
+	buf.first_chunk::<M>().unwrap()
+}
+
+/// Mutably reborrows the first `M` bytes of an array as a smaller array.
+#[cfg(not(feature = "safe"))]
+#[inline]
+pub fn array_prefix_mut<const N: usize, const M: usize>(
+	buf: &mut [u8; N],
+) -> &mut [u8; M] {
+	const { assert!(M <= N) };
+	// SAFETY: `M <= N`, so the prefix lies within `buf`, and `[u8; M]` has
+	// alignment 1.
+	unsafe { &mut *(buf as *mut [u8; N]).cast::<[u8; M]>() }
+}
+
+/// Mutably reborrows the first `M` bytes of an array as a smaller array.
+#[cfg(feature = "safe")]
+#[inline]
+pub fn array_prefix_mut<const N: usize, const M: usize>(
+	buf: &mut [u8; N],
+) -> &mut [u8; M] {
+	const { assert!(M <= N) };
+	buf.first_chunk_mut::<M>().unwrap()
 }
 
 /// Copies `len` bytes from `src` to `dst` starting at `dst_offset`.
@@ -0,0 +1,176 @@
+//! Sampled offset index for random access into value streams
+//!
+//! An [`Index`] records the byte offset of every `every_n`-th value of a
+//! buffer. [`Index::seek`] jumps to the nearest sample at or before the
+//! requested value and skips forward from there, so locating any value
+//! decodes at most `every_n - 1` values of the stream.
+//!
+//! The index is itself vlen-encoded: the sampling interval, value count and
+//! byte length of the stream, followed by the gaps between consecutive
+//! sampled offsets. Its bytes can be stored next to the stream and reloaded
+//! with [`Index::from_bytes`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::index::Index;
+//!
+//! let values: Vec<u64> = (0..10_000).map(|i| i * i).collect();
+//! let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+//!
+//! let index = Index::<u64>::build(&buf, 64).unwrap();
+//! let offset = index.seek(&buf, 5000).unwrap();
+//! assert_eq!(vlen::decode_value::<u64>(&buf[offset..]), Ok(5000 * 5000));
+//! ```
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::decode::{decode_iter, skip_values, Decode};
+
+/// A sampled index of value offsets into a buffer of `T` values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index<T> {
+	bytes: Vec<u8>,
+	every_n: usize,
+	len: usize,
+	end: usize,
+	samples_start: usize,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Index<T>
+where
+	T: Decode,
+{
+	/// Builds an index recording the offset of every `every_n`-th value in
+	/// `buf`.
+	pub fn build(buf: &[u8], every_n: usize) -> Result<Self, &'static str> {
+		if every_n == 0 {
+			return Err("index interval must be non-zero");
+		}
+		let mut samples = Vec::new();
+		let mut last_sample = 0;
+		let mut len = 0;
+		let mut values = decode_iter::<T>(buf);
+		loop {
+			let offset = values.offset();
+			if len % every_n == 0 && offset < buf.len() {
+				crate::encode_append(
+					&mut samples,
+					(offset - last_sample) as u64,
+				)?;
+				last_sample = offset;
+			}
+			match values.next() {
+				Some(value) => value?,
+				None => break,
+			};
+			len += 1;
+		}
+
+		let mut bytes = Vec::new();
+		crate::encode_append(&mut bytes, every_n as u64)?;
+		crate::encode_append(&mut bytes, len as u64)?;
+		crate::encode_append(&mut bytes, buf.len() as u64)?;
+		let samples_start = bytes.len();
+		bytes.extend_from_slice(&samples);
+		Ok(Self {
+			bytes,
+			every_n,
+			len,
+			end: buf.len(),
+			samples_start,
+			_marker: PhantomData,
+		})
+	}
+
+	/// Loads an index from bytes returned by [`Index::as_bytes`].
+	pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, &'static str> {
+		let mut header = decode_iter::<u64>(&bytes);
+		let mut next = || -> Result<usize, &'static str> {
+			let value = header.next().ok_or("truncated vlen value")??;
+			usize::try_from(value).map_err(|_| "index header out of range")
+		};
+		let (every_n, len, end) = (next()?, next()?, next()?);
+		if every_n == 0 {
+			return Err("index interval must be non-zero");
+		}
+		let samples_start = header.offset();
+		let expected = len.div_ceil(every_n);
+		if crate::decode::count_values::<u64>(&bytes[samples_start..])?
+			!= expected
+		{
+			return Err("index holds the wrong number of samples");
+		}
+		Ok(Self {
+			bytes,
+			every_n,
+			len,
+			end,
+			samples_start,
+			_marker: PhantomData,
+		})
+	}
+
+	/// Returns the byte offset of the `n`-th value in `buf`.
+	///
+	/// `n` may equal [`Index::len`], giving the end of the last value. `buf`
+	/// must be the buffer the index was built from.
+	pub fn seek(&self, buf: &[u8], n: usize) -> Result<usize, &'static str> {
+		if n > self.len {
+			return Err("value index out of range");
+		}
+		if n == self.len {
+			return Ok(self.end);
+		}
+		let offset = self.sample_offset(n / self.every_n + 1)?;
+		let rest = buf.get(offset..).ok_or("index does not match buffer")?;
+		Ok(offset + skip_values::<T>(rest, n % self.every_n)?)
+	}
+
+	/// Returns the offset of the last of the first `samples` samples.
+	fn sample_offset(&self, samples: usize) -> Result<usize, &'static str> {
+		let mut offset = 0usize;
+		for gap in
+			decode_iter::<u64>(&self.bytes[self.samples_start..]).take(samples)
+		{
+			// Gaps come from `from_bytes` input, so they may sum past `usize`.
+			offset = usize::try_from(gap?)
+				.ok()
+				.and_then(|gap| offset.checked_add(gap))
+				.ok_or("index offset out of range")?;
+		}
+		Ok(offset)
+	}
+}
+
+impl<T> Index<T> {
+	/// Returns the sampling interval.
+	#[inline]
+	#[must_use]
+	pub fn every_n(&self) -> usize {
+		self.every_n
+	}
+
+	/// Returns the number of values in the indexed buffer.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the indexed buffer holds no values.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the encoded index.
+	#[inline]
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+}
@@ -0,0 +1,83 @@
+//! Adapter for the `integer-encoding` crate's `VarInt` trait
+//!
+//! Code written against the `VarInt` trait of
+//! [`integer_encoding`](::integer_encoding) can switch its wire format to
+//! vlen by wrapping its integers in [`Vlen`].
+//! `Vlen<T>` implements `VarInt` for the integer types vlen encodes, so
+//! `required_space`, `encode_var`, `decode_var` and `encode_var_vec` produce
+//! and read vlen bytes instead of LEB128.
+//!
+//! The crate's `VarIntReader` and `VarIntWriter` are not usable with
+//! `Vlen<T>`: they find the end of a value by the LEB128 continuation bit,
+//! which vlen does not have. Frame streams with [`frame`](crate::frame)
+//! instead.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use integer_encoding::VarInt;
+//! use vlen::integer_encoding::Vlen;
+//!
+//! let encoded = Vlen(300u32).encode_var_vec();
+//! assert_eq!(encoded, vlen::encode_to_vec(300u32).unwrap());
+//! assert_eq!(Vlen(300u32).required_space(), encoded.len());
+//! assert_eq!(Vlen::<u32>::decode_var(&encoded), Some((Vlen(300), 2)));
+//! ```
+
+use ::integer_encoding::VarInt;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// An integer that `VarInt` encodes as vlen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Vlen<T>(pub T);
+
+impl<T> Vlen<T> {
+	/// Returns the wrapped integer.
+	#[inline]
+	#[must_use]
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T> From<T> for Vlen<T> {
+	#[inline]
+	fn from(value: T) -> Self {
+		Self(value)
+	}
+}
+
+macro_rules! impl_var_int {
+	($($t:ty),*) => {$(
+		impl VarInt for Vlen<$t> {
+			#[inline]
+			fn required_space(self) -> usize {
+				<$t as Encode>::encoded_size(self.0)
+					.expect("integers always have an encoding")
+			}
+
+			/// Returns `None` if `src` does not start with a whole value.
+			#[inline]
+			fn decode_var(src: &[u8]) -> Option<(Self, usize)> {
+				<$t as Decode>::decode(src)
+					.ok()
+					.map(|(value, len)| (Self(value), len))
+			}
+
+			/// Panics if `src` is shorter than `required_space()`, as the
+			/// trait allows.
+			#[inline]
+			fn encode_var(self, src: &mut [u8]) -> usize {
+				let mut buf = [0u8; <$t as Encode>::MAX_ENCODED_SIZE];
+				let len = <$t as Encode>::encode(&mut buf, self.0)
+					.expect("integers always have an encoding");
+				src[..len].copy_from_slice(&buf[..len]);
+				len
+			}
+		}
+	)*};
+}
+
+impl_var_int!(u16, u32, u64, i16, i32, i64);
@@ -0,0 +1,177 @@
+//! CBOR data item heads (RFC 8949, section 3)
+//!
+//! Every CBOR data item starts with a head: a major type in the top three
+//! bits of the first byte and an argument in the low five bits, either
+//! stored directly (below 24) or in the 1, 2, 4 or 8 big-endian bytes that
+//! follow. The argument is the value of an integer, the length of a string,
+//! array or map, or a tag number. These helpers read and write heads and
+//! integers for lightweight readers that do not need a full CBOR library.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::interop::cbor::{decode_head, encode_head, MajorType};
+//!
+//! // A text string of 500 bytes.
+//! let mut buf = [0u8; 9];
+//! let len = encode_head(&mut buf, MajorType::TextString, 500);
+//! assert_eq!(buf[..len], [0x79, 0x01, 0xF4]);
+//!
+//! let (major, argument, _) = decode_head(&buf[..len]).unwrap();
+//! assert_eq!((major, argument), (MajorType::TextString, Some(500)));
+//! ```
+
+/// Maximum encoded length of a head.
+pub const MAX_HEAD_LEN: usize = 9;
+
+/// The kind of data item a head introduces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MajorType {
+	/// An unsigned integer equal to the argument.
+	Unsigned = 0,
+	/// A negative integer equal to `-1 - argument`.
+	Negative = 1,
+	/// A byte string of `argument` bytes.
+	ByteString = 2,
+	/// A UTF-8 string of `argument` bytes.
+	TextString = 3,
+	/// An array of `argument` data items.
+	Array = 4,
+	/// A map of `argument` key/value pairs.
+	Map = 5,
+	/// A tag numbered `argument` applied to the next data item.
+	Tag = 6,
+	/// A float, simple value or break code.
+	Simple = 7,
+}
+
+impl MajorType {
+	/// Returns the major type of a head's first byte.
+	#[inline]
+	#[must_use]
+	pub const fn from_initial_byte(byte: u8) -> Self {
+		match byte >> 5 {
+			0 => MajorType::Unsigned,
+			1 => MajorType::Negative,
+			2 => MajorType::ByteString,
+			3 => MajorType::TextString,
+			4 => MajorType::Array,
+			5 => MajorType::Map,
+			6 => MajorType::Tag,
+			_ => MajorType::Simple,
+		}
+	}
+}
+
+/// Returns the encoded length of a head with the given argument.
+#[inline]
+#[must_use]
+pub const fn head_len(argument: u64) -> usize {
+	match argument {
+		0..=23 => 1,
+		24..=0xFF => 2,
+		0x100..=0xFFFF => 3,
+		0x1_0000..=0xFFFF_FFFF => 5,
+		_ => 9,
+	}
+}
+
+/// Encodes a head in its shortest form, returning the encoded length.
+#[inline]
+pub fn encode_head(
+	buf: &mut [u8; MAX_HEAD_LEN],
+	major: MajorType,
+	argument: u64,
+) -> usize {
+	let len = head_len(argument);
+	let info = match len {
+		1 => argument as u8,
+		2 => 24,
+		3 => 25,
+		5 => 26,
+		_ => 27,
+	};
+	buf[0] = (major as u8) << 5 | info;
+	buf[1..len].copy_from_slice(&argument.to_be_bytes()[9 - len..]);
+	len
+}
+
+/// Encodes the head of an indefinite-length string, array or map, or the
+/// break code that ends one, returning the encoded length.
+#[inline]
+pub fn encode_indefinite_head(
+	buf: &mut [u8; MAX_HEAD_LEN],
+	major: MajorType,
+) -> Result<usize, &'static str> {
+	match major {
+		MajorType::Unsigned | MajorType::Negative | MajorType::Tag => {
+			Err("CBOR major type has no indefinite length")
+		},
+		_ => {
+			buf[0] = (major as u8) << 5 | 31;
+			Ok(1)
+		},
+	}
+}
+
+/// Decodes a head, returning its major type, argument and encoded length.
+///
+/// The argument is `None` for the head of an indefinite-length item or a
+/// break code.
+#[inline]
+pub fn decode_head(
+	buf: &[u8],
+) -> Result<(MajorType, Option<u64>, usize), &'static str> {
+	let &first = buf.first().ok_or("truncated CBOR head")?;
+	let major = MajorType::from_initial_byte(first);
+	let len = match first & 0x1F {
+		info @ 0..=23 => return Ok((major, Some(u64::from(info)), 1)),
+		24 => 2,
+		25 => 3,
+		26 => 5,
+		27 => 9,
+		31 => {
+			return match major {
+				MajorType::Unsigned | MajorType::Negative | MajorType::Tag => {
+					Err("CBOR major type has no indefinite length")
+				},
+				_ => Ok((major, None, 1)),
+			};
+		},
+		_ => return Err("reserved CBOR additional information"),
+	};
+	let encoded = buf.get(1..len).ok_or("truncated CBOR head")?;
+	let mut bytes = [0u8; 8];
+	bytes[9 - len..].copy_from_slice(encoded);
+	Ok((major, Some(u64::from_be_bytes(bytes)), len))
+}
+
+/// Encodes an integer as an unsigned or negative integer item, returning
+/// the encoded length.
+#[inline]
+pub fn encode_i64(buf: &mut [u8; MAX_HEAD_LEN], value: i64) -> usize {
+	if value < 0 {
+		encode_head(buf, MajorType::Negative, !value as u64)
+	} else {
+		encode_head(buf, MajorType::Unsigned, value as u64)
+	}
+}
+
+/// Decodes an unsigned or negative integer item, returning the value and
+/// encoded length.
+#[inline]
+pub fn decode_i64(buf: &[u8]) -> Result<(i64, usize), &'static str> {
+	let (major, argument, len) = decode_head(buf)?;
+	let argument = match (major, argument) {
+		(MajorType::Unsigned | MajorType::Negative, Some(argument)) => {
+			i64::try_from(argument).map_err(|_| "CBOR integer out of range")?
+		},
+		_ => return Err("CBOR item is not an integer"),
+	};
+	if major == MajorType::Negative {
+		Ok((!argument, len))
+	} else {
+		Ok((argument, len))
+	}
+}
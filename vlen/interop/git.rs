@@ -0,0 +1,96 @@
+//! Git packfile offset varints
+//!
+//! An `OFS_DELTA` entry in a Git packfile locates its base object with a
+//! big-endian varint: seven bits per byte, most significant group first,
+//! with the high bit set on every byte but the last. Each continuation also
+//! adds one to the value, so every length covers a range of its own and no
+//! value has two encodings.
+//!
+//! [`offset_to_vlen`] and [`vlen_to_offset`] convert between that form and
+//! the vlen `u64` encoding.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::interop::git::{decode_offset, encode_offset};
+//!
+//! let mut buf = [0u8; 10];
+//! let len = encode_offset(&mut buf, 128);
+//! assert_eq!(buf[..len], [0x80, 0x00]);
+//! assert_eq!(decode_offset(&buf[..len]), Ok((128, 2)));
+//! ```
+
+use crate::decode::Decode;
+use crate::encode::encode_u64;
+
+/// Maximum encoded length of an offset.
+pub const MAX_OFFSET_LEN: usize = 10;
+
+/// Returns the encoded length of an offset.
+#[inline]
+#[must_use]
+pub const fn offset_len(mut value: u64) -> usize {
+	let mut len = 1;
+	while value >= 0x80 {
+		value = (value >> 7) - 1;
+		len += 1;
+	}
+	len
+}
+
+/// Encodes an offset, returning the encoded length.
+#[inline]
+pub fn encode_offset(buf: &mut [u8; MAX_OFFSET_LEN], mut value: u64) -> usize {
+	let mut temp_buf = [0u8; MAX_OFFSET_LEN];
+	let mut pos = MAX_OFFSET_LEN - 1;
+	temp_buf[pos] = value as u8 & 0x7F;
+	while value >= 0x80 {
+		value = (value >> 7) - 1;
+		pos -= 1;
+		temp_buf[pos] = value as u8 | 0x80;
+	}
+	let len = MAX_OFFSET_LEN - pos;
+	buf[..len].copy_from_slice(&temp_buf[pos..]);
+	len
+}
+
+/// Decodes an offset, returning the value and encoded length.
+#[inline]
+pub fn decode_offset(buf: &[u8]) -> Result<(u64, usize), &'static str> {
+	let &first = buf.first().ok_or("truncated git offset")?;
+	let mut value = u64::from(first & 0x7F);
+	let mut byte = first;
+	let mut len = 1;
+	while byte & 0x80 != 0 {
+		byte = *buf.get(len).ok_or("truncated git offset")?;
+		let biased = value
+			.checked_add(1)
+			.filter(|&biased| biased <= u64::MAX >> 7)
+			.ok_or("git offset overflows u64")?;
+		value = (biased << 7) | u64::from(byte & 0x7F);
+		len += 1;
+	}
+	Ok((value, len))
+}
+
+/// Re-encodes a Git offset as a vlen `u64`, returning the number of bytes
+/// read and written.
+#[inline]
+pub fn offset_to_vlen(
+	buf: &[u8],
+	out: &mut [u8; 9],
+) -> Result<(usize, usize), &'static str> {
+	let (value, len) = decode_offset(buf)?;
+	Ok((len, encode_u64(out, value)))
+}
+
+/// Re-encodes a vlen `u64` as a Git offset, returning the number of bytes
+/// read and written.
+#[inline]
+pub fn vlen_to_offset(
+	buf: &[u8],
+	out: &mut [u8; MAX_OFFSET_LEN],
+) -> Result<(usize, usize), &'static str> {
+	let (value, len) = u64::decode(buf)?;
+	Ok((len, encode_offset(out, value)))
+}
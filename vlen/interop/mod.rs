@@ -0,0 +1,32 @@
+//! Helpers for reading and writing other variable-length integer formats
+//!
+//! Each submodule implements one foreign wire format next to the vlen codec,
+//! so tools that parse those formats can keep their integers in vlen form.
+
+pub mod cbor;
+pub mod git;
+pub mod protobuf;
+pub mod quic;
+
+/// Converts every value of `buf` with `convert`, appending to `out`.
+fn transcode<const N: usize, F>(
+	buf: &[u8],
+	out: &mut [u8],
+	convert: F,
+) -> Result<usize, &'static str>
+where
+	F: Fn(&[u8], &mut [u8; N]) -> Result<(usize, usize), &'static str>,
+{
+	let mut offset = 0;
+	let mut written = 0;
+	while offset < buf.len() {
+		let mut temp_buf = [0u8; N];
+		let (read, len) = convert(&buf[offset..], &mut temp_buf)?;
+		out.get_mut(written..written + len)
+			.ok_or("buffer too small for bulk encoding")?
+			.copy_from_slice(&temp_buf[..len]);
+		offset += read;
+		written += len;
+	}
+	Ok(written)
+}
@@ -0,0 +1,307 @@
+//! Protocol Buffers field headers and varints
+//!
+//! Protobuf encodes integers as LEB128 varints: seven bits per byte, least
+//! significant group first, with the high bit set on every byte but the
+//! last. Each field starts with a tag varint holding the field number and
+//! a [`WireType`]. [`decode_varint`] reads up to eight bytes at once when the
+//! buffer allows, and the bulk functions handle packed repeated fields. The
+//! converters re-encode varints as vlen `u64` values and back, so a packed
+//! field can be handed to vlen's bulk decoders and written out again.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::interop::protobuf::{decode_tag, decode_varint, WireType};
+//!
+//! // Field 1 holding the varint 150.
+//! let message = [0x08, 0x96, 0x01];
+//!
+//! let (field, wire_type, len) = decode_tag(&message).unwrap();
+//! assert_eq!((field, wire_type), (1, WireType::Varint));
+//!
+//! let (value, _) = decode_varint(&message[len..]).unwrap();
+//! assert_eq!(value, 150);
+//! ```
+
+use super::transcode;
+use crate::decode::Decode;
+use crate::encode::encode_u64;
+
+/// Largest field number protobuf allows.
+pub const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+
+/// Maximum encoded length of a varint.
+pub const MAX_VARINT_LEN: usize = 10;
+
+/// How a field's payload is laid out after its tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum WireType {
+	/// A varint.
+	Varint = 0,
+	/// Eight little-endian bytes.
+	I64 = 1,
+	/// A varint length followed by that many bytes.
+	Len = 2,
+	/// Start of a deprecated group.
+	StartGroup = 3,
+	/// End of a deprecated group.
+	EndGroup = 4,
+	/// Four little-endian bytes.
+	I32 = 5,
+}
+
+impl TryFrom<u8> for WireType {
+	type Error = &'static str;
+
+	#[inline]
+	fn try_from(value: u8) -> Result<Self, Self::Error> {
+		match value {
+			0 => Ok(WireType::Varint),
+			1 => Ok(WireType::I64),
+			2 => Ok(WireType::Len),
+			3 => Ok(WireType::StartGroup),
+			4 => Ok(WireType::EndGroup),
+			5 => Ok(WireType::I32),
+			_ => Err("invalid protobuf wire type"),
+		}
+	}
+}
+
+/// Returns the encoded length of a varint.
+#[inline]
+#[must_use]
+pub const fn varint_len(value: u64) -> usize {
+	(64 - (value | 1).leading_zeros()).div_ceil(7) as usize
+}
+
+/// Encodes a varint, returning the encoded length.
+#[inline]
+pub fn encode_varint(buf: &mut [u8; MAX_VARINT_LEN], mut value: u64) -> usize {
+	let mut len = 0;
+	while value >= 0x80 {
+		buf[len] = value as u8 | 0x80;
+		value >>= 7;
+		len += 1;
+	}
+	buf[len] = value as u8;
+	len + 1
+}
+
+/// Decodes a varint, returning the value and encoded length.
+#[inline]
+pub fn decode_varint(buf: &[u8]) -> Result<(u64, usize), &'static str> {
+	match buf.first() {
+		Some(&byte) if byte < 0x80 => return Ok((u64::from(byte), 1)),
+		None => return Err("truncated protobuf varint"),
+		Some(_) => {},
+	}
+	if let Some(chunk) = buf.first_chunk::<8>() {
+		let word = u64::from_le_bytes(*chunk);
+		let stops = !word & 0x8080_8080_8080_8080;
+		if stops != 0 {
+			let len = (stops.trailing_zeros() / 8 + 1) as usize;
+			return Ok((compact_groups(word, len), len));
+		}
+	}
+	decode_varint_slow(buf)
+}
+
+/// Packs the seven-bit groups of the first `len` bytes of `word`.
+#[inline]
+const fn compact_groups(word: u64, len: usize) -> u64 {
+	let mask = if len == 8 { !0 } else { (1 << (8 * len)) - 1 };
+	let mut x = word & mask & 0x7F7F_7F7F_7F7F_7F7F;
+	x = ((x & 0x7F00_7F00_7F00_7F00) >> 1) | (x & 0x007F_007F_007F_007F);
+	x = ((x & 0x3FFF_0000_3FFF_0000) >> 2) | (x & 0x0000_3FFF_0000_3FFF);
+	((x & 0x0FFF_FFFF_0000_0000) >> 4) | (x & 0x0000_0000_0FFF_FFFF)
+}
+
+/// Decodes a varint one byte at a time.
+fn decode_varint_slow(buf: &[u8]) -> Result<(u64, usize), &'static str> {
+	let mut value = 0u64;
+	for (i, &byte) in buf.iter().take(MAX_VARINT_LEN).enumerate() {
+		if i == MAX_VARINT_LEN - 1 && byte > 1 {
+			return Err("protobuf varint overflows u64");
+		}
+		value |= u64::from(byte & 0x7F) << (7 * i);
+		if byte < 0x80 {
+			return Ok((value, i + 1));
+		}
+	}
+	if buf.len() >= MAX_VARINT_LEN {
+		Err("protobuf varint overflows u64")
+	} else {
+		Err("truncated protobuf varint")
+	}
+}
+
+/// Maps a signed value to an unsigned one for `sint32`/`sint64` fields.
+#[inline]
+#[must_use]
+pub const fn zigzag_encode(value: i64) -> u64 {
+	((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Reverses [`zigzag_encode`].
+#[inline]
+#[must_use]
+pub const fn zigzag_decode(value: u64) -> i64 {
+	((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Encodes a field tag, returning the encoded length.
+#[inline]
+pub fn encode_tag(
+	buf: &mut [u8; 5],
+	field: u32,
+	wire_type: WireType,
+) -> Result<usize, &'static str> {
+	if field == 0 || field > MAX_FIELD_NUMBER {
+		return Err("protobuf field number out of range");
+	}
+	let mut temp_buf = [0u8; MAX_VARINT_LEN];
+	let len =
+		encode_varint(&mut temp_buf, u64::from(field << 3 | wire_type as u32));
+	buf.copy_from_slice(&temp_buf[..5]);
+	Ok(len)
+}
+
+/// Decodes a field tag, returning the field number, wire type and encoded
+/// length.
+#[inline]
+pub fn decode_tag(buf: &[u8]) -> Result<(u32, WireType, usize), &'static str> {
+	let (tag, len) = decode_varint(buf)?;
+	let tag = u32::try_from(tag).map_err(|_| "protobuf tag out of range")?;
+	let field = tag >> 3;
+	if field == 0 {
+		return Err("protobuf field number out of range");
+	}
+	let wire_type = WireType::try_from((tag & 0x07) as u8)?;
+	Ok((field, wire_type, len))
+}
+
+/// Encodes a varint field, tag included, returning the encoded length.
+pub fn encode_varint_field(
+	buf: &mut [u8],
+	field: u32,
+	value: u64,
+) -> Result<usize, &'static str> {
+	let mut tag_buf = [0u8; 5];
+	let tag_len = encode_tag(&mut tag_buf, field, WireType::Varint)?;
+	let mut value_buf = [0u8; MAX_VARINT_LEN];
+	let value_len = encode_varint(&mut value_buf, value);
+	let len = tag_len + value_len;
+	let out = buf
+		.get_mut(..len)
+		.ok_or("buffer too small for protobuf encoding")?;
+	out[..tag_len].copy_from_slice(&tag_buf[..tag_len]);
+	out[tag_len..].copy_from_slice(&value_buf[..value_len]);
+	Ok(len)
+}
+
+/// Returns the length of the payload that follows a tag of `wire_type`.
+///
+/// Groups are not supported.
+pub fn skip_field(
+	buf: &[u8],
+	wire_type: WireType,
+) -> Result<usize, &'static str> {
+	let len = match wire_type {
+		WireType::Varint => decode_varint(buf)?.1,
+		WireType::I64 => 8,
+		WireType::I32 => 4,
+		WireType::Len => {
+			let (payload_len, len) = decode_varint(buf)?;
+			usize::try_from(payload_len)
+				.ok()
+				.and_then(|payload_len| payload_len.checked_add(len))
+				.ok_or("truncated protobuf field")?
+		},
+		WireType::StartGroup | WireType::EndGroup => {
+			return Err("unsupported protobuf wire type");
+		},
+	};
+	if len > buf.len() {
+		return Err("truncated protobuf field");
+	}
+	Ok(len)
+}
+
+/// Bulk encodes varints, as in the payload of a packed repeated field.
+///
+/// Returns the number of bytes written.
+pub fn bulk_encode_varints(
+	buf: &mut [u8],
+	values: &[u64],
+) -> Result<usize, &'static str> {
+	let mut offset = 0;
+	for &value in values {
+		let mut temp_buf = [0u8; MAX_VARINT_LEN];
+		let len = encode_varint(&mut temp_buf, value);
+		buf.get_mut(offset..offset + len)
+			.ok_or("buffer too small for protobuf encoding")?
+			.copy_from_slice(&temp_buf[..len]);
+		offset += len;
+	}
+	Ok(offset)
+}
+
+/// Bulk decodes varints, as in the payload of a packed repeated field.
+///
+/// Decodes until `values` is full or `buf` is exhausted, returning the number
+/// of values decoded and the number of bytes consumed.
+pub fn bulk_decode_varints(
+	buf: &[u8],
+	values: &mut [u64],
+) -> Result<(usize, usize), &'static str> {
+	let mut offset = 0;
+	let mut i = 0;
+	while i < values.len() && offset < buf.len() {
+		let (value, len) = decode_varint(&buf[offset..])?;
+		values[i] = value;
+		offset += len;
+		i += 1;
+	}
+	Ok((i, offset))
+}
+
+/// Re-encodes a varint as a vlen `u64`, returning the number of bytes read
+/// and written.
+#[inline]
+pub fn varint_to_vlen(
+	buf: &[u8],
+	out: &mut [u8; 9],
+) -> Result<(usize, usize), &'static str> {
+	let (value, len) = decode_varint(buf)?;
+	Ok((len, encode_u64(out, value)))
+}
+
+/// Re-encodes a vlen `u64` as a varint, returning the number of bytes read
+/// and written.
+#[inline]
+pub fn vlen_to_varint(
+	buf: &[u8],
+	out: &mut [u8; MAX_VARINT_LEN],
+) -> Result<(usize, usize), &'static str> {
+	let (value, len) = u64::decode(buf)?;
+	Ok((len, encode_varint(out, value)))
+}
+
+/// Re-encodes the payload of a packed varint field as vlen `u64` values,
+/// returning the number of bytes written.
+pub fn bulk_varint_to_vlen(
+	buf: &[u8],
+	out: &mut [u8],
+) -> Result<usize, &'static str> {
+	transcode(buf, out, varint_to_vlen)
+}
+
+/// Re-encodes a buffer of vlen `u64` values as the payload of a packed
+/// varint field, returning the number of bytes written.
+pub fn bulk_vlen_to_varint(
+	buf: &[u8],
+	out: &mut [u8],
+) -> Result<usize, &'static str> {
+	transcode(buf, out, vlen_to_varint)
+}
@@ -0,0 +1,103 @@
+//! QUIC variable-length integers (RFC 9000, section 16)
+//!
+//! A QUIC varint stores its length in the two high bits of the first byte,
+//! giving 1, 2, 4 or 8 bytes with 6, 14, 30 or 62 bits of big-endian value.
+//! Values above [`MAX_VALUE`] cannot be encoded. The converters re-encode
+//! fields parsed from QUIC frames as vlen `u64` values and back, one at a
+//! time or a whole buffer at once.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::interop::quic::{decode_varint, encode_varint};
+//!
+//! let mut buf = [0u8; 8];
+//! let len = encode_varint(&mut buf, 15293).unwrap();
+//! assert_eq!(buf[..len], [0x7B, 0xBD]);
+//! assert_eq!(decode_varint(&buf[..len]), Ok((15293, 2)));
+//! ```
+
+use super::transcode;
+use crate::decode::Decode;
+use crate::encode::encode_u64;
+
+/// Largest value a QUIC varint can hold.
+pub const MAX_VALUE: u64 = (1 << 62) - 1;
+
+/// Returns the encoded length of a varint, or an error if `value` exceeds
+/// [`MAX_VALUE`].
+#[inline]
+pub const fn varint_len(value: u64) -> Result<usize, &'static str> {
+	match value {
+		0..=0x3F => Ok(1),
+		0x40..=0x3FFF => Ok(2),
+		0x4000..=0x3FFF_FFFF => Ok(4),
+		0x4000_0000..=MAX_VALUE => Ok(8),
+		_ => Err("value too large for QUIC varint"),
+	}
+}
+
+/// Encodes a varint, returning the encoded length.
+#[inline]
+pub fn encode_varint(
+	buf: &mut [u8; 8],
+	value: u64,
+) -> Result<usize, &'static str> {
+	let len = varint_len(value)?;
+	let prefix = u64::from(len.trailing_zeros()) << (8 * len - 2);
+	let bytes = (value | prefix).to_be_bytes();
+	buf[..len].copy_from_slice(&bytes[8 - len..]);
+	Ok(len)
+}
+
+/// Decodes a varint, returning the value and encoded length.
+#[inline]
+pub fn decode_varint(buf: &[u8]) -> Result<(u64, usize), &'static str> {
+	let &first = buf.first().ok_or("truncated QUIC varint")?;
+	let len = 1 << (first >> 6);
+	let encoded = buf.get(..len).ok_or("truncated QUIC varint")?;
+	let mut bytes = [0u8; 8];
+	bytes[8 - len..].copy_from_slice(encoded);
+	bytes[8 - len] &= 0x3F;
+	Ok((u64::from_be_bytes(bytes), len))
+}
+
+/// Re-encodes a QUIC varint as a vlen `u64`, returning the number of bytes
+/// read and written.
+#[inline]
+pub fn varint_to_vlen(
+	buf: &[u8],
+	out: &mut [u8; 9],
+) -> Result<(usize, usize), &'static str> {
+	let (value, len) = decode_varint(buf)?;
+	Ok((len, encode_u64(out, value)))
+}
+
+/// Re-encodes a vlen `u64` as a QUIC varint, returning the number of bytes
+/// read and written.
+#[inline]
+pub fn vlen_to_varint(
+	buf: &[u8],
+	out: &mut [u8; 8],
+) -> Result<(usize, usize), &'static str> {
+	let (value, len) = u64::decode(buf)?;
+	Ok((len, encode_varint(out, value)?))
+}
+
+/// Re-encodes a buffer of QUIC varints as vlen `u64` values, returning the
+/// number of bytes written.
+pub fn bulk_varint_to_vlen(
+	buf: &[u8],
+	out: &mut [u8],
+) -> Result<usize, &'static str> {
+	transcode(buf, out, varint_to_vlen)
+}
+
+/// Re-encodes a buffer of vlen `u64` values as QUIC varints, returning the
+/// number of bytes written.
+pub fn bulk_vlen_to_varint(
+	buf: &[u8],
+	out: &mut [u8],
+) -> Result<usize, &'static str> {
+	transcode(buf, out, vlen_to_varint)
+}
@@ -0,0 +1,288 @@
+//! Append-only key-value maps for compact metadata blobs
+//!
+//! A [`VlenMap`] stores `(key, value)` pairs back to back as vlen values, so
+//! a small attribute set with integer keys such as `u16` field ids or `u64`
+//! hashes takes a few bytes per entry and can be shipped or stored as is.
+//! Entries are only ever appended; inserting a key again shadows the earlier
+//! entry, and [`VlenMap::get`] returns the value inserted last.
+//!
+//! Lookups scan the pairs linearly, which is fastest for the handful of
+//! entries a metadata blob usually holds. For larger maps,
+//! [`VlenMap::build_index`] keeps a sorted table of value offsets next to
+//! the bytes, and lookups binary search it instead.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::map::VlenMap;
+//!
+//! let mut attrs = VlenMap::<u16, u64>::new();
+//! attrs.insert(1, 1_700_000_000)?;
+//! attrs.insert(7, 42)?;
+//! attrs.insert(1, 1_700_000_060)?;
+//! assert_eq!(attrs.len(), 3);
+//! assert_eq!(attrs.get(1)?, Some(1_700_000_060));
+//! assert_eq!(attrs.get(3)?, None);
+//!
+//! let reloaded = VlenMap::<u16, u64>::from_bytes(attrs.into_bytes())?;
+//! let pairs: Result<Vec<_>, _> = reloaded.iter().collect();
+//! assert_eq!(pairs?, [(1, 1_700_000_000), (7, 42), (1, 1_700_000_060)]);
+//! # Ok::<(), &'static str>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// An append-only map of vlen-encoded `(key, value)` pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VlenMap<K, V> {
+	bytes: Vec<u8>,
+	len: usize,
+	index: Option<Vec<(K, usize)>>,
+	_marker: PhantomData<fn() -> V>,
+}
+
+impl<K, V> VlenMap<K, V> {
+	/// Creates an empty map.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			bytes: Vec::new(),
+			len: 0,
+			index: None,
+			_marker: PhantomData,
+		}
+	}
+
+	/// Returns the number of entries, counting shadowed ones.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the map holds no entries.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns `true` if lookups use the offset index.
+	#[inline]
+	#[must_use]
+	pub fn is_indexed(&self) -> bool {
+		self.index.is_some()
+	}
+
+	/// Returns the encoded entries.
+	#[inline]
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	/// Returns the encoded entries, dropping the index.
+	#[inline]
+	#[must_use]
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.bytes
+	}
+}
+
+impl<K, V> Default for VlenMap<K, V> {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K, V> VlenMap<K, V>
+where
+	K: Encode + Decode + Copy + Ord,
+	V: Encode + Decode + Clone,
+{
+	/// Loads a map from bytes returned by [`VlenMap::into_bytes`].
+	///
+	/// Fails if the bytes do not hold whole `(key, value)` pairs.
+	pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, &'static str> {
+		let mut len = 0;
+		for entry in Iter::<K, V>::new(&bytes) {
+			entry?;
+			len += 1;
+		}
+		Ok(Self {
+			bytes,
+			len,
+			index: None,
+			_marker: PhantomData,
+		})
+	}
+
+	/// Appends an entry, shadowing any earlier entry with the same key.
+	///
+	/// The map is unchanged on error.
+	pub fn insert(&mut self, key: K, value: V) -> Result<(), &'static str> {
+		let start = self.bytes.len();
+		let key_len = crate::encode_append(&mut self.bytes, key)?;
+		if let Err(err) = crate::encode_append(&mut self.bytes, value) {
+			self.bytes.truncate(start);
+			return Err(err);
+		}
+		if let Some(index) = &mut self.index {
+			index_insert(index, key, start + key_len);
+		}
+		self.len += 1;
+		Ok(())
+	}
+
+	/// Returns the value inserted last for `key`, or `None` if there is no
+	/// entry for it.
+	pub fn get(&self, key: K) -> Result<Option<V>, &'static str> {
+		if let Some(index) = &self.index {
+			let Ok(position) = index.binary_search_by_key(&key, |&(k, _)| k)
+			else {
+				return Ok(None);
+			};
+			let (value, _) = V::decode(&self.bytes[index[position].1..])?;
+			return Ok(Some(value));
+		}
+		let mut found = None;
+		for entry in self.iter() {
+			let (entry_key, value) = entry?;
+			if entry_key == key {
+				found = Some(value);
+			}
+		}
+		Ok(found)
+	}
+
+	/// Returns `true` if the map has an entry for `key`.
+	pub fn contains_key(&self, key: K) -> Result<bool, &'static str> {
+		if let Some(index) = &self.index {
+			return Ok(index.binary_search_by_key(&key, |&(k, _)| k).is_ok());
+		}
+		for entry in self.iter() {
+			if entry?.0 == key {
+				return Ok(true);
+			}
+		}
+		Ok(false)
+	}
+
+	/// Builds the offset index, so later lookups binary search it instead
+	/// of scanning every entry.
+	///
+	/// The index holds one key and offset per distinct key and is kept up
+	/// to date by [`VlenMap::insert`].
+	pub fn build_index(&mut self) -> Result<(), &'static str> {
+		let mut index = Vec::new();
+		let mut offset = 0;
+		while offset < self.bytes.len() {
+			let (key, key_len) = K::decode(&self.bytes[offset..])?;
+			let value_offset = offset + key_len;
+			let (_, value_len) = V::decode(&self.bytes[value_offset..])?;
+			index_insert(&mut index, key, value_offset);
+			offset = value_offset + value_len;
+		}
+		self.index = Some(index);
+		Ok(())
+	}
+
+	/// Returns an iterator over the entries in insertion order, including
+	/// shadowed ones.
+	#[inline]
+	pub fn iter(&self) -> Iter<'_, K, V> {
+		Iter::new(&self.bytes)
+	}
+}
+
+/// Points `key` at `value_offset` in a sorted index.
+fn index_insert<K: Ord>(
+	index: &mut Vec<(K, usize)>,
+	key: K,
+	value_offset: usize,
+) {
+	match index.binary_search_by(|(k, _)| k.cmp(&key)) {
+		Ok(position) => index[position].1 = value_offset,
+		Err(position) => index.insert(position, (key, value_offset)),
+	}
+}
+
+impl<'a, K, V> IntoIterator for &'a VlenMap<K, V>
+where
+	K: Encode + Decode + Copy + Ord,
+	V: Encode + Decode + Clone,
+{
+	type Item = Result<(K, V), &'static str>;
+	type IntoIter = Iter<'a, K, V>;
+
+	#[inline]
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// Iterator over the entries of a [`VlenMap`].
+///
+/// Yields an error and stops if the bytes end inside an entry.
+#[derive(Debug, Clone)]
+pub struct Iter<'a, K, V> {
+	bytes: &'a [u8],
+	offset: usize,
+	_marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+	#[inline]
+	fn new(bytes: &'a [u8]) -> Self {
+		Self {
+			bytes,
+			offset: 0,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<K, V> Iterator for Iter<'_, K, V>
+where
+	K: Decode,
+	V: Decode,
+{
+	type Item = Result<(K, V), &'static str>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.offset >= self.bytes.len() {
+			return None;
+		}
+		let entry =
+			K::decode(&self.bytes[self.offset..]).and_then(|(key, key_len)| {
+				let value_offset = self.offset + key_len;
+				let (value, value_len) =
+					V::decode(&self.bytes[value_offset..])?;
+				Ok((key, value, value_offset + value_len))
+			});
+		match entry {
+			Ok((key, value, end)) => {
+				self.offset = end;
+				Some(Ok((key, value)))
+			},
+			Err(err) => {
+				self.offset = self.bytes.len();
+				Some(Err(err))
+			},
+		}
+	}
+}
+
+impl<K, V> FusedIterator for Iter<'_, K, V>
+where
+	K: Decode,
+	V: Decode,
+{
+}
@@ -0,0 +1,92 @@
+//! Memory-mapped file decoding via `memmap2`
+//!
+//! [`VlenFile`] maps an encoded file into memory, so large datasets can be
+//! scanned with [`decode_iter`](crate::decode::decode_iter) without reading
+//! them fully into RAM. Pages are loaded on demand by the operating system.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use vlen::mmap::VlenFile;
+//!
+//! let file = VlenFile::open("values.vlen")?;
+//! let total: u64 = file.iter::<u64>().map(Result::unwrap).sum();
+//! let tail = file.skip::<u64>(1000).unwrap().count();
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::decode::{
+	count_values, decode_iter, skip_values, Decode, DecodeIter,
+};
+
+/// A read-only memory-mapped file of vlen-encoded values.
+///
+/// The file must not be modified while it is mapped; doing so is undefined
+/// behavior on most platforms.
+#[derive(Debug)]
+pub struct VlenFile {
+	map: Mmap,
+}
+
+impl VlenFile {
+	/// Opens and memory-maps the file at `path`.
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let file = File::open(path)?;
+		// SAFETY: the mapping is read-only, and callers are documented to
+		// not modify the file while it is mapped.
+		let map = unsafe { Mmap::map(&file)? };
+		Ok(Self { map })
+	}
+
+	/// Returns the mapped bytes.
+	#[inline]
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.map
+	}
+
+	/// Returns an iterator that lazily decodes the values in the file.
+	#[inline]
+	pub fn iter<T>(&self) -> DecodeIter<'_, T>
+	where
+		T: Decode,
+	{
+		decode_iter(&self.map)
+	}
+
+	/// Counts the values in the file without storing them.
+	///
+	/// Returns an error if the file ends partway through a value.
+	#[inline]
+	pub fn count_values<T>(&self) -> Result<usize, &'static str>
+	where
+		T: Decode,
+	{
+		count_values::<T>(&self.map)
+	}
+
+	/// Returns an iterator over the values after the first `n`.
+	///
+	/// Returns an error if the file holds fewer than `n` values.
+	#[inline]
+	pub fn skip<T>(&self, n: usize) -> Result<DecodeIter<'_, T>, &'static str>
+	where
+		T: Decode,
+	{
+		let offset = skip_values::<T>(&self.map, n)?;
+		Ok(decode_iter(&self.map[offset..]))
+	}
+}
+
+impl AsRef<[u8]> for VlenFile {
+	#[inline]
+	fn as_ref(&self) -> &[u8] {
+		&self.map
+	}
+}
@@ -29,40 +29,172 @@
 //!
 
 #![cfg_attr(not(test), no_std)]
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
+#![cfg_attr(feature = "safe", forbid(unsafe_code))]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+#[cfg(all(feature = "mmap", feature = "safe"))]
+compile_error!("the `mmap` feature needs `unsafe` code and conflicts with `safe`");
+#[cfg(all(feature = "ffi", feature = "safe"))]
+compile_error!("the `ffi` feature needs `unsafe` code and conflicts with `safe`");
+#[cfg(all(feature = "rkyv", feature = "safe"))]
+compile_error!("the `rkyv` feature needs `unsafe` code and conflicts with `safe`");
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "async")]
+pub mod async_io;
+#[cfg(feature = "alloc")]
+pub mod batch;
+#[cfg(feature = "bincode")]
+pub mod bincode;
+#[cfg(feature = "num-bigint")]
+pub mod bigint;
+pub mod blocks;
+pub mod bits;
+#[cfg(feature = "borsh")]
+pub mod borsh;
+#[cfg(feature = "alloc")]
+pub mod buf;
+pub mod checksum;
+#[cfg(feature = "std")]
+pub mod column;
+#[cfg(any(feature = "zstd", feature = "lz4_flex"))]
+pub mod compress;
+pub mod decimal;
 pub mod decode;
+pub mod decoder;
+#[cfg(feature = "alloc")]
+pub mod dict;
+#[cfg(feature = "alloc")]
+pub mod diff;
 pub mod encode;
 pub mod const_decode;
 pub mod const_encode;
+pub mod convert;
+pub mod cursor;
+#[cfg(feature = "embedded-io")]
+pub mod embedded_io;
+#[cfg(feature = "alloc")]
+pub mod edit;
+pub mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod float;
+pub mod frame;
+#[cfg(feature = "heapless")]
+pub mod heapless;
+#[cfg(feature = "half")]
+pub mod half;
+pub mod header;
 mod helpers;
+#[cfg(feature = "alloc")]
+pub mod index;
+#[cfg(feature = "integer-encoding")]
+pub mod integer_encoding;
+pub mod interop;
+#[cfg(feature = "alloc")]
+pub mod map;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod net;
+#[cfg(feature = "num-traits")]
+pub mod num;
+pub mod pfor;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rev;
+#[cfg(feature = "rkyv")]
+pub mod rkyv;
+#[cfg(feature = "schemars")]
+pub mod schemars;
 #[cfg(feature = "serde")]
 pub mod serde;
-#[cfg(feature = "simd")]
+#[cfg(all(feature = "simd", not(feature = "safe")))]
+pub mod simd;
+#[cfg(all(feature = "simd", feature = "safe"))]
+#[path = "simd/safe.rs"]
 pub mod simd;
+pub mod sign;
+pub mod sink;
+pub mod sortedset;
+pub mod stats;
+pub mod time;
+#[cfg(feature = "alloc")]
+pub mod transcode;
+pub mod uuid;
+pub mod validate;
+#[cfg(feature = "alloc")]
+pub mod value;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod wrappers;
 
 // Export specific functions from decode module
 pub use decode::{
 	bulk_decode,
+	bulk_decode_counted,
+	bulk_decode_opt_u32,
+	bulk_decode_uninit,
+	count_values,
 	decode,
+	decode_any,
+	decode_checked,
+	decode_fit,
 	decode_f32,
 	decode_f64,
 	decode_i128,
 	decode_i16,
 	decode_i32,
 	decode_i64,
+	decode_iter,
 	decode_u128,
 	decode_u16,
 	decode_u32,
 	decode_u64,
+	skip_values,
+	xor_decode_u64,
 	Decode,
+	DecodeFit,
+	DecodeIter,
 };
+#[cfg(feature = "alloc")]
+pub use decode::decode_in_chunks;
+
+// Export one-shot conversions
+#[cfg(feature = "alloc")]
+pub use convert::ToVlen;
+pub use convert::FromVlen;
+
+// Export the growable buffer
+#[cfg(feature = "alloc")]
+pub use buf::VlenBuf;
+
+// Export the append-only map
+#[cfg(feature = "alloc")]
+pub use map::VlenMap;
+
+// Export the sink abstraction
+pub use sink::{encode_into, Sink};
+
+// Export the positioned cursor
+pub use cursor::VlenCursor;
+
+// Export the configurable decoder
+pub use decoder::{Decoder, DecoderIter};
 
 // Export specific functions from encode module
 pub use encode::{
 	bulk_encode,
+	bulk_encode_dyn,
+	bulk_encode_opt_u32,
+	bulk_encode_uninit,
 	encode,
 	encode_f32,
 	encode_f64,
@@ -70,27 +202,80 @@ pub use encode::{
 	encode_i16,
 	encode_i32,
 	encode_i64,
+	encode_seq,
 	encode_u128,
 	encode_u16,
 	encode_u32,
 	encode_u64,
+	encode_uninit,
 	encoded_len,
+	encoded_len_u128,
+	encoded_len_u16,
+	encoded_len_u32,
+	encoded_len_u64,
 	encoded_size,
 	encoded_size_u128,
 	encoded_size_u16,
 	encoded_size_u32,
 	encoded_size_u64,
+	xor_encode_u64,
+	DynEncode,
 	Encode,
+	ENCODED_LEN_TABLE,
 };
 
+// Export in-place buffer edits
+#[cfg(feature = "alloc")]
+pub use edit::{insert_value, remove_value, replace_value};
+
+// Export array patches
+#[cfg(feature = "alloc")]
+pub use diff::{diff_apply, diff_apply_in_place, diff_encode};
+
+// Export buffer inspection
+pub use explain::{explain, Token};
+
+// Export wire-format versioning
+pub use header::{format_version, read_header, write_header};
+
+// Export buffer validation
+pub use validate::{
+	validate,
+	validate_canonical,
+	ValidationError,
+	ValidationReport,
+};
+
+// Export UUID helpers
+pub use uuid::{decode_uuid, encode_uuid};
+
 // Export SIMD-specific functions with unique names to avoid conflicts
 #[cfg(feature = "simd")]
-pub use simd::{bulk_decode_u32_safe, bulk_encode_u32_safe};
+pub use simd::{
+	bulk_decode_u32_safe,
+	bulk_decode_u64_safe,
+	bulk_encode_u32_safe,
+	bulk_encode_u64_safe,
+	bulk_encoded_size_u32,
+	delta_bulk_decode_u32_safe,
+	delta_bulk_encode_u32_safe,
+};
 
 // Re-export the unsafe SIMD functions with unique names
+#[cfg(all(feature = "simd", not(feature = "safe")))]
+pub use simd::{
+	bulk_decode_u64, bulk_encode_u64, delta_bulk_decode_u32,
+	delta_bulk_encode_u32,
+};
 #[cfg(all(
 	feature = "simd",
-	any(target_arch = "x86_64", target_arch = "aarch64")
+	not(feature = "safe"),
+	any(
+		target_arch = "x86_64",
+		target_arch = "aarch64",
+		all(target_arch = "wasm32", target_feature = "simd128"),
+		feature = "portable-simd"
+	)
 ))]
 pub use simd::{bulk_decode_u32, bulk_encode_u32};
 
@@ -99,15 +284,201 @@ pub use simd::{bulk_decode_u32, bulk_encode_u32};
 #[cfg(feature = "alloc")]
 pub fn encode_to_vec<T>(value: T) -> Result<alloc::vec::Vec<u8>, &'static str>
 where
-	T: encode::Encode + Copy,
+	T: encode::Encode + Clone,
 {
-	let max_size = T::MAX_ENCODED_SIZE;
-	let mut buf = alloc::vec![0u8; max_size];
-	let encoded_len = T::encode(&mut buf, value)?;
-	buf.truncate(encoded_len);
+	let mut buf = alloc::vec::Vec::with_capacity(small_encoded_size::<T>());
+	append_reserved(&mut buf, value)?;
 	Ok(buf)
 }
 
+/// Encodes a value onto the end of an existing vector.
+///
+/// Reserves exactly the encoded size and returns the number of bytes
+/// appended.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn encode_append<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let len = T::encoded_size(value.clone())?;
+	vec.reserve(len);
+	append_reserved(vec, value)
+}
+
+/// Encodes multiple values onto the end of an existing vector.
+///
+/// Reserves exactly the total encoded size up front and returns the number
+/// of bytes appended.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn bulk_encode_append<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	values: &[T],
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let mut total = 0;
+	for value in values {
+		total += T::encoded_size(value.clone())?;
+	}
+	vec.reserve(total);
+	for value in values {
+		append_reserved(vec, value.clone())?;
+	}
+	Ok(total)
+}
+
+/// Error returned by the `try_` helpers when allocation fails.
+#[cfg(feature = "alloc")]
+const ALLOC_FAILED: &str = "memory allocation failed";
+
+/// Like [`encode_to_vec`], but returns an error instead of aborting if the
+/// buffer cannot be allocated.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn try_encode_to_vec<T>(
+	value: T,
+) -> Result<alloc::vec::Vec<u8>, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let mut vec = alloc::vec::Vec::new();
+	try_encode_append(&mut vec, value)?;
+	Ok(vec)
+}
+
+/// Like [`encode_append`], but returns an error instead of aborting if the
+/// space cannot be allocated. The vector is unchanged on error.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn try_encode_append<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let len = T::encoded_size(value.clone())?;
+	vec.try_reserve(len).map_err(|_| ALLOC_FAILED)?;
+	append_reserved(vec, value)
+}
+
+/// Like [`bulk_encode_to_vec`], but returns an error instead of aborting if
+/// the buffer cannot be allocated.
+///
+/// The buffer is sized exactly from the encoded sizes of the values.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn try_bulk_encode_to_vec<T>(
+	values: &[T],
+) -> Result<alloc::vec::Vec<u8>, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let mut vec = alloc::vec::Vec::new();
+	try_bulk_encode_append(&mut vec, values)?;
+	Ok(vec)
+}
+
+/// Like [`bulk_encode_append`], but returns an error instead of aborting if
+/// the space cannot be allocated. The vector is unchanged if allocation
+/// fails.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn try_bulk_encode_append<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	values: &[T],
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let mut total: usize = 0;
+	for value in values {
+		total = total
+			.checked_add(T::encoded_size(value.clone())?)
+			.ok_or(ALLOC_FAILED)?;
+	}
+	vec.try_reserve(total).map_err(|_| ALLOC_FAILED)?;
+	for value in values {
+		append_reserved(vec, value.clone())?;
+	}
+	Ok(total)
+}
+
+/// Returns the maximum encoded size of `T` if it fits the scratch buffer
+/// used for appending, and zero for larger types, which are sized exactly.
+#[cfg(feature = "alloc")]
+const fn small_encoded_size<T>() -> usize
+where
+	T: encode::Encode,
+{
+	if T::MAX_ENCODED_SIZE > 32 {
+		0
+	} else {
+		T::MAX_ENCODED_SIZE
+	}
+}
+
+/// Appends a value whose encoded size has already been reserved.
+#[cfg(all(feature = "alloc", not(feature = "safe")))]
+fn append_reserved<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	if T::MAX_ENCODED_SIZE > 32 {
+		return append_large(vec, value);
+	}
+	let start = vec.len();
+	let len = encode_uninit(vec.spare_capacity_mut(), value)?;
+	// SAFETY: `encode_uninit` initialized `len` bytes past the old length.
+	unsafe { vec.set_len(start + len) };
+	Ok(len)
+}
+
+/// Appends a value whose encoded size has already been reserved.
+#[cfg(all(feature = "alloc", feature = "safe"))]
+fn append_reserved<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let mut temp_buf = [0u8; 32];
+	if T::MAX_ENCODED_SIZE <= temp_buf.len() {
+		let len = T::encode(&mut temp_buf, value)?;
+		vec.extend_from_slice(&temp_buf[..len]);
+		return Ok(len);
+	}
+	append_large(vec, value)
+}
+
+/// Appends a value whose encoding may not fit the scratch buffer, writing it
+/// in place over zero-filled space of exactly its encoded size.
+#[cfg(feature = "alloc")]
+fn append_large<T>(
+	vec: &mut alloc::vec::Vec<u8>,
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: encode::Encode + Clone,
+{
+	let len = T::encoded_size(value.clone())?;
+	let start = vec.len();
+	vec.resize(start + len, 0);
+	let result = T::encode(&mut vec[start..], value);
+	vec.truncate(start + *result.as_ref().unwrap_or(&0));
+	result
+}
+
 /// Convenience function to decode a value from a slice.
 pub fn decode_value<T>(buf: &[u8]) -> Result<T, &'static str>
 where
@@ -124,12 +495,14 @@ pub fn bulk_encode_to_vec<T>(
 	values: &[T],
 ) -> Result<alloc::vec::Vec<u8>, &'static str>
 where
-	T: encode::Encode + Copy,
+	T: encode::Encode + Clone,
 {
-	let max_size_per_value = T::MAX_ENCODED_SIZE;
-	let mut buf = alloc::vec![0u8; values.len() * max_size_per_value];
-	let encoded_len = bulk_encode(&mut buf, values)?;
-	buf.truncate(encoded_len);
+	let max_size_per_value = small_encoded_size::<T>();
+	let mut buf =
+		alloc::vec::Vec::with_capacity(values.len() * max_size_per_value);
+	for value in values {
+		append_reserved(&mut buf, value.clone())?;
+	}
 	Ok(buf)
 }
 
@@ -142,8 +515,10 @@ pub fn bulk_decode_values<T>(
 where
 	T: decode::Decode,
 {
-	// Estimate capacity: assume average encoding is half of max size
-	let estimated_count = buf.len() / (T::MAX_ENCODED_SIZE / 2).max(1);
+	// Estimate capacity: assume average encoding is half of max size, but
+	// never more than the buffer could possibly hold
+	let estimated_count = buf.len()
+		/ (T::MAX_ENCODED_SIZE / 2).max(T::MIN_ENCODED_SIZE).max(1);
 	let mut values = alloc::vec::Vec::with_capacity(estimated_count);
 	let mut offset = 0;
 
@@ -154,3 +529,97 @@ where
 	}
 	Ok(values)
 }
+
+/// Decodes multiple values from a slice, enforcing limits on the input.
+///
+/// Returns an error if `buf` is longer than `max_bytes` or holds more than
+/// `max_values` values, so untrusted input cannot drive unbounded work or
+/// allocation.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn bulk_decode_values_bounded<T>(
+	buf: &[u8],
+	max_values: usize,
+	max_bytes: usize,
+) -> Result<alloc::vec::Vec<T>, &'static str>
+where
+	T: decode::Decode,
+{
+	if buf.len() > max_bytes {
+		return Err("buffer exceeds decoding byte limit");
+	}
+	let estimated_count = buf.len()
+		/ (T::MAX_ENCODED_SIZE / 2).max(T::MIN_ENCODED_SIZE).max(1);
+	let mut values =
+		alloc::vec::Vec::with_capacity(estimated_count.min(max_values));
+	let mut offset = 0;
+
+	while offset < buf.len() {
+		if values.len() == max_values {
+			return Err("value count exceeds decoding limit");
+		}
+		let (value, len) = T::decode(&buf[offset..])?;
+		values.push(value);
+		offset += len;
+	}
+	Ok(values)
+}
+
+/// Decodes exactly `count` values from a slice.
+///
+/// Returns an error if the buffer holds fewer or more than `count` values.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn bulk_decode_values_exact<T>(
+	buf: &[u8],
+	count: usize,
+) -> Result<alloc::vec::Vec<T>, &'static str>
+where
+	T: decode::Decode,
+{
+	if count > buf.len() / T::MIN_ENCODED_SIZE.max(1) {
+		return Err("buffer holds fewer values than expected");
+	}
+	let mut values = alloc::vec::Vec::with_capacity(count);
+	let mut offset = 0;
+
+	while values.len() < count {
+		if offset >= buf.len() {
+			return Err("buffer holds fewer values than expected");
+		}
+		let (value, len) = T::decode(&buf[offset..])?;
+		values.push(value);
+		offset += len;
+	}
+	if offset < buf.len() {
+		return Err("buffer holds more values than expected");
+	}
+	Ok(values)
+}
+
+/// Decodes a sequence written by [`encode_seq`] into a newly allocated vector.
+///
+/// Returns the decoded values and the number of bytes consumed.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn decode_seq_to_vec<T>(
+	buf: &[u8],
+) -> Result<(alloc::vec::Vec<T>, usize), &'static str>
+where
+	T: decode::Decode,
+{
+	let (count, mut offset) = u64::decode(buf)?;
+	// Every element occupies at least `T::MIN_ENCODED_SIZE` bytes, so a larger
+	// count is corrupt and must not drive the allocation size.
+	if count > ((buf.len() - offset) / T::MIN_ENCODED_SIZE.max(1)) as u64 {
+		return Err("sequence count exceeds buffer length");
+	}
+	let count = count as usize;
+	let mut values = alloc::vec::Vec::with_capacity(count);
+	while values.len() < count {
+		let (value, len) = T::decode(&buf[offset..])?;
+		values.push(value);
+		offset += len;
+	}
+	Ok((values, offset))
+}
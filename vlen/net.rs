@@ -0,0 +1,317 @@
+//! Encoding of network addresses for vlen
+//!
+//! An [`Ipv4Addr`] is encoded as its address read as a big-endian `u32`, and
+//! an [`Ipv6Addr`] as a `u128`, so they take the same vlen paths as those
+//! integers. Addresses with leading zero octets, such as `0.0.1.2` or
+//! `::ffff:7f00:1`, encode short, while typical public addresses take one
+//! byte more than their fixed width.
+//!
+//! [`IpAddr`] and [`SocketAddr`] start with a tag byte, `4` or `6`, followed
+//! by the address. A socket address appends the port as a vlen `u16`, and
+//! for IPv6 the flow info and scope ID as vlen `u32`s, so the round trip is
+//! exact.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::net::{Ipv4Addr, SocketAddr};
+//!
+//! let mut buf = [0u8; 32];
+//! let len = vlen::encode(&mut buf, Ipv4Addr::new(0, 0, 1, 2)).unwrap();
+//! assert_eq!(len, 2);
+//!
+//! let addr: SocketAddr = "[2001:db8::1]:8080".parse().unwrap();
+//! let len = vlen::encode(&mut buf, addr).unwrap();
+//! assert_eq!(vlen::decode::<SocketAddr>(&buf[..len]), Ok((addr, len)));
+//! ```
+
+use core::net::{
+	IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
+};
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+const TAG_V4: u8 = 4;
+const TAG_V6: u8 = 6;
+
+/// Writes the tag byte of a tagged address.
+#[inline]
+fn encode_tag(buf: &mut [u8], tag: u8) -> Result<usize, &'static str> {
+	*buf.first_mut()
+		.ok_or("buffer too small for address encoding")? = tag;
+	Ok(1)
+}
+
+/// Reads the tag byte of a tagged address.
+#[inline]
+fn decode_tag(buf: &[u8]) -> Result<u8, &'static str> {
+	match buf.first() {
+		Some(&tag @ (TAG_V4 | TAG_V6)) => Ok(tag),
+		Some(_) => Err("invalid address tag"),
+		None => Err("truncated vlen value"),
+	}
+}
+
+impl Encode for Ipv4Addr {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		u32::encode(buf, value.to_bits())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		u32::encoded_size(value.to_bits())
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u32 as Encode>::MAX_ENCODED_SIZE;
+}
+
+impl Decode for Ipv4Addr {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (bits, len) = u32::decode(buf)?;
+		Ok((Ipv4Addr::from_bits(bits), len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (bits, len) = u32::decode_checked(buf)?;
+		Ok((Ipv4Addr::from_bits(bits), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u32 as Decode>::MAX_ENCODED_SIZE;
+}
+
+impl Encode for Ipv6Addr {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		u128::encode(buf, value.to_bits())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		u128::encoded_size(value.to_bits())
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u128 as Encode>::MAX_ENCODED_SIZE;
+}
+
+impl Decode for Ipv6Addr {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (bits, len) = u128::decode(buf)?;
+		Ok((Ipv6Addr::from_bits(bits), len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (bits, len) = u128::decode_checked(buf)?;
+		Ok((Ipv6Addr::from_bits(bits), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u128 as Decode>::MAX_ENCODED_SIZE;
+}
+
+impl Encode for IpAddr {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		match value {
+			IpAddr::V4(addr) => {
+				let tag_len = encode_tag(buf, TAG_V4)?;
+				Ok(tag_len + Ipv4Addr::encode(&mut buf[tag_len..], addr)?)
+			},
+			IpAddr::V6(addr) => {
+				let tag_len = encode_tag(buf, TAG_V6)?;
+				Ok(tag_len + Ipv6Addr::encode(&mut buf[tag_len..], addr)?)
+			},
+		}
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		match value {
+			IpAddr::V4(addr) => Ok(1 + Ipv4Addr::encoded_size(addr)?),
+			IpAddr::V6(addr) => Ok(1 + Ipv6Addr::encoded_size(addr)?),
+		}
+	}
+
+	const MAX_ENCODED_SIZE: usize = 1 + <Ipv6Addr as Encode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+impl Decode for IpAddr {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		if decode_tag(buf)? == TAG_V4 {
+			let (addr, len) = Ipv4Addr::decode(&buf[1..])?;
+			Ok((IpAddr::V4(addr), 1 + len))
+		} else {
+			let (addr, len) = Ipv6Addr::decode(&buf[1..])?;
+			Ok((IpAddr::V6(addr), 1 + len))
+		}
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		if decode_tag(buf)? == TAG_V4 {
+			let (addr, len) = Ipv4Addr::decode_checked(&buf[1..])?;
+			Ok((IpAddr::V4(addr), 1 + len))
+		} else {
+			let (addr, len) = Ipv6Addr::decode_checked(&buf[1..])?;
+			Ok((IpAddr::V6(addr), 1 + len))
+		}
+	}
+
+	const MAX_ENCODED_SIZE: usize = 1 + <Ipv6Addr as Decode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+impl Encode for SocketAddrV4 {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		let ip_len = Ipv4Addr::encode(buf, *value.ip())?;
+		Ok(ip_len + u16::encode(&mut buf[ip_len..], value.port())?)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(Ipv4Addr::encoded_size(*value.ip())?
+			+ u16::encoded_size(value.port())?)
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<u32 as Encode>::MAX_ENCODED_SIZE + <u16 as Encode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+impl Decode for SocketAddrV4 {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (ip, ip_len) = Ipv4Addr::decode(buf)?;
+		let (port, port_len) = u16::decode(&buf[ip_len..])?;
+		Ok((SocketAddrV4::new(ip, port), ip_len + port_len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (ip, ip_len) = Ipv4Addr::decode_checked(buf)?;
+		let (port, port_len) = u16::decode_checked(&buf[ip_len..])?;
+		Ok((SocketAddrV4::new(ip, port), ip_len + port_len))
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<u32 as Decode>::MAX_ENCODED_SIZE + <u16 as Decode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 2;
+}
+
+impl Encode for SocketAddrV6 {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		let mut len = Ipv6Addr::encode(buf, *value.ip())?;
+		len += u16::encode(&mut buf[len..], value.port())?;
+		len += u32::encode(&mut buf[len..], value.flowinfo())?;
+		len += u32::encode(&mut buf[len..], value.scope_id())?;
+		Ok(len)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(Ipv6Addr::encoded_size(*value.ip())?
+			+ u16::encoded_size(value.port())?
+			+ u32::encoded_size(value.flowinfo())?
+			+ u32::encoded_size(value.scope_id())?)
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u128 as Encode>::MAX_ENCODED_SIZE
+		+ <u16 as Encode>::MAX_ENCODED_SIZE
+		+ 2 * <u32 as Encode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 4;
+}
+
+impl Decode for SocketAddrV6 {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (ip, mut len) = Ipv6Addr::decode(buf)?;
+		let (port, port_len) = u16::decode(&buf[len..])?;
+		len += port_len;
+		let (flowinfo, flowinfo_len) = u32::decode(&buf[len..])?;
+		len += flowinfo_len;
+		let (scope_id, scope_id_len) = u32::decode(&buf[len..])?;
+		len += scope_id_len;
+		Ok((SocketAddrV6::new(ip, port, flowinfo, scope_id), len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (ip, mut len) = Ipv6Addr::decode_checked(buf)?;
+		let (port, port_len) = u16::decode_checked(&buf[len..])?;
+		len += port_len;
+		let (flowinfo, flowinfo_len) = u32::decode_checked(&buf[len..])?;
+		len += flowinfo_len;
+		let (scope_id, scope_id_len) = u32::decode_checked(&buf[len..])?;
+		len += scope_id_len;
+		Ok((SocketAddrV6::new(ip, port, flowinfo, scope_id), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u128 as Decode>::MAX_ENCODED_SIZE
+		+ <u16 as Decode>::MAX_ENCODED_SIZE
+		+ 2 * <u32 as Decode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 4;
+}
+
+impl Encode for SocketAddr {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		match value {
+			SocketAddr::V4(addr) => {
+				let tag_len = encode_tag(buf, TAG_V4)?;
+				Ok(tag_len + SocketAddrV4::encode(&mut buf[tag_len..], addr)?)
+			},
+			SocketAddr::V6(addr) => {
+				let tag_len = encode_tag(buf, TAG_V6)?;
+				Ok(tag_len + SocketAddrV6::encode(&mut buf[tag_len..], addr)?)
+			},
+		}
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		match value {
+			SocketAddr::V4(addr) => Ok(1 + SocketAddrV4::encoded_size(addr)?),
+			SocketAddr::V6(addr) => Ok(1 + SocketAddrV6::encoded_size(addr)?),
+		}
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		1 + <SocketAddrV6 as Encode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 3;
+}
+
+impl Decode for SocketAddr {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		if decode_tag(buf)? == TAG_V4 {
+			let (addr, len) = SocketAddrV4::decode(&buf[1..])?;
+			Ok((SocketAddr::V4(addr), 1 + len))
+		} else {
+			let (addr, len) = SocketAddrV6::decode(&buf[1..])?;
+			Ok((SocketAddr::V6(addr), 1 + len))
+		}
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		if decode_tag(buf)? == TAG_V4 {
+			let (addr, len) = SocketAddrV4::decode_checked(&buf[1..])?;
+			Ok((SocketAddr::V4(addr), 1 + len))
+		} else {
+			let (addr, len) = SocketAddrV6::decode_checked(&buf[1..])?;
+			Ok((SocketAddr::V6(addr), 1 + len))
+		}
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		1 + <SocketAddrV6 as Decode>::MAX_ENCODED_SIZE;
+	const MIN_ENCODED_SIZE: usize = 3;
+}
@@ -0,0 +1,164 @@
+//! Generic integer support via `num-traits`
+//!
+//! Any [`PrimInt`] can be encoded without listing the concrete widths: values
+//! are routed to the narrowest built-in encoder that holds the type, using
+//! zigzag encoding for signed types. This also covers `u8`, `i8`, `usize`
+//! and `isize`, which have no dedicated encoders.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::num::{decode_prim_int, encode_prim_int, Prim};
+//!
+//! fn round_trip<T: num_traits::PrimInt>(value: T) -> T {
+//!     let mut buf = [0u8; 17];
+//!     let len = encode_prim_int(&mut buf, value).unwrap();
+//!     let (decoded, decoded_len) = decode_prim_int::<T>(&buf).unwrap();
+//!     assert_eq!(len, decoded_len);
+//!     decoded
+//! }
+//!
+//! assert_eq!(round_trip(200u8), 200);
+//! assert_eq!(round_trip(-5isize), -5);
+//!
+//! // `Prim` exposes the same dispatch through the `Encode`/`Decode` traits.
+//! let mut buf = [0u8; 17];
+//! let len = vlen::encode(&mut buf, Prim(300usize)).unwrap();
+//! assert_eq!(vlen::decode::<Prim<usize>>(&buf).unwrap(), (Prim(300), len));
+//! ```
+
+use core::mem::size_of;
+
+use num_traits::PrimInt;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// Returns the maximum encoded size of an integer type with `bytes` bytes.
+const fn max_encoded_size(bytes: usize) -> usize {
+	match bytes {
+		0..=2 => 3,
+		3..=4 => 5,
+		5..=8 => 9,
+		_ => 17,
+	}
+}
+
+#[inline]
+fn is_signed<T: PrimInt>() -> bool {
+	T::min_value() < T::zero()
+}
+
+/// Dispatches `$body` with `$wide` bound to the built-in encoding type that
+/// matches the width and signedness of `$t`.
+macro_rules! dispatch_width {
+	($t:ty, $wide:ident => $body:expr) => {
+		match (size_of::<$t>(), is_signed::<$t>()) {
+			(0..=2, false) => {
+				type $wide = u16;
+				$body
+			},
+			(0..=2, true) => {
+				type $wide = i16;
+				$body
+			},
+			(3..=4, false) => {
+				type $wide = u32;
+				$body
+			},
+			(3..=4, true) => {
+				type $wide = i32;
+				$body
+			},
+			(5..=8, false) => {
+				type $wide = u64;
+				$body
+			},
+			(5..=8, true) => {
+				type $wide = i64;
+				$body
+			},
+			(_, false) => {
+				type $wide = u128;
+				$body
+			},
+			(_, true) => {
+				type $wide = i128;
+				$body
+			},
+		}
+	};
+}
+
+/// Encodes any primitive integer, returning the encoded length.
+#[inline]
+pub fn encode_prim_int<T>(
+	buf: &mut [u8],
+	value: T,
+) -> Result<usize, &'static str>
+where
+	T: PrimInt,
+{
+	dispatch_width!(T, W => {
+		let wide = num_traits::cast::<T, W>(value)
+			.ok_or("integer too wide for vlen encoding")?;
+		W::encode(buf, wide)
+	})
+}
+
+/// Calculates the encoded size of any primitive integer without encoding it.
+#[inline]
+pub fn encoded_size_prim_int<T>(value: T) -> Result<usize, &'static str>
+where
+	T: PrimInt,
+{
+	dispatch_width!(T, W => {
+		let wide = num_traits::cast::<T, W>(value)
+			.ok_or("integer too wide for vlen encoding")?;
+		W::encoded_size(wide)
+	})
+}
+
+/// Decodes any primitive integer, returning the value and encoded length.
+///
+/// Returns an error if the encoded value does not fit in `T`.
+#[inline]
+pub fn decode_prim_int<T>(buf: &[u8]) -> Result<(T, usize), &'static str>
+where
+	T: PrimInt,
+{
+	dispatch_width!(T, W => {
+		let (wide, len) = W::decode(buf)?;
+		let value = num_traits::cast::<W, T>(wide)
+			.ok_or("value out of range for decoding")?;
+		Ok((value, len))
+	})
+}
+
+/// A wrapper that implements [`Encode`] and [`Decode`] for any [`PrimInt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Prim<T>(pub T);
+
+impl<T: PrimInt> Encode for Prim<T> {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		encode_prim_int(buf, value.0)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		encoded_size_prim_int(value.0)
+	}
+
+	const MAX_ENCODED_SIZE: usize = max_encoded_size(size_of::<T>());
+}
+
+impl<T: PrimInt> Decode for Prim<T> {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = decode_prim_int(buf)?;
+		Ok((Prim(value), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = max_encoded_size(size_of::<T>());
+}
@@ -0,0 +1,300 @@
+//! Patched bit-packing for blocks of small integers
+//!
+//! Values are split into blocks of [`BLOCK_LEN`], and each block is stored
+//! whichever way is smaller: as plain vlen values, or packed at a fixed bit
+//! width with the few values too wide for it patched in as exceptions. A
+//! block of values that all fit in 5 bits takes 80 bytes packed instead of
+//! 128 as vlen, and one large outlier costs a few bytes instead of
+//! widening the whole block.
+//!
+//! The stream starts with the value count as a vlen `u64`. A packed block is
+//! its bit width, the number of exceptions as a vlen `u32`, the low `width`
+//! bits of every value packed least significant bit first, the position of
+//! each exception as a byte, and the remaining high bits of each exception
+//! as a vlen `u32`. A vlen block is the byte `0xFF` followed by the values.
+//! The width is chosen by computing the exact size of every candidate.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::pfor;
+//!
+//! let mut values = [0u32; 256];
+//! for (i, value) in values.iter_mut().enumerate() {
+//!     *value = i as u32 % 20;
+//! }
+//! values[100] = 1_000_000;
+//!
+//! let mut buf = [0u8; pfor::encoded_size_bound(256)];
+//! let len = pfor::encode(&mut buf, &values).unwrap();
+//! // Plain vlen takes 258 bytes.
+//! assert_eq!(len, 170);
+//!
+//! let mut decoded = [0u32; 256];
+//! assert_eq!(pfor::decode(&buf[..len], &mut decoded), Ok((256, len)));
+//! assert_eq!(decoded, values);
+//! ```
+
+use crate::decode::Decode;
+use crate::encode::{encode_u32, encode_u64, encoded_size_u32};
+
+/// Number of values in a full block.
+pub const BLOCK_LEN: usize = 128;
+
+/// Block header of a block stored as plain vlen values.
+const VLEN_BLOCK: u8 = 0xFF;
+
+/// Returns an upper bound on the encoded size of `count` values.
+#[inline]
+#[must_use]
+pub const fn encoded_size_bound(count: usize) -> usize {
+	9 + count.div_ceil(BLOCK_LEN) + count * 5
+}
+
+/// Returns the number of significant bits in `value`.
+#[inline]
+fn bit_len(value: u32) -> usize {
+	(u32::BITS - value.leading_zeros()) as usize
+}
+
+/// Returns the encoded size of a value with `bits` significant bits.
+#[inline]
+fn size_for_bits(bits: usize) -> usize {
+	match bits {
+		0 => 1,
+		_ => encoded_size_u32(u32::MAX >> (32 - bits)),
+	}
+}
+
+/// Returns a mask of the low `width` bits.
+#[inline]
+fn low_mask(width: usize) -> u32 {
+	((1u64 << width) - 1) as u32
+}
+
+/// Writes `value` at the start of `dst`, which may be shorter than the
+/// largest encoding, returning the encoded length.
+#[inline]
+fn put_u32(dst: &mut [u8], value: u32) -> usize {
+	let mut temp_buf = [0u8; 5];
+	let len = encode_u32(&mut temp_buf, value);
+	dst[..len].copy_from_slice(&temp_buf[..len]);
+	len
+}
+
+/// Chooses how to store `block`, returning the bit width to pack at, or
+/// `None` for plain vlen, and the encoded size of the block.
+fn plan_block(block: &[u32]) -> (Option<usize>, usize) {
+	let mut histogram = [0usize; 33];
+	for &value in block {
+		histogram[bit_len(value)] += 1;
+	}
+	let vlen_size: usize = histogram
+		.iter()
+		.enumerate()
+		.map(|(bits, &count)| count * size_for_bits(bits))
+		.sum();
+	let mut best = (None, 1 + vlen_size);
+	for width in 0..=32 {
+		let mut exceptions = 0;
+		let mut exceptions_size = 0;
+		for (bits, &count) in histogram.iter().enumerate().skip(width + 1) {
+			exceptions += count;
+			exceptions_size += count * (1 + size_for_bits(bits - width));
+		}
+		let size = 1
+			+ encoded_size_u32(exceptions as u32)
+			+ (block.len() * width).div_ceil(8)
+			+ exceptions_size;
+		if size < best.1 {
+			best = (Some(width), size);
+		}
+	}
+	best
+}
+
+/// Encodes values as patched bit-packed blocks, returning the encoded
+/// length.
+///
+/// A buffer of [`encoded_size_bound`] bytes is always large enough.
+pub fn encode(buf: &mut [u8], values: &[u32]) -> Result<usize, &'static str> {
+	const TOO_SMALL: &str = "buffer too small for pfor encoding";
+	let mut prefix_buf = [0u8; 9];
+	let prefix_len = encode_u64(&mut prefix_buf, values.len() as u64);
+	buf.get_mut(..prefix_len)
+		.ok_or(TOO_SMALL)?
+		.copy_from_slice(&prefix_buf[..prefix_len]);
+	let mut offset = prefix_len;
+	for block in values.chunks(BLOCK_LEN) {
+		let (width, size) = plan_block(block);
+		let dst = buf.get_mut(offset..offset + size).ok_or(TOO_SMALL)?;
+		match width {
+			Some(width) => encode_packed(dst, block, width),
+			None => {
+				dst[0] = VLEN_BLOCK;
+				let mut len = 1;
+				for &value in block {
+					len += put_u32(&mut dst[len..], value);
+				}
+			},
+		}
+		offset += size;
+	}
+	Ok(offset)
+}
+
+/// Writes `block` packed at `width` bits into `dst`, which has exactly the
+/// size computed by [`plan_block`].
+fn encode_packed(dst: &mut [u8], block: &[u32], width: usize) {
+	dst[0] = width as u8;
+	let exceptions = block.iter().filter(|&&value| bit_len(value) > width);
+	let mut offset = 1 + put_u32(&mut dst[1..], exceptions.count() as u32);
+
+	let packed_len = (block.len() * width).div_ceil(8);
+	let packed = &mut dst[offset..offset + packed_len];
+	packed.fill(0);
+	let mut acc = 0u64;
+	let mut bits = 0;
+	let mut out = 0;
+	for &value in block {
+		acc |= u64::from(value & low_mask(width)) << bits;
+		bits += width;
+		while bits >= 8 {
+			packed[out] = acc as u8;
+			acc >>= 8;
+			bits -= 8;
+			out += 1;
+		}
+	}
+	if bits > 0 {
+		packed[out] = acc as u8;
+	}
+	offset += packed_len;
+
+	for (position, &value) in block.iter().enumerate() {
+		if bit_len(value) > width {
+			dst[offset] = position as u8;
+			offset += 1;
+		}
+	}
+	for &value in block {
+		if bit_len(value) > width {
+			offset += put_u32(&mut dst[offset..], value >> width);
+		}
+	}
+}
+
+/// Decodes values written by [`encode`] into `values`.
+///
+/// Returns the number of values decoded and the number of bytes consumed.
+/// Fails if `values` is shorter than the encoded sequence.
+pub fn decode(
+	buf: &[u8],
+	values: &mut [u32],
+) -> Result<(usize, usize), &'static str> {
+	let (count, mut offset) = u64::decode(buf)?;
+	let count =
+		usize::try_from(count).map_err(|_| "vlen value out of range")?;
+	let values = values
+		.get_mut(..count)
+		.ok_or("buffer holds more values than expected")?;
+	for block in values.chunks_mut(BLOCK_LEN) {
+		offset += decode_block(&buf[offset..], block)?;
+	}
+	Ok((count, offset))
+}
+
+/// Decodes one block into `block`, returning its encoded length.
+fn decode_block(buf: &[u8], block: &mut [u32]) -> Result<usize, &'static str> {
+	let width = match buf.first() {
+		Some(&VLEN_BLOCK) => {
+			let (decoded, len) =
+				crate::decode::bulk_decode_counted(&buf[1..], block)?;
+			if decoded < block.len() {
+				return Err("truncated vlen value");
+			}
+			return Ok(1 + len);
+		},
+		Some(&width) if width <= 32 => usize::from(width),
+		Some(_) => return Err("invalid pfor block header"),
+		None => return Err("truncated vlen value"),
+	};
+	let (exceptions, exceptions_len) = u32::decode(&buf[1..])?;
+	let mut offset = 1 + exceptions_len;
+
+	let packed_len = (block.len() * width).div_ceil(8);
+	let packed = buf
+		.get(offset..offset + packed_len)
+		.ok_or("truncated vlen value")?;
+	let mut bytes = packed.iter();
+	let mut acc = 0u64;
+	let mut bits = 0;
+	for value in block.iter_mut() {
+		while bits < width {
+			// The packed bytes hold exactly `width` bits per value.
+			acc |= u64::from(*bytes.next().unwrap_or(&0)) << bits;
+			bits += 8;
+		}
+		*value = acc as u32 & low_mask(width);
+		acc >>= width;
+		bits -= width;
+	}
+	offset += packed_len;
+
+	let exceptions = exceptions as usize;
+	let positions = buf
+		.get(offset..offset + exceptions)
+		.ok_or("truncated vlen value")?;
+	offset += exceptions;
+	for &position in positions {
+		let (high, len) = u32::decode(&buf[offset..])?;
+		let value = block
+			.get_mut(usize::from(position))
+			.ok_or("invalid pfor exception position")?;
+		let high = u32::try_from(u64::from(high) << width)
+			.map_err(|_| "vlen value out of range")?;
+		*value |= high;
+		offset += len;
+	}
+	Ok(offset)
+}
+
+/// Encodes values as patched bit-packed blocks into a newly allocated
+/// vector.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn encode_to_vec(
+	values: &[u32],
+) -> Result<alloc::vec::Vec<u8>, &'static str> {
+	let mut vec = alloc::vec![0; encoded_size_bound(values.len())];
+	let len = encode(&mut vec, values)?;
+	vec.truncate(len);
+	Ok(vec)
+}
+
+/// Decodes values written by [`encode`] into a newly allocated vector.
+///
+/// Returns the values and the number of bytes consumed.
+///
+/// ```rust
+/// let values: Vec<u32> = (0..1000).map(|i| i % 7).collect();
+/// let buf = vlen::pfor::encode_to_vec(&values).unwrap();
+/// assert!(buf.len() < 400);
+/// assert_eq!(vlen::pfor::decode_to_vec(&buf), Ok((values, buf.len())));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn decode_to_vec(
+	buf: &[u8],
+) -> Result<(alloc::vec::Vec<u32>, usize), &'static str> {
+	let (count, prefix_len) = u64::decode(buf)?;
+	// Every block takes at least two bytes for up to `BLOCK_LEN` values, so
+	// a larger count is corrupt and must not drive the allocation size.
+	let max_count = (buf.len() - prefix_len) / 2 * BLOCK_LEN;
+	if count > max_count as u64 {
+		return Err("sequence count exceeds buffer length");
+	}
+	let mut values = alloc::vec![0; count as usize];
+	let (_, len) = decode(buf, &mut values)?;
+	Ok((values, len))
+}
@@ -0,0 +1,93 @@
+//! Python bindings via PyO3
+//!
+//! Builds a `vlen` extension module with single-value `u64` encoding and
+//! bulk encoding of `u32`/`u64` arrays. Bulk encoding accepts any object
+//! supporting the buffer protocol, such as `numpy` arrays or `array.array`,
+//! and bulk decoding returns native-endian bytes that `numpy.frombuffer`
+//! views without copying. Errors are raised as `ValueError`.
+//!
+//! Build the module with `maturin build --features python,pyo3/extension-module`.
+//!
+//! ## Example
+//!
+//! ```python
+//! import numpy as np
+//! import vlen
+//!
+//! data = vlen.bulk_encode_u32(np.array([1, 300, 70000], dtype=np.uint32))
+//! values = np.frombuffer(vlen.bulk_decode_u32(data), dtype=np.uint32)
+//! assert vlen.decode_u64(vlen.encode_u64(300)) == (300, 2)
+//! ```
+
+use std::vec::Vec;
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::decode::Decode;
+
+fn value_error(err: &'static str) -> PyErr {
+	PyValueError::new_err(err)
+}
+
+/// Encodes a `u64`, returning the encoded bytes.
+#[pyfunction]
+pub fn encode_u64(py: Python<'_>, value: u64) -> Bound<'_, PyBytes> {
+	let mut buf = [0u8; 9];
+	let len = crate::encode::encode_u64(&mut buf, value);
+	PyBytes::new(py, &buf[..len])
+}
+
+/// Decodes a `u64` from the start of `buf`, returning the value and the
+/// number of bytes consumed.
+#[pyfunction]
+pub fn decode_u64(buf: &[u8]) -> PyResult<(u64, usize)> {
+	u64::decode(buf).map_err(value_error)
+}
+
+/// Generates the bulk bindings for a type.
+macro_rules! bulk_functions {
+	($t:ty, $encode:ident, $decode:ident) => {
+		#[doc = concat!("Encodes a buffer of `", stringify!($t), "` values, returning the encoded bytes.")]
+		#[pyfunction]
+		pub fn $encode<'py>(
+			py: Python<'py>,
+			values: PyBuffer<$t>,
+		) -> PyResult<Bound<'py, PyBytes>> {
+			let values = values.to_vec(py)?;
+			let buf = crate::bulk_encode_to_vec(&values).map_err(value_error)?;
+			Ok(PyBytes::new(py, &buf))
+		}
+
+		#[doc = concat!("Decodes every value in `buf`, returning them as native-endian `", stringify!($t), "` bytes.")]
+		#[pyfunction]
+		pub fn $decode<'py>(
+			py: Python<'py>,
+			buf: &[u8],
+		) -> PyResult<Bound<'py, PyBytes>> {
+			let values: Vec<$t> =
+				crate::bulk_decode_values(buf).map_err(value_error)?;
+			let bytes: Vec<u8> =
+				values.iter().flat_map(|value| value.to_ne_bytes()).collect();
+			Ok(PyBytes::new(py, &bytes))
+		}
+	};
+}
+
+bulk_functions!(u32, bulk_encode_u32, bulk_decode_u32);
+bulk_functions!(u64, bulk_encode_u64, bulk_decode_u64);
+
+/// The `vlen` Python module.
+#[pymodule]
+#[pyo3(name = "vlen")]
+pub fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+	m.add_function(wrap_pyfunction!(encode_u64, m)?)?;
+	m.add_function(wrap_pyfunction!(decode_u64, m)?)?;
+	m.add_function(wrap_pyfunction!(bulk_encode_u32, m)?)?;
+	m.add_function(wrap_pyfunction!(bulk_decode_u32, m)?)?;
+	m.add_function(wrap_pyfunction!(bulk_encode_u64, m)?)?;
+	m.add_function(wrap_pyfunction!(bulk_decode_u64, m)?)?;
+	Ok(())
+}
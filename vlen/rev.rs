@@ -0,0 +1,112 @@
+//! Reverse-decodable encoding for streams read from the end
+//!
+//! [`encode_u64_rev`] writes the bytes of the regular `u64` encoding in
+//! reverse order, so the prefix byte that determines the length comes last.
+//! A stream of such values can be walked backwards from its end with
+//! [`decode_u64_rev_from_end`] or [`rev_iter_u64`], as when scanning an
+//! append-only log for its most recent entries. The variant costs no extra
+//! space, but it cannot be decoded front to back.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::rev::{encode_u64_rev, rev_iter_u64};
+//!
+//! let mut log = Vec::new();
+//! for value in [1, 300, 70000] {
+//!     let mut buf = [0u8; 9];
+//!     let len = encode_u64_rev(&mut buf, value);
+//!     log.extend_from_slice(&buf[..len]);
+//! }
+//!
+//! let newest_first: Result<Vec<u64>, _> = rev_iter_u64(&log).collect();
+//! assert_eq!(newest_first.unwrap(), [70000, 300, 1]);
+//! ```
+
+use core::iter::FusedIterator;
+
+use crate::decode::decode_u64;
+use crate::encode::{encode_u64, encoded_len_u64};
+
+/// Encodes a `u64` with its length in the final byte, returning the encoded
+/// length.
+#[inline]
+pub fn encode_u64_rev(buf: &mut [u8; 9], value: u64) -> usize {
+	let len = encode_u64(buf, value);
+	buf[..len].reverse();
+	len
+}
+
+/// Decodes the `u64` that ends at the end of `buf`, returning the value and
+/// its encoded length.
+///
+/// The value starts at `buf.len() - len`.
+#[inline]
+pub fn decode_u64_rev_from_end(
+	buf: &[u8],
+) -> Result<(u64, usize), &'static str> {
+	let &last = buf.last().ok_or("truncated vlen value")?;
+	let len = encoded_len_u64(last)?;
+	let start = buf.len().checked_sub(len).ok_or("truncated vlen value")?;
+	let mut temp_buf = [0u8; 9];
+	temp_buf[..len].copy_from_slice(&buf[start..]);
+	temp_buf[..len].reverse();
+	let (value, decoded_len) = decode_u64(&temp_buf);
+	if decoded_len != len {
+		return Err("invalid vlen prefix byte");
+	}
+	Ok((value, len))
+}
+
+/// Returns an iterator that decodes values from the end of `buf` backwards.
+#[inline]
+pub fn rev_iter_u64(buf: &[u8]) -> RevIter<'_> {
+	RevIter { buf }
+}
+
+/// Iterator that decodes reverse-encoded `u64` values from the end of a
+/// buffer.
+///
+/// Yields an error and then stops if the buffer starts partway through a
+/// value. Created by [`rev_iter_u64`].
+#[derive(Debug, Clone)]
+pub struct RevIter<'a> {
+	buf: &'a [u8],
+}
+
+impl<'a> RevIter<'a> {
+	/// Returns the bytes that have not been decoded yet.
+	#[inline]
+	#[must_use]
+	pub fn remaining(&self) -> &'a [u8] {
+		self.buf
+	}
+}
+
+impl Iterator for RevIter<'_> {
+	type Item = Result<u64, &'static str>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.buf.is_empty() {
+			return None;
+		}
+		match decode_u64_rev_from_end(self.buf) {
+			Ok((value, len)) => {
+				self.buf = &self.buf[..self.buf.len() - len];
+				Some(Ok(value))
+			},
+			Err(err) => {
+				self.buf = &[];
+				Some(Err(err))
+			},
+		}
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.buf.len().min(1), Some(self.buf.len()))
+	}
+}
+
+impl FusedIterator for RevIter<'_> {}
@@ -0,0 +1,146 @@
+//! Zero-copy archiving via `rkyv`
+//!
+//! The [`Vlen*`](crate::wrappers) wrapper types archive as an
+//! [`ArchivedVlen`], which stores the compact vlen encoding of the value
+//! instead of its fixed-width native form. Archived values are decoded on
+//! access with [`ArchivedVlen::get`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rkyv::rancor::Error;
+//! use vlen::wrappers::VlenU64;
+//!
+//! let bytes = rkyv::to_bytes::<Error>(&VlenU64(300)).unwrap();
+//! let archived = rkyv::access::<vlen::rkyv::ArchivedVlen<u64>, Error>(&bytes)
+//!     .unwrap();
+//! assert_eq!(archived.as_bytes(), [0xac, 0x04]);
+//! assert_eq!(archived.get(), Ok(300));
+//!
+//! let value = rkyv::from_bytes::<VlenU64, Error>(&bytes).unwrap();
+//! assert_eq!(value, VlenU64(300));
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use ::rkyv::rancor::{Fallible, Source};
+use ::rkyv::ser::{Allocator, Writer};
+use ::rkyv::vec::{ArchivedVec, VecResolver};
+use ::rkyv::{Archive, Deserialize, Place, Portable, Serialize};
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+use crate::wrappers::{
+	VlenF32, VlenF64, VlenI128, VlenI16, VlenI32, VlenI64, VlenU128, VlenU16,
+	VlenU32, VlenU64,
+};
+
+/// The archived form of a `Vlen*` wrapper around a `T`.
+#[derive(Portable, ::rkyv::bytecheck::CheckBytes)]
+#[bytecheck(crate = ::rkyv::bytecheck)]
+#[rkyv(crate = ::rkyv)]
+#[repr(transparent)]
+pub struct ArchivedVlen<T> {
+	bytes: ArchivedVec<u8>,
+	_marker: PhantomData<T>,
+}
+
+impl<T> ArchivedVlen<T> {
+	/// Returns the stored vlen encoding.
+	#[inline]
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		self.bytes.as_slice()
+	}
+}
+
+impl<T: Decode> ArchivedVlen<T> {
+	/// Decodes the stored value.
+	#[inline]
+	pub fn get(&self) -> Result<T, &'static str> {
+		let (value, len) = T::decode(self.as_bytes())?;
+		if len != self.bytes.len() {
+			return Err("trailing bytes after archived vlen value");
+		}
+		Ok(value)
+	}
+}
+
+impl<T: Decode + fmt::Debug> fmt::Debug for ArchivedVlen<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.get() {
+			Ok(value) => value.fmt(f),
+			Err(_) => f
+				.debug_tuple("ArchivedVlen")
+				.field(&self.as_bytes())
+				.finish(),
+		}
+	}
+}
+
+/// An archived value that could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveError(pub &'static str);
+
+impl fmt::Display for ArchiveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.0)
+	}
+}
+
+impl core::error::Error for ArchiveError {}
+
+/// Implements the `rkyv` traits for wrapper types.
+macro_rules! impl_rkyv_wrapper {
+	($($wrapper:ident($inner:ty)),* $(,)?) => {$(
+		impl Archive for $wrapper {
+			type Archived = ArchivedVlen<$inner>;
+			type Resolver = VecResolver;
+
+			fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+				::rkyv::munge::munge!(let ArchivedVlen { bytes, .. } = out);
+				let len = <$inner as Encode>::encoded_size(self.0).unwrap_or(0);
+				ArchivedVec::<u8>::resolve_from_len(len, resolver, bytes);
+			}
+		}
+
+		impl<S> Serialize<S> for $wrapper
+		where
+			S: Fallible + Allocator + Writer + ?Sized,
+			S::Error: Source,
+		{
+			fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+				let mut buf = [0u8; <$inner as Encode>::MAX_ENCODED_SIZE];
+				let len = <$inner as Encode>::encode(&mut buf, self.0)
+					.map_err(|e| S::Error::new(ArchiveError(e)))?;
+				ArchivedVec::<u8>::serialize_from_slice(&buf[..len], serializer)
+			}
+		}
+
+		impl<D> Deserialize<$wrapper, D> for ArchivedVlen<$inner>
+		where
+			D: Fallible + ?Sized,
+			D::Error: Source,
+		{
+			fn deserialize(&self, _: &mut D) -> Result<$wrapper, D::Error> {
+				self.get()
+					.map($wrapper)
+					.map_err(|e| D::Error::new(ArchiveError(e)))
+			}
+		}
+	)*};
+}
+
+impl_rkyv_wrapper!(
+	VlenU16(u16),
+	VlenU32(u32),
+	VlenU64(u64),
+	VlenU128(u128),
+	VlenI16(i16),
+	VlenI32(i32),
+	VlenI64(i64),
+	VlenI128(i128),
+	VlenF32(f32),
+	VlenF64(f64),
+);
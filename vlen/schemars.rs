@@ -0,0 +1,120 @@
+//! JSON Schema support for the serde wrapper types
+//!
+//! The [`Vlen*`](crate::wrappers) wrappers and [`VlenSeq`] implement
+//! `schemars::JsonSchema`, so structs holding them can derive `JsonSchema`.
+//! JSON Schema describes the JSON form, where serde writes each value as a
+//! base64 string of its vlen encoding (see [`crate::serde`]). The schemas say
+//! so with `contentEncoding`, and bound the length of scalar values.
+//!
+//! Fields stored with `#[serde(with = "vlen::serde::seq")]` can borrow the
+//! schema of [`VlenSeq`] with `#[schemars(with = "VlenSeq<T>")]`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use schemars::JsonSchema;
+//! use vlen::wrappers::VlenU32;
+//!
+//! #[derive(JsonSchema)]
+//! struct Record {
+//!     id: VlenU32,
+//! }
+//!
+//! let schema = schemars::schema_for!(Record);
+//! let id = &schema.as_value()["properties"]["id"];
+//! assert_eq!(id["type"], "string");
+//! assert_eq!(id["contentEncoding"], "base64");
+//! assert_eq!(id["maxLength"], 8);
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::format;
+
+use ::schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::encode::Encode;
+use crate::serde::VlenSeq;
+use crate::wrappers::{
+	VlenF32, VlenF64, VlenI128, VlenI16, VlenI32, VlenI64, VlenU128, VlenU16,
+	VlenU32, VlenU64,
+};
+
+/// Implements `JsonSchema` for wrapper types.
+macro_rules! impl_schemars_wrapper {
+	($($wrapper:ident($inner:ty)),* $(,)?) => {$(
+		impl JsonSchema for $wrapper {
+			#[inline]
+			fn inline_schema() -> bool {
+				true
+			}
+
+			#[inline]
+			fn schema_name() -> Cow<'static, str> {
+				Cow::Borrowed(stringify!($wrapper))
+			}
+
+			#[inline]
+			fn schema_id() -> Cow<'static, str> {
+				Cow::Borrowed(concat!("vlen::", stringify!($wrapper)))
+			}
+
+			fn json_schema(_: &mut SchemaGenerator) -> Schema {
+				let max_len =
+					<$inner as Encode>::MAX_ENCODED_SIZE.div_ceil(3) * 4;
+				json_schema!({
+					"type": "string",
+					"contentEncoding": "base64",
+					"minLength": 4,
+					"maxLength": max_len,
+					"description": concat!(
+						"vlen encoded `",
+						stringify!($inner),
+						"`, as base64",
+					),
+				})
+			}
+		}
+	)*};
+}
+
+impl_schemars_wrapper! {
+	VlenU16(u16),
+	VlenU32(u32),
+	VlenU64(u64),
+	VlenU128(u128),
+	VlenI16(i16),
+	VlenI32(i32),
+	VlenI64(i64),
+	VlenI128(i128),
+	VlenF32(f32),
+	VlenF64(f64),
+}
+
+impl<T> JsonSchema for VlenSeq<T>
+where
+	T: JsonSchema,
+{
+	#[inline]
+	fn inline_schema() -> bool {
+		true
+	}
+
+	fn schema_name() -> Cow<'static, str> {
+		Cow::Owned(format!("VlenSeq_of_{}", T::schema_name()))
+	}
+
+	fn schema_id() -> Cow<'static, str> {
+		Cow::Owned(format!("vlen::VlenSeq<{}>", T::schema_id()))
+	}
+
+	fn json_schema(_: &mut SchemaGenerator) -> Schema {
+		json_schema!({
+			"type": "string",
+			"contentEncoding": "base64",
+			"description": format!(
+				"vlen encoded count and sequence of `{}`, as base64",
+				T::schema_name(),
+			),
+		})
+	}
+}
@@ -4,6 +4,15 @@
 //! supported numeric types using vlen encoding. This allows you to use vlen
 //! encoding with serde-based serialization formats.
 //!
+//! Binary formats such as bincode or CBOR store the encoding as a byte
+//! string. Human-readable formats such as JSON store it as a base64 string.
+//! Deserialization accepts byte strings, borrowed or owned, sequences of
+//! bytes and base64 strings from any format.
+//!
+//! With the `alloc` feature, [`VlenSeq`] and the [`seq`] helpers store a
+//! whole vector the same way, as one blob holding the element count and the
+//! encoded elements, rather than one string per element.
+//!
 //! ## Example
 //!
 //! ```rust
@@ -29,556 +38,365 @@
 //! assert_eq!(data.timestamp.0, deserialized.timestamp.0);
 //! ```
 
-#[cfg(feature = "serde")]
+use core::fmt;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::de::{self, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{decode::Decode, encode::Encode};
-use core::ops;
-
-/// A wrapper type that serializes and deserializes `u16` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenU16(pub u16);
-
-/// A wrapper type that serializes and deserializes `u32` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenU32(pub u32);
-
-/// A wrapper type that serializes and deserializes `u64` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenU64(pub u64);
-
-/// A wrapper type that serializes and deserializes `u128` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenU128(pub u128);
-
-/// A wrapper type that serializes and deserializes `i16` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenI16(pub i16);
-
-/// A wrapper type that serializes and deserializes `i32` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenI32(pub i32);
-
-/// A wrapper type that serializes and deserializes `i64` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenI64(pub i64);
-
-/// A wrapper type that serializes and deserializes `i128` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VlenI128(pub i128);
-
-/// A wrapper type that serializes and deserializes `f32` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct VlenF32(pub f32);
-
-/// A wrapper type that serializes and deserializes `f64` values using vlen encoding.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct VlenF64(pub f64);
-
-// Macro to generate serde implementations for unsigned integer types
-macro_rules! impl_serde_unsigned {
-	($wrapper:ident, $inner:ty) => {
-		#[cfg(feature = "serde")]
-		impl Serialize for $wrapper {
-			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-			where
-				S: Serializer,
-			{
-				let mut buf = [0u8; 17];
-				let len = <$inner>::encode(&mut buf, self.0)
-					.map_err(|e| serde::ser::Error::custom(e))?;
-				#[cfg(feature = "alloc")]
-				{
-					use alloc::string::String;
-					let base64 = base64::encode(&buf[..len]);
-					serializer.serialize_str(&base64)
-				}
-				#[cfg(not(feature = "alloc"))]
-				{
-					serializer.serialize_bytes(&buf[..len])
-				}
-			}
-		}
-
-		#[cfg(feature = "serde")]
-		impl<'de> Deserialize<'de> for $wrapper {
-			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-			where
-				D: Deserializer<'de>,
-			{
-				#[cfg(feature = "alloc")]
-				{
-					use alloc::string::String;
-					let s = String::deserialize(deserializer)?;
-					let bytes = base64::decode(&s)
-						.map_err(|e| serde::de::Error::custom(e))?;
-					let value = match core::any::type_name::<$inner>() {
-						"u16" => {
-							let mut arr = [0u8; 3];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"u32" => {
-							let mut arr = [0u8; 5];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"u64" => {
-							let mut arr = [0u8; 9];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"u128" => {
-							let mut arr = [0u8; 17];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						_ => {
-							return Err(serde::de::Error::custom(
-								"unsupported type for vlen deserialization",
-							))
-						},
-					};
-					let (value, _) =
-						value.map_err(|e| serde::de::Error::custom(e))?;
-					Ok($wrapper(value))
-				}
-				#[cfg(not(feature = "alloc"))]
-				{
-					let bytes = <&[u8]>::deserialize(deserializer)?;
-					let value = match core::any::type_name::<$inner>() {
-						"u16" => {
-							let mut arr = [0u8; 3];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"u32" => {
-							let mut arr = [0u8; 5];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"u64" => {
-							let mut arr = [0u8; 9];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"u128" => {
-							let mut arr = [0u8; 17];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						_ => {
-							return Err(serde::de::Error::custom(
-								"unsupported type for vlen deserialization",
-							))
-						},
-					};
-					let (value, _) =
-						value.map_err(|e| serde::de::Error::custom(e))?;
-					Ok($wrapper(value))
-				}
-			}
-		}
-	};
-}
-
-// Macro to generate serde implementations for signed integer types
-macro_rules! impl_serde_signed {
-	($wrapper:ident, $inner:ty) => {
-		#[cfg(feature = "serde")]
-		impl Serialize for $wrapper {
-			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-			where
-				S: Serializer,
-			{
-				let mut buf = [0u8; 17];
-				let len = <$inner>::encode(&mut buf, self.0)
-					.map_err(|e| serde::ser::Error::custom(e))?;
-				#[cfg(feature = "alloc")]
-				{
-					use alloc::string::String;
-					let base64 = base64::encode(&buf[..len]);
-					serializer.serialize_str(&base64)
-				}
-				#[cfg(not(feature = "alloc"))]
-				{
-					serializer.serialize_bytes(&buf[..len])
-				}
-			}
-		}
-
-		#[cfg(feature = "serde")]
-		impl<'de> Deserialize<'de> for $wrapper {
-			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-			where
-				D: Deserializer<'de>,
-			{
-				#[cfg(feature = "alloc")]
-				{
-					use alloc::string::String;
-					let s = String::deserialize(deserializer)?;
-					let bytes = base64::decode(&s)
-						.map_err(|e| serde::de::Error::custom(e))?;
-					let value = match core::any::type_name::<$inner>() {
-						"i16" => {
-							let mut arr = [0u8; 3];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"i32" => {
-							let mut arr = [0u8; 5];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"i64" => {
-							let mut arr = [0u8; 9];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"i128" => {
-							let mut arr = [0u8; 17];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						_ => {
-							return Err(serde::de::Error::custom(
-								"unsupported type for vlen deserialization",
-							))
-						},
-					};
-					let (value, _) =
-						value.map_err(|e| serde::de::Error::custom(e))?;
-					Ok($wrapper(value))
-				}
-				#[cfg(not(feature = "alloc"))]
-				{
-					let bytes = <&[u8]>::deserialize(deserializer)?;
-					let value = match core::any::type_name::<$inner>() {
-						"i16" => {
-							let mut arr = [0u8; 3];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"i32" => {
-							let mut arr = [0u8; 5];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"i64" => {
-							let mut arr = [0u8; 9];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"i128" => {
-							let mut arr = [0u8; 17];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						_ => {
-							return Err(serde::de::Error::custom(
-								"unsupported type for vlen deserialization",
-							))
-						},
-					};
-					let (value, _) =
-						value.map_err(|e| serde::de::Error::custom(e))?;
-					Ok($wrapper(value))
-				}
-			}
-		}
-	};
-}
-
-// Macro to generate serde implementations for floating-point types
-macro_rules! impl_serde_float {
-	($wrapper:ident, $inner:ty) => {
-		#[cfg(feature = "serde")]
-		impl Serialize for $wrapper {
-			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-			where
-				S: Serializer,
-			{
-				let mut buf = [0u8; 17];
-				let len = <$inner>::encode(&mut buf, self.0)
-					.map_err(|e| serde::ser::Error::custom(e))?;
-				#[cfg(feature = "alloc")]
-				{
-					use alloc::string::String;
-					let base64 = base64::encode(&buf[..len]);
-					serializer.serialize_str(&base64)
-				}
-				#[cfg(not(feature = "alloc"))]
-				{
-					serializer.serialize_bytes(&buf[..len])
-				}
-			}
-		}
-
-		#[cfg(feature = "serde")]
-		impl<'de> Deserialize<'de> for $wrapper {
-			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-			where
-				D: Deserializer<'de>,
-			{
-				#[cfg(feature = "alloc")]
-				{
-					use alloc::string::String;
-					let s = String::deserialize(deserializer)?;
-					let bytes = base64::decode(&s)
-						.map_err(|e| serde::de::Error::custom(e))?;
-					let value = match core::any::type_name::<$inner>() {
-						"f32" => {
-							let mut arr = [0u8; 5];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						"f64" => {
-							let mut arr = [0u8; 9];
-							arr[..bytes.len()].copy_from_slice(&bytes);
-							<$inner>::decode(&arr)
-						},
-						_ => {
-							return Err(serde::de::Error::custom(
-								"unsupported type for vlen deserialization",
-							))
-						},
-					};
-					let (value, _) =
-						value.map_err(|e| serde::de::Error::custom(e))?;
-					Ok($wrapper(value))
-				}
-				#[cfg(not(feature = "alloc"))]
-				{
-					let bytes = <&[u8]>::deserialize(deserializer)?;
-					let value = match core::any::type_name::<$inner>() {
-						"f32" => {
-							let mut arr = [0u8; 5];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						"f64" => {
-							let mut arr = [0u8; 9];
-							arr[..bytes.len()].copy_from_slice(bytes);
-							<$inner>::decode(&arr)
-						},
-						_ => {
-							return Err(serde::de::Error::custom(
-								"unsupported type for vlen deserialization",
-							))
-						},
-					};
-					let (value, _) =
-						value.map_err(|e| serde::de::Error::custom(e))?;
-					Ok($wrapper(value))
-				}
-			}
-		}
-	};
-}
 
-// Generate serde implementations for all types
-impl_serde_unsigned!(VlenU16, u16);
-impl_serde_unsigned!(VlenU32, u32);
-impl_serde_unsigned!(VlenU64, u64);
-impl_serde_unsigned!(VlenU128, u128);
+pub use crate::wrappers::{
+	VlenF32, VlenF64, VlenI128, VlenI16, VlenI32, VlenI64, VlenU128, VlenU16,
+	VlenU32, VlenU64,
+};
 
-impl_serde_signed!(VlenI16, i16);
-impl_serde_signed!(VlenI32, i32);
-impl_serde_signed!(VlenI64, i64);
-impl_serde_signed!(VlenI128, i128);
+/// Longest encoding of any wrapped type.
+const MAX_ENCODED_LEN: usize = 17;
 
-impl_serde_float!(VlenF32, f32);
-impl_serde_float!(VlenF64, f64);
+/// Longest base64 text of an encoding.
+const MAX_BASE64_LEN: usize = MAX_ENCODED_LEN.div_ceil(3) * 4;
 
-// Implement From traits for easy conversion
-impl From<u16> for VlenU16 {
-	fn from(value: u16) -> Self {
-		VlenU16(value)
+/// Writes an encoding as bytes, or as base64 for human-readable formats.
+fn serialize_encoded<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+	S: Serializer,
+{
+	if !serializer.is_human_readable() {
+		return serializer.serialize_bytes(bytes);
 	}
+	let mut text = [0u8; MAX_BASE64_LEN];
+	let len = STANDARD
+		.encode_slice(bytes, &mut text)
+		.map_err(serde::ser::Error::custom)?;
+	// Base64 output is always ASCII.
+	let text = core::str::from_utf8(&text[..len])
+		.map_err(serde::ser::Error::custom)?;
+	serializer.serialize_str(text)
 }
 
-impl From<u32> for VlenU32 {
-	fn from(value: u32) -> Self {
-		VlenU32(value)
-	}
+/// An encoding read back by [`deserialize_encoded`].
+struct Encoded {
+	buf: [u8; MAX_ENCODED_LEN],
+	len: usize,
 }
 
-impl From<u64> for VlenU64 {
-	fn from(value: u64) -> Self {
-		VlenU64(value)
+impl Encoded {
+	fn as_slice(&self) -> &[u8] {
+		&self.buf[..self.len]
 	}
-}
 
-impl From<u128> for VlenU128 {
-	fn from(value: u128) -> Self {
-		VlenU128(value)
+	fn from_bytes<E>(bytes: &[u8]) -> Result<Self, E>
+	where
+		E: de::Error,
+	{
+		let mut encoded = Self {
+			buf: [0; MAX_ENCODED_LEN],
+			len: bytes.len(),
+		};
+		encoded
+			.buf
+			.get_mut(..bytes.len())
+			.ok_or_else(|| E::invalid_length(bytes.len(), &EncodedVisitor))?
+			.copy_from_slice(bytes);
+		Ok(encoded)
 	}
 }
 
-impl From<i16> for VlenI16 {
-	fn from(value: i16) -> Self {
-		VlenI16(value)
-	}
-}
+struct EncodedVisitor;
 
-impl From<i32> for VlenI32 {
-	fn from(value: i32) -> Self {
-		VlenI32(value)
-	}
-}
+impl<'de> Visitor<'de> for EncodedVisitor {
+	type Value = Encoded;
 
-impl From<i64> for VlenI64 {
-	fn from(value: i64) -> Self {
-		VlenI64(value)
+	fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "a vlen encoding of at most {MAX_ENCODED_LEN} bytes")
 	}
-}
 
-impl From<i128> for VlenI128 {
-	fn from(value: i128) -> Self {
-		VlenI128(value)
+	fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Encoded, E>
+	where
+		E: de::Error,
+	{
+		Encoded::from_bytes(bytes)
 	}
-}
 
-impl From<f32> for VlenF32 {
-	fn from(value: f32) -> Self {
-		VlenF32(value)
+	fn visit_str<E>(self, text: &str) -> Result<Encoded, E>
+	where
+		E: de::Error,
+	{
+		if text.len() > MAX_BASE64_LEN {
+			return Err(E::invalid_length(text.len(), &self));
+		}
+		let mut buf = [0u8; MAX_BASE64_LEN];
+		let len = STANDARD.decode_slice(text, &mut buf).map_err(E::custom)?;
+		Encoded::from_bytes(&buf[..len])
 	}
-}
 
-impl From<f64> for VlenF64 {
-	fn from(value: f64) -> Self {
-		VlenF64(value)
+	fn visit_seq<A>(self, mut seq: A) -> Result<Encoded, A::Error>
+	where
+		A: SeqAccess<'de>,
+	{
+		let mut encoded = Encoded {
+			buf: [0; MAX_ENCODED_LEN],
+			len: 0,
+		};
+		while let Some(byte) = seq.next_element()? {
+			if encoded.len == MAX_ENCODED_LEN {
+				return Err(de::Error::invalid_length(encoded.len + 1, &self));
+			}
+			encoded.buf[encoded.len] = byte;
+			encoded.len += 1;
+		}
+		Ok(encoded)
 	}
 }
 
-// Implement Deref for easy access to inner values
-impl ops::Deref for VlenU16 {
-	type Target = u16;
-	fn deref(&self) -> &Self::Target {
-		&self.0
+/// Reads an encoding written by [`serialize_encoded`].
+fn deserialize_encoded<'de, D>(deserializer: D) -> Result<Encoded, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	if deserializer.is_human_readable() {
+		deserializer.deserialize_str(EncodedVisitor)
+	} else {
+		deserializer.deserialize_bytes(EncodedVisitor)
 	}
 }
 
-impl ops::Deref for VlenU32 {
-	type Target = u32;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
-}
+// Macro to generate serde implementations for a wrapper type
+macro_rules! impl_serde {
+	($($wrapper:ident($inner:ty)),* $(,)?) => {
+		$(
+			const _: () = assert!(
+				<$inner as Encode>::MAX_ENCODED_SIZE <= MAX_ENCODED_LEN
+			);
 
-impl ops::Deref for VlenU64 {
-	type Target = u64;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
-}
+			impl Serialize for $wrapper {
+				fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+				where
+					S: Serializer,
+				{
+					let mut buf = [0u8; <$inner as Encode>::MAX_ENCODED_SIZE];
+					let len = <$inner>::encode(&mut buf, self.0)
+						.map_err(serde::ser::Error::custom)?;
+					serialize_encoded(&buf[..len], serializer)
+				}
+			}
 
-impl ops::Deref for VlenU128 {
-	type Target = u128;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
+			impl<'de> Deserialize<'de> for $wrapper {
+				fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+				where
+					D: Deserializer<'de>,
+				{
+					let encoded = deserialize_encoded(deserializer)?;
+					let bytes = encoded.as_slice();
+					let (value, len) = <$inner>::decode_checked(bytes)
+						.map_err(de::Error::custom)?;
+					if len != bytes.len() {
+						return Err(de::Error::custom(
+							"trailing bytes after vlen value",
+						));
+					}
+					Ok($wrapper(value))
+				}
+			}
+		)*
+	};
 }
 
-impl ops::Deref for VlenI16 {
-	type Target = i16;
-	fn deref(&self) -> &Self::Target {
-		&self.0
+impl_serde! {
+	VlenU16(u16),
+	VlenU32(u32),
+	VlenU64(u64),
+	VlenU128(u128),
+	VlenI16(i16),
+	VlenI32(i32),
+	VlenI64(i64),
+	VlenI128(i128),
+	VlenF32(f32),
+	VlenF64(f64),
+}
+
+/// Serde helpers that store a vector as one vlen blob.
+///
+/// The blob is the element count as a vlen `u64` followed by the encoded
+/// elements, as written by [`encode_seq`](crate::encode_seq). Use the module
+/// on a `Vec<T>` field:
+///
+/// ```rust
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Column {
+///     #[serde(with = "vlen::serde::seq")]
+///     values: Vec<u32>,
+/// }
+///
+/// let column = Column { values: vec![1, 300, 70000] };
+/// let json = serde_json::to_string(&column).unwrap();
+/// assert_eq!(json, r#"{"values":"AwGsBNCLCA=="}"#);
+/// let decoded: Column = serde_json::from_str(&json).unwrap();
+/// assert_eq!(decoded.values, column.values);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub mod seq {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+	use core::fmt;
+
+	use base64::engine::general_purpose::STANDARD;
+	use base64::Engine;
+	use serde::de::{self, SeqAccess, Visitor};
+	use serde::{Deserializer, Serializer};
+
+	use crate::{decode::Decode, encode::Encode};
+
+	/// Serializes `values` as a single blob.
+	pub fn serialize<T, S>(
+		values: &[T],
+		serializer: S,
+	) -> Result<S::Ok, S::Error>
+	where
+		T: Encode + Clone,
+		S: Serializer,
+	{
+		let mut blob = Vec::new();
+		crate::encode_append(&mut blob, values.len() as u64)
+			.map_err(serde::ser::Error::custom)?;
+		crate::bulk_encode_append(&mut blob, values)
+			.map_err(serde::ser::Error::custom)?;
+		if serializer.is_human_readable() {
+			serializer.serialize_str(&STANDARD.encode(&blob))
+		} else {
+			serializer.serialize_bytes(&blob)
+		}
 	}
-}
 
-impl ops::Deref for VlenI32 {
-	type Target = i32;
-	fn deref(&self) -> &Self::Target {
-		&self.0
+	/// Deserializes a blob written by [`serialize`].
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+	where
+		T: Decode,
+		D: Deserializer<'de>,
+	{
+		let blob = if deserializer.is_human_readable() {
+			deserializer.deserialize_str(BlobVisitor)?
+		} else {
+			deserializer.deserialize_byte_buf(BlobVisitor)?
+		};
+		let (values, len) =
+			crate::decode_seq_to_vec(&blob).map_err(de::Error::custom)?;
+		if len != blob.len() {
+			return Err(de::Error::custom(
+				"trailing bytes after vlen sequence",
+			));
+		}
+		Ok(values)
 	}
-}
 
-impl ops::Deref for VlenI64 {
-	type Target = i64;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
-}
+	struct BlobVisitor;
 
-impl ops::Deref for VlenI128 {
-	type Target = i128;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
-}
+	impl<'de> Visitor<'de> for BlobVisitor {
+		type Value = Vec<u8>;
 
-impl ops::Deref for VlenF32 {
-	type Target = f32;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
-}
+		fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			f.write_str("a vlen encoded sequence")
+		}
 
-impl ops::Deref for VlenF64 {
-	type Target = f64;
-	fn deref(&self) -> &Self::Target {
-		&self.0
-	}
-}
+		fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Vec<u8>, E>
+		where
+			E: de::Error,
+		{
+			Ok(bytes.to_vec())
+		}
 
-// Implement DerefMut for mutable access
-impl ops::DerefMut for VlenU16 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
-	}
-}
+		fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E>
+		where
+			E: de::Error,
+		{
+			Ok(bytes)
+		}
 
-impl ops::DerefMut for VlenU32 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
-	}
-}
+		fn visit_str<E>(self, text: &str) -> Result<Vec<u8>, E>
+		where
+			E: de::Error,
+		{
+			STANDARD.decode(text).map_err(E::custom)
+		}
 
-impl ops::DerefMut for VlenU64 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
-	}
-}
+		fn visit_string<E>(self, text: String) -> Result<Vec<u8>, E>
+		where
+			E: de::Error,
+		{
+			self.visit_str(&text)
+		}
 
-impl ops::DerefMut for VlenU128 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
+		fn visit_seq<A>(self, mut seq: A) -> Result<Vec<u8>, A::Error>
+		where
+			A: SeqAccess<'de>,
+		{
+			let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+			while let Some(byte) = seq.next_element()? {
+				bytes.push(byte);
+			}
+			Ok(bytes)
+		}
 	}
 }
 
-impl ops::DerefMut for VlenI16 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
-	}
-}
+/// A vector that serializes as one vlen blob.
+///
+/// See [`seq`] for the format; this wrapper applies it without a field
+/// attribute, for example inside other containers.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct VlenSeq<T>(pub alloc::vec::Vec<T>);
 
-impl ops::DerefMut for VlenI32 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
+#[cfg(feature = "alloc")]
+impl<T> From<alloc::vec::Vec<T>> for VlenSeq<T> {
+	fn from(values: alloc::vec::Vec<T>) -> Self {
+		VlenSeq(values)
 	}
 }
 
-impl ops::DerefMut for VlenI64 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
+#[cfg(feature = "alloc")]
+impl<T> core::ops::Deref for VlenSeq<T> {
+	type Target = alloc::vec::Vec<T>;
+	fn deref(&self) -> &Self::Target {
+		&self.0
 	}
 }
 
-impl ops::DerefMut for VlenI128 {
+#[cfg(feature = "alloc")]
+impl<T> core::ops::DerefMut for VlenSeq<T> {
 	fn deref_mut(&mut self) -> &mut Self::Target {
 		&mut self.0
 	}
 }
 
-impl ops::DerefMut for VlenF32 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
+#[cfg(feature = "alloc")]
+impl<T> Serialize for VlenSeq<T>
+where
+	T: Encode + Clone,
+{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		seq::serialize(&self.0, serializer)
 	}
 }
 
-impl ops::DerefMut for VlenF64 {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
+#[cfg(feature = "alloc")]
+impl<'de, T> Deserialize<'de> for VlenSeq<T>
+where
+	T: Decode,
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		seq::deserialize(deserializer).map(VlenSeq)
 	}
 }
@@ -0,0 +1,169 @@
+//! Choice of sign representation for signed integers
+//!
+//! Signed integers are zigzag encoded by default: `0, -1, 1, -2, …` map to
+//! `0, 1, 2, 3, …` before the unsigned encoding. The sign-bit mode instead
+//! stores the magnitude shifted left by one with the sign in the low bit, so
+//! the magnitude can be read back with a plain shift, as sign-magnitude wire
+//! formats expect. The otherwise unused negative zero stands for the
+//! minimum value.
+//!
+//! The sign costs one bit either way, so the two modes give non-negative
+//! values identical encodings. A negative value takes the same length in
+//! both, except at the very top of a length class, where the sign-bit form
+//! needs one byte more (`-64` takes two bytes instead of one).
+//!
+//! The mode is chosen per call with [`encode_signed`] and [`decode_signed`],
+//! or in the type with the [`SignBit`] wrapper.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::sign::{decode_signed, encode_signed, SignBit, SignMode};
+//!
+//! let mut buf = [0u8; 9];
+//! let len = encode_signed(&mut buf, -5i64, SignMode::SignBit).unwrap();
+//! assert_eq!(buf[0], 5 << 1 | 1);
+//! assert_eq!(decode_signed::<i64>(&buf, SignMode::SignBit), Ok((-5, len)));
+//!
+//! let len = vlen::encode(&mut buf, SignBit(-5i64)).unwrap();
+//! let value = vlen::decode_value::<SignBit<i64>>(&buf[..len]);
+//! assert_eq!(value, Ok(SignBit(-5)));
+//! ```
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// How the sign of a signed integer is represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SignMode {
+	/// Zigzag mapping, as used by the `Encode` and `Decode` implementations.
+	#[default]
+	ZigZag,
+	/// Magnitude shifted left by one with the sign in the low bit.
+	SignBit,
+}
+
+/// Signed integers that support both sign representations.
+pub trait SignedInt: Encode + Decode + Copy {
+	/// The unsigned integer of the same width.
+	type Unsigned: Encode + Decode + Copy;
+
+	/// Maps a value to its sign-bit form.
+	fn to_sign_bit(self) -> Self::Unsigned;
+
+	/// Reverses [`to_sign_bit`](Self::to_sign_bit).
+	fn from_sign_bit(bits: Self::Unsigned) -> Self;
+}
+
+macro_rules! impl_signed_int {
+	($($t:ty => $ut:ty),* $(,)?) => {
+		$(
+			impl SignedInt for $t {
+				type Unsigned = $ut;
+
+				#[inline]
+				fn to_sign_bit(self) -> $ut {
+					if self == <$t>::MIN {
+						1
+					} else {
+						self.unsigned_abs() << 1 | <$ut>::from(self < 0)
+					}
+				}
+
+				#[inline]
+				fn from_sign_bit(bits: $ut) -> $t {
+					let magnitude = (bits >> 1) as $t;
+					match (bits & 1 != 0, magnitude) {
+						(true, 0) => <$t>::MIN,
+						(true, _) => -magnitude,
+						(false, _) => magnitude,
+					}
+				}
+			}
+		)*
+	};
+}
+
+impl_signed_int!(i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+/// Encodes a signed integer with the given sign representation, returning
+/// the encoded length.
+#[inline]
+pub fn encode_signed<T>(
+	buf: &mut [u8],
+	value: T,
+	mode: SignMode,
+) -> Result<usize, &'static str>
+where
+	T: SignedInt,
+{
+	match mode {
+		SignMode::ZigZag => T::encode(buf, value),
+		SignMode::SignBit => T::Unsigned::encode(buf, value.to_sign_bit()),
+	}
+}
+
+/// Decodes a signed integer with the given sign representation, returning
+/// the value and encoded length.
+#[inline]
+pub fn decode_signed<T>(
+	buf: &[u8],
+	mode: SignMode,
+) -> Result<(T, usize), &'static str>
+where
+	T: SignedInt,
+{
+	match mode {
+		SignMode::ZigZag => T::decode(buf),
+		SignMode::SignBit => {
+			let (bits, len) = T::Unsigned::decode(buf)?;
+			Ok((T::from_sign_bit(bits), len))
+		},
+	}
+}
+
+/// A signed integer that encodes with [`SignMode::SignBit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct SignBit<T>(pub T);
+
+impl<T> Encode for SignBit<T>
+where
+	T: SignedInt,
+{
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		T::Unsigned::encode(buf, value.0.to_sign_bit())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		T::Unsigned::encoded_size(value.0.to_sign_bit())
+	}
+
+	const MAX_ENCODED_SIZE: usize = <T::Unsigned as Encode>::MAX_ENCODED_SIZE;
+}
+
+impl<T> Decode for SignBit<T>
+where
+	T: SignedInt,
+{
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (bits, len) = T::Unsigned::decode(buf)?;
+		Ok((SignBit(T::from_sign_bit(bits)), len))
+	}
+
+	#[inline]
+	fn decode_checked(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (bits, len) = T::Unsigned::decode_checked(buf)?;
+		Ok((SignBit(T::from_sign_bit(bits)), len))
+	}
+
+	#[inline]
+	fn check_prefix(first: u8) -> Result<(), &'static str> {
+		T::Unsigned::check_prefix(first)
+	}
+
+	const MAX_ENCODED_SIZE: usize = <T::Unsigned as Decode>::MAX_ENCODED_SIZE;
+}
@@ -1,6 +1,10 @@
 //! aarch64 SIMD implementation using ARM NEON instructions
 
-use super::{handle_remaining_decode, handle_remaining_encode, SimdImpl};
+use super::{
+	group_len, handle_remaining_decode, handle_remaining_encode,
+	prefix_sum_chunked, store_group, sum_encoded_sizes_u32, SimdImpl,
+	SIZE_BOUNDARIES_U32,
+};
 
 #[cfg(not(test))]
 use core::arch::aarch64::*;
@@ -54,24 +58,100 @@ impl SimdImpl for Aarch64Simd {
 		let mut offset = 0;
 		let mut i = 0;
 
-		while i + 3 < values.len() && offset + 20 <= buf.len() {
-			let first_byte = buf[offset];
-			let bytes_needed = if first_byte < 0xC0 {
-				decode_2byte(buf, offset, values, i)
-			} else if first_byte < 0xE0 {
-				decode_3byte(buf, offset, values, i)
-			} else if first_byte < 0xF0 {
-				decode_4byte(buf, offset, values, i)
-			} else {
-				decode_5byte(buf, offset, values, i)
+		while i + 4 <= values.len() {
+			let Some(len) = group_len(buf, offset) else {
+				if offset >= buf.len() {
+					break;
+				}
+				let group = &mut values[..=i];
+				offset = handle_remaining_decode(buf, group, offset, i);
+				i += 1;
+				continue;
 			};
-
-			offset = bytes_needed;
+			let decoded = match len {
+				1 => decode_unary(load_partial::<4>(buf, offset), len),
+				2 => decode_unary(load_partial::<8>(buf, offset), len),
+				3 => decode_unary(load_partial::<12>(buf, offset), len),
+				4 => decode_unary(load_partial::<16>(buf, offset), len),
+				_ => decode_5byte(buf, offset),
+			};
+			store_values(values, i, decoded);
+			offset += 4 * len;
 			i += 4;
 		}
 
 		handle_remaining_decode(buf, values, offset, i)
 	}
+
+	#[inline]
+	fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+		sum_encoded_sizes_u32(values, |block| unsafe {
+			count_boundaries(block)
+		})
+	}
+
+	#[inline]
+	fn prefix_sum_u32(prev: u32, values: &mut [u32]) -> u32 {
+		prefix_sum_chunked(prev, values, |prev, body| unsafe {
+			prefix_sum_kernel(prev, body)
+		})
+	}
+}
+
+/// Prefix sums whole 4-value chunks of `values` in place, returning the
+/// running sum after them.
+///
+/// Two shift-and-add steps give each lane the sum of the lanes up to it,
+/// shifting zeros in with `ext`, and the running sum so far is added to
+/// every lane.
+#[inline]
+unsafe fn prefix_sum_kernel(prev: u32, values: &mut [u32]) -> u32 {
+	let zero = vdupq_n_u32(0);
+	let mut carry = vdupq_n_u32(prev);
+	for chunk in values.chunks_exact_mut(4) {
+		let mut sums = vld1q_u32(chunk.as_ptr());
+		sums = vaddq_u32(sums, vextq_u32(zero, sums, 3));
+		sums = vaddq_u32(sums, vextq_u32(zero, sums, 2));
+		sums = vaddq_u32(sums, carry);
+		vst1q_u32(chunk.as_mut_ptr(), sums);
+		carry = vdupq_laneq_u32(sums, 3);
+	}
+	vgetq_lane_u32(carry, 0)
+}
+
+/// Counts how many size class boundaries each value in `block` reaches.
+#[inline]
+unsafe fn count_boundaries(block: &[u32]) -> usize {
+	let bounds = SIZE_BOUNDARIES_U32.map(|bound| vdupq_n_u32(bound));
+	let mut counts = vdupq_n_u32(0);
+	for chunk in block.chunks_exact(4) {
+		let values = vld1q_u32(chunk.as_ptr());
+		for bound in bounds {
+			// Each reached boundary sets the lane to all ones, i.e. -1.
+			counts = vsubq_u32(counts, vcgeq_u32(values, bound));
+		}
+	}
+	vaddvq_u32(counts) as usize
+}
+
+/// Loads the `N` bytes of `buf` at `offset` into the low bytes of a
+/// vector, zeroing the rest, without reading past them.
+#[inline]
+unsafe fn load_partial<const N: usize>(
+	buf: &[u8],
+	offset: usize,
+) -> uint8x16_t {
+	debug_assert!(offset + N <= buf.len(), "SIMD load past end of buffer");
+	let mut bytes = [0u8; 16];
+	bytes[..N].copy_from_slice(buf.get_unchecked(offset..offset + N));
+	vld1q_u8(bytes.as_ptr())
+}
+
+/// Stores four decoded values to `values[i..i + 4]`.
+#[inline]
+unsafe fn store_values(values: &mut [u32], i: usize, decoded: uint32x4_t) {
+	debug_assert!(i + 4 <= values.len(), "SIMD store past end of values");
+	vst1q_u32(values.as_mut_ptr().add(i), decoded);
 }
 
 #[inline]
@@ -95,7 +175,7 @@ unsafe fn encode_2byte(
 	combined[6] = 0x80 | ((v3 & 0x3F) as u8);
 	combined[7] = (v3 >> 6) as u8;
 
-	vst1q_u8(buf.as_mut_ptr().add(offset), vld1q_u8(combined.as_ptr()));
+	store_group(buf, offset, &combined);
 	8
 }
 
@@ -124,11 +204,7 @@ unsafe fn encode_3byte(
 	combined[10] = (v3 >> 5) as u8;
 	combined[11] = (v3 >> 13) as u8;
 
-	vst1q_u8(buf.as_mut_ptr().add(offset), vld1q_u8(combined.as_ptr()));
-	vst1_u8(
-		buf.as_mut_ptr().add(offset + 8),
-		vld1_u8(combined.as_ptr().add(8)),
-	);
+	store_group(buf, offset, &combined);
 	12
 }
 
@@ -161,11 +237,7 @@ unsafe fn encode_4byte(
 	combined[14] = (v3 >> 12) as u8;
 	combined[15] = (v3 >> 20) as u8;
 
-	vst1q_u8(buf.as_mut_ptr().add(offset), vld1q_u8(combined.as_ptr()));
-	vst1q_u8(
-		buf.as_mut_ptr().add(offset + 8),
-		vld1q_u8(combined.as_ptr().add(8)),
-	);
+	store_group(buf, offset, &combined);
 	16
 }
 
@@ -202,112 +274,43 @@ unsafe fn encode_5byte(
 	combined[18] = (v3 >> 16) as u8;
 	combined[19] = (v3 >> 24) as u8;
 
-	vst1q_u8(buf.as_mut_ptr().add(offset), vld1q_u8(combined.as_ptr()));
-	vst1q_u8(
-		buf.as_mut_ptr().add(offset + 8),
-		vld1q_u8(combined.as_ptr().add(8)),
-	);
-	vst1_u32(
-		buf.as_mut_ptr().add(offset + 16) as *mut u32,
-		vld1_u32(combined.as_ptr().add(16) as *const u32),
-	);
+	store_group(buf, offset, &combined);
 	20
 }
 
+/// Table indices moving each value of a group into its own lane, by
+/// encoded length. Indices past the table zero the byte.
+const GATHER: [[u8; 16]; 4] = [
+	[0, 16, 16, 16, 1, 16, 16, 16, 2, 16, 16, 16, 3, 16, 16, 16],
+	[0, 1, 16, 16, 2, 3, 16, 16, 4, 5, 16, 16, 6, 7, 16, 16],
+	[0, 1, 2, 16, 3, 4, 5, 16, 6, 7, 8, 16, 9, 10, 11, 16],
+	[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+];
+
+/// Decodes four values with a unary length prefix of `len` bytes each from
+/// the low `4 * len` bytes of `data`.
+///
+/// Each value is the low bits of its prefix byte with the payload bytes
+/// placed above them.
 #[inline]
-unsafe fn decode_2byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let data = vld1q_u8(buf.as_ptr().add(offset));
-
-	let low_bits = vandq_u8(data, vdupq_n_u8(0x3F));
-	let high_bits = vshlq_n_u32(vreinterpretq_u32_u8(vshrq_n_u8(data, 1)), 6);
-	let combined = vorrq_u32(vreinterpretq_u32_u8(low_bits), high_bits);
-
-	vst1q_u32(values.as_mut_ptr().add(i), combined);
-
-	offset + 8
-}
-
-#[inline]
-unsafe fn decode_3byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let data_low = vld1q_u8(buf.as_ptr().add(offset));
-
-	let low_bits = vandq_u8(data_low, vdupq_n_u8(0x1F));
-	let mid_bits =
-		vshlq_n_u32(vreinterpretq_u32_u8(vshrq_n_u8(data_low, 1)), 5);
-	let high_bits =
-		vshlq_n_u32(vreinterpretq_u32_u8(vshrq_n_u8(data_low, 2)), 13);
-
-	let combined = vorrq_u32(
-		vorrq_u32(vreinterpretq_u32_u8(low_bits), mid_bits),
-		high_bits,
-	);
-
-	vst1q_u32(values.as_mut_ptr().add(i), combined);
-
-	offset + 12
-}
-
-#[inline]
-unsafe fn decode_4byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let data = vld1q_u8(buf.as_ptr().add(offset));
-
-	let low_bits = vandq_u8(data, vdupq_n_u8(0x0F));
-	let mid1_bits = vshlq_n_u32(vreinterpretq_u32_u8(vshrq_n_u8(data, 1)), 4);
-	let mid2_bits = vshlq_n_u32(vreinterpretq_u32_u8(vshrq_n_u8(data, 2)), 12);
-	let high_bits = vshlq_n_u32(vreinterpretq_u32_u8(vshrq_n_u8(data, 3)), 20);
-
-	let combined = vorrq_u32(
-		vorrq_u32(
-			vorrq_u32(vreinterpretq_u32_u8(low_bits), mid1_bits),
-			mid2_bits,
-		),
-		high_bits,
-	);
-
-	vst1q_u32(values.as_mut_ptr().add(i), combined);
-
-	offset + 16
+unsafe fn decode_unary(data: uint8x16_t, len: usize) -> uint32x4_t {
+	let index = vld1q_u8(GATHER[len - 1].as_ptr());
+	let words = vreinterpretq_u32_u8(vqtbl1q_u8(data, index));
+	if len == 1 {
+		return words;
+	}
+	let (low_mask, shift) = [(0x3F, 6), (0x1F, 5), (0x0F, 4)][len - 2];
+	let low = vandq_u32(words, vdupq_n_u32(low_mask));
+	let high = vshlq_u32(vshrq_n_u32(words, 8), vdupq_n_s32(shift));
+	vorrq_u32(low, high)
 }
 
+/// Decodes four values with the 5-byte binary length prefix.
 #[inline]
-unsafe fn decode_5byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let mut temp_buf = [0u8; 20];
-	let copy_len = core::cmp::min(20, buf.len() - offset);
-	temp_buf[..copy_len].copy_from_slice(&buf[offset..offset + copy_len]);
-
-	let mut out = [0u32; 4];
-	for (j, item) in out.iter_mut().enumerate() {
-		if i + j >= values.len() {
-			break;
-		}
-		let data_offset = j * 5 + 1;
-		*item = u32::from_le_bytes([
-			temp_buf[data_offset],
-			temp_buf[data_offset + 1],
-			temp_buf[data_offset + 2],
-			temp_buf[data_offset + 3],
-		]);
-	}
-	vst1q_u32(values.as_mut_ptr().add(i), vld1q_u32(out.as_ptr()));
-	offset + 20
+unsafe fn decode_5byte(buf: &[u8], offset: usize) -> uint32x4_t {
+	let group = &buf[offset..offset + 20];
+	let out = [1, 6, 11, 16].map(|start| {
+		u32::from_le_bytes(group[start..start + 4].try_into().unwrap())
+	});
+	vld1q_u32(out.as_ptr())
 }
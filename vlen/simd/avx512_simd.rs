@@ -0,0 +1,351 @@
+//! x86_64 AVX-512 implementation using VBMI byte permutes and masked stores
+//!
+//! Sixteen values are processed per iteration: lane lengths are computed in
+//! vector registers, a `vpermb` index packs (or unpacks) the variable-length
+//! encodings, and masked stores write exactly the encoded bytes. Groups that
+//! contain values needing the binary length prefix fall back to the scalar
+//! codec, so the output is always canonical.
+
+use super::x86_64_simd::X86_64Simd;
+use super::{
+	decode_scalar, encode_scalar, handle_remaining_decode,
+	handle_remaining_encode, SimdImpl,
+};
+use crate::decode::{decode_u32, decode_u64, prefix_encoded_len};
+use crate::encode::encode_u64;
+
+#[cfg(not(test))]
+use core::arch::x86_64::*;
+#[cfg(test)]
+use std::arch::x86_64::*;
+
+/// Values at or above this bound use the binary length prefix.
+const PREFIX_VARINT_BOUND: i32 = 0x1000_0000;
+
+/// x86_64 SIMD implementation using AVX-512F, AVX-512BW and AVX-512VBMI
+///
+/// Callers must check [`is_available`] before using this implementation
+/// directly; the `bulk_*` functions in [`crate::simd`] do so automatically.
+pub struct Avx512Simd;
+
+/// Returns whether the CPU supports the instructions used by [`Avx512Simd`].
+///
+/// Detection happens at runtime with the `std` feature, and otherwise
+/// depends on the target features enabled at compile time.
+#[inline]
+#[must_use]
+pub fn is_available() -> bool {
+	#[cfg(feature = "std")]
+	{
+		std::is_x86_feature_detected!("avx512f")
+			&& std::is_x86_feature_detected!("avx512bw")
+			&& std::is_x86_feature_detected!("avx512vbmi")
+	}
+	#[cfg(not(feature = "std"))]
+	{
+		cfg!(all(
+			target_feature = "avx512f",
+			target_feature = "avx512bw",
+			target_feature = "avx512vbmi"
+		))
+	}
+}
+
+impl SimdImpl for Avx512Simd {
+	#[inline]
+	unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
+		encode_u32_kernel(buf, values)
+	}
+
+	#[inline]
+	unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
+		decode_u32_kernel(buf, values)
+	}
+
+	#[inline]
+	unsafe fn bulk_encode_u64(buf: &mut [u8], values: &[u64]) -> usize {
+		encode_u64_kernel(buf, values)
+	}
+
+	#[inline]
+	unsafe fn bulk_decode_u64(buf: &[u8], values: &mut [u64]) -> usize {
+		decode_u64_kernel(buf, values)
+	}
+
+	// Sixteen-lane differences and sums would need cross-lane permutes for
+	// little gain, so the SSE2 kernels are used.
+	#[inline]
+	fn delta_u32(prev: u32, values: &[u32], deltas: &mut [u32]) {
+		X86_64Simd::delta_u32(prev, values, deltas);
+	}
+
+	#[inline]
+	fn prefix_sum_u32(prev: u32, values: &mut [u32]) -> u32 {
+		X86_64Simd::prefix_sum_u32(prev, values)
+	}
+}
+
+// The `SimdImpl` methods forward to these kernels so that the intrinsics are
+// compiled with the AVX-512 features enabled.
+
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw,avx512vbmi")]
+unsafe fn encode_u32_kernel(buf: &mut [u8], values: &[u32]) -> usize {
+	let mut offset = 0;
+	let mut i = 0;
+
+	while i + 16 <= values.len() {
+		let values_vec = _mm512_loadu_si512(values.as_ptr().add(i).cast());
+
+		if all_below_bound_u32(values_vec) {
+			offset += encode16(buf, offset, values_vec);
+		} else {
+			offset = handle_remaining_encode(buf, &values[..i + 16], offset, i);
+		}
+		i += 16;
+	}
+
+	handle_remaining_encode(buf, values, offset, i)
+}
+
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw,avx512vbmi")]
+unsafe fn decode_u32_kernel(buf: &[u8], values: &mut [u32]) -> usize {
+	let mut offset = 0;
+	let mut i = 0;
+
+	while i + 16 <= values.len() && offset + 64 <= buf.len() {
+		match decode16(buf, offset, &mut values[i..i + 16]) {
+			Some(len) => {
+				offset += len;
+				i += 16;
+			},
+			None => {
+				// At least 64 bytes remain, so a full `u32` window is valid.
+				let window = buf[offset..].first_chunk().unwrap();
+				let (value, len) = decode_u32(window);
+				values[i] = value;
+				offset += len;
+				i += 1;
+			},
+		}
+	}
+
+	handle_remaining_decode(buf, values, offset, i)
+}
+
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw,avx512vbmi")]
+unsafe fn encode_u64_kernel(buf: &mut [u8], values: &[u64]) -> usize {
+	let mut offset = 0;
+	let mut i = 0;
+
+	while i + 16 <= values.len() {
+		let low = _mm512_loadu_si512(values.as_ptr().add(i).cast());
+		let high = _mm512_loadu_si512(values.as_ptr().add(i + 8).cast());
+
+		if all_below_bound_u64(low) && all_below_bound_u64(high) {
+			let narrowed = _mm512_inserti64x4::<1>(
+				_mm512_castsi256_si512(_mm512_cvtepi64_epi32(low)),
+				_mm512_cvtepi64_epi32(high),
+			);
+			offset += encode16(buf, offset, narrowed);
+		} else {
+			let group = &values[..i + 16];
+			offset = encode_scalar(buf, group, offset, i, encode_u64);
+		}
+		i += 16;
+	}
+
+	encode_scalar(buf, values, offset, i, encode_u64)
+}
+
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw,avx512vbmi")]
+unsafe fn decode_u64_kernel(buf: &[u8], values: &mut [u64]) -> usize {
+	let mut offset = 0;
+	let mut i = 0;
+
+	while i + 16 <= values.len() && offset + 64 <= buf.len() {
+		let mut narrow = [0u32; 16];
+		match decode16(buf, offset, &mut narrow) {
+			Some(len) => {
+				widen16(&narrow, &mut values[i..i + 16]);
+				offset += len;
+				i += 16;
+			},
+			None => {
+				// At least 64 bytes remain, so a full `u64` window is valid.
+				let window = buf[offset..].first_chunk().unwrap();
+				let (value, len) = decode_u64(window);
+				values[i] = value;
+				offset += len;
+				i += 1;
+			},
+		}
+	}
+
+	decode_scalar(buf, values, offset, i, decode_u64)
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn all_below_bound_u32(values: __m512i) -> bool {
+	let bound = _mm512_set1_epi32(PREFIX_VARINT_BOUND);
+	_mm512_cmpge_epu32_mask(values, bound) == 0
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn all_below_bound_u64(values: __m512i) -> bool {
+	let bound = _mm512_set1_epi64(PREFIX_VARINT_BOUND as i64);
+	_mm512_cmpge_epu64_mask(values, bound) == 0
+}
+
+/// Encodes sixteen values below `2^28` into `buf` at `offset`, returning
+/// the number of bytes written.
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw,avx512vbmi")]
+unsafe fn encode16(buf: &mut [u8], offset: usize, values: __m512i) -> usize {
+	// Encoded length of each lane, from 1 to 4 bytes.
+	let one = _mm512_set1_epi32(1);
+	let mut len = one;
+	for bound in [0x80, 0x4000, 0x20_0000] {
+		let mask = _mm512_cmpge_epu32_mask(values, _mm512_set1_epi32(bound));
+		len = _mm512_mask_add_epi32(len, mask, len, one);
+	}
+
+	// Each lane becomes `prefix | (value & low_mask) | (value >> shift) << 8`,
+	// with the parameters looked up by length.
+	let prefix = _mm512_permutexvar_epi32(
+		len,
+		_mm512_setr_epi32(
+			0, 0, 0x80, 0xC0, 0xE0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		),
+	);
+	let low_mask = _mm512_permutexvar_epi32(
+		len,
+		_mm512_setr_epi32(
+			0, 0xFF, 0x3F, 0x1F, 0x0F, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		),
+	);
+	let shift = _mm512_permutexvar_epi32(
+		len,
+		_mm512_setr_epi32(0, 8, 6, 5, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0),
+	);
+	let words = _mm512_or_si512(
+		_mm512_or_si512(prefix, _mm512_and_si512(values, low_mask)),
+		_mm512_slli_epi32::<8>(_mm512_srlv_epi32(values, shift)),
+	);
+
+	// Point each output byte at its source byte. Lanes are written in order,
+	// so the unused tail of each lane is overwritten by the next one.
+	let mut lens = [0u32; 16];
+	_mm512_storeu_si512(lens.as_mut_ptr().cast(), len);
+	let mut index = [0u8; 68];
+	let mut total = 0;
+	for (lane, &lane_len) in lens.iter().enumerate() {
+		let source = 0x0302_0100 + (lane as u32) * 0x0404_0404;
+		index
+			.as_mut_ptr()
+			.add(total)
+			.cast::<u32>()
+			.write_unaligned(source);
+		total += lane_len as usize;
+	}
+
+	let packed = _mm512_permutexvar_epi8(
+		_mm512_loadu_si512(index.as_ptr().cast()),
+		words,
+	);
+	debug_assert!(offset + total <= buf.len(), "SIMD store past end of buffer");
+	let out = buf.as_mut_ptr().add(offset);
+	_mm512_mask_storeu_epi8(out.cast(), byte_mask(total), packed);
+	total
+}
+
+/// Decodes sixteen values from the 64 bytes of `buf` at `offset` into `out`,
+/// returning the number of bytes consumed.
+///
+/// Returns `None` if any of the values uses the binary length prefix.
+#[inline]
+#[target_feature(enable = "avx512f,avx512bw,avx512vbmi")]
+unsafe fn decode16(
+	buf: &[u8],
+	offset: usize,
+	out: &mut [u32],
+) -> Option<usize> {
+	debug_assert!(offset + 64 <= buf.len(), "SIMD load past end of buffer");
+	debug_assert!(out.len() >= 16, "SIMD store past end of values");
+	let input = buf.as_ptr().add(offset);
+	// Walk the prefix bytes to find where each lane's bytes start.
+	let mut lens = [0u32; 16];
+	let mut index = [0u8; 64];
+	let mut total = 0;
+	for (lane, lane_len) in lens.iter_mut().enumerate() {
+		let prefix = *input.add(total);
+		if prefix >= 0xF0 {
+			return None;
+		}
+		let source = 0x0302_0100 + (total as u32) * 0x0101_0101;
+		index
+			.as_mut_ptr()
+			.add(lane * 4)
+			.cast::<u32>()
+			.write_unaligned(source);
+		*lane_len = prefix_encoded_len(prefix) as u32;
+		total += *lane_len as usize;
+	}
+
+	// Gather each value's bytes into its lane, zeroing bytes past its length.
+	let len = _mm512_loadu_si512(lens.as_ptr().cast());
+	let len_bytes = _mm512_mullo_epi32(len, _mm512_set1_epi32(0x0101_0101));
+	let keep =
+		_mm512_cmplt_epu8_mask(_mm512_set1_epi32(0x0302_0100), len_bytes);
+	let words = _mm512_maskz_permutexvar_epi8(
+		keep,
+		_mm512_loadu_si512(index.as_ptr().cast()),
+		_mm512_loadu_si512(input.cast()),
+	);
+
+	// Each lane decodes as `(word & low_mask) | (word >> 8) << shift`.
+	let low_mask = _mm512_permutexvar_epi32(
+		len,
+		_mm512_setr_epi32(
+			0, 0x7F, 0x3F, 0x1F, 0x0F, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		),
+	);
+	let shift = _mm512_permutexvar_epi32(
+		len,
+		_mm512_setr_epi32(0, 0, 6, 5, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0),
+	);
+	let decoded = _mm512_or_si512(
+		_mm512_and_si512(words, low_mask),
+		_mm512_sllv_epi32(_mm512_srli_epi32::<8>(words), shift),
+	);
+	_mm512_storeu_si512(out.as_mut_ptr().cast(), decoded);
+	Some(total)
+}
+
+/// Widens sixteen decoded `u32` values to `u64`.
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn widen16(narrow: &[u32; 16], out: &mut [u64]) {
+	debug_assert!(out.len() >= 16, "SIMD store past end of values");
+	let out = out.as_mut_ptr();
+	let values = _mm512_loadu_si512(narrow.as_ptr().cast());
+	let low = _mm512_castsi512_si256(values);
+	let high = _mm512_extracti64x4_epi64::<1>(values);
+	_mm512_storeu_si512(out.cast(), _mm512_cvtepu32_epi64(low));
+	_mm512_storeu_si512(out.add(8).cast(), _mm512_cvtepu32_epi64(high));
+}
+
+/// Returns a mask selecting the first `len` bytes of a vector.
+#[inline]
+fn byte_mask(len: usize) -> __mmask64 {
+	if len >= 64 {
+		u64::MAX
+	} else {
+		(1u64 << len) - 1
+	}
+}
@@ -1,7 +1,19 @@
 //! SIMD-accelerated bulk encoding for vlen
 
-use crate::decode::decode_u32;
-use crate::encode::encode_u32;
+use crate::decode::{decode_u32, decode_u64};
+use crate::encode::{encode_u32, encode_u64, encoded_len, encoded_size_u32};
+
+/// Values from which a `u32` takes one more byte to encode.
+#[cfg_attr(
+	not(any(
+		target_arch = "x86_64",
+		target_arch = "aarch64",
+		all(target_arch = "wasm32", target_feature = "simd128"),
+		feature = "portable-simd"
+	)),
+	allow(dead_code)
+)]
+const SIZE_BOUNDARIES_U32: [u32; 4] = [0x80, 0x4000, 0x20_0000, 0x1000_0000];
 
 /// Trait that all SIMD implementations must implement
 /// This ensures consistency across different architectures
@@ -23,38 +35,301 @@ pub trait SimdImpl {
 	/// - The values array must be large enough to hold all decoded values
 	/// - The caller must ensure the buffer is valid for the duration of the operation
 	unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize;
+
+	/// Bulk encode u64 values using SIMD optimizations
+	///
+	/// The default implementation uses the scalar codec.
+	///
+	/// # Safety
+	///
+	/// - The buffer must be large enough to hold all encoded values
+	/// - The buffer size should be at least `values.len() * 9` bytes
+	/// - The caller must ensure the buffer is valid for the duration of the operation
+	unsafe fn bulk_encode_u64(buf: &mut [u8], values: &[u64]) -> usize {
+		encode_scalar(buf, values, 0, 0, encode_u64)
+	}
+
+	/// Bulk decode u64 values using SIMD optimizations
+	///
+	/// The default implementation uses the scalar codec.
+	///
+	/// # Safety
+	///
+	/// - The buffer must contain valid encoded data
+	/// - The values array must be large enough to hold all decoded values
+	/// - The caller must ensure the buffer is valid for the duration of the operation
+	unsafe fn bulk_decode_u64(buf: &[u8], values: &mut [u64]) -> usize {
+		decode_scalar(buf, values, 0, 0, decode_u64)
+	}
+
+	/// Sums the encoded sizes of u32 values using SIMD optimizations
+	///
+	/// The default implementation uses the scalar size function.
+	fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+		values.iter().map(|&value| encoded_size_u32(value)).sum()
+	}
+
+	/// Writes the wrapping difference between each value and the one before
+	/// it to `deltas`, taking `prev` as the value before the first
+	///
+	/// The default implementation uses a scalar loop.
+	fn delta_u32(prev: u32, values: &[u32], deltas: &mut [u32]) {
+		delta_scalar(prev, values, deltas);
+	}
+
+	/// Replaces each value with the wrapping sum of `prev` and the values up
+	/// to and including it, returning the last sum
+	///
+	/// The default implementation uses a scalar loop.
+	fn prefix_sum_u32(prev: u32, values: &mut [u32]) -> u32 {
+		prefix_sum_scalar(prev, values)
+	}
+
+	/// Delta encodes u32 values and bulk encodes the differences in one pass
+	///
+	/// The differences are computed a block at a time into a stack buffer
+	/// that stays in cache while it is encoded.
+	///
+	/// # Safety
+	///
+	/// - The buffer must be large enough to hold all encoded values
+	/// - The buffer size should be at least `values.len() * 5` bytes
+	/// - The caller must ensure the buffer is valid for the duration of the operation
+	unsafe fn delta_bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
+		let mut deltas = [0u32; DELTA_BLOCK_LEN];
+		let mut prev = 0;
+		let mut offset = 0;
+		for block in values.chunks(DELTA_BLOCK_LEN) {
+			let deltas = &mut deltas[..block.len()];
+			Self::delta_u32(prev, block, deltas);
+			offset += Self::bulk_encode_u32(&mut buf[offset..], deltas);
+			prev = block[block.len() - 1];
+		}
+		offset
+	}
+
+	/// Bulk decodes differences written by
+	/// [`delta_bulk_encode_u32`](Self::delta_bulk_encode_u32) and sums them
+	/// back into the original values in one pass
+	///
+	/// # Safety
+	///
+	/// - The buffer must contain valid encoded data
+	/// - The values array must be large enough to hold all decoded values
+	/// - The caller must ensure the buffer is valid for the duration of the operation
+	unsafe fn delta_bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
+		let mut prev = 0;
+		let mut offset = 0;
+		for block in values.chunks_mut(DELTA_BLOCK_LEN) {
+			if offset >= buf.len() {
+				break;
+			}
+			offset += Self::bulk_decode_u32(&buf[offset..], block);
+			prev = Self::prefix_sum_u32(prev, block);
+		}
+		offset
+	}
+}
+
+/// Number of values the fused delta kernels difference or sum at a time.
+///
+/// A multiple of the 4-value groups of the bulk kernels, so block boundaries
+/// never split a group and encoder and decoder agree on the grouping.
+const DELTA_BLOCK_LEN: usize = 256;
+
+/// Scalar fallback for [`SimdImpl::delta_u32`].
+#[inline]
+fn delta_scalar(mut prev: u32, values: &[u32], deltas: &mut [u32]) {
+	for (delta, &value) in deltas.iter_mut().zip(values) {
+		*delta = value.wrapping_sub(prev);
+		prev = value;
+	}
+}
+
+/// Prefix sums `values` with a vector kernel.
+///
+/// The kernel receives the whole 4-value chunks and the running sum before
+/// them, and returns the running sum after them. The last few values are
+/// summed with the scalar loop.
+#[cfg_attr(
+	not(any(
+		target_arch = "x86_64",
+		target_arch = "aarch64",
+		all(target_arch = "wasm32", target_feature = "simd128"),
+		feature = "portable-simd"
+	)),
+	allow(dead_code)
+)]
+#[inline(always)]
+fn prefix_sum_chunked(
+	prev: u32,
+	values: &mut [u32],
+	kernel: impl FnOnce(u32, &mut [u32]) -> u32,
+) -> u32 {
+	let body_len = values.len() - values.len() % 4;
+	let (body, tail) = values.split_at_mut(body_len);
+	prefix_sum_scalar(kernel(prev, body), tail)
+}
+
+/// Scalar fallback for [`SimdImpl::prefix_sum_u32`].
+#[inline]
+fn prefix_sum_scalar(prev: u32, values: &mut [u32]) -> u32 {
+	values.iter_mut().fold(prev, |sum, value| {
+		*value = value.wrapping_add(sum);
+		*value
+	})
+}
+
+/// Sums the encoded sizes of `values` with a vector kernel.
+///
+/// The kernel receives blocks of whole 4-value chunks, short enough that its
+/// 32-bit lane counters cannot overflow, and returns how many entries of
+/// [`SIZE_BOUNDARIES_U32`] the values in the block reach. The last few
+/// values are sized with the scalar function.
+#[cfg_attr(
+	not(any(
+		target_arch = "x86_64",
+		target_arch = "aarch64",
+		all(target_arch = "wasm32", target_feature = "simd128"),
+		feature = "portable-simd"
+	)),
+	allow(dead_code)
+)]
+#[inline(always)]
+fn sum_encoded_sizes_u32(
+	values: &[u32],
+	count_boundaries: impl Fn(&[u32]) -> usize,
+) -> usize {
+	const BLOCK_LEN: usize = 1 << 20;
+	let (body, tail) = values.split_at(values.len() - values.len() % 4);
+	let mut total: usize =
+		tail.iter().map(|&value| encoded_size_u32(value)).sum();
+	total += body.len();
+	for block in body.chunks(BLOCK_LEN) {
+		total += count_boundaries(block);
+	}
+	total
+}
+
+/// Returns the encoded length shared by the four values at `offset`, if they
+/// all fall in the same length class and end within `buf`.
+///
+/// The classes are the unary prefixes of 1 to 4 bytes and the 5-byte binary
+/// prefix `0xF3`. Shorter binary prefixes are left to the scalar decoder.
+#[cfg_attr(
+	not(any(target_arch = "x86_64", target_arch = "aarch64")),
+	allow(dead_code)
+)]
+#[inline(always)]
+fn group_len(buf: &[u8], offset: usize) -> Option<usize> {
+	let class = |prefix: u8| match prefix {
+		0x00..=0xEF => encoded_len(prefix),
+		0xF3 => 5,
+		_ => 0,
+	};
+	let len = class(*buf.get(offset)?);
+	if len == 0 || buf.len() - offset < 4 * len {
+		return None;
+	}
+	let same = (1..4).all(|k| class(buf[offset + k * len]) == len);
+	same.then_some(len)
+}
+
+/// Copies an encoded group of values to `buf` at `offset`.
+///
+/// # Safety
+///
+/// `offset + group.len()` must not exceed `buf.len()`.
+#[cfg_attr(
+	not(any(target_arch = "x86_64", target_arch = "aarch64")),
+	allow(dead_code)
+)]
+#[inline(always)]
+unsafe fn store_group(buf: &mut [u8], offset: usize, group: &[u8]) {
+	debug_assert!(
+		offset + group.len() <= buf.len(),
+		"SIMD store past end of buffer"
+	);
+	buf.get_unchecked_mut(offset..offset + group.len())
+		.copy_from_slice(group);
 }
 
-#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 #[inline]
 fn handle_remaining_encode(
 	buf: &mut [u8],
 	values: &[u32],
+	offset: usize,
+	i: usize,
+) -> usize {
+	encode_scalar(buf, values, offset, i, encode_u32)
+}
+
+/// Encodes `values[i..]` with a scalar encoder, starting at `offset`.
+///
+/// Values are encoded in place while a full `N`-byte window remains, so only
+/// the last few values near the end of the buffer go through a temporary,
+/// and a buffer sized exactly to the encoding is never written past its end.
+#[inline(always)]
+fn encode_scalar<T: Copy, const N: usize>(
+	buf: &mut [u8],
+	values: &[T],
 	mut offset: usize,
 	i: usize,
+	encode_fn: fn(&mut [u8; N], T) -> usize,
 ) -> usize {
-	for &value in values[i..].iter() {
-		unsafe {
-			let buf_ptr = buf.as_mut_ptr().add(offset) as *mut [u8; 5];
-			offset += encode_u32(&mut *buf_ptr, value);
+	for &value in &values[i..] {
+		match buf.get_mut(offset..).and_then(<[u8]>::first_chunk_mut::<N>) {
+			Some(window) => offset += encode_fn(window, value),
+			None => {
+				let mut temp_buf = [0u8; N];
+				let len = encode_fn(&mut temp_buf, value);
+				buf[offset..offset + len].copy_from_slice(&temp_buf[..len]);
+				offset += len;
+			},
 		}
 	}
 	offset
 }
 
-#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 #[inline]
 fn handle_remaining_decode(
 	buf: &[u8],
 	values: &mut [u32],
+	offset: usize,
+	i: usize,
+) -> usize {
+	decode_scalar(buf, values, offset, i, decode_u32)
+}
+
+/// Decodes into `values[i..]` with a scalar decoder, starting at `offset`.
+///
+/// Values are decoded in place while a full `N`-byte window remains, so only
+/// the last few values near the end of the buffer are copied into a padded
+/// temporary.
+#[inline(always)]
+fn decode_scalar<T, const N: usize>(
+	buf: &[u8],
+	values: &mut [T],
 	mut offset: usize,
 	mut i: usize,
+	decode_fn: fn(&[u8; N]) -> (T, usize),
 ) -> usize {
+	while i < values.len() {
+		let Some(window) = buf.get(offset..).and_then(<[u8]>::first_chunk::<N>)
+		else {
+			break;
+		};
+		let (value, len) = decode_fn(window);
+		values[i] = value;
+		offset += len;
+		i += 1;
+	}
+
 	while i < values.len() && offset < buf.len() {
-		let mut temp_buf = [0u8; 5];
-		let copy_len = core::cmp::min(5, buf.len() - offset);
-		temp_buf[..copy_len].copy_from_slice(&buf[offset..offset + copy_len]);
-		let (value, len) = decode_u32(&temp_buf);
+		let tail = &buf[offset..];
+		let mut temp_buf = [0u8; N];
+		temp_buf[..tail.len()].copy_from_slice(tail);
+		let (value, len) = decode_fn(&temp_buf);
 		values[i] = value;
 		offset += len;
 		i += 1;
@@ -66,20 +341,61 @@ fn handle_remaining_decode(
 #[cfg(target_arch = "x86_64")]
 mod x86_64_simd;
 
+#[cfg(target_arch = "x86_64")]
+mod avx512_simd;
+
 #[cfg(target_arch = "aarch64")]
 mod aarch64_simd;
 
-#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm32_simd;
+
+#[cfg(feature = "portable-simd")]
+mod portable_simd;
+
+#[cfg(not(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	all(target_arch = "wasm32", target_feature = "simd128"),
+	feature = "portable-simd"
+)))]
 mod generic_simd;
 
 // Re-export the appropriate implementation
 #[cfg(target_arch = "x86_64")]
 pub use x86_64_simd::X86_64Simd as CurrentSimd;
 
+// AVX-512 is selected at runtime by the `bulk_*` functions when available.
+#[cfg(target_arch = "x86_64")]
+pub use avx512_simd::{is_available as avx512_available, Avx512Simd};
+
 #[cfg(target_arch = "aarch64")]
 pub use aarch64_simd::Aarch64Simd as CurrentSimd;
 
-#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub use wasm32_simd::Wasm32Simd as CurrentSimd;
+
+// The portable backend is selected only where no hand-written backend exists,
+// but is always exported so the intrinsics backends can be checked against it.
+#[cfg(feature = "portable-simd")]
+pub use portable_simd::PortableSimd;
+
+#[cfg(all(
+	feature = "portable-simd",
+	not(any(
+		target_arch = "x86_64",
+		target_arch = "aarch64",
+		all(target_arch = "wasm32", target_feature = "simd128")
+	))
+))]
+pub use portable_simd::PortableSimd as CurrentSimd;
+
+#[cfg(not(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	all(target_arch = "wasm32", target_feature = "simd128"),
+	feature = "portable-simd"
+)))]
 pub use generic_simd::GenericSimd as CurrentSimd;
 
 /// Bulk encoding function for u32 values using SIMD optimizations.
@@ -89,9 +405,18 @@ pub use generic_simd::GenericSimd as CurrentSimd;
 /// - The buffer must be large enough to hold all encoded values
 /// - The buffer size should be at least `values.len() * 5` bytes
 /// - The caller must ensure the buffer is valid for the duration of the operation
-#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[cfg(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	all(target_arch = "wasm32", target_feature = "simd128"),
+	feature = "portable-simd"
+))]
 #[inline]
 pub unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	if avx512_available() {
+		return Avx512Simd::bulk_encode_u32(buf, values);
+	}
 	CurrentSimd::bulk_encode_u32(buf, values)
 }
 
@@ -102,9 +427,18 @@ pub unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
 /// - The buffer must contain valid encoded data
 /// - The values array must be large enough to hold all decoded values
 /// - The caller must ensure the buffer is valid for the duration of the operation
-#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[cfg(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	all(target_arch = "wasm32", target_feature = "simd128"),
+	feature = "portable-simd"
+))]
 #[inline]
 pub unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	if avx512_available() {
+		return Avx512Simd::bulk_decode_u32(buf, values);
+	}
 	CurrentSimd::bulk_decode_u32(buf, values)
 }
 
@@ -115,7 +449,12 @@ pub unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
 /// - The buffer must be large enough to hold all encoded values
 /// - The buffer size should be at least `values.len() * 5` bytes
 /// - The caller must ensure the buffer is valid for the duration of the operation
-#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[cfg(not(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	all(target_arch = "wasm32", target_feature = "simd128"),
+	feature = "portable-simd"
+)))]
 #[inline]
 pub unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
 	CurrentSimd::bulk_encode_u32(buf, values)
@@ -128,12 +467,101 @@ pub unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
 /// - The buffer must contain valid encoded data
 /// - The values array must be large enough to hold all decoded values
 /// - The caller must ensure the buffer is valid for the duration of the operation
-#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[cfg(not(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	all(target_arch = "wasm32", target_feature = "simd128"),
+	feature = "portable-simd"
+)))]
 #[inline]
 pub unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
 	CurrentSimd::bulk_decode_u32(buf, values)
 }
 
+/// Bulk encoding function for u64 values using SIMD optimizations.
+///
+/// # Safety
+///
+/// - The buffer must be large enough to hold all encoded values
+/// - The buffer size should be at least `values.len() * 9` bytes
+/// - The caller must ensure the buffer is valid for the duration of the operation
+#[inline]
+pub unsafe fn bulk_encode_u64(buf: &mut [u8], values: &[u64]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	if avx512_available() {
+		return Avx512Simd::bulk_encode_u64(buf, values);
+	}
+	CurrentSimd::bulk_encode_u64(buf, values)
+}
+
+/// Bulk decoding function for u64 values using SIMD optimizations.
+///
+/// # Safety
+///
+/// - The buffer must contain valid encoded data
+/// - The values array must be large enough to hold all decoded values
+/// - The caller must ensure the buffer is valid for the duration of the operation
+#[inline]
+pub unsafe fn bulk_decode_u64(buf: &[u8], values: &mut [u64]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	if avx512_available() {
+		return Avx512Simd::bulk_decode_u64(buf, values);
+	}
+	CurrentSimd::bulk_decode_u64(buf, values)
+}
+
+/// Delta encodes u32 values and bulk encodes the differences in one pass.
+///
+/// Each value is stored as its wrapping difference from the one before, the
+/// first from zero, so a sorted column encodes as small gaps. Computing the
+/// differences inside the encoding loop reads the values once instead of
+/// writing and re-reading a delta array.
+///
+/// # Safety
+///
+/// - The buffer must be large enough to hold all encoded values
+/// - The buffer size should be at least `values.len() * 5` bytes
+/// - The caller must ensure the buffer is valid for the duration of the operation
+#[inline]
+pub unsafe fn delta_bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	if avx512_available() {
+		return Avx512Simd::delta_bulk_encode_u32(buf, values);
+	}
+	CurrentSimd::delta_bulk_encode_u32(buf, values)
+}
+
+/// Decodes values written by [`delta_bulk_encode_u32`], summing the
+/// differences back into the original values in the same pass.
+///
+/// If `buf` ends before `values` is full, the contents of the slots after
+/// the last decoded value are unspecified.
+///
+/// # Safety
+///
+/// - The buffer must contain valid encoded data
+/// - The values array must be large enough to hold all decoded values
+/// - The caller must ensure the buffer is valid for the duration of the operation
+#[inline]
+pub unsafe fn delta_bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	if avx512_available() {
+		return Avx512Simd::delta_bulk_decode_u32(buf, values);
+	}
+	CurrentSimd::delta_bulk_decode_u32(buf, values)
+}
+
+/// Sums the encoded sizes of u32 values using SIMD optimizations.
+///
+/// Gives the same result as summing
+/// [`encoded_size_u32`](crate::encode::encoded_size_u32) over the values,
+/// comparing four values at a time against the size class boundaries.
+#[inline]
+#[must_use]
+pub fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+	CurrentSimd::bulk_encoded_size_u32(values)
+}
+
 /// Generic bulk encoding function that works with any integer type.
 #[inline]
 pub fn bulk_encode<T>(
@@ -141,14 +569,14 @@ pub fn bulk_encode<T>(
 	values: &[T],
 ) -> Result<usize, &'static str>
 where
-	T: crate::encode::Encode + Copy,
+	T: crate::encode::Encode + Clone,
 {
 	let mut offset = 0;
-	for &value in values {
+	for value in values {
 		if offset >= buf.len() {
 			return Err("buffer too small for bulk encoding");
 		}
-		let len = T::encode(&mut buf[offset..], value)?;
+		let len = T::encode(&mut buf[offset..], value.clone())?;
 		offset += len;
 	}
 	Ok(offset)
@@ -198,6 +626,54 @@ pub fn bulk_decode_u32_safe(
 	Ok(unsafe { bulk_decode_u32(buf, values) })
 }
 
+/// Safe wrapper for bulk encoding u64 values.
+#[inline]
+pub fn bulk_encode_u64_safe(
+	buf: &mut [u8],
+	values: &[u64],
+) -> Result<usize, &'static str> {
+	if buf.len() < values.len() * 9 {
+		return Err("buffer too small for bulk encoding");
+	}
+	Ok(unsafe { bulk_encode_u64(buf, values) })
+}
+
+/// Safe wrapper for bulk decoding u64 values.
+#[inline]
+pub fn bulk_decode_u64_safe(
+	buf: &[u8],
+	values: &mut [u64],
+) -> Result<usize, &'static str> {
+	if buf.is_empty() {
+		return Ok(0);
+	}
+	Ok(unsafe { bulk_decode_u64(buf, values) })
+}
+
+/// Safe wrapper for delta encoding u32 values.
+#[inline]
+pub fn delta_bulk_encode_u32_safe(
+	buf: &mut [u8],
+	values: &[u32],
+) -> Result<usize, &'static str> {
+	if buf.len() < values.len() * 5 {
+		return Err("buffer too small for bulk encoding");
+	}
+	Ok(unsafe { delta_bulk_encode_u32(buf, values) })
+}
+
+/// Safe wrapper for delta decoding u32 values.
+#[inline]
+pub fn delta_bulk_decode_u32_safe(
+	buf: &[u8],
+	values: &mut [u32],
+) -> Result<usize, &'static str> {
+	if buf.is_empty() {
+		return Ok(0);
+	}
+	Ok(unsafe { delta_bulk_decode_u32(buf, values) })
+}
+
 #[cfg(test)]
 #[cfg(feature = "simd")]
 mod tests {
@@ -274,4 +750,265 @@ mod tests {
 		};
 		assert_eq!(values, decoded_values);
 	}
+
+	#[test]
+	fn test_decode_scalar_near_buffer_end() {
+		let values = [1u32, 0x10000000, 1000, 100000, 5, 0xFFFFFFFF, 7];
+		let mut buf = [0u8; 40];
+		let encoded_len =
+			crate::encode::bulk_encode(&mut buf, &values).unwrap();
+		for start in 0..values.len() {
+			let offset =
+				crate::encode::bulk_encode(&mut [0u8; 40], &values[..start])
+					.unwrap();
+			let mut decoded = [0u32; 7];
+			let decoded_len = handle_remaining_decode(
+				&buf[..encoded_len],
+				&mut decoded,
+				offset,
+				start,
+			);
+			assert_eq!(decoded_len, encoded_len);
+			assert_eq!(decoded[start..], values[start..]);
+		}
+		let mut decoded = [0u64; 7];
+		let wide = values.map(u64::from);
+		let mut buf = [0u8; 70];
+		let encoded_len = crate::encode::bulk_encode(&mut buf, &wide).unwrap();
+		let decoded_len =
+			decode_scalar(&buf[..encoded_len], &mut decoded, 0, 0, decode_u64);
+		assert_eq!(decoded_len, encoded_len);
+		assert_eq!(decoded, wide);
+	}
+
+	#[test]
+	fn test_encode_scalar_exact_buffer() {
+		let values = [1u32, 0x10000000, 1000, 100000, 5, 0xFFFFFFFF, 7];
+		let mut expected = [0u8; 40];
+		let encoded_len =
+			crate::encode::bulk_encode(&mut expected, &values).unwrap();
+		// One spare byte past the encoding catches writes beyond the end.
+		let mut buf = [0xAAu8; 41];
+		let len =
+			handle_remaining_encode(&mut buf[..encoded_len], &values, 0, 0);
+		assert_eq!(len, encoded_len);
+		assert_eq!(buf[..len], expected[..len]);
+		assert_eq!(buf[len], 0xAA);
+
+		let wide = values.map(u64::from);
+		let mut buf = [0xAAu8; 41];
+		let len =
+			unsafe { bulk_encode_u64(&mut buf[..encoded_len], &wide) };
+		assert_eq!(len, encoded_len);
+		assert_eq!(buf[..len], expected[..len]);
+		assert_eq!(buf[len], 0xAA);
+	}
+
+	#[test]
+	fn test_bulk_encoded_size_u32() {
+		let values: std::vec::Vec<u32> = (0..1001u32)
+			.map(|i| i.wrapping_mul(0x9E37_79B9) >> (i % 32))
+			.collect();
+		for len in [0, 3, 4, 7, 1000, 1001] {
+			let expected: usize = values[..len]
+				.iter()
+				.map(|&value| encoded_size_u32(value))
+				.sum();
+			assert_eq!(bulk_encoded_size_u32(&values[..len]), expected);
+			assert_eq!(
+				GenericSizes::bulk_encoded_size_u32(&values[..len]),
+				expected
+			);
+			#[cfg(feature = "portable-simd")]
+			assert_eq!(
+				PortableSimd::bulk_encoded_size_u32(&values[..len]),
+				expected
+			);
+		}
+		let boundaries = [
+			0x7F,
+			0x80,
+			0x3FFF,
+			0x4000,
+			0x1F_FFFF,
+			0x20_0000,
+			0x0FFF_FFFF,
+			0x1000_0000,
+		];
+		assert_eq!(bulk_encoded_size_u32(&boundaries), 24);
+	}
+
+	#[test]
+	fn test_delta_kernels_match_scalar() {
+		let values: std::vec::Vec<u32> = (0..1003u32)
+			.map(|i| i.wrapping_mul(0x9E37_79B9) >> (i % 32))
+			.collect();
+		for len in [0, 1, 3, 4, 5, 8, 1003] {
+			let mut expected = std::vec![0u32; len];
+			delta_scalar(7, &values[..len], &mut expected);
+			let mut deltas = std::vec![0u32; len];
+			CurrentSimd::delta_u32(7, &values[..len], &mut deltas);
+			assert_eq!(deltas, expected);
+
+			let mut sums = deltas.clone();
+			let last = CurrentSimd::prefix_sum_u32(7, &mut sums);
+			assert_eq!(sums, values[..len]);
+			assert_eq!(last, values[..len].last().copied().unwrap_or(7));
+
+			#[cfg(feature = "portable-simd")]
+			{
+				let mut sums = deltas.clone();
+				PortableSimd::prefix_sum_u32(7, &mut sums);
+				assert_eq!(sums, values[..len]);
+			}
+		}
+	}
+
+	#[test]
+	fn test_delta_bulk_round_trip() {
+		// Sorted with small gaps, plus a drop that wraps the difference.
+		let mut values: std::vec::Vec<u32> =
+			(0..600u32).map(|i| 1_000_000 + i * i).collect();
+		values[300] = 5;
+		for len in [0, 1, 4, 255, 256, 257, 600] {
+			let mut buf = std::vec![0u8; len * 5 + 20];
+			let encoded_len =
+				unsafe { delta_bulk_encode_u32(&mut buf, &values[..len]) };
+			let mut decoded = std::vec![0u32; len];
+			let decoded_len = unsafe {
+				delta_bulk_decode_u32(&buf[..encoded_len], &mut decoded)
+			};
+			assert_eq!(decoded_len, encoded_len);
+			assert_eq!(decoded, values[..len]);
+		}
+	}
+
+	#[test]
+	fn test_decode_kernels_exact_buffer() {
+		// Runs of every length class, then groups mixing them.
+		let mut values = std::vec::Vec::new();
+		for base in [1u32, 0x80, 0x4000, 0x20_0000, 0x1000_0000] {
+			values.extend((0..9).map(|k| base + k * 7));
+		}
+		values.extend([5, 0x1234, u32::MAX, 300, 0x30_0000, 0, 1 << 27, 9]);
+		let mut buf = std::vec![0u8; values.len() * 5];
+		let len = crate::encode::bulk_encode(&mut buf, &values).unwrap();
+		// Nothing follows the encoding, so a load past it would be caught.
+		buf.truncate(len);
+
+		for start in 0..4 {
+			let offset =
+				crate::encode::bulk_encode(&mut [0u8; 20], &values[..start])
+					.unwrap();
+			let mut decoded = std::vec![0u32; values.len() - start];
+			let decoded_len = unsafe {
+				CurrentSimd::bulk_decode_u32(&buf[offset..], &mut decoded)
+			};
+			assert_eq!(decoded_len, len - offset);
+			assert_eq!(decoded, values[start..]);
+		}
+
+		// The SSE2 and NEON encoders write each group in one length class.
+		let mut buf = std::vec![0u8; values.len() * 5];
+		let len = unsafe { CurrentSimd::bulk_encode_u32(&mut buf, &values) };
+		let mut decoded = std::vec![0u32; values.len()];
+		let decoded_len =
+			unsafe { CurrentSimd::bulk_decode_u32(&buf[..len], &mut decoded) };
+		assert_eq!(decoded_len, len);
+		assert_eq!(decoded, values);
+	}
+
+	/// Uses the default size implementation.
+	struct GenericSizes;
+
+	impl SimdImpl for GenericSizes {
+		unsafe fn bulk_encode_u32(_: &mut [u8], _: &[u32]) -> usize {
+			unreachable!()
+		}
+
+		unsafe fn bulk_decode_u32(_: &[u8], _: &mut [u32]) -> usize {
+			unreachable!()
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "portable-simd")]
+	fn test_portable_matches_scalar() {
+		let values: std::vec::Vec<u32> = (0..100u32)
+			.map(|i| if i % 23 == 0 { i << 20 } else { i })
+			.collect();
+
+		let mut expected = [0u8; 500];
+		let mut expected_len = 0;
+		for &value in &values {
+			let buf = (&mut expected[expected_len..expected_len + 5])
+				.try_into()
+				.unwrap();
+			expected_len += encode_u32(buf, value);
+		}
+
+		let mut buf = [0u8; 500];
+		let len = unsafe { PortableSimd::bulk_encode_u32(&mut buf, &values) };
+		assert_eq!(&buf[..len], &expected[..expected_len]);
+
+		let mut decoded = [0u32; 100];
+		let decoded_len =
+			unsafe { PortableSimd::bulk_decode_u32(&buf[..len], &mut decoded) };
+		assert_eq!(decoded_len, len);
+		assert_eq!(&decoded[..], &values[..]);
+	}
+
+	#[test]
+	#[cfg(target_arch = "x86_64")]
+	fn test_avx512_matches_scalar() {
+		if !(std::is_x86_feature_detected!("avx512f")
+			&& std::is_x86_feature_detected!("avx512bw")
+			&& std::is_x86_feature_detected!("avx512vbmi"))
+		{
+			return;
+		}
+
+		// Mix every encoded length, with some groups needing the scalar path.
+		let values: std::vec::Vec<u64> = (0..200u64)
+			.map(|i| match i % 5 {
+				0 => i,
+				1 => 0x80 + i * 31,
+				2 => 0x4000 + i * 977,
+				3 => 0x20_0000 + i * 65_537,
+				_ if i > 150 => u64::MAX - i,
+				_ => 0x0FFF_FFFF - i,
+			})
+			.collect();
+
+		let mut expected = [0u8; 1800];
+		let mut expected_len = 0;
+		for &value in &values {
+			let buf = (&mut expected[expected_len..expected_len + 9])
+				.try_into()
+				.unwrap();
+			expected_len += encode_u64(buf, value);
+		}
+
+		let mut buf = [0u8; 1800];
+		let len = unsafe { Avx512Simd::bulk_encode_u64(&mut buf, &values) };
+		assert_eq!(&buf[..len], &expected[..expected_len]);
+
+		let mut decoded = [0u64; 200];
+		let decoded_len =
+			unsafe { Avx512Simd::bulk_decode_u64(&buf[..len], &mut decoded) };
+		assert_eq!(decoded_len, len);
+		assert_eq!(&decoded[..], &values[..]);
+
+		let narrow: std::vec::Vec<u32> = values
+			.iter()
+			.map(|&value| value as u32 & 0x0FFF_FFFF)
+			.collect();
+		let mut buf = [0u8; 1000];
+		let len = unsafe { Avx512Simd::bulk_encode_u32(&mut buf, &narrow) };
+		let mut decoded = [0u32; 200];
+		let decoded_len =
+			unsafe { Avx512Simd::bulk_decode_u32(&buf[..len], &mut decoded) };
+		assert_eq!(decoded_len, len);
+		assert_eq!(&decoded[..], &narrow[..]);
+	}
 }
@@ -0,0 +1,113 @@
+//! Portable SIMD implementation using `core::simd`
+//!
+//! Requires a nightly compiler. Runs of single-byte values are handled a
+//! vector at a time; any other values fall back to the scalar codec, so the
+//! output is always canonical.
+
+use core::simd::cmp::SimdPartialOrd;
+use core::simd::num::SimdUint;
+use core::simd::{simd_swizzle, u32x4, u8x16, Select};
+
+use super::{
+	handle_remaining_decode, handle_remaining_encode, prefix_sum_chunked,
+	sum_encoded_sizes_u32, SimdImpl, SIZE_BOUNDARIES_U32,
+};
+use crate::decode::decode_u32;
+
+/// Portable SIMD implementation using `core::simd`
+pub struct PortableSimd;
+
+impl SimdImpl for PortableSimd {
+	#[inline]
+	unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
+		let mut offset = 0;
+		let mut i = 0;
+
+		while i + 3 < values.len() {
+			let values_vec = u32x4::from_slice(&values[i..i + 4]);
+
+			if values_vec.simd_lt(u32x4::splat(0x80)).all() {
+				values_vec
+					.cast::<u8>()
+					.copy_to_slice(&mut buf[offset..offset + 4]);
+				offset += 4;
+			} else {
+				offset =
+					handle_remaining_encode(buf, &values[..i + 4], offset, i);
+			}
+			i += 4;
+		}
+
+		handle_remaining_encode(buf, values, offset, i)
+	}
+
+	#[inline]
+	unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
+		let mut offset = 0;
+		let mut i = 0;
+
+		while i + 16 <= values.len() && offset + 16 <= buf.len() {
+			let data = u8x16::from_slice(&buf[offset..offset + 16]);
+
+			if data.simd_lt(u8x16::splat(0x80)).all() {
+				data.cast::<u32>().copy_to_slice(&mut values[i..i + 16]);
+				offset += 16;
+				i += 16;
+			} else {
+				// At least 16 bytes remain, so a full `u32` window is valid.
+				let window = buf[offset..].first_chunk().unwrap();
+				let (value, len) = decode_u32(window);
+				values[i] = value;
+				offset += len;
+				i += 1;
+			}
+		}
+
+		handle_remaining_decode(buf, values, offset, i)
+	}
+	#[inline]
+	fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+		sum_encoded_sizes_u32(values, count_boundaries)
+	}
+
+	#[inline]
+	fn prefix_sum_u32(prev: u32, values: &mut [u32]) -> u32 {
+		prefix_sum_chunked(prev, values, prefix_sum_kernel)
+	}
+}
+
+/// Prefix sums whole 4-value chunks of `values` in place, returning the
+/// running sum after them.
+///
+/// Two shift-and-add steps give each lane the sum of the lanes up to it,
+/// swizzling zeros in from below, and the running sum so far is added to
+/// every lane.
+#[inline]
+fn prefix_sum_kernel(prev: u32, values: &mut [u32]) -> u32 {
+	let zero = u32x4::splat(0);
+	let mut carry = prev;
+	for chunk in values.chunks_exact_mut(4) {
+		let mut sums = u32x4::from_slice(chunk);
+		sums += simd_swizzle!(zero, sums, [0, 4, 5, 6]);
+		sums += simd_swizzle!(zero, sums, [0, 1, 4, 5]);
+		sums += u32x4::splat(carry);
+		sums.copy_to_slice(chunk);
+		carry = sums[3];
+	}
+	carry
+}
+
+/// Counts how many size class boundaries each value in `block` reaches.
+#[inline]
+fn count_boundaries(block: &[u32]) -> usize {
+	let bounds = SIZE_BOUNDARIES_U32.map(u32x4::splat);
+	let (one, zero) = (u32x4::splat(1), u32x4::splat(0));
+	let mut counts = u32x4::splat(0);
+	for chunk in block.chunks_exact(4) {
+		let values = u32x4::from_slice(chunk);
+		for bound in bounds {
+			counts += values.simd_ge(bound).select(one, zero);
+		}
+	}
+	counts.reduce_sum() as usize
+}
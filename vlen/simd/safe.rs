@@ -0,0 +1,120 @@
+//! Bulk encoding for vlen without `unsafe` code
+//!
+//! With the `safe` feature the SIMD backends are compiled out, and the safe
+//! bulk functions use the scalar codec instead. They keep the signatures and
+//! buffer requirements of their accelerated counterparts.
+
+use crate::decode::{bulk_decode_counted, Decode};
+use crate::encode::{encoded_size_u32, Encode};
+
+/// Number of values delta coded at a time.
+const DELTA_BLOCK_LEN: usize = 256;
+
+/// Generic bulk encoding function that works with any integer type.
+#[inline]
+pub fn bulk_encode<T>(
+	buf: &mut [u8],
+	values: &[T],
+) -> Result<usize, &'static str>
+where
+	T: Encode + Clone,
+{
+	crate::encode::bulk_encode(buf, values)
+}
+
+/// Generic bulk decoding function that works with any integer type.
+#[inline]
+pub fn bulk_decode<T>(
+	buf: &[u8],
+	values: &mut [T],
+) -> Result<usize, &'static str>
+where
+	T: Decode,
+{
+	crate::decode::bulk_decode(buf, values)
+}
+
+/// Sums the encoded sizes of u32 values.
+#[inline]
+#[must_use]
+pub fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+	values.iter().map(|&value| encoded_size_u32(value)).sum()
+}
+
+/// Safe wrapper for bulk encoding u32 values.
+#[inline]
+pub fn bulk_encode_u32_safe(
+	buf: &mut [u8],
+	values: &[u32],
+) -> Result<usize, &'static str> {
+	if buf.len() < values.len() * 5 {
+		return Err("buffer too small for bulk encoding");
+	}
+	bulk_encode(buf, values)
+}
+
+/// Safe wrapper for bulk decoding u32 values.
+#[inline]
+pub fn bulk_decode_u32_safe(
+	buf: &[u8],
+	values: &mut [u32],
+) -> Result<usize, &'static str> {
+	bulk_decode(buf, values)
+}
+
+/// Safe wrapper for bulk encoding u64 values.
+#[inline]
+pub fn bulk_encode_u64_safe(
+	buf: &mut [u8],
+	values: &[u64],
+) -> Result<usize, &'static str> {
+	if buf.len() < values.len() * 9 {
+		return Err("buffer too small for bulk encoding");
+	}
+	bulk_encode(buf, values)
+}
+
+/// Safe wrapper for bulk decoding u64 values.
+#[inline]
+pub fn bulk_decode_u64_safe(
+	buf: &[u8],
+	values: &mut [u64],
+) -> Result<usize, &'static str> {
+	bulk_decode(buf, values)
+}
+
+/// Safe wrapper for delta encoding u32 values.
+#[inline]
+pub fn delta_bulk_encode_u32_safe(
+	buf: &mut [u8],
+	values: &[u32],
+) -> Result<usize, &'static str> {
+	if buf.len() < values.len() * 5 {
+		return Err("buffer too small for bulk encoding");
+	}
+	let mut deltas = [0u32; DELTA_BLOCK_LEN];
+	let mut prev = 0u32;
+	let mut offset = 0;
+	for block in values.chunks(DELTA_BLOCK_LEN) {
+		for (delta, &value) in deltas.iter_mut().zip(block) {
+			*delta = value.wrapping_sub(prev);
+			prev = value;
+		}
+		offset += bulk_encode(&mut buf[offset..], &deltas[..block.len()])?;
+	}
+	Ok(offset)
+}
+
+/// Safe wrapper for delta decoding u32 values.
+#[inline]
+pub fn delta_bulk_decode_u32_safe(
+	buf: &[u8],
+	values: &mut [u32],
+) -> Result<usize, &'static str> {
+	let (count, offset) = bulk_decode_counted(buf, values)?;
+	values[..count].iter_mut().fold(0u32, |sum, value| {
+		*value = value.wrapping_add(sum);
+		*value
+	});
+	Ok(offset)
+}
@@ -0,0 +1,147 @@
+//! wasm32 SIMD implementation using SIMD128 instructions
+//!
+//! Runs of single-byte values are handled a vector at a time; any other
+//! values fall back to the scalar codec, so the output is always canonical.
+
+use super::{
+	handle_remaining_decode, handle_remaining_encode, prefix_sum_chunked,
+	sum_encoded_sizes_u32, SimdImpl, SIZE_BOUNDARIES_U32,
+};
+use crate::decode::decode_u32;
+
+#[cfg(not(test))]
+use core::arch::wasm32::*;
+#[cfg(test)]
+use std::arch::wasm32::*;
+
+/// wasm32 SIMD implementation using SIMD128 instructions
+pub struct Wasm32Simd;
+
+impl SimdImpl for Wasm32Simd {
+	#[inline]
+	unsafe fn bulk_encode_u32(buf: &mut [u8], values: &[u32]) -> usize {
+		let mut offset = 0;
+		let mut i = 0;
+
+		while i + 3 < values.len() {
+			let values_vec = v128_load(values.as_ptr().add(i).cast());
+
+			if u32x4_all_true(u32x4_lt(values_vec, u32x4_splat(0x80))) {
+				offset += encode_1byte(buf, offset, values_vec);
+			} else {
+				offset =
+					handle_remaining_encode(buf, &values[..i + 4], offset, i);
+			}
+			i += 4;
+		}
+
+		handle_remaining_encode(buf, values, offset, i)
+	}
+
+	#[inline]
+	unsafe fn bulk_decode_u32(buf: &[u8], values: &mut [u32]) -> usize {
+		let mut offset = 0;
+		let mut i = 0;
+
+		while i + 16 <= values.len() && offset + 16 <= buf.len() {
+			let data = v128_load(buf.as_ptr().add(offset).cast());
+
+			if u8x16_bitmask(data) == 0 {
+				decode_1byte(data, values, i);
+				offset += 16;
+				i += 16;
+			} else {
+				// At least 16 bytes remain, so a full `u32` window is valid.
+				let window = buf[offset..].first_chunk().unwrap();
+				let (value, len) = decode_u32(window);
+				values[i] = value;
+				offset += len;
+				i += 1;
+			}
+		}
+
+		handle_remaining_decode(buf, values, offset, i)
+	}
+
+	#[inline]
+	fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+		sum_encoded_sizes_u32(values, |block| unsafe {
+			count_boundaries(block)
+		})
+	}
+
+	#[inline]
+	fn prefix_sum_u32(prev: u32, values: &mut [u32]) -> u32 {
+		prefix_sum_chunked(prev, values, |prev, body| unsafe {
+			prefix_sum_kernel(prev, body)
+		})
+	}
+}
+
+/// Prefix sums whole 4-value chunks of `values` in place, returning the
+/// running sum after them.
+///
+/// Two shift-and-add steps give each lane the sum of the lanes up to it,
+/// shuffling zeros in from below, and the running sum so far is added to
+/// every lane.
+#[inline]
+unsafe fn prefix_sum_kernel(prev: u32, values: &mut [u32]) -> u32 {
+	let zero = u32x4_splat(0);
+	let mut carry = u32x4_splat(prev);
+	for chunk in values.chunks_exact_mut(4) {
+		let mut sums = v128_load(chunk.as_ptr().cast());
+		sums = u32x4_add(sums, i32x4_shuffle::<0, 4, 5, 6>(zero, sums));
+		sums = u32x4_add(sums, i32x4_shuffle::<0, 1, 4, 5>(zero, sums));
+		sums = u32x4_add(sums, carry);
+		v128_store(chunk.as_mut_ptr().cast(), sums);
+		carry = i32x4_shuffle::<3, 3, 3, 3>(sums, sums);
+	}
+	u32x4_extract_lane::<0>(carry)
+}
+
+/// Counts how many size class boundaries each value in `block` reaches.
+#[inline]
+unsafe fn count_boundaries(block: &[u32]) -> usize {
+	let bounds = SIZE_BOUNDARIES_U32.map(|bound| u32x4_splat(bound));
+	let mut counts = u32x4_splat(0);
+	for chunk in block.chunks_exact(4) {
+		let values = v128_load(chunk.as_ptr().cast());
+		for bound in bounds {
+			// Each reached boundary sets the lane to all ones, i.e. -1.
+			counts = i32x4_sub(counts, u32x4_ge(values, bound));
+		}
+	}
+	let lanes = [
+		u32x4_extract_lane::<0>(counts),
+		u32x4_extract_lane::<1>(counts),
+		u32x4_extract_lane::<2>(counts),
+		u32x4_extract_lane::<3>(counts),
+	];
+	lanes.iter().map(|&count| count as usize).sum()
+}
+
+/// Narrows four values below `0x80` to one byte each.
+#[inline]
+unsafe fn encode_1byte(buf: &mut [u8], offset: usize, values: v128) -> usize {
+	let narrowed =
+		i8x16_shuffle::<0, 4, 8, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0>(
+			values, values,
+		);
+	let bytes = u32x4_extract_lane::<0>(narrowed).to_le_bytes();
+	buf[offset..offset + 4].copy_from_slice(&bytes);
+	4
+}
+
+/// Widens sixteen single-byte values to `u32`.
+#[inline]
+unsafe fn decode_1byte(data: v128, values: &mut [u32], i: usize) {
+	let low = u16x8_extend_low_u8x16(data);
+	let high = u16x8_extend_high_u8x16(data);
+	debug_assert!(i + 16 <= values.len(), "SIMD store past end of values");
+	let out = values.as_mut_ptr().add(i);
+
+	v128_store(out.cast(), u32x4_extend_low_u16x8(low));
+	v128_store(out.add(4).cast(), u32x4_extend_high_u16x8(low));
+	v128_store(out.add(8).cast(), u32x4_extend_low_u16x8(high));
+	v128_store(out.add(12).cast(), u32x4_extend_high_u16x8(high));
+}
@@ -1,6 +1,10 @@
 //! x86_64 SIMD implementation using SSE2 instructions
 
-use super::{handle_remaining_decode, handle_remaining_encode, SimdImpl};
+use super::{
+	delta_scalar, group_len, handle_remaining_decode, handle_remaining_encode,
+	prefix_sum_chunked, store_group, sum_encoded_sizes_u32, SimdImpl,
+	SIZE_BOUNDARIES_U32,
+};
 
 #[cfg(not(test))]
 use core::arch::x86_64::*;
@@ -47,24 +51,142 @@ impl SimdImpl for X86_64Simd {
 		let mut offset = 0;
 		let mut i = 0;
 
-		while i + 3 < values.len() && offset + 20 <= buf.len() {
-			let first_byte = buf[offset];
-			let bytes_needed = if first_byte < 0xC0 {
-				decode_2byte(buf, offset, values, i)
-			} else if first_byte < 0xE0 {
-				decode_3byte(buf, offset, values, i)
-			} else if first_byte < 0xF0 {
-				decode_4byte(buf, offset, values, i)
-			} else {
-				decode_5byte(buf, offset, values, i)
+		while i + 4 <= values.len() {
+			let Some(len) = group_len(buf, offset) else {
+				if offset >= buf.len() {
+					break;
+				}
+				let group = &mut values[..=i];
+				offset = handle_remaining_decode(buf, group, offset, i);
+				i += 1;
+				continue;
 			};
-
-			offset = bytes_needed;
+			let decoded = match len {
+				1 => decode_1byte(buf, offset),
+				2 => decode_2byte(buf, offset),
+				3 => decode_3byte(buf, offset),
+				4 => decode_4byte(buf, offset),
+				_ => decode_5byte(buf, offset),
+			};
+			store_values(values, i, decoded);
+			offset += 4 * len;
 			i += 4;
 		}
 
 		handle_remaining_decode(buf, values, offset, i)
 	}
+
+	#[inline]
+	fn bulk_encoded_size_u32(values: &[u32]) -> usize {
+		sum_encoded_sizes_u32(values, |block| unsafe {
+			count_boundaries(block)
+		})
+	}
+
+	#[inline]
+	fn delta_u32(prev: u32, values: &[u32], deltas: &mut [u32]) {
+		let len = values.len().min(deltas.len());
+		let body_len = len - len % 4;
+		unsafe { delta_kernel(prev, &values[..body_len], deltas) };
+		let prev = match body_len {
+			0 => prev,
+			_ => values[body_len - 1],
+		};
+		delta_scalar(prev, &values[body_len..len], &mut deltas[body_len..]);
+	}
+
+	#[inline]
+	fn prefix_sum_u32(prev: u32, values: &mut [u32]) -> u32 {
+		prefix_sum_chunked(prev, values, |prev, body| unsafe {
+			prefix_sum_kernel(prev, body)
+		})
+	}
+}
+
+/// Differences whole 4-value chunks of `values` into `deltas`.
+///
+/// Each lane's predecessor is the vector shifted up one lane, with the last
+/// lane of the previous chunk shifted in at the bottom.
+#[inline]
+unsafe fn delta_kernel(prev: u32, values: &[u32], deltas: &mut [u32]) {
+	let mut carry = _mm_set1_epi32(prev as i32);
+	for (chunk, out) in values.chunks_exact(4).zip(deltas.chunks_exact_mut(4)) {
+		let current = _mm_loadu_si128(chunk.as_ptr().cast());
+		let previous =
+			_mm_or_si128(_mm_slli_si128(current, 4), _mm_srli_si128(carry, 12));
+		_mm_storeu_si128(
+			out.as_mut_ptr().cast(),
+			_mm_sub_epi32(current, previous),
+		);
+		carry = current;
+	}
+}
+
+/// Prefix sums whole 4-value chunks of `values` in place, returning the
+/// running sum after them.
+///
+/// Two shift-and-add steps give each lane the sum of the lanes up to it,
+/// and the running sum so far is broadcast and added to every lane, so the
+/// only dependency between chunks is that one addition.
+#[inline]
+unsafe fn prefix_sum_kernel(prev: u32, values: &mut [u32]) -> u32 {
+	let mut carry = _mm_set1_epi32(prev as i32);
+	for chunk in values.chunks_exact_mut(4) {
+		let mut sums = _mm_loadu_si128(chunk.as_ptr().cast());
+		sums = _mm_add_epi32(sums, _mm_slli_si128(sums, 4));
+		sums = _mm_add_epi32(sums, _mm_slli_si128(sums, 8));
+		sums = _mm_add_epi32(sums, carry);
+		_mm_storeu_si128(chunk.as_mut_ptr().cast(), sums);
+		carry = _mm_shuffle_epi32(sums, 0xFF);
+	}
+	_mm_cvtsi128_si32(carry) as u32
+}
+
+/// Counts how many size class boundaries each value in `block` reaches.
+///
+/// SSE2 only compares signed lanes, so values and boundaries are biased by
+/// `i32::MIN` to compare them as unsigned.
+#[inline]
+unsafe fn count_boundaries(block: &[u32]) -> usize {
+	let bias = _mm_set1_epi32(i32::MIN);
+	let bounds = SIZE_BOUNDARIES_U32
+		.map(|bound| _mm_set1_epi32((bound - 1) as i32 ^ i32::MIN));
+	let mut counts = _mm_setzero_si128();
+	for chunk in block.chunks_exact(4) {
+		let values =
+			_mm_xor_si128(_mm_loadu_si128(chunk.as_ptr().cast()), bias);
+		for bound in bounds {
+			// Each reached boundary sets the lane to -1.
+			counts = _mm_sub_epi32(counts, _mm_cmpgt_epi32(values, bound));
+		}
+	}
+	let mut lanes = [0u32; 4];
+	_mm_storeu_si128(lanes.as_mut_ptr().cast(), counts);
+	lanes.iter().map(|&count| count as usize).sum()
+}
+
+/// Loads the `N` bytes of `buf` at `offset` into the low bytes of a
+/// vector, zeroing the rest, without reading past them.
+#[inline]
+unsafe fn load_partial<const N: usize>(buf: &[u8], offset: usize) -> __m128i {
+	debug_assert!(offset + N <= buf.len(), "SIMD load past end of buffer");
+	let ptr = buf.as_ptr().add(offset);
+	match N {
+		4 => _mm_cvtsi32_si128(ptr.cast::<i32>().read_unaligned()),
+		8 => _mm_loadl_epi64(ptr.cast()),
+		12 => _mm_unpacklo_epi64(
+			_mm_loadl_epi64(ptr.cast()),
+			_mm_cvtsi32_si128(ptr.add(8).cast::<i32>().read_unaligned()),
+		),
+		_ => _mm_loadu_si128(ptr.cast()),
+	}
+}
+
+/// Stores four decoded values to `values[i..i + 4]`.
+#[inline]
+unsafe fn store_values(values: &mut [u32], i: usize, decoded: __m128i) {
+	debug_assert!(i + 4 <= values.len(), "SIMD store past end of values");
+	_mm_storeu_si128(values.as_mut_ptr().add(i).cast(), decoded);
 }
 
 #[inline]
@@ -88,10 +210,7 @@ unsafe fn encode_2byte(
 	combined[6] = 0x80 | ((v3 & 0x3F) as u8);
 	combined[7] = (v3 >> 6) as u8;
 
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset).cast(),
-		_mm_loadu_si128(combined.as_ptr().cast()),
-	);
+	store_group(buf, offset, &combined);
 	8
 }
 
@@ -120,14 +239,7 @@ unsafe fn encode_3byte(
 	combined[10] = (v3 >> 5) as u8;
 	combined[11] = (v3 >> 13) as u8;
 
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset).cast(),
-		_mm_loadu_si128(combined.as_ptr().cast()),
-	);
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset + 8).cast(),
-		_mm_loadu_si128(combined.as_ptr().add(8).cast()),
-	);
+	store_group(buf, offset, &combined);
 	12
 }
 
@@ -160,14 +272,7 @@ unsafe fn encode_4byte(
 	combined[14] = (v3 >> 12) as u8;
 	combined[15] = (v3 >> 20) as u8;
 
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset).cast(),
-		_mm_loadu_si128(combined.as_ptr().cast()),
-	);
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset + 8).cast(),
-		_mm_loadu_si128(combined.as_ptr().add(8).cast()),
-	);
+	store_group(buf, offset, &combined);
 	16
 }
 
@@ -204,124 +309,76 @@ unsafe fn encode_5byte(
 	combined[18] = (v3 >> 16) as u8;
 	combined[19] = (v3 >> 24) as u8;
 
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset).cast(),
-		_mm_loadu_si128(combined.as_ptr().cast()),
-	);
-	_mm_storeu_si128(
-		buf.as_mut_ptr().add(offset + 8).cast(),
-		_mm_loadu_si128(combined.as_ptr().add(8).cast()),
-	);
-	*(buf.as_mut_ptr().add(offset + 16) as *mut u32) = u32::from_le_bytes([
-		combined[16],
-		combined[17],
-		combined[18],
-		combined[19],
-	]);
+	store_group(buf, offset, &combined);
 	20
 }
 
+/// Decodes four 1-byte values.
 #[inline]
-unsafe fn decode_2byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let data = _mm_loadu_si128(buf.as_ptr().add(offset).cast());
-
-	let low_bits = _mm_and_si128(data, _mm_set1_epi8(0x3F));
-	let high_bits =
-		_mm_slli_epi32(_mm_cvtepu8_epi32(_mm_srli_si128(data, 1)), 6);
-	let combined = _mm_or_si128(_mm_cvtepu8_epi32(low_bits), high_bits);
-
-	_mm_storeu_si128(values.as_mut_ptr().add(i).cast(), combined);
-
-	offset + 8
+unsafe fn decode_1byte(buf: &[u8], offset: usize) -> __m128i {
+	let zero = _mm_setzero_si128();
+	let data = load_partial::<4>(buf, offset);
+	_mm_unpacklo_epi16(_mm_unpacklo_epi8(data, zero), zero)
 }
 
+/// Decodes four 2-byte values.
 #[inline]
-unsafe fn decode_3byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let data = _mm_loadu_si128(buf.as_ptr().add(offset).cast());
-
-	let low_bits = _mm_and_si128(data, _mm_set1_epi8(0x1F));
-	let mid_bits =
-		_mm_slli_epi32(_mm_cvtepu8_epi32(_mm_srli_si128(data, 1)), 5);
-	let high_bits =
-		_mm_slli_epi32(_mm_cvtepu8_epi32(_mm_srli_si128(data, 2)), 13);
+unsafe fn decode_2byte(buf: &[u8], offset: usize) -> __m128i {
+	let data = load_partial::<8>(buf, offset);
+	let words = _mm_unpacklo_epi16(data, _mm_setzero_si128());
+	decode_unary(words, 0x3F, 6)
+}
 
-	let combined = _mm_or_si128(
-		_mm_or_si128(_mm_cvtepu8_epi32(low_bits), mid_bits),
-		high_bits,
+/// Decodes four 3-byte values.
+#[inline]
+unsafe fn decode_3byte(buf: &[u8], offset: usize) -> __m128i {
+	let data = load_partial::<12>(buf, offset);
+	let words = gather_lanes(
+		data,
+		_mm_srli_si128(data, 3),
+		_mm_srli_si128(data, 6),
+		_mm_srli_si128(data, 9),
 	);
-
-	_mm_storeu_si128(values.as_mut_ptr().add(i).cast(), combined);
-
-	offset + 12
+	let words = _mm_and_si128(words, _mm_set1_epi32(0x00FF_FFFF));
+	decode_unary(words, 0x1F, 5)
 }
 
+/// Decodes four 4-byte values.
 #[inline]
-unsafe fn decode_4byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let data = _mm_loadu_si128(buf.as_ptr().add(offset).cast());
-
-	let low_bits = _mm_and_si128(data, _mm_set1_epi8(0x0F));
-	let mid1_bits =
-		_mm_slli_epi32(_mm_cvtepu8_epi32(_mm_srli_si128(data, 1)), 4);
-	let mid2_bits =
-		_mm_slli_epi32(_mm_cvtepu8_epi32(_mm_srli_si128(data, 2)), 12);
-	let high_bits =
-		_mm_slli_epi32(_mm_cvtepu8_epi32(_mm_srli_si128(data, 3)), 20);
-
-	let combined = _mm_or_si128(
-		_mm_or_si128(
-			_mm_or_si128(_mm_cvtepu8_epi32(low_bits), mid1_bits),
-			mid2_bits,
-		),
-		high_bits,
-	);
+unsafe fn decode_4byte(buf: &[u8], offset: usize) -> __m128i {
+	decode_unary(load_partial::<16>(buf, offset), 0x0F, 4)
+}
 
-	_mm_storeu_si128(values.as_mut_ptr().add(i).cast(), combined);
+/// Decodes four values with the 5-byte binary length prefix.
+#[inline]
+unsafe fn decode_5byte(buf: &[u8], offset: usize) -> __m128i {
+	let data = load_partial::<16>(buf, offset);
+	gather_lanes(
+		_mm_srli_si128(data, 1),
+		_mm_srli_si128(data, 6),
+		_mm_srli_si128(data, 11),
+		load_partial::<4>(buf, offset + 16),
+	)
+}
 
-	offset + 16
+/// Combines the low lanes of four vectors, in order.
+#[inline]
+unsafe fn gather_lanes(
+	a: __m128i,
+	b: __m128i,
+	c: __m128i,
+	d: __m128i,
+) -> __m128i {
+	_mm_unpacklo_epi64(_mm_unpacklo_epi32(a, b), _mm_unpacklo_epi32(c, d))
 }
 
+/// Decodes lanes that each hold one value with a unary length prefix,
+/// keeping `low_mask` of the prefix byte and placing the payload bytes
+/// above them at `shift`.
 #[inline]
-unsafe fn decode_5byte(
-	buf: &[u8],
-	offset: usize,
-	values: &mut [u32],
-	i: usize,
-) -> usize {
-	let mut temp_buf = [0u8; 20];
-	let copy_len = core::cmp::min(20, buf.len() - offset);
-	temp_buf[..copy_len].copy_from_slice(&buf[offset..offset + copy_len]);
-
-	let mut out = [0u32; 4];
-	for (j, item) in out.iter_mut().enumerate() {
-		if i + j >= values.len() {
-			break;
-		}
-		let data_offset = j * 5 + 1;
-		*item = u32::from_le_bytes([
-			temp_buf[data_offset],
-			temp_buf[data_offset + 1],
-			temp_buf[data_offset + 2],
-			temp_buf[data_offset + 3],
-		]);
-	}
-	_mm_storeu_si128(
-		values.as_mut_ptr().add(i).cast(),
-		_mm_loadu_si128(out.as_ptr().cast()),
-	);
-	offset + 20
+unsafe fn decode_unary(words: __m128i, low_mask: i32, shift: i32) -> __m128i {
+	let low = _mm_and_si128(words, _mm_set1_epi32(low_mask));
+	let high =
+		_mm_sll_epi32(_mm_srli_epi32(words, 8), _mm_cvtsi32_si128(shift));
+	_mm_or_si128(low, high)
 }
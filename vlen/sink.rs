@@ -0,0 +1,196 @@
+//! Destinations that encoded values can be written to
+//!
+//! A [`Sink`] accepts encoded bytes and values, whatever it stores them in.
+//! It is implemented for byte slices, which are filled from the front like
+//! `std::io::Write` does, and with the `alloc` feature for `Vec<u8>` and
+//! [`VlenBuf`](crate::VlenBuf). With the `std` feature, [`IoSink`] adapts any
+//! `std::io::Write`. Code generic over a sink, such as [`encode_into`] or
+//! [`blocks::encode_into`](crate::blocks::encode_into), writes the same
+//! encoding to all of them.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::encode_into;
+//!
+//! let mut buf = [0u8; 8];
+//! let mut sink = &mut buf[..];
+//! encode_into(&mut sink, 300u32)?;
+//! encode_into(&mut sink, -1i64)?;
+//! assert_eq!(sink.len(), 5);
+//! assert_eq!(vlen::decode_value::<u32>(&buf), Ok(300));
+//! # Ok::<(), &'static str>(())
+//! ```
+
+use crate::encode::Encode;
+
+/// Error returned when a byte slice has no room for a write.
+const FULL: &str = "buffer too small for sink write";
+
+/// Largest encoding written through a stack buffer.
+const SCRATCH_LEN: usize = 32;
+
+/// A destination for encoded bytes.
+pub trait Sink {
+	/// Writes `bytes` in full.
+	fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), &'static str>;
+
+	/// Writes the encoding of `value`, returning its encoded length.
+	///
+	/// The default implementation encodes into a scratch buffer and passes
+	/// it to [`write_bytes`](Self::write_bytes); it fails for types that may
+	/// encode to more than 32 bytes.
+	fn write_value<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode + Clone,
+	{
+		if T::MAX_ENCODED_SIZE > SCRATCH_LEN {
+			return Err("value too large for sink scratch buffer");
+		}
+		write_scratch(self, value)
+	}
+}
+
+/// Encodes a value of at most [`SCRATCH_LEN`] bytes on the stack and writes
+/// it to `sink`.
+#[inline]
+fn write_scratch<S, T>(sink: &mut S, value: T) -> Result<usize, &'static str>
+where
+	S: Sink + ?Sized,
+	T: Encode,
+{
+	let mut scratch = [0u8; SCRATCH_LEN];
+	let len = T::encode(&mut scratch, value)?;
+	sink.write_bytes(&scratch[..len])?;
+	Ok(len)
+}
+
+/// Writes to the front of the slice and advances it past the written bytes.
+///
+/// A write that does not fit fails and leaves the slice where it was.
+impl Sink for &mut [u8] {
+	fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+		if bytes.len() > self.len() {
+			return Err(FULL);
+		}
+		let (dst, rest) = core::mem::take(self).split_at_mut(bytes.len());
+		dst.copy_from_slice(bytes);
+		*self = rest;
+		Ok(())
+	}
+
+	fn write_value<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode + Clone,
+	{
+		let mut scratch = [0u8; SCRATCH_LEN];
+		let len = if T::MAX_ENCODED_SIZE > SCRATCH_LEN {
+			// Large types size their own output, so encode in place.
+			T::encode(self, value)?
+		} else {
+			let len = T::encode(&mut scratch, value)?;
+			self.get_mut(..len)
+				.ok_or(FULL)?
+				.copy_from_slice(&scratch[..len]);
+			len
+		};
+		*self = &mut core::mem::take(self)[len..];
+		Ok(len)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Sink for alloc::vec::Vec<u8> {
+	#[inline]
+	fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+		self.extend_from_slice(bytes);
+		Ok(())
+	}
+
+	#[inline]
+	fn write_value<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode + Clone,
+	{
+		crate::encode_append(self, value)
+	}
+}
+
+/// Encodes `value` into `sink`, returning its encoded length.
+#[inline]
+pub fn encode_into<S, T>(sink: &mut S, value: T) -> Result<usize, &'static str>
+where
+	S: Sink + ?Sized,
+	T: Encode + Clone,
+{
+	sink.write_value(value)
+}
+
+/// Adapts a `std::io::Write` into a [`Sink`].
+///
+/// `Sink` errors are `&'static str`, so an I/O error is reported as
+/// `"sink write failed"` and kept for [`take_error`](Self::take_error).
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSink<W> {
+	writer: W,
+	error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoSink<W> {
+	/// Wraps `writer`.
+	#[inline]
+	pub const fn new(writer: W) -> Self {
+		Self {
+			writer,
+			error: None,
+		}
+	}
+
+	/// Returns the I/O error behind the last failed write, if any.
+	#[inline]
+	pub fn take_error(&mut self) -> Option<std::io::Error> {
+		self.error.take()
+	}
+
+	/// Returns a reference to the underlying writer.
+	#[inline]
+	#[must_use]
+	pub fn get_ref(&self) -> &W {
+		&self.writer
+	}
+
+	/// Returns the underlying writer.
+	#[inline]
+	#[must_use]
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+}
+
+#[cfg(feature = "std")]
+impl<W> Sink for IoSink<W>
+where
+	W: std::io::Write,
+{
+	fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+		self.writer.write_all(bytes).map_err(|err| {
+			self.error = Some(err);
+			"sink write failed"
+		})
+	}
+
+	fn write_value<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode + Clone,
+	{
+		if T::MAX_ENCODED_SIZE > SCRATCH_LEN {
+			let encoded = crate::encode_to_vec(value)?;
+			self.write_bytes(&encoded)?;
+			return Ok(encoded.len());
+		}
+		write_scratch(self, value)
+	}
+}
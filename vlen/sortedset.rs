@@ -0,0 +1,414 @@
+//! Sorted integer sets with set operations on the encoded form
+//!
+//! A set is stored as its smallest element followed by the gap minus one
+//! between each pair of consecutive elements, so dense sets such as
+//! postings lists encode to about a byte per element. [`union`],
+//! [`intersect`] and [`contains`] stream over the encoded inputs and never
+//! materialize them. For large sets, [`search`] finds an element through a
+//! [`SetIndex`] of sampled elements and decodes only the stretch between
+//! two samples.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::sortedset;
+//!
+//! let mut a = [0u8; 16];
+//! let mut b = [0u8; 16];
+//! let a_len = sortedset::encode::<u32>(&mut a, &[1, 2, 3, 1000]).unwrap();
+//! let b_len = sortedset::encode::<u32>(&mut b, &[2, 1000, 5000]).unwrap();
+//! let (a, b) = (&a[..a_len], &b[..b_len]);
+//!
+//! let mut out = [0u8; 32];
+//! let len = sortedset::intersect::<u32>(a, b, &mut out).unwrap();
+//! let values: Result<Vec<u32>, _> = sortedset::iter(&out[..len]).collect();
+//! assert_eq!(values.unwrap(), [2, 1000]);
+//! assert!(sortedset::contains(b, 5000u32).unwrap());
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::FusedIterator;
+
+use crate::decode::{decode_iter, Decode, DecodeIter};
+use crate::encode::Encode;
+
+/// Error returned when the output has no room for the next element.
+const TOO_SMALL: &str = "buffer too small for sorted set encoding";
+
+/// Integer types that can be stored in a sorted set.
+pub trait SetElement: Encode + Decode + Copy + Ord {
+	/// Returns the stored gap between `prev` and a larger `next`.
+	fn gap(prev: Self, next: Self) -> Self;
+
+	/// Returns the element stored `gap` after `prev`, or `None` if it
+	/// overflows.
+	fn step(prev: Self, gap: Self) -> Option<Self>;
+}
+
+macro_rules! impl_set_element {
+	($($t:ty),* $(,)?) => {$(
+		impl SetElement for $t {
+			#[inline]
+			fn gap(prev: Self, next: Self) -> Self {
+				next - prev - 1
+			}
+
+			#[inline]
+			fn step(prev: Self, gap: Self) -> Option<Self> {
+				prev.checked_add(gap)?.checked_add(1)
+			}
+		}
+	)*};
+}
+
+impl_set_element!(u32, u64);
+
+/// Writes a sorted set into a buffer one element at a time.
+#[derive(Debug)]
+pub struct SetEncoder<'a, T> {
+	buf: &'a mut [u8],
+	offset: usize,
+	prev: Option<T>,
+}
+
+impl<'a, T> SetEncoder<'a, T>
+where
+	T: SetElement,
+{
+	/// Creates an encoder that writes to the start of `buf`.
+	#[inline]
+	pub fn new(buf: &'a mut [u8]) -> Self {
+		Self {
+			buf,
+			offset: 0,
+			prev: None,
+		}
+	}
+
+	/// Appends an element larger than every element pushed so far.
+	pub fn push(&mut self, value: T) -> Result<(), &'static str> {
+		let stored = match self.prev {
+			None => value,
+			Some(prev) if prev < value => T::gap(prev, value),
+			Some(_) => return Err("sorted set is not strictly increasing"),
+		};
+		let rest = &mut self.buf[self.offset..];
+		if rest.is_empty() {
+			return Err(TOO_SMALL);
+		}
+		let mut temp_buf = [0u8; 32];
+		self.offset += if <T as Encode>::MAX_ENCODED_SIZE <= temp_buf.len() {
+			// Encode through a scratch buffer so the output only needs
+			// room for the actual encoding.
+			let len = T::encode(&mut temp_buf, stored)?;
+			rest.get_mut(..len)
+				.ok_or(TOO_SMALL)?
+				.copy_from_slice(&temp_buf[..len]);
+			len
+		} else {
+			T::encode(rest, stored)?
+		};
+		self.prev = Some(value);
+		Ok(())
+	}
+
+	/// Returns the number of bytes written so far.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.offset
+	}
+
+	/// Returns `true` if no elements have been pushed.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.offset == 0
+	}
+}
+
+/// Encodes a strictly increasing set, returning the encoded length.
+pub fn encode<T>(buf: &mut [u8], set: &[T]) -> Result<usize, &'static str>
+where
+	T: SetElement,
+{
+	let mut encoder = SetEncoder::new(buf);
+	for &value in set {
+		encoder.push(value)?;
+	}
+	Ok(encoder.len())
+}
+
+/// Returns an iterator over the elements of an encoded set.
+#[inline]
+pub fn iter<T>(buf: &[u8]) -> SetIter<'_, T>
+where
+	T: SetElement,
+{
+	SetIter {
+		inner: decode_iter(buf),
+		prev: None,
+	}
+}
+
+/// Iterator over the elements of an encoded set.
+///
+/// Yields an error and then stops if the buffer is truncated or an element
+/// overflows `T`. Created by [`iter`].
+#[derive(Debug, Clone)]
+pub struct SetIter<'a, T> {
+	inner: DecodeIter<'a, T>,
+	prev: Option<T>,
+}
+
+impl<T> Iterator for SetIter<'_, T>
+where
+	T: SetElement,
+{
+	type Item = Result<T, &'static str>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let stored = match self.inner.next()? {
+			Ok(stored) => stored,
+			Err(err) => return Some(Err(err)),
+		};
+		let value = match self.prev {
+			None => stored,
+			Some(prev) => match T::step(prev, stored) {
+				Some(value) => value,
+				None => {
+					// Fuse the iterator like a truncated value does.
+					for _ in self.inner.by_ref() {}
+					return Some(Err("sorted set element out of range"));
+				},
+			},
+		};
+		self.prev = Some(value);
+		Some(Ok(value))
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.inner.size_hint()
+	}
+}
+
+impl<T> FusedIterator for SetIter<'_, T> where T: SetElement {}
+
+/// Returns whether an encoded set contains `value`.
+///
+/// Stops decoding at the first element not smaller than `value`.
+pub fn contains<T>(buf: &[u8], value: T) -> Result<bool, &'static str>
+where
+	T: SetElement,
+{
+	for element in iter::<T>(buf) {
+		match element?.cmp(&value) {
+			Ordering::Less => {},
+			Ordering::Equal => return Ok(true),
+			Ordering::Greater => return Ok(false),
+		}
+	}
+	Ok(false)
+}
+
+/// A sampled index of an encoded sorted set, used by [`search`].
+///
+/// Records every `every_n`-th element with the offset of the gap that
+/// follows it, so decoding can resume at any sample.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetIndex<T> {
+	every_n: usize,
+	len: usize,
+	samples: Vec<(usize, T)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> SetIndex<T>
+where
+	T: SetElement,
+{
+	/// Builds an index sampling every `every_n`-th element of the set in
+	/// `buf`.
+	pub fn build(buf: &[u8], every_n: usize) -> Result<Self, &'static str> {
+		if every_n == 0 {
+			return Err("index interval must be non-zero");
+		}
+		let mut samples = Vec::new();
+		let mut len = 0;
+		let mut elements = iter::<T>(buf);
+		while let Some(element) = elements.next() {
+			let element = element?;
+			if len % every_n == 0 {
+				samples.push((elements.inner.offset(), element));
+			}
+			len += 1;
+		}
+		Ok(Self {
+			every_n,
+			len,
+			samples,
+		})
+	}
+
+	/// Returns the sampling interval.
+	#[inline]
+	#[must_use]
+	pub fn every_n(&self) -> usize {
+		self.every_n
+	}
+
+	/// Returns the number of elements in the indexed set.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if the indexed set is empty.
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
+
+/// Returns the position of `target` in an encoded set, or `None` if the set
+/// does not contain it.
+///
+/// Gallops over the samples of `index` and binary searches the last stretch
+/// to find the sample at or before `target`, then decodes at most
+/// `every_n - 1` elements after it. `index` must have been built from
+/// `buf`.
+///
+/// ```rust
+/// use vlen::sortedset::{self, SetIndex};
+///
+/// let postings: Vec<u32> = (0..100_000).map(|i| i * 3).collect();
+/// let mut buf = vec![0u8; 200_000];
+/// let len = sortedset::encode(&mut buf, &postings).unwrap();
+/// let buf = &buf[..len];
+///
+/// let index = SetIndex::<u32>::build(buf, 128).unwrap();
+/// assert_eq!(sortedset::search(buf, &index, 150_000), Ok(Some(50_000)));
+/// assert_eq!(sortedset::search(buf, &index, 150_001), Ok(None));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub fn search<T>(
+	buf: &[u8],
+	index: &SetIndex<T>,
+	target: T,
+) -> Result<Option<usize>, &'static str>
+where
+	T: SetElement,
+{
+	let samples = &index.samples;
+	match samples.first() {
+		Some(&(_, first)) if first <= target => {},
+		_ => return Ok(None),
+	}
+	let mut end = 1;
+	while end < samples.len() && samples[end].1 <= target {
+		end *= 2;
+	}
+	let start = end / 2;
+	let end = end.min(samples.len());
+	let sample = start
+		+ samples[start..end].partition_point(|&(_, value)| value <= target)
+		- 1;
+	let (offset, value) = samples[sample];
+	let mut position = sample * index.every_n;
+	if value == target {
+		return Ok(Some(position));
+	}
+	let rest = buf.get(offset..).ok_or("index does not match buffer")?;
+	let elements = SetIter {
+		inner: decode_iter(rest),
+		prev: Some(value),
+	};
+	for element in elements.take(index.every_n - 1) {
+		position += 1;
+		match element?.cmp(&target) {
+			Ordering::Less => {},
+			Ordering::Equal => return Ok(Some(position)),
+			Ordering::Greater => return Ok(None),
+		}
+	}
+	Ok(None)
+}
+
+/// Writes the union of two encoded sets to `out`, returning its length.
+///
+/// The union never encodes to more than `a.len() + b.len()` bytes.
+pub fn union<T>(
+	a: &[u8],
+	b: &[u8],
+	out: &mut [u8],
+) -> Result<usize, &'static str>
+where
+	T: SetElement,
+{
+	let mut out = SetEncoder::new(out);
+	let (mut a, mut b) = (iter::<T>(a), iter::<T>(b));
+	let (mut x, mut y) = (a.next().transpose()?, b.next().transpose()?);
+	loop {
+		match (x, y) {
+			(Some(u), Some(v)) => match u.cmp(&v) {
+				Ordering::Less => {
+					out.push(u)?;
+					x = a.next().transpose()?;
+				},
+				Ordering::Greater => {
+					out.push(v)?;
+					y = b.next().transpose()?;
+				},
+				Ordering::Equal => {
+					out.push(u)?;
+					x = a.next().transpose()?;
+					y = b.next().transpose()?;
+				},
+			},
+			(Some(u), None) => {
+				out.push(u)?;
+				x = a.next().transpose()?;
+			},
+			(None, Some(v)) => {
+				out.push(v)?;
+				y = b.next().transpose()?;
+			},
+			(None, None) => return Ok(out.len()),
+		}
+	}
+}
+
+/// Writes the intersection of two encoded sets to `out`, returning its
+/// length.
+pub fn intersect<T>(
+	a: &[u8],
+	b: &[u8],
+	out: &mut [u8],
+) -> Result<usize, &'static str>
+where
+	T: SetElement,
+{
+	let mut out = SetEncoder::new(out);
+	let (mut a, mut b) = (iter::<T>(a), iter::<T>(b));
+	let (mut x, mut y) = (a.next().transpose()?, b.next().transpose()?);
+	while let (Some(u), Some(v)) = (x, y) {
+		match u.cmp(&v) {
+			Ordering::Less => x = a.next().transpose()?,
+			Ordering::Greater => y = b.next().transpose()?,
+			Ordering::Equal => {
+				out.push(u)?;
+				x = a.next().transpose()?;
+				y = b.next().transpose()?;
+			},
+		}
+	}
+	Ok(out.len())
+}
@@ -0,0 +1,129 @@
+//! Encoded size statistics
+//!
+//! [`analyze`] reports how many values encode at each length and compares
+//! the total encoded size with storing the same values at their fixed
+//! in-memory width, which helps decide per column whether vlen pays off.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let stats = vlen::stats::analyze(&[1u32, 2, 300, 70000]).unwrap();
+//! assert_eq!(stats.counts[1], 2);
+//! assert_eq!(stats.counts[2], 1);
+//! assert_eq!(stats.counts[3], 1);
+//! assert_eq!(stats.encoded_size, 7);
+//! assert_eq!(stats.fixed_size, 16);
+//! assert_eq!(stats.max_len(), 3);
+//! ```
+
+use core::mem::size_of;
+
+use crate::encode::Encode;
+
+/// Longest encoded length counted in a bucket of its own.
+pub const MAX_TRACKED_LEN: usize = 32;
+
+/// Encoded lengths of a set of values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SizeHistogram {
+	/// Number of values by encoded length, so `counts[2]` is the number of
+	/// values that take two bytes. Lengths above [`MAX_TRACKED_LEN`] are
+	/// counted in the last entry.
+	pub counts: [usize; MAX_TRACKED_LEN + 1],
+	/// Number of values recorded.
+	pub values: usize,
+	/// Total encoded size in bytes.
+	pub encoded_size: usize,
+	/// Total size in bytes at the fixed width of each value's type.
+	pub fixed_size: usize,
+}
+
+impl SizeHistogram {
+	/// Creates an empty histogram.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			counts: [0; MAX_TRACKED_LEN + 1],
+			values: 0,
+			encoded_size: 0,
+			fixed_size: 0,
+		}
+	}
+
+	/// Adds a value, returning its encoded length.
+	#[inline]
+	pub fn record<T>(&mut self, value: T) -> Result<usize, &'static str>
+	where
+		T: Encode,
+	{
+		let len = T::encoded_size(value)?;
+		self.counts[len.min(MAX_TRACKED_LEN)] += 1;
+		self.values += 1;
+		self.encoded_size += len;
+		self.fixed_size += size_of::<T>();
+		Ok(len)
+	}
+
+	/// Returns the longest encoded length recorded, or zero if empty.
+	///
+	/// Lengths above [`MAX_TRACKED_LEN`] are reported as that length.
+	#[inline]
+	#[must_use]
+	pub fn max_len(&self) -> usize {
+		self.counts
+			.iter()
+			.rposition(|&count| count > 0)
+			.unwrap_or(0)
+	}
+
+	/// Returns the mean encoded length, or zero if empty.
+	#[inline]
+	#[must_use]
+	pub fn mean_len(&self) -> f64 {
+		if self.values == 0 {
+			return 0.0;
+		}
+		self.encoded_size as f64 / self.values as f64
+	}
+
+	/// Returns the encoded size as a fraction of the fixed-width size, or
+	/// zero if empty. Values below one mean vlen saves space.
+	#[inline]
+	#[must_use]
+	pub fn ratio(&self) -> f64 {
+		if self.fixed_size == 0 {
+			return 0.0;
+		}
+		self.encoded_size as f64 / self.fixed_size as f64
+	}
+
+	/// Returns the total size if every value were stored at `width` bytes.
+	///
+	/// Useful for comparing with a narrower fixed width than the type's,
+	/// such as the width of the largest value.
+	#[inline]
+	#[must_use]
+	pub const fn fixed_size_at(&self, width: usize) -> usize {
+		self.values * width
+	}
+}
+
+impl Default for SizeHistogram {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Computes the encoded size histogram of `values`.
+pub fn analyze<T>(values: &[T]) -> Result<SizeHistogram, &'static str>
+where
+	T: Encode + Clone,
+{
+	let mut histogram = SizeHistogram::new();
+	for value in values {
+		histogram.record(value.clone())?;
+	}
+	Ok(histogram)
+}
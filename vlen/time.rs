@@ -0,0 +1,150 @@
+//! Encoding of time values for vlen
+//!
+//! A [`Duration`] is encoded as its whole seconds (vlen `u64`) followed by its
+//! subsecond nanoseconds (vlen `u32`). With the `std` feature, a `SystemTime`
+//! is encoded as the signed offset from the Unix epoch: whole seconds (vlen
+//! `i64`) followed by a non-negative nanosecond adjustment (vlen `u32`).
+
+use core::time::Duration;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Encodes the seconds and nanoseconds components of a time value.
+#[inline]
+fn encode_parts<S: Encode>(
+	buf: &mut [u8],
+	secs: S,
+	nanos: u32,
+) -> Result<usize, &'static str> {
+	let secs_len = S::encode(buf, secs)?;
+	let nanos_len = u32::encode(&mut buf[secs_len..], nanos)?;
+	Ok(secs_len + nanos_len)
+}
+
+/// Decodes the seconds and nanoseconds components of a time value.
+#[inline]
+fn decode_parts<S: Decode>(
+	buf: &[u8],
+) -> Result<(S, u32, usize), &'static str> {
+	let (secs, secs_len) = S::decode(buf)?;
+	let (nanos, nanos_len) = u32::decode(&buf[secs_len..])?;
+	if nanos >= NANOS_PER_SEC {
+		return Err("nanoseconds out of range for time decoding");
+	}
+	Ok((secs, nanos, secs_len + nanos_len))
+}
+
+impl Encode for Duration {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		if buf.len() < <Self as Encode>::MAX_ENCODED_SIZE {
+			return Err("buffer too small for Duration encoding");
+		}
+		encode_parts(buf, value.as_secs(), value.subsec_nanos())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		Ok(crate::encode::encoded_size_u64(value.as_secs())
+			+ crate::encode::encoded_size_u32(value.subsec_nanos()))
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<u64 as Encode>::MAX_ENCODED_SIZE + <u32 as Encode>::MAX_ENCODED_SIZE;
+
+	const MIN_ENCODED_SIZE: usize =
+		<u64 as Encode>::MIN_ENCODED_SIZE + <u32 as Encode>::MIN_ENCODED_SIZE;
+}
+
+impl Decode for Duration {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (secs, nanos, len) = decode_parts::<u64>(buf)?;
+		Ok((Duration::new(secs, nanos), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<u64 as Decode>::MAX_ENCODED_SIZE + <u32 as Decode>::MAX_ENCODED_SIZE;
+
+	const MIN_ENCODED_SIZE: usize =
+		<u64 as Decode>::MIN_ENCODED_SIZE + <u32 as Decode>::MIN_ENCODED_SIZE;
+}
+
+/// Converts a `SystemTime` into seconds and nanoseconds relative to the Unix
+/// epoch, with the nanoseconds always counting forward from the seconds.
+#[cfg(feature = "std")]
+fn unix_parts(
+	value: std::time::SystemTime,
+) -> Result<(i64, u32), &'static str> {
+	const OUT_OF_RANGE: &str = "SystemTime out of range for encoding";
+	match value.duration_since(std::time::UNIX_EPOCH) {
+		Ok(after) => {
+			let secs =
+				i64::try_from(after.as_secs()).map_err(|_| OUT_OF_RANGE)?;
+			Ok((secs, after.subsec_nanos()))
+		},
+		Err(err) => {
+			let before = err.duration();
+			let secs =
+				i64::try_from(before.as_secs()).map_err(|_| OUT_OF_RANGE)?;
+			match before.subsec_nanos() {
+				0 => Ok((-secs, 0)),
+				nanos => Ok((-secs - 1, NANOS_PER_SEC - nanos)),
+			}
+		},
+	}
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl Encode for std::time::SystemTime {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		if buf.len() < <Self as Encode>::MAX_ENCODED_SIZE {
+			return Err("buffer too small for SystemTime encoding");
+		}
+		let (secs, nanos) = unix_parts(value)?;
+		encode_parts(buf, secs, nanos)
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		let (secs, nanos) = unix_parts(value)?;
+		Ok(i64::encoded_size(secs)? + crate::encode::encoded_size_u32(nanos))
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<i64 as Encode>::MAX_ENCODED_SIZE + <u32 as Encode>::MAX_ENCODED_SIZE;
+
+	const MIN_ENCODED_SIZE: usize =
+		<i64 as Encode>::MIN_ENCODED_SIZE + <u32 as Encode>::MIN_ENCODED_SIZE;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl Decode for std::time::SystemTime {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		const OUT_OF_RANGE: &str = "SystemTime out of range for decoding";
+		let (secs, nanos, len) = decode_parts::<i64>(buf)?;
+		let offset = Duration::new(secs.unsigned_abs(), 0);
+		let whole = if secs >= 0 {
+			std::time::UNIX_EPOCH.checked_add(offset)
+		} else {
+			std::time::UNIX_EPOCH.checked_sub(offset)
+		};
+		let value = whole
+			.and_then(|t| t.checked_add(Duration::new(0, nanos)))
+			.ok_or(OUT_OF_RANGE)?;
+		Ok((value, len))
+	}
+
+	const MAX_ENCODED_SIZE: usize =
+		<i64 as Decode>::MAX_ENCODED_SIZE + <u32 as Decode>::MAX_ENCODED_SIZE;
+
+	const MIN_ENCODED_SIZE: usize =
+		<i64 as Decode>::MIN_ENCODED_SIZE + <u32 as Decode>::MIN_ENCODED_SIZE;
+}
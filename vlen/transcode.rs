@@ -0,0 +1,100 @@
+//! Rewriting encoded buffers as a different integer width
+//!
+//! The widening functions rewrite a buffer of one integer type as a buffer
+//! of the next wider type without decoding it into a vector first. Most
+//! encodings are shared between widths: every `u32` encoding is also a
+//! valid `u64` and `u128` encoding, so those buffers are checked and copied
+//! in runs. `u16` prefix bytes from `0xC0` up mean something else in wider
+//! types, so those values are re-encoded unless they use the `0xF0` or
+//! `0xF1` binary forms, which every width shares. Signed types follow their
+//! unsigned counterparts, since the zigzag mapping does not depend on the
+//! width.
+//!
+//! The narrowing functions decode each value, fail with
+//! `"vlen value out of range"` if it does not fit the narrower type, and
+//! re-encode it.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::transcode::{narrow_u32_to_u16, widen_u16_to_u32};
+//!
+//! let values = [1u16, 300, 0x8000];
+//! let buf = vlen::bulk_encode_to_vec(&values).unwrap();
+//!
+//! let wide = widen_u16_to_u32(&buf).unwrap();
+//! let decoded = vlen::bulk_decode_values::<u32>(&wide).unwrap();
+//! assert_eq!(decoded, [1, 300, 0x8000]);
+//!
+//! assert_eq!(narrow_u32_to_u16(&wide).unwrap(), buf);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// Rewrites a buffer of `S` values as `D` values, copying the encoding of
+/// every value whose prefix byte satisfies `shared` and re-encoding the
+/// rest.
+fn transcode<S, D>(
+	buf: &[u8],
+	shared: fn(u8) -> bool,
+) -> Result<Vec<u8>, &'static str>
+where
+	S: Decode,
+	D: Encode + Copy + TryFrom<S>,
+{
+	let mut out = Vec::with_capacity(buf.len());
+	let mut run_start = 0;
+	let mut offset = 0;
+	while let Some(&prefix) = buf.get(offset) {
+		let (value, len) = S::decode_checked(&buf[offset..])?;
+		if offset + len > buf.len() {
+			return Err("truncated vlen value");
+		}
+		if !shared(prefix) {
+			let value =
+				D::try_from(value).map_err(|_| "vlen value out of range")?;
+			out.extend_from_slice(&buf[run_start..offset]);
+			crate::encode_append(&mut out, value)?;
+			run_start = offset + len;
+		}
+		offset += len;
+	}
+	out.extend_from_slice(&buf[run_start..]);
+	Ok(out)
+}
+
+/// Returns whether a `u16` prefix byte means the same in `u32`.
+#[inline]
+fn shared_u16_prefix(prefix: u8) -> bool {
+	prefix < 0xC0 || matches!(prefix, 0xF0 | 0xF1)
+}
+
+macro_rules! impl_transcode {
+	($($name:ident($from:ty => $to:ty, $shared:expr)),* $(,)?) => {
+		$(
+			#[doc = concat!("Rewrites a buffer of `", stringify!($from), "` values as `", stringify!($to), "` values.")]
+			#[inline]
+			pub fn $name(buf: &[u8]) -> Result<Vec<u8>, &'static str> {
+				transcode::<$from, $to>(buf, $shared)
+			}
+		)*
+	};
+}
+
+impl_transcode! {
+	widen_u16_to_u32(u16 => u32, shared_u16_prefix),
+	widen_u32_to_u64(u32 => u64, |_| true),
+	widen_u64_to_u128(u64 => u128, |_| true),
+	widen_i16_to_i32(i16 => i32, shared_u16_prefix),
+	widen_i32_to_i64(i32 => i64, |_| true),
+	widen_i64_to_i128(i64 => i128, |_| true),
+	narrow_u32_to_u16(u32 => u16, |_| false),
+	narrow_u64_to_u32(u64 => u32, |_| false),
+	narrow_u128_to_u64(u128 => u64, |_| false),
+	narrow_i32_to_i16(i32 => i16, |_| false),
+	narrow_i64_to_i32(i64 => i32, |_| false),
+	narrow_i128_to_i64(i128 => i64, |_| false),
+}
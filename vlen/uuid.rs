@@ -0,0 +1,56 @@
+//! UUID encoding for vlen
+//!
+//! UUIDs are encoded through the `u128` path, reading the 16 bytes as a
+//! big-endian integer. Integer-like UUIDs (small sequential IDs, the nil UUID)
+//! therefore get the compact encoding, while random UUIDs take 17 bytes.
+//!
+//! With the `uuid` feature, [`Encode`](crate::Encode) and
+//! [`Decode`](crate::Decode) are also implemented for `uuid::Uuid`.
+
+use crate::decode::decode_u128;
+use crate::encode::encode_u128;
+#[cfg(feature = "uuid")]
+use crate::{decode::Decode, encode::Encode};
+
+/// Encodes a UUID given as its 16 raw bytes, returning the encoded length.
+#[inline]
+#[must_use]
+pub fn encode_uuid(buf: &mut [u8; 17], uuid: [u8; 16]) -> usize {
+	encode_u128(buf, u128::from_be_bytes(uuid))
+}
+
+/// Decodes a UUID as its 16 raw bytes, returning the bytes and encoded length.
+#[inline]
+#[must_use]
+pub fn decode_uuid(buf: &[u8; 17]) -> ([u8; 16], usize) {
+	let (value, len) = decode_u128(buf);
+	(value.to_be_bytes(), len)
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+#[cfg(feature = "uuid")]
+impl Encode for ::uuid::Uuid {
+	#[inline]
+	fn encode(buf: &mut [u8], value: Self) -> Result<usize, &'static str> {
+		u128::encode(buf, value.as_u128())
+	}
+
+	#[inline]
+	fn encoded_size(value: Self) -> Result<usize, &'static str> {
+		u128::encoded_size(value.as_u128())
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u128 as Encode>::MAX_ENCODED_SIZE;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+#[cfg(feature = "uuid")]
+impl Decode for ::uuid::Uuid {
+	#[inline]
+	fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		let (value, len) = u128::decode(buf)?;
+		Ok((::uuid::Uuid::from_u128(value), len))
+	}
+
+	const MAX_ENCODED_SIZE: usize = <u128 as Decode>::MAX_ENCODED_SIZE;
+}
@@ -0,0 +1,136 @@
+//! Whole-buffer validation before ingesting untrusted data
+//!
+//! [`validate`] scans a buffer of `T` values and checks that every prefix
+//! byte is legal for `T`, that no value is cut short, and that every value
+//! fits in `T`. [`validate_canonical`] additionally rejects overlong
+//! encodings, i.e. values that the encoder would have written differently.
+//! Both return the number of values on success and the offset of the first
+//! bad value on failure.
+//!
+//! ## Example
+//!
+//! ```rust
+//! let mut buf = [0u8; 12];
+//! let len = vlen::bulk_encode(&mut buf, &[1u32, 300, 70000]).unwrap();
+//!
+//! let report = vlen::validate::<u32>(&buf[..len]).unwrap();
+//! assert_eq!(report.values, 3);
+//!
+//! let err = vlen::validate::<u32>(&buf[..len - 1]).unwrap_err();
+//! assert_eq!(err.offset, 3);
+//! assert_eq!(err.values, 2);
+//! ```
+
+use core::fmt;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// Summary of a buffer that passed validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidationReport {
+	/// Number of values in the buffer.
+	pub values: usize,
+	/// Number of values whose encoding is longer than necessary.
+	///
+	/// Always zero for [`validate_canonical`].
+	pub non_canonical: usize,
+}
+
+/// The first problem found by [`validate`] or [`validate_canonical`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidationError {
+	/// Byte offset of the bad value.
+	pub offset: usize,
+	/// Number of valid values before it.
+	pub values: usize,
+	/// What is wrong with the value.
+	pub reason: &'static str,
+}
+
+impl fmt::Display for ValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{} at offset {} (after {} valid values)",
+			self.reason, self.offset, self.values
+		)
+	}
+}
+
+impl core::error::Error for ValidationError {}
+
+/// Checks that `buf` holds only complete, in-range `T` values.
+#[inline]
+pub fn validate<T>(buf: &[u8]) -> Result<ValidationReport, ValidationError>
+where
+	T: Decode + Encode,
+{
+	scan::<T>(buf, false)
+}
+
+/// Checks that `buf` holds only complete, in-range, canonically encoded `T`
+/// values.
+#[inline]
+pub fn validate_canonical<T>(
+	buf: &[u8],
+) -> Result<ValidationReport, ValidationError>
+where
+	T: Decode + Encode,
+{
+	scan::<T>(buf, true)
+}
+
+fn scan<T>(
+	buf: &[u8],
+	canonical: bool,
+) -> Result<ValidationReport, ValidationError>
+where
+	T: Decode + Encode,
+{
+	let mut report = ValidationReport {
+		values: 0,
+		non_canonical: 0,
+	};
+	let mut offset = 0;
+	while offset < buf.len() {
+		let fail = |reason| ValidationError {
+			offset,
+			values: report.values,
+			reason,
+		};
+		let rest = &buf[offset..];
+		let (value, len) = T::decode_checked(rest).map_err(fail)?;
+		let encoding = rest
+			.get(..len)
+			.ok_or_else(|| fail("truncated vlen value"))?;
+		T::check_prefix(rest[0]).map_err(fail)?;
+		if len > <T as Decode>::MAX_ENCODED_SIZE {
+			return Err(fail("invalid vlen prefix byte"));
+		}
+		if !is_canonical(encoding, value) {
+			if canonical {
+				return Err(fail("non-canonical vlen encoding"));
+			}
+			report.non_canonical += 1;
+		}
+		offset += len;
+		report.values += 1;
+	}
+	Ok(report)
+}
+
+/// Returns whether `encoding` is what the encoder writes for `value`.
+pub(crate) fn is_canonical<T>(encoding: &[u8], value: T) -> bool
+where
+	T: Encode,
+{
+	let mut temp_buf = [0u8; 32];
+	if <T as Encode>::MAX_ENCODED_SIZE > temp_buf.len() {
+		return T::encoded_size(value) == Ok(encoding.len());
+	}
+	match T::encode(&mut temp_buf, value) {
+		Ok(len) => temp_buf[..len] == *encoding,
+		Err(_) => false,
+	}
+}
@@ -0,0 +1,230 @@
+//! Self-describing tagged values for schema-less vlen streams
+//!
+//! Each [`VlenValue`] is written as a one-byte type tag followed by its
+//! payload, so heterogeneous streams can be produced and inspected without
+//! any out-of-band schema.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use vlen::value::VlenValue;
+//!
+//! let value = VlenValue::Seq(vec![
+//!     VlenValue::U64(42),
+//!     VlenValue::I64(-7),
+//!     VlenValue::Bytes(b"hello".to_vec()),
+//! ]);
+//!
+//! let buf = value.encode_to_vec();
+//! let (decoded, len) = VlenValue::decode(&buf).unwrap();
+//!
+//! assert_eq!(decoded, value);
+//! assert_eq!(len, buf.len());
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// Type tag for [`VlenValue::U64`].
+pub const TAG_U64: u8 = 0x00;
+/// Type tag for [`VlenValue::I64`].
+pub const TAG_I64: u8 = 0x01;
+/// Type tag for [`VlenValue::F64`].
+pub const TAG_F64: u8 = 0x02;
+/// Type tag for [`VlenValue::Bytes`].
+pub const TAG_BYTES: u8 = 0x03;
+/// Type tag for [`VlenValue::Seq`].
+pub const TAG_SEQ: u8 = 0x04;
+
+/// Maximum nesting depth of [`VlenValue::Seq`] accepted by the decoder.
+pub const MAX_DEPTH: usize = 64;
+
+/// A dynamically typed value with a one-byte type tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VlenValue {
+	/// An unsigned integer.
+	U64(u64),
+	/// A signed integer, zigzag encoded.
+	I64(i64),
+	/// A floating-point value.
+	F64(f64),
+	/// A length-prefixed byte string.
+	Bytes(Vec<u8>),
+	/// A count-prefixed sequence of nested values.
+	Seq(Vec<VlenValue>),
+}
+
+impl VlenValue {
+	/// Returns the type tag written before this value's payload.
+	#[must_use]
+	pub const fn tag(&self) -> u8 {
+		match self {
+			VlenValue::U64(_) => TAG_U64,
+			VlenValue::I64(_) => TAG_I64,
+			VlenValue::F64(_) => TAG_F64,
+			VlenValue::Bytes(_) => TAG_BYTES,
+			VlenValue::Seq(_) => TAG_SEQ,
+		}
+	}
+
+	/// Calculates the encoded size of the value, including its tag.
+	#[must_use]
+	pub fn encoded_size(&self) -> usize {
+		1 + match self {
+			VlenValue::U64(v) => crate::encode::encoded_size_u64(*v),
+			VlenValue::I64(v) => encoded_size_of(*v),
+			VlenValue::F64(v) => encoded_size_of(*v),
+			VlenValue::Bytes(bytes) => {
+				crate::encode::encoded_size_u64(bytes.len() as u64)
+					+ bytes.len()
+			},
+			VlenValue::Seq(values) => {
+				crate::encode::encoded_size_u64(values.len() as u64)
+					+ values.iter().map(VlenValue::encoded_size).sum::<usize>()
+			},
+		}
+	}
+
+	/// Encodes the value into the provided buffer, returning the encoded
+	/// length.
+	pub fn encode(&self, buf: &mut [u8]) -> Result<usize, &'static str> {
+		if buf.len() < self.encoded_size() {
+			return Err("buffer too small for VlenValue encoding");
+		}
+		Ok(self.write(buf))
+	}
+
+	/// Encodes the value into a newly allocated buffer.
+	#[must_use]
+	pub fn encode_to_vec(&self) -> Vec<u8> {
+		let mut buf = alloc::vec![0u8; self.encoded_size()];
+		let len = self.write(&mut buf);
+		debug_assert_eq!(len, buf.len());
+		buf
+	}
+
+	/// Decodes a value from the provided buffer, returning the value and the
+	/// number of bytes consumed.
+	pub fn decode(buf: &[u8]) -> Result<(Self, usize), &'static str> {
+		Self::decode_nested(buf, 0)
+	}
+
+	/// Writes the value into a buffer already checked to be large enough.
+	fn write(&self, buf: &mut [u8]) -> usize {
+		buf[0] = self.tag();
+		let mut offset = 1;
+		match self {
+			VlenValue::U64(v) => offset += write_exact(&mut buf[offset..], *v),
+			VlenValue::I64(v) => offset += write_exact(&mut buf[offset..], *v),
+			VlenValue::F64(v) => offset += write_exact(&mut buf[offset..], *v),
+			VlenValue::Bytes(bytes) => {
+				offset += write_exact(&mut buf[offset..], bytes.len() as u64);
+				buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+				offset += bytes.len();
+			},
+			VlenValue::Seq(values) => {
+				offset += write_exact(&mut buf[offset..], values.len() as u64);
+				for value in values {
+					offset += value.write(&mut buf[offset..]);
+				}
+			},
+		}
+		offset
+	}
+
+	fn decode_nested(
+		buf: &[u8],
+		depth: usize,
+	) -> Result<(Self, usize), &'static str> {
+		let (&tag, payload) = buf
+			.split_first()
+			.ok_or("buffer too small for VlenValue decoding")?;
+		let (value, len) = match tag {
+			TAG_U64 => {
+				let (v, len) = u64::decode(payload)?;
+				(VlenValue::U64(v), len)
+			},
+			TAG_I64 => {
+				let (v, len) = i64::decode(payload)?;
+				(VlenValue::I64(v), len)
+			},
+			TAG_F64 => {
+				let (v, len) = f64::decode(payload)?;
+				(VlenValue::F64(v), len)
+			},
+			TAG_BYTES => {
+				let (count, len) = u64::decode(payload)?;
+				if count > (payload.len() - len) as u64 {
+					return Err("byte string length exceeds buffer length");
+				}
+				let end = len + count as usize;
+				(VlenValue::Bytes(payload[len..end].to_vec()), end)
+			},
+			TAG_SEQ => {
+				if depth >= MAX_DEPTH {
+					return Err("VlenValue nesting too deep");
+				}
+				let (count, mut offset) = u64::decode(payload)?;
+				// Every nested value occupies at least two bytes.
+				if count > ((payload.len() - offset) / 2) as u64 {
+					return Err("sequence count exceeds buffer length");
+				}
+				let mut values = Vec::with_capacity(count as usize);
+				for _ in 0..count {
+					let (value, len) =
+						Self::decode_nested(&payload[offset..], depth + 1)?;
+					values.push(value);
+					offset += len;
+				}
+				(VlenValue::Seq(values), offset)
+			},
+			_ => return Err("unknown VlenValue type tag"),
+		};
+		Ok((value, len + 1))
+	}
+}
+
+impl From<u64> for VlenValue {
+	fn from(value: u64) -> Self {
+		VlenValue::U64(value)
+	}
+}
+
+impl From<i64> for VlenValue {
+	fn from(value: i64) -> Self {
+		VlenValue::I64(value)
+	}
+}
+
+impl From<f64> for VlenValue {
+	fn from(value: f64) -> Self {
+		VlenValue::F64(value)
+	}
+}
+
+impl From<Vec<u8>> for VlenValue {
+	fn from(value: Vec<u8>) -> Self {
+		VlenValue::Bytes(value)
+	}
+}
+
+impl From<Vec<VlenValue>> for VlenValue {
+	fn from(value: Vec<VlenValue>) -> Self {
+		VlenValue::Seq(value)
+	}
+}
+
+fn encoded_size_of<T: Encode>(value: T) -> usize {
+	// Encoded size is infallible for every built-in type.
+	T::encoded_size(value).unwrap_or(T::MAX_ENCODED_SIZE)
+}
+
+/// Encodes `value` into `buf`, which only needs room for the exact encoding.
+fn write_exact<T: Encode>(buf: &mut [u8], value: T) -> usize {
+	let mut temp_buf = [0u8; 17];
+	let len = T::encode(&mut temp_buf, value).unwrap_or(0);
+	buf[..len].copy_from_slice(&temp_buf[..len]);
+	len
+}
@@ -0,0 +1,59 @@
+//! JavaScript bindings via `wasm-bindgen`
+//!
+//! Exposes single-value encoding and decoding for `u32`, `u64` and `f64`,
+//! and bulk `u32` encoding between `Uint32Array` and `Uint8Array`, so
+//! JavaScript clients can read the same wire format as Rust servers. `u64`
+//! values cross the boundary as `BigInt`, and errors are thrown as `Error`.
+//!
+//! ## Example
+//!
+//! ```js
+//! import { encodeU32, decodeU32, bulkDecodeU32 } from "vlen";
+//!
+//! const bytes = encodeU32(300); // Uint8Array [0xac, 0x04]
+//! console.log(decodeU32(bytes)); // 300
+//! console.log(bulkDecodeU32(bytes)); // Uint32Array [300]
+//! ```
+
+use alloc::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+
+use crate::decode::Decode;
+use crate::encode::Encode;
+
+/// Generates the single-value bindings for a type.
+macro_rules! wasm_functions {
+	($t:ty, $encode:ident, $encode_js:literal, $decode:ident, $decode_js:literal) => {
+		#[doc = concat!("Encodes a `", stringify!($t), "` into a new `Uint8Array`.")]
+		#[wasm_bindgen(js_name = $encode_js)]
+		pub fn $encode(value: $t) -> Vec<u8> {
+			let mut buf = [0u8; <$t as Encode>::MAX_ENCODED_SIZE];
+			let len = crate::encode::$encode(&mut buf, value);
+			buf[..len].to_vec()
+		}
+
+		#[doc = concat!("Decodes a `", stringify!($t), "` from the start of a `Uint8Array`.")]
+		#[wasm_bindgen(js_name = $decode_js)]
+		pub fn $decode(buf: &[u8]) -> Result<$t, JsError> {
+			let (value, _) = <$t>::decode(buf).map_err(JsError::new)?;
+			Ok(value)
+		}
+	};
+}
+
+wasm_functions!(u32, encode_u32, "encodeU32", decode_u32, "decodeU32");
+wasm_functions!(u64, encode_u64, "encodeU64", decode_u64, "decodeU64");
+wasm_functions!(f64, encode_f64, "encodeF64", decode_f64, "decodeF64");
+
+/// Encodes a `Uint32Array` into a new `Uint8Array`.
+#[wasm_bindgen(js_name = "bulkEncodeU32")]
+pub fn bulk_encode_u32(values: &[u32]) -> Result<Vec<u8>, JsError> {
+	crate::bulk_encode_to_vec(values).map_err(JsError::new)
+}
+
+/// Decodes every value in a `Uint8Array` into a new `Uint32Array`.
+#[wasm_bindgen(js_name = "bulkDecodeU32")]
+pub fn bulk_decode_u32(buf: &[u8]) -> Result<Vec<u32>, JsError> {
+	crate::bulk_decode_values(buf).map_err(JsError::new)
+}
@@ -0,0 +1,335 @@
+//! Wrapper types that opt a field into vlen encoding
+//!
+//! Serialization integrations such as `serde` store these wrappers in their
+//! compact vlen form instead of the format's native integer representation.
+//! The wrappers dereference to their inner value, and format, parse and
+//! support arithmetic like it.
+
+use core::{cmp, fmt, hash, ops, str};
+
+/// A wrapper type that serializes `u16` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenU16(pub u16);
+
+/// A wrapper type that serializes `u32` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenU32(pub u32);
+
+/// A wrapper type that serializes `u64` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenU64(pub u64);
+
+/// A wrapper type that serializes `u128` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenU128(pub u128);
+
+/// A wrapper type that serializes `i16` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenI16(pub i16);
+
+/// A wrapper type that serializes `i32` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenI32(pub i32);
+
+/// A wrapper type that serializes `i64` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenI64(pub i64);
+
+/// A wrapper type that serializes `i128` values using vlen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VlenI128(pub i128);
+
+/// A wrapper type that serializes `f32` values using vlen encoding.
+///
+/// Comparison and hashing use the IEEE 754 total order, so `-0.0` sorts
+/// before `0.0` and NaNs with the same bits are equal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VlenF32(pub f32);
+
+/// A wrapper type that serializes `f64` values using vlen encoding.
+///
+/// Comparison and hashing use the IEEE 754 total order, so `-0.0` sorts
+/// before `0.0` and NaNs with the same bits are equal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VlenF64(pub f64);
+
+// Implement From traits for easy conversion
+impl From<u16> for VlenU16 {
+	fn from(value: u16) -> Self {
+		VlenU16(value)
+	}
+}
+
+impl From<u32> for VlenU32 {
+	fn from(value: u32) -> Self {
+		VlenU32(value)
+	}
+}
+
+impl From<u64> for VlenU64 {
+	fn from(value: u64) -> Self {
+		VlenU64(value)
+	}
+}
+
+impl From<u128> for VlenU128 {
+	fn from(value: u128) -> Self {
+		VlenU128(value)
+	}
+}
+
+impl From<i16> for VlenI16 {
+	fn from(value: i16) -> Self {
+		VlenI16(value)
+	}
+}
+
+impl From<i32> for VlenI32 {
+	fn from(value: i32) -> Self {
+		VlenI32(value)
+	}
+}
+
+impl From<i64> for VlenI64 {
+	fn from(value: i64) -> Self {
+		VlenI64(value)
+	}
+}
+
+impl From<i128> for VlenI128 {
+	fn from(value: i128) -> Self {
+		VlenI128(value)
+	}
+}
+
+impl From<f32> for VlenF32 {
+	fn from(value: f32) -> Self {
+		VlenF32(value)
+	}
+}
+
+impl From<f64> for VlenF64 {
+	fn from(value: f64) -> Self {
+		VlenF64(value)
+	}
+}
+
+// Implement Deref for easy access to inner values
+impl ops::Deref for VlenU16 {
+	type Target = u16;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenU32 {
+	type Target = u32;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenU64 {
+	type Target = u64;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenU128 {
+	type Target = u128;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenI16 {
+	type Target = i16;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenI32 {
+	type Target = i32;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenI64 {
+	type Target = i64;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenI128 {
+	type Target = i128;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenF32 {
+	type Target = f32;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl ops::Deref for VlenF64 {
+	type Target = f64;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+// Implement DerefMut for mutable access
+impl ops::DerefMut for VlenU16 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenU32 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenU64 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenU128 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenI16 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenI32 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenI64 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenI128 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenF32 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl ops::DerefMut for VlenF64 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+/// Macro to implement comparison and hashing by IEEE 754 total order
+macro_rules! impl_total_order {
+	($($wrapper:ident),*) => {
+		$(
+			impl PartialEq for $wrapper {
+				fn eq(&self, other: &Self) -> bool {
+					self.0.to_bits() == other.0.to_bits()
+				}
+			}
+
+			impl Eq for $wrapper {}
+
+			impl PartialOrd for $wrapper {
+				fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+					Some(self.cmp(other))
+				}
+			}
+
+			impl Ord for $wrapper {
+				fn cmp(&self, other: &Self) -> cmp::Ordering {
+					self.0.total_cmp(&other.0)
+				}
+			}
+
+			impl hash::Hash for $wrapper {
+				fn hash<H: hash::Hasher>(&self, state: &mut H) {
+					self.0.to_bits().hash(state);
+				}
+			}
+		)*
+	};
+}
+
+impl_total_order!(VlenF32, VlenF64);
+
+/// Macro to delegate formatting, parsing and arithmetic to the inner value
+macro_rules! impl_numeric {
+	($($wrapper:ident($t:ty)),* $(,)?) => {
+		$(
+			impl fmt::Display for $wrapper {
+				fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+					fmt::Display::fmt(&self.0, f)
+				}
+			}
+
+			impl str::FromStr for $wrapper {
+				type Err = <$t as str::FromStr>::Err;
+
+				fn from_str(s: &str) -> Result<Self, Self::Err> {
+					s.parse().map($wrapper)
+				}
+			}
+
+			impl_numeric!(@op $wrapper, Add, add, AddAssign, add_assign, +);
+			impl_numeric!(@op $wrapper, Sub, sub, SubAssign, sub_assign, -);
+			impl_numeric!(@op $wrapper, Mul, mul, MulAssign, mul_assign, *);
+		)*
+	};
+	(@op $wrapper:ident, $op:ident, $op_fn:ident, $assign:ident, $assign_fn:ident, $sym:tt) => {
+		impl ops::$op for $wrapper {
+			type Output = Self;
+
+			fn $op_fn(self, rhs: Self) -> Self {
+				$wrapper(self.0 $sym rhs.0)
+			}
+		}
+
+		impl ops::$assign for $wrapper {
+			fn $assign_fn(&mut self, rhs: Self) {
+				self.0 = self.0 $sym rhs.0;
+			}
+		}
+	};
+}
+
+impl_numeric! {
+	VlenU16(u16),
+	VlenU32(u32),
+	VlenU64(u64),
+	VlenU128(u128),
+	VlenI16(i16),
+	VlenI32(i32),
+	VlenI64(i64),
+	VlenI128(i128),
+	VlenF32(f32),
+	VlenF64(f64),
+}